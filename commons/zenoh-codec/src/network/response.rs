@@ -180,11 +180,14 @@ where
             rid,
             ext_qos,
             ext_tstamp,
+            ext_cancel,
         } = x;
 
         // Header
         let mut header = id::RESPONSE_FINAL;
-        let mut n_exts = ((ext_qos != &ext::QoSType::DEFAULT) as u8) + (ext_tstamp.is_some() as u8);
+        let mut n_exts = ((ext_qos != &ext::QoSType::DEFAULT) as u8)
+            + (ext_tstamp.is_some() as u8)
+            + (ext_cancel.is_some() as u8);
         if n_exts != 0 {
             header |= flag::Z;
         }
@@ -202,6 +205,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (ts, n_exts != 0))?;
         }
+        if let Some(cancel) = ext_cancel.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (cancel, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -238,6 +245,7 @@ where
         // Extensions
         let mut ext_qos = ext::QoSType::DEFAULT;
         let mut ext_tstamp = None;
+        let mut ext_cancel = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -254,6 +262,11 @@ where
                     ext_tstamp = Some(t);
                     has_ext = ext;
                 }
+                ext::Cancel::ID => {
+                    let (c, ext): (ext::Cancel, bool) = eodec.read(&mut *reader)?;
+                    ext_cancel = Some(c);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "ResponseFinal", ext)?;
                 }
@@ -264,6 +277,7 @@ where
             rid,
             ext_qos,
             ext_tstamp,
+            ext_cancel,
         })
     }
 }
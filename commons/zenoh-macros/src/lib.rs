@@ -520,8 +520,10 @@ pub fn ke(tokens: TokenStream) -> TokenStream {
     }
 }
 
+mod zenoh_ext_derive;
 mod zenoh_runtime_derive;
 use syn::DeriveInput;
+use zenoh_ext_derive::{derive_deserialize, derive_serialize};
 use zenoh_runtime_derive::{derive_generic_runtime_param, derive_register_param};
 
 /// Make the underlying struct `Param` be generic over any `T` satisfying a generated `trait DefaultParam { fn param() -> Param; }`
@@ -555,6 +557,45 @@ pub fn register_param(input: proc_macro::TokenStream) -> proc_macro::TokenStream
         .into()
 }
 
+/// Derive `zenoh_ext::Serialize` for a struct or an enum, whose fields must implement it.
+///
+/// The fields of a struct are serialized in their declaration order, in the format of the tuple of
+/// these fields. A variant of an enum is serialized as the tuple of its discriminant, typed by the
+/// `#[repr]` of the enum (`i32` by default), followed by its fields: each variant must then have an
+/// explicit discriminant.
+/// ```rust,ignore
+/// #[derive(zenoh_ext::Serialize, zenoh_ext::Deserialize)]
+/// struct Position {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// #[derive(zenoh_ext::Serialize, zenoh_ext::Deserialize)]
+/// #[repr(u8)]
+/// enum Command {
+///     Stop = 0,
+///     Move(Position) = 1,
+/// }
+/// ```
+#[proc_macro_derive(Serialize)]
+pub fn serialize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input: DeriveInput = syn::parse_macro_input!(input);
+    derive_serialize(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `zenoh_ext::Deserialize` for a struct or an enum, whose fields must implement it.
+///
+/// See [`Serialize`](macro@Serialize) for the format.
+#[proc_macro_derive(Deserialize)]
+pub fn deserialize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input: DeriveInput = syn::parse_macro_input!(input);
+    derive_deserialize(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 /// Macro `#[internal_trait]` should precede
 /// `impl Trait for Struct { ... }`
 ///
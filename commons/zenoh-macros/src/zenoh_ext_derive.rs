@@ -0,0 +1,222 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The derive macros of the `zenoh-ext` serialization traits.
+//!
+//! The fields of a struct are serialized in their declaration order, as the tuple of these fields.
+//! A variant of an enum is serialized as the tuple of its discriminant, typed by the `#[repr]` of
+//! the enum (`i32` by default), followed by its fields.
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, spanned::Spanned, Data, DataEnum, DeriveInput, Error, Expr, Fields, Generics,
+    Ident, Path,
+};
+
+const REPR_TYPES: [&str; 10] = [
+    "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128",
+];
+
+/// Adds the `bound` trait to the bounds of the type parameters.
+fn with_bounds(mut generics: Generics, bound: &Path) -> Generics {
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+/// The identifiers to which the fields are bound in the patterns.
+fn bindings(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect()
+}
+
+/// The pattern destructuring `fields` of `path` into their [`bindings`].
+fn pattern(path: TokenStream, fields: &Fields) -> TokenStream {
+    let bindings = bindings(fields);
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Unit => path,
+    }
+}
+
+/// The expression constructing `path` with the deserialized `fields`.
+fn construction(path: TokenStream, fields: &Fields) -> TokenStream {
+    let deserialize = quote!(::zenoh_ext::Deserialize::deserialize(deserializer)?);
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names: #deserialize),* })
+        }
+        Fields::Unnamed(unnamed) => {
+            let deserialize = unnamed.unnamed.iter().map(|_| &deserialize);
+            quote!(#path(#(#deserialize),*))
+        }
+        Fields::Unit => path,
+    }
+}
+
+/// The type of the discriminants of an enum, given by its `#[repr]`.
+fn discriminant_type(input: &DeriveInput) -> syn::Result<Ident> {
+    let mut repr = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                if REPR_TYPES.contains(&ident.to_string().as_str()) {
+                    repr = Some(ident.clone());
+                }
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr.unwrap_or_else(|| format_ident!("i32")))
+}
+
+/// The explicit discriminants of the variants of an enum.
+fn discriminants(data: &DataEnum) -> syn::Result<Vec<&Expr>> {
+    data.variants
+        .iter()
+        .map(|variant| {
+            variant
+                .discriminant
+                .as_ref()
+                .map(|(_, discriminant)| discriminant)
+                .ok_or_else(|| {
+                    Error::new(
+                        variant.span(),
+                        "the variants must have an explicit discriminant, which is serialized \
+                         to identify them",
+                    )
+                })
+        })
+        .collect()
+}
+
+pub(crate) fn derive_serialize(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let generics = with_bounds(
+        input.generics.clone(),
+        &parse_quote!(::zenoh_ext::Serialize),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let pattern = pattern(quote!(Self), &data.fields);
+            let bindings = bindings(&data.fields);
+            quote! {
+                let #pattern = self;
+                #(::zenoh_ext::Serialize::serialize(#bindings, serializer);)*
+            }
+        }
+        Data::Enum(data) => {
+            let repr = discriminant_type(&input)?;
+            let discriminants = discriminants(data)?;
+            let arms = data
+                .variants
+                .iter()
+                .zip(discriminants)
+                .map(|(variant, discriminant)| {
+                    let ident = &variant.ident;
+                    let pattern = pattern(quote!(Self::#ident), &variant.fields);
+                    let bindings = bindings(&variant.fields);
+                    quote! {
+                        #pattern => {
+                            let discriminant: #repr = #discriminant;
+                            ::zenoh_ext::Serialize::serialize(&discriminant, serializer);
+                            #(::zenoh_ext::Serialize::serialize(#bindings, serializer);)*
+                        }
+                    }
+                });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span(),
+                "unions can't be serialized",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::zenoh_ext::Serialize for #name #ty_generics #where_clause {
+            fn serialize(&self, serializer: &mut ::zenoh_ext::ZSerializer) {
+                #body
+            }
+        }
+    })
+}
+
+pub(crate) fn derive_deserialize(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let generics = with_bounds(
+        input.generics.clone(),
+        &parse_quote!(::zenoh_ext::Deserialize),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let construction = construction(quote!(Self), &data.fields);
+            quote!(Ok(#construction))
+        }
+        Data::Enum(data) => {
+            let repr = discriminant_type(&input)?;
+            let discriminants = discriminants(data)?;
+            let consts: Vec<Ident> = (0..data.variants.len())
+                .map(|i| format_ident!("__DISCRIMINANT_{}", i))
+                .collect();
+            let constructions = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                construction(quote!(Self::#ident), &variant.fields)
+            });
+            quote! {
+                #(const #consts: #repr = #discriminants;)*
+                match <#repr as ::zenoh_ext::Deserialize>::deserialize(deserializer)? {
+                    #(#consts => Ok(#constructions),)*
+                    _ => Err(::zenoh_ext::ZDeserializeError),
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span(),
+                "unions can't be deserialized",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::zenoh_ext::Deserialize for #name #ty_generics #where_clause {
+            fn deserialize(
+                deserializer: &mut ::zenoh_ext::ZDeserializer,
+            ) -> ::core::result::Result<Self, ::zenoh_ext::ZDeserializeError> {
+                #body
+            }
+        }
+    })
+}
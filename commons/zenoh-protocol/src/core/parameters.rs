@@ -139,9 +139,8 @@ pub fn insert_sort<'s>(s: &'s str, k: &'s str, v: &'s str) -> (String, Option<&'
 
 /// Remove a key-value `(&str, &str)` pair from `s` preserving the insertion order.
 pub fn remove<'s>(s: &'s str, k: &str) -> (String, Option<&'s str>) {
-    let mut iter = iter(s);
-    let item = iter.find(|(key, _)| *key == k).map(|(_, v)| v);
-    let iter = iter.filter(|x| x.0 != k);
+    let item = get(s, k);
+    let iter = iter(s).filter(|x| x.0 != k);
     (concat(iter), item)
 }
 
@@ -522,4 +521,18 @@ mod tests {
         hm.insert(Cow::from("p1"), Cow::from("v1"));
         assert_eq!(Parameters::from(hm), Parameters::from("p1=v1"));
     }
+
+    #[test]
+    fn test_parameters_remove() {
+        let mut parameters = Parameters::from("p1=v1;p2=v2;p3=v3");
+
+        assert_eq!(parameters.remove("p2"), Some("v2".to_string()));
+        assert_eq!(parameters, Parameters::from("p1=v1;p3=v3"));
+
+        assert_eq!(parameters.remove("p4"), None);
+        assert_eq!(parameters, Parameters::from("p1=v1;p3=v3"));
+
+        assert_eq!(parameters.remove("p3"), Some("v3".to_string()));
+        assert_eq!(parameters, Parameters::from("p1=v1"));
+    }
 }
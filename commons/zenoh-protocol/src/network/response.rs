@@ -57,8 +57,8 @@ pub struct Response {
 
 pub mod ext {
     use crate::{
-        common::{ZExtZ64, ZExtZBuf},
-        zextz64, zextzbuf,
+        common::{ZExtUnit, ZExtZ64, ZExtZBuf},
+        zextunit, zextz64, zextzbuf,
     };
     pub type QoS = zextz64!(0x1, false);
     pub type QoSType = crate::network::ext::QoSType<{ QoS::ID }>;
@@ -68,6 +68,13 @@ pub mod ext {
 
     pub type ResponderId = zextzbuf!(0x3, false);
     pub type ResponderIdType = crate::network::ext::EntityGlobalIdType<{ ResponderId::ID }>;
+
+    // ```text
+    // - Cancel (0x4)
+    // ```
+    // Set on a ResponseFinal sent in the direction of the Request, it cancels the Request.
+    // It is only sent to the nodes that negotiated a patch version supporting it.
+    pub type Cancel = zextunit!(0x4, true);
 }
 
 impl Response {
@@ -113,11 +120,15 @@ impl Response {
 ///
 /// (*) The resolution of the request id is negotiated during the session establishment.
 ///     This implementation limits the resolution to 32bit.
+///
+/// A ResponseFinal is sent by the responder when all the responses to a Request have been sent,
+/// or by the requester with the Cancel extension when the responses are no longer expected.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ResponseFinal {
     pub rid: RequestId,
     pub ext_qos: ext::QoSType,
     pub ext_tstamp: Option<ext::TimestampType>,
+    pub ext_cancel: Option<ext::Cancel>,
 }
 
 impl ResponseFinal {
@@ -129,11 +140,13 @@ impl ResponseFinal {
         let rid: RequestId = rng.gen();
         let ext_qos = ext::QoSType::rand();
         let ext_tstamp = rng.gen_bool(0.5).then(ext::TimestampType::rand);
+        let ext_cancel = rng.gen_bool(0.5).then(ext::Cancel::rand);
 
         Self {
             rid,
             ext_qos,
             ext_tstamp,
+            ext_cancel,
         }
     }
 }
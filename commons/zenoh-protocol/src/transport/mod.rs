@@ -317,7 +317,7 @@ pub mod ext {
 
     impl<const ID: u8> PatchType<ID> {
         pub const NONE: Self = Self(0);
-        pub const CURRENT: Self = Self(1);
+        pub const CURRENT: Self = Self(2);
 
         pub fn new(int: u8) -> Self {
             Self(int)
@@ -331,6 +331,10 @@ pub mod ext {
             self.0 >= 1
         }
 
+        pub fn has_query_cancellation(&self) -> bool {
+            self.0 >= 2
+        }
+
         #[cfg(feature = "test")]
         pub fn rand() -> Self {
             use rand::Rng;
@@ -98,6 +98,34 @@ impl TransportUnicast {
         Ok(transport.is_shm())
     }
 
+    #[inline(always)]
+    pub fn is_qos(&self) -> ZResult<bool> {
+        let transport = self.get_inner()?;
+        Ok(transport.is_qos())
+    }
+
+    #[inline(always)]
+    pub fn is_multilink(&self) -> ZResult<bool> {
+        let _transport = self.get_inner()?;
+        Ok(zcondfeat!(
+            "transport_multilink",
+            _transport.get_config().multilink.is_some(),
+            false
+        ))
+    }
+
+    #[inline(always)]
+    pub fn is_lowlatency(&self) -> ZResult<bool> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().is_lowlatency)
+    }
+
+    #[inline(always)]
+    pub fn get_patch(&self) -> ZResult<PatchType> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().patch)
+    }
+
     #[inline(always)]
     pub fn get_callback(&self) -> ZResult<Option<Arc<dyn TransportPeerEventHandler>>> {
         let transport = self.get_inner()?;
@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::{convert::TryFrom, time::Duration};
+use std::{convert::TryFrom, path::PathBuf, time::Duration};
 
 use const_format::concatcp;
 use derive_more::{AsMut, AsRef};
@@ -67,6 +67,16 @@ pub struct StorageConfig {
     pub garbage_collection_config: GarbageCollectionConfig,
     // Note: ReplicaConfig is optional. Alignment will be performed only if it is a replica
     pub replication: Option<ReplicaConfig>,
+    pub ttl: TtlConfig,
+    // Note: the snapshot is imported each time the storage is started
+    pub import_snapshot: Option<PathBuf>,
+    // Note: the `export` and `import` administrative operations are only enabled when a snapshot
+    //       directory is configured, and they can only access files within it
+    pub snapshot_dir: Option<PathBuf>,
+    // Note: the administrative operations accessing the content of the storage (e.g. `purge` or
+    //       `export`) are disabled unless this is set, they also require the write permission on
+    //       the adminspace
+    pub admin_operations: bool,
 }
 // Note: All parameters should be same for replicas, else will result on huge overhead
 #[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
@@ -169,6 +179,29 @@ impl Default for GarbageCollectionConfig {
     }
 }
 
+// The configuration for the expiration of stored data in storage manager
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct TtlConfig {
+    // The time-to-live applied to samples that do not carry their own
+    // If `None`, such samples never expire
+    pub default: Option<Duration>,
+    // The duration between two checks for expired entries
+    pub period: Duration,
+    // The longest time-to-live accepted, longer ones (including the ones carried by samples) are
+    // reduced to it
+    pub max: Duration,
+}
+
+impl Default for TtlConfig {
+    fn default() -> Self {
+        Self {
+            default: None,
+            period: Duration::from_secs(1),
+            max: Duration::from_secs(365 * 86400),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ConfigDiff {
     DeleteVolume(VolumeConfig),
@@ -624,6 +657,86 @@ impl StorageConfig {
             }
             None => None,
         };
+        let ttl = match config.get("ttl") {
+            Some(s) => {
+                let mut ttl = TtlConfig::default();
+                if let Some(max) = s.get("max") {
+                    let max = max
+                        .to_string()
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(|max| Duration::try_from_secs_f64(max).ok());
+                    match max {
+                        Some(max) if !max.is_zero() && max.as_secs() <= u32::MAX as u64 => {
+                            ttl.max = max
+                        }
+                        _ => bail!(
+                            "Invalid value for field `max` in `ttl` of storage `{}`. Only \
+                             strictly positive numbers of seconds up to {} are accepted.",
+                            storage_name,
+                            u32::MAX
+                        ),
+                    }
+                }
+                if let Some(default) = s.get("default") {
+                    let default = default
+                        .to_string()
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(|default| Duration::try_from_secs_f64(default).ok());
+                    match default {
+                        Some(default) if !default.is_zero() && default <= ttl.max => {
+                            ttl.default = Some(default)
+                        }
+                        _ => bail!(
+                            "Invalid value for field `default` in `ttl` of storage `{}`. Only \
+                             strictly positive numbers not greater than `max` are accepted.",
+                            storage_name
+                        ),
+                    }
+                }
+                if let Some(period) = s.get("period") {
+                    let period = period.to_string().parse::<f64>();
+                    match period {
+                        Ok(period) if period > 0.0 => ttl.period = Duration::from_secs_f64(period),
+                        _ => bail!(
+                            "Invalid value for field `period` in `ttl` of storage `{}`. Only \
+                             strictly positive numbers are accepted.",
+                            storage_name
+                        ),
+                    }
+                }
+                ttl
+            }
+            None => TtlConfig::default(),
+        };
+        let import_snapshot = match config.get("import_snapshot") {
+            Some(Value::String(path)) => Some(PathBuf::from(path)),
+            None => None,
+            _ => bail!(
+                "Invalid type for field `import_snapshot` of storage `{}`. Only strings are \
+                 accepted.",
+                storage_name
+            ),
+        };
+        let snapshot_dir = match config.get("snapshot_dir") {
+            Some(Value::String(path)) => Some(PathBuf::from(path)),
+            None => None,
+            _ => bail!(
+                "Invalid type for field `snapshot_dir` of storage `{}`. Only strings are \
+                 accepted.",
+                storage_name
+            ),
+        };
+        let admin_operations = match config.get("admin_operations") {
+            Some(Value::Bool(admin_operations)) => *admin_operations,
+            None => false,
+            _ => bail!(
+                "Invalid type for field `admin_operations` of storage `{}`. Only booleans are \
+                 accepted.",
+                storage_name
+            ),
+        };
         Ok(StorageConfig {
             name: storage_name.into(),
             key_expr,
@@ -633,6 +746,10 @@ impl StorageConfig {
             volume_cfg,
             garbage_collection_config,
             replication,
+            ttl,
+            import_snapshot,
+            snapshot_dir,
+            admin_operations,
         })
     }
 }
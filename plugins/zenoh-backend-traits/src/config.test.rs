@@ -17,7 +17,7 @@ use std::time::Duration;
 use serde_json::json;
 
 use super::StorageConfig;
-use crate::config::ReplicaConfig;
+use crate::config::{ReplicaConfig, TtlConfig};
 
 #[test]
 fn test_replica_config() {
@@ -76,3 +76,66 @@ Actual message: {err}",
         })
     );
 }
+
+#[test]
+fn test_ttl_config() {
+    let no_ttl_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+    });
+    let storage_config =
+        StorageConfig::try_from("test-plugin", "test-storage", &no_ttl_config).unwrap();
+    assert_eq!(storage_config.ttl, TtlConfig::default());
+
+    let ttl_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "ttl": {
+            "default": 30,
+            "period": 0.5,
+            "max": 3600,
+        }
+    });
+    let storage_config =
+        StorageConfig::try_from("test-plugin", "test-storage", &ttl_config).unwrap();
+    assert_eq!(
+        storage_config.ttl,
+        TtlConfig {
+            default: Some(Duration::from_secs(30)),
+            period: Duration::from_millis(500),
+            max: Duration::from_secs(3600),
+        }
+    );
+
+    let incorrect_ttl_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "ttl": {
+            "default": -1,
+        }
+    });
+    assert!(StorageConfig::try_from("test-plugin", "test-storage", &incorrect_ttl_config).is_err());
+
+    let overflowing_ttl_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "ttl": {
+            "default": 1e30,
+        }
+    });
+    assert!(
+        StorageConfig::try_from("test-plugin", "test-storage", &overflowing_ttl_config).is_err()
+    );
+
+    let default_above_max_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "ttl": {
+            "default": 60,
+            "max": 30,
+        }
+    });
+    assert!(
+        StorageConfig::try_from("test-plugin", "test-storage", &default_above_max_config).is_err()
+    );
+}
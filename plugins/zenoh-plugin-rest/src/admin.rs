@@ -0,0 +1,189 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The configuration of the router, only available to the authenticated users listed in the
+//! `admins` of the `auth` configuration:
+//!
+//! - `GET /@/config[/<path>]` answers with the JSON value of the configuration at `<path>`, or of
+//!   the whole configuration, without its private values;
+//! - `PUT /@/config/<path>` sets the JSON5 value of the body at `<path>`;
+//! - `DELETE /@/config/<path>` removes the value at `<path>`, which must be in `plugins/`.
+//!
+//! The modifications are published on `@/<zid>/<whatami>/config/<path>`, so that they are
+//! applied by the adminspace as those of the Zenoh clients, depending on
+//! `adminspace.permissions`.
+use serde_json::Value;
+use tide::{Request, Response, StatusCode};
+use zenoh::{
+    internal::{access_control::AclMessage, runtime::Runtime},
+    key_expr::KeyExpr,
+};
+
+use crate::{auth::AuthenticatedUser, config, forbidden, is_authorized, response, State};
+
+pub(crate) const CONFIG_PATH: &str = "/@/config";
+
+fn config_path(req: &Request<State>) -> &str {
+    let path = req.url().path();
+    let path = path.strip_prefix(CONFIG_PATH).unwrap_or(path);
+    path.trim_matches('/')
+}
+
+fn adminspace_key_expr(runtime: &Runtime, path: &str) -> Result<KeyExpr<'static>, Response> {
+    let key_expr = match path {
+        "" => format!("@/{}/{}/config", runtime.zid(), runtime.whatami()),
+        path => format!("@/{}/{}/config/{path}", runtime.zid(), runtime.whatami()),
+    };
+    KeyExpr::try_from(key_expr).map_err(|e| bad_request(&e.to_string()))
+}
+
+fn bad_request(message: &str) -> Response {
+    response(StatusCode::BadRequest, "text/plain", message)
+}
+
+fn internal_error(message: &str) -> Response {
+    response(StatusCode::InternalServerError, "text/plain", message)
+}
+
+/// Checks that the user is an administrator, and that the access control policy allows `action`
+/// on the adminspace key expression of the path.
+fn authorize(req: &Request<State>, action: AclMessage) -> Result<KeyExpr<'static>, Response> {
+    let is_admin = match (req.ext::<AuthenticatedUser>(), &req.state().config.auth) {
+        (Some(user), Some(auth)) => auth.admins.contains(&user.0),
+        _ => false,
+    };
+    if !is_admin {
+        return Err(response(
+            StatusCode::Forbidden,
+            "text/plain",
+            "The configuration of the router is only available to the admins",
+        ));
+    }
+    let key_expr = adminspace_key_expr(&req.state().runtime, config_path(req))?;
+    if !is_authorized(req, action, &key_expr) {
+        return Err(forbidden(action, &key_expr));
+    }
+    Ok(key_expr)
+}
+
+/// Removes the secrets of the `auth` configurations of the REST plugins, which are not private
+/// values.
+fn redact(mut value: Value) -> Value {
+    if let Some(plugins) = value.get_mut("plugins").and_then(Value::as_object_mut) {
+        for plugin in plugins.values_mut() {
+            if let Ok(conf) = serde_json::from_value::<config::Config>(plugin.clone()) {
+                plugin["auth"] = Value::from(&conf)["auth"].take();
+            }
+        }
+    }
+    value
+}
+
+/// Returns the JSON value of the configuration at `path`, without its private values and
+/// secrets.
+fn get_value(runtime: &Runtime, path: &str) -> Option<Value> {
+    let config = runtime.config().lock().sift_privates();
+    let value = redact(serde_json::to_value(&config).ok()?);
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |mut value, segment| match &mut value {
+            Value::Object(object) => object.remove(segment),
+            Value::Array(array) => segment
+                .parse::<usize>()
+                .ok()
+                .filter(|index| *index < array.len())
+                .map(|index| array.swap_remove(index)),
+            _ => None,
+        })
+}
+
+fn value_response(runtime: &Runtime, path: &str) -> Response {
+    match get_value(runtime, path) {
+        Some(value) => response(StatusCode::Ok, "application/json", &value.to_string()),
+        None => response(
+            StatusCode::NotFound,
+            "text/plain",
+            &format!("No configuration at '{path}'"),
+        ),
+    }
+}
+
+pub(crate) async fn get_config(req: Request<State>) -> tide::Result<Response> {
+    if let Err(response) = authorize(&req, AclMessage::Query) {
+        return Ok(response);
+    }
+    let runtime = &req.state().runtime;
+    if !runtime.config().lock().adminspace.permissions().read {
+        return Ok(response(
+            StatusCode::Forbidden,
+            "text/plain",
+            "adminspace.permissions.read=false in the configuration of the router",
+        ));
+    }
+    Ok(value_response(runtime, config_path(&req)))
+}
+
+pub(crate) async fn put_config(mut req: Request<State>) -> tide::Result<Response> {
+    let key_expr = match authorize(&req, AclMessage::Put) {
+        Ok(key_expr) => key_expr,
+        Err(response) => return Ok(response),
+    };
+    let body = req.body_string().await?;
+    let path = config_path(&req).to_string();
+    let runtime = &req.state().runtime;
+    {
+        // Validated on a copy, the adminspace only logging the errors
+        let mut config = runtime.config().lock().clone();
+        if !*config.adminspace.enabled() || !config.adminspace.permissions().write {
+            return Ok(write_forbidden());
+        }
+        if let Err(e) = config.insert_json5(&path, &body) {
+            return Ok(bad_request(&format!("Invalid value at '{path}': {e}")));
+        }
+    }
+    if let Err(e) = req.state().session.put(&key_expr, body).await {
+        return Ok(internal_error(&e.to_string()));
+    }
+    Ok(value_response(runtime, &path))
+}
+
+pub(crate) async fn delete_config(req: Request<State>) -> tide::Result<Response> {
+    let key_expr = match authorize(&req, AclMessage::Delete) {
+        Ok(key_expr) => key_expr,
+        Err(response) => return Ok(response),
+    };
+    let path = config_path(&req);
+    let runtime = &req.state().runtime;
+    {
+        let mut config = runtime.config().lock().clone();
+        if !*config.adminspace.enabled() || !config.adminspace.permissions().write {
+            return Ok(write_forbidden());
+        }
+        if let Err(e) = config.remove(path) {
+            return Ok(bad_request(&format!("Can't remove '{path}': {e}")));
+        }
+    }
+    if let Err(e) = req.state().session.delete(&key_expr).await {
+        return Ok(internal_error(&e.to_string()));
+    }
+    Ok(Response::new(StatusCode::NoContent))
+}
+
+fn write_forbidden() -> Response {
+    response(
+        StatusCode::Forbidden,
+        "text/plain",
+        "The adminspace of the router is disabled or adminspace.permissions.write=false",
+    )
+}
@@ -0,0 +1,224 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose, Engine};
+use sha3::{Digest, Sha3_256};
+use subtle::ConstantTimeEq;
+use tide::{utils::async_trait, Middleware, Next, Request, Response, StatusCode};
+use zenoh::{
+    internal::{bail, zerror},
+    Result as ZResult,
+};
+
+use crate::config::AuthConfig;
+
+const BASIC_SCHEME: &str = "basic";
+const BEARER_SCHEME: &str = "bearer";
+
+type Sha3Digest = [u8; 32];
+
+fn sha3_256(secret: &str) -> Sha3Digest {
+    Sha3_256::digest(secret.as_bytes()).into()
+}
+
+/// The user authenticated by the [Authenticator], stored in the extensions of the request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AuthenticatedUser(pub(crate) String);
+
+/// A middleware rejecting the requests that do not carry the credentials of a configured user,
+/// either through HTTP Basic authentication or through a bearer token.
+///
+/// Only the SHA3-256 digests of the secrets are kept, and they are compared in constant time so
+/// that the duration of the comparison does not tell how much of a secret was guessed.
+pub(crate) struct Authenticator {
+    // username -> digest of the password
+    users: HashMap<String, Sha3Digest>,
+    // (digest of the token, username)
+    tokens: Vec<(Sha3Digest, String)>,
+}
+
+impl Authenticator {
+    pub(crate) fn new(config: &AuthConfig) -> ZResult<Self> {
+        let mut users = HashMap::with_capacity(config.users.len());
+        for user in &config.users {
+            let digest = match (&user.password, &user.password_sha3_256) {
+                (Some(password), None) => sha3_256(password),
+                (None, Some(hash)) => {
+                    let mut digest = Sha3Digest::default();
+                    hex::decode_to_slice(hash, &mut digest).map_err(|e| {
+                        zerror!(
+                            "Invalid `password_sha3_256` for user '{}': {e}",
+                            user.username
+                        )
+                    })?;
+                    digest
+                }
+                _ => bail!(
+                    "User '{}' must have either a `password` or a `password_sha3_256`",
+                    user.username
+                ),
+            };
+            users.insert(user.username.clone(), digest);
+        }
+
+        Ok(Self {
+            users,
+            tokens: config
+                .tokens
+                .iter()
+                .map(|token| (sha3_256(&token.token), token.username.clone()))
+                .collect(),
+        })
+    }
+
+    /// Returns the user identified by the value of an `Authorization` header, if any.
+    fn authenticate(&self, authorization: &str) -> Option<AuthenticatedUser> {
+        let (scheme, credentials) = authorization.trim().split_once(' ')?;
+        let credentials = credentials.trim();
+
+        if scheme.eq_ignore_ascii_case(BASIC_SCHEME) {
+            let credentials = general_purpose::STANDARD.decode(credentials).ok()?;
+            let credentials = String::from_utf8(credentials).ok()?;
+            let (username, password) = credentials.split_once(':')?;
+            let digest = sha3_256(password);
+            bool::from(self.users.get(username)?.ct_eq(&digest))
+                .then(|| AuthenticatedUser(username.to_string()))
+        } else if scheme.eq_ignore_ascii_case(BEARER_SCHEME) {
+            // All the tokens are compared, not to tell which one matched from the duration.
+            let digest = sha3_256(credentials);
+            self.tokens
+                .iter()
+                .fold(None, |user, (token, username)| {
+                    if bool::from(token.ct_eq(&digest)) {
+                        Some(username)
+                    } else {
+                        user
+                    }
+                })
+                .cloned()
+                .map(AuthenticatedUser)
+        } else {
+            None
+        }
+    }
+}
+
+#[async_trait]
+impl<State: Clone + Send + Sync + 'static> Middleware<State> for Authenticator {
+    async fn handle(&self, mut req: Request<State>, next: Next<'_, State>) -> tide::Result {
+        let user = req
+            .header("authorization")
+            .and_then(|values| self.authenticate(values.last().as_str()));
+
+        match user {
+            Some(user) => {
+                tracing::trace!("Authenticated {:?} for {:?}", user, req.url().path());
+                req.set_ext(user);
+                Ok(next.run(req).await)
+            }
+            None => {
+                tracing::debug!("Unauthenticated request on {:?}", req.url().path());
+                let mut response = Response::new(StatusCode::Unauthorized);
+                response.insert_header("WWW-Authenticate", r#"Basic realm="zenoh""#);
+                Ok(response)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose, Engine};
+
+    use super::{AuthenticatedUser, Authenticator};
+    use crate::config::AuthConfig;
+
+    fn authenticator() -> Authenticator {
+        let config = serde_json::from_str::<AuthConfig>(
+            r#"{
+                "users": [
+                    { "username": "alice", "password": "secret" },
+                    {
+                        "username": "carol",
+                        "password_sha3_256":
+                            "f5a5207a8729b1f709cb710311751eb2fc8acad5a1fb8ac991b736e69b6529a3"
+                    }
+                ],
+                "tokens": [{ "token": "abcdef", "username": "bob" }]
+            }"#,
+        )
+        .unwrap();
+        Authenticator::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_basic_authentication() {
+        let authenticator = authenticator();
+
+        let credentials = general_purpose::STANDARD.encode("alice:secret");
+        assert_eq!(
+            authenticator.authenticate(&format!("Basic {credentials}")),
+            Some(AuthenticatedUser("alice".to_string()))
+        );
+
+        let credentials = general_purpose::STANDARD.encode("alice:wrong");
+        assert_eq!(
+            authenticator.authenticate(&format!("Basic {credentials}")),
+            None
+        );
+
+        let credentials = general_purpose::STANDARD.encode("bob:secret");
+        assert_eq!(
+            authenticator.authenticate(&format!("Basic {credentials}")),
+            None
+        );
+
+        let credentials = general_purpose::STANDARD.encode("carol:secret");
+        assert_eq!(
+            authenticator.authenticate(&format!("Basic {credentials}")),
+            Some(AuthenticatedUser("carol".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_users() {
+        for users in [
+            r#"[{ "username": "alice" }]"#,
+            r#"[{ "username": "alice", "password": "secret", "password_sha3_256": "00" }]"#,
+            r#"[{ "username": "alice", "password_sha3_256": "not hexadecimal" }]"#,
+        ] {
+            let config =
+                serde_json::from_str::<AuthConfig>(&format!(r#"{{ "users": {users} }}"#)).unwrap();
+            assert!(Authenticator::new(&config).is_err());
+        }
+    }
+
+    #[test]
+    fn test_bearer_authentication() {
+        let authenticator = authenticator();
+
+        assert_eq!(
+            authenticator.authenticate("Bearer abcdef"),
+            Some(AuthenticatedUser("bob".to_string()))
+        );
+        assert_eq!(
+            authenticator.authenticate("bearer abcdef"),
+            Some(AuthenticatedUser("bob".to_string()))
+        );
+        assert_eq!(authenticator.authenticate("Bearer secret"), None);
+        assert_eq!(authenticator.authenticate("Digest abcdef"), None);
+        assert_eq!(authenticator.authenticate("abcdef"), None);
+    }
+}
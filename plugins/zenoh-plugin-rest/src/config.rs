@@ -23,6 +23,12 @@ use serde::{
 const DEFAULT_HTTP_INTERFACE: &str = "[::]";
 pub const DEFAULT_WORK_THREAD_NUM: usize = 2;
 pub const DEFAULT_MAX_BLOCK_THREAD_NUM: usize = 50;
+pub const ANY_ORIGIN: &str = "*";
+pub const DEFAULT_SSE_QUEUE_SIZE: usize = 256;
+pub const DEFAULT_SSE_HEARTBEAT_INTERVAL: u64 = 15;
+pub const DEFAULT_WS_QUEUE_SIZE: usize = 256;
+pub const DEFAULT_LIVELINESS_LEASE: u64 = 30;
+pub const DEFAULT_LIVELINESS_MAX_LEASE: u64 = 3600;
 
 #[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
@@ -33,6 +39,18 @@ pub struct Config {
     pub work_thread_num: usize,
     #[serde(default = "default_max_block_thread_num")]
     pub max_block_thread_num: usize,
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    #[serde(default)]
+    pub sse: SseConfig,
+    #[serde(default)]
+    pub ws: WsConfig,
+    #[serde(default)]
+    pub liveliness: LivelinessConfig,
     #[serde(default, deserialize_with = "deserialize_path")]
     __path__: Option<Vec<String>>,
     __required__: Option<bool>,
@@ -40,6 +58,143 @@ pub struct Config {
     __plugin__: Option<String>,
 }
 
+/// The certificate and private key, in PEM format, used to serve the REST API over HTTPS.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    pub listen_certificate: String,
+    pub listen_private_key: String,
+}
+
+/// The users allowed to access the REST API, either through HTTP Basic authentication or through
+/// a bearer token.
+///
+/// The username of an authenticated user is matched against the `usernames` of the ACL subjects
+/// of the router. Only the `admins` may read and modify the configuration of the router.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub users: Vec<UserConfig>,
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
+    #[serde(default)]
+    pub admins: Vec<String>,
+}
+
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct UserConfig {
+    pub username: String,
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    // The hex-encoded SHA3-256 digest of the password, to avoid writing it in the configuration
+    #[serde(default, skip_serializing)]
+    pub password_sha3_256: Option<String>,
+}
+
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TokenConfig {
+    #[serde(skip_serializing)]
+    pub token: String,
+    pub username: String,
+}
+
+/// The origins allowed by the CORS policy of the REST API, `"*"` allowing any origin.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CorsConfig {
+    #[serde(default = "default_allowed_origins")]
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: default_allowed_origins(),
+        }
+    }
+}
+
+/// The server-sent events streams of the subscriptions.
+///
+/// The samples received while a client is not reading its stream are queued up to `queue_size`,
+/// after which the `slow_client` policy applies. A heartbeat comment is sent on a stream which
+/// has been idle for `heartbeat_interval` seconds, `0` disabling the heartbeats.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SseConfig {
+    #[serde(default = "default_sse_queue_size")]
+    pub queue_size: usize,
+    #[serde(default)]
+    pub slow_client: SlowClientPolicy,
+    #[serde(default = "default_sse_heartbeat_interval")]
+    pub heartbeat_interval: u64,
+}
+
+impl Default for SseConfig {
+    fn default() -> Self {
+        Self {
+            queue_size: default_sse_queue_size(),
+            slow_client: SlowClientPolicy::default(),
+            heartbeat_interval: default_sse_heartbeat_interval(),
+        }
+    }
+}
+
+/// What to do when the queue of a server-sent events stream is full.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowClientPolicy {
+    /// Drop the oldest queued samples.
+    #[default]
+    DropOldest,
+    /// Close the stream, the client being expected to reconnect and resume it.
+    Disconnect,
+}
+
+/// The WebSocket connections.
+///
+/// The messages to send to a client that is not reading its connection are queued up to
+/// `queue_size`, after which the connection is closed.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WsConfig {
+    #[serde(default = "default_ws_queue_size")]
+    pub queue_size: usize,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            queue_size: default_ws_queue_size(),
+        }
+    }
+}
+
+/// The leases, in seconds, of the liveliness tokens declared through the REST API.
+///
+/// A token is undeclared if its lease is not renewed in time, a client requesting a lease of at
+/// most `max_lease`.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct LivelinessConfig {
+    #[serde(default = "default_liveliness_lease")]
+    pub lease: u64,
+    #[serde(default = "default_liveliness_max_lease")]
+    pub max_lease: u64,
+}
+
+impl Default for LivelinessConfig {
+    fn default() -> Self {
+        Self {
+            lease: default_liveliness_lease(),
+            max_lease: default_liveliness_max_lease(),
+        }
+    }
+}
+
 impl From<&Config> for serde_json::Value {
     fn from(c: &Config) -> Self {
         serde_json::to_value(c).unwrap()
@@ -61,6 +216,30 @@ fn default_max_block_thread_num() -> usize {
     DEFAULT_MAX_BLOCK_THREAD_NUM
 }
 
+fn default_allowed_origins() -> Vec<String> {
+    vec![ANY_ORIGIN.to_string()]
+}
+
+fn default_sse_queue_size() -> usize {
+    DEFAULT_SSE_QUEUE_SIZE
+}
+
+fn default_sse_heartbeat_interval() -> u64 {
+    DEFAULT_SSE_HEARTBEAT_INTERVAL
+}
+
+fn default_ws_queue_size() -> usize {
+    DEFAULT_WS_QUEUE_SIZE
+}
+
+fn default_liveliness_lease() -> u64 {
+    DEFAULT_LIVELINESS_LEASE
+}
+
+fn default_liveliness_max_lease() -> u64 {
+    DEFAULT_LIVELINESS_MAX_LEASE
+}
+
 struct HttpPortVisitor;
 
 impl Visitor<'_> for HttpPortVisitor {
@@ -159,7 +338,10 @@ impl<'de> serde::de::Visitor<'de> for PathVisitor {
 
 #[cfg(test)]
 mod tests {
-    use super::{Config, DEFAULT_HTTP_INTERFACE};
+    use super::{
+        Config, SlowClientPolicy, ANY_ORIGIN, DEFAULT_HTTP_INTERFACE,
+        DEFAULT_SSE_HEARTBEAT_INTERVAL, DEFAULT_SSE_QUEUE_SIZE, DEFAULT_WS_QUEUE_SIZE,
+    };
 
     #[test]
     fn test_path_field() {
@@ -217,6 +399,84 @@ mod tests {
         assert_eq!(__required__, Some(true));
     }
 
+    #[test]
+    fn test_tls_auth_and_cors_fields() {
+        let config = serde_json::from_str::<Config>(
+            r#"{
+                "http_port": 8443,
+                "tls": {
+                    "listen_certificate": "server.pem",
+                    "listen_private_key": "server.key"
+                },
+                "auth": {
+                    "users": [{ "username": "alice", "password": "secret" }],
+                    "tokens": [{ "token": "abcdef", "username": "bob" }],
+                    "admins": ["alice"]
+                },
+                "cors": { "allowed_origins": ["https://example.com"] }
+            }"#,
+        )
+        .unwrap();
+
+        let tls = config.tls.as_ref().unwrap();
+        assert_eq!(tls.listen_certificate, "server.pem");
+        assert_eq!(tls.listen_private_key, "server.key");
+        let auth = config.auth.as_ref().unwrap();
+        assert_eq!(auth.users[0].username, "alice");
+        assert_eq!(auth.tokens[0].username, "bob");
+        assert_eq!(auth.admins, vec!["alice"]);
+        assert_eq!(config.cors.allowed_origins, vec!["https://example.com"]);
+
+        // The secrets are not exposed on the adminspace
+        let value = serde_json::Value::from(&config);
+        assert!(value["auth"]["users"][0].get("password").is_none());
+        assert!(value["auth"]["users"][0].get("password_sha3_256").is_none());
+        assert!(value["auth"]["tokens"][0].get("token").is_none());
+    }
+
+    #[test]
+    fn test_default_cors_field() {
+        let config = serde_json::from_str::<Config>(r#"{"http_port": 8080}"#).unwrap();
+
+        assert!(config.tls.is_none());
+        assert!(config.auth.is_none());
+        assert_eq!(config.cors.allowed_origins, vec![ANY_ORIGIN]);
+    }
+
+    #[test]
+    fn test_sse_field() {
+        let config = serde_json::from_str::<Config>(
+            r#"{"http_port": 8080, "sse": { "queue_size": 16, "slow_client": "disconnect" }}"#,
+        )
+        .unwrap();
+        assert_eq!(config.sse.queue_size, 16);
+        assert_eq!(config.sse.slow_client, SlowClientPolicy::Disconnect);
+        assert_eq!(
+            config.sse.heartbeat_interval,
+            DEFAULT_SSE_HEARTBEAT_INTERVAL
+        );
+
+        let config = serde_json::from_str::<Config>(r#"{"http_port": 8080}"#).unwrap();
+        assert_eq!(config.sse.queue_size, DEFAULT_SSE_QUEUE_SIZE);
+        assert_eq!(config.sse.slow_client, SlowClientPolicy::DropOldest);
+
+        assert!(serde_json::from_str::<Config>(
+            r#"{"http_port": 8080, "sse": { "slow_client": "block" }}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_ws_field() {
+        let config =
+            serde_json::from_str::<Config>(r#"{"http_port": 8080, "ws": { "queue_size": 16 }}"#)
+                .unwrap();
+        assert_eq!(config.ws.queue_size, 16);
+
+        let config = serde_json::from_str::<Config>(r#"{"http_port": 8080}"#).unwrap();
+        assert_eq!(config.ws.queue_size, DEFAULT_WS_QUEUE_SIZE);
+    }
+
     #[test]
     fn test_no_path_field_and_no_required_field() {
         // See: https://github.com/eclipse-zenoh/zenoh-plugin-webserver/issues/19
@@ -0,0 +1,250 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The conversions of the payloads between the encodings that the plugin can decode:
+//!
+//! - JSON (`application/json`, `text/json` and `text/json5`)
+//! - CBOR (`application/cbor`)
+//! - text (`text/*` and `zenoh/string`)
+//! - the `zenoh-ext` serialization format of the primitive types (`zenoh/serialized`), whose
+//!   schema is the name of the Rust type: `bool`, `i8` to `i128`, `u8` to `u128`, `f32`, `f64`
+//!   or `String` (e.g. `zenoh/serialized;f64`)
+//!
+//! A payload is converted by decoding it into a JSON value, then encoding this value, the text
+//! being only encoded as `text/plain` or `zenoh/string`.
+use serde_json::Value;
+use zenoh::bytes::{Encoding, ZBytes};
+use zenoh_ext::{z_deserialize, z_serialize};
+
+/// Decodes a payload into a JSON value, returning `None` if its encoding can't be decoded.
+pub(crate) fn decode(payload: &ZBytes, encoding: &Encoding) -> Option<Value> {
+    let bytes = payload.to_bytes();
+    match without_schema(encoding) {
+        Encoding::APPLICATION_JSON | Encoding::TEXT_JSON | Encoding::TEXT_JSON5 => {
+            serde_json::from_slice(&bytes)
+                .map_err(|e| tracing::warn!("Encoding is JSON but data is not JSON: {e:?}"))
+                .ok()
+        }
+        Encoding::APPLICATION_CBOR => ciborium::from_reader(&*bytes)
+            .map_err(|e| tracing::warn!("Encoding is CBOR but data is not CBOR: {e:?}"))
+            .ok(),
+        Encoding::ZENOH_SERIALIZED => deserialize_primitive(schema(encoding)?, payload),
+        _ if is_text(encoding) => String::from_utf8(bytes.into_owned())
+            .map(Value::String)
+            .map_err(|e| tracing::warn!("Encoding is String but data is not String: {e:?}"))
+            .ok(),
+        _ => None,
+    }
+}
+
+/// Converts a payload into the `target` encoding.
+///
+/// Returns `Ok(None)` if the payload is to be kept as is, either because it already has the
+/// `target` encoding or because the plugin can't produce this encoding, and an error if the
+/// payload can't be converted.
+pub(crate) fn convert(
+    payload: &ZBytes,
+    encoding: &Encoding,
+    target: &Encoding,
+) -> Result<Option<ZBytes>, String> {
+    if encoding == target || !is_convertible(target) {
+        return Ok(None);
+    }
+    let value = decode(payload, encoding)
+        .ok_or_else(|| format!("Can't convert a payload encoded as {encoding} to {target}"))?;
+    encode(&value, target)
+        .map(Some)
+        .ok_or_else(|| format!("Can't convert {value} to {target}"))
+}
+
+/// Encodes a JSON value, returning `None` if it can't be encoded with `encoding`.
+pub(crate) fn encode(value: &Value, encoding: &Encoding) -> Option<ZBytes> {
+    match without_schema(encoding) {
+        Encoding::APPLICATION_JSON | Encoding::TEXT_JSON | Encoding::TEXT_JSON5 => {
+            serde_json::to_vec(value).ok().map(ZBytes::from)
+        }
+        Encoding::APPLICATION_CBOR => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(value, &mut bytes).ok()?;
+            Some(bytes.into())
+        }
+        Encoding::ZENOH_SERIALIZED => serialize_primitive(schema(encoding)?, value),
+        Encoding::TEXT_PLAIN | Encoding::ZENOH_STRING => match value {
+            Value::String(string) => Some(string.clone().into()),
+            value => Some(value.to_string().into()),
+        },
+        _ => None,
+    }
+}
+
+fn is_convertible(encoding: &Encoding) -> bool {
+    match without_schema(encoding) {
+        Encoding::APPLICATION_JSON
+        | Encoding::TEXT_JSON
+        | Encoding::TEXT_JSON5
+        | Encoding::APPLICATION_CBOR
+        | Encoding::TEXT_PLAIN
+        | Encoding::ZENOH_STRING => true,
+        Encoding::ZENOH_SERIALIZED => schema(encoding).is_some(),
+        _ => false,
+    }
+}
+
+fn is_text(encoding: &Encoding) -> bool {
+    without_schema(encoding) == Encoding::ZENOH_STRING || encoding.to_string().starts_with("text/")
+}
+
+fn without_schema(encoding: &Encoding) -> Encoding {
+    Encoding::new(encoding.id(), None)
+}
+
+fn schema(encoding: &Encoding) -> Option<&str> {
+    std::str::from_utf8(encoding.schema()?).ok()
+}
+
+macro_rules! primitives {
+    ($schema:expr, $f:ident, $arg:expr, $($ty:ty),*) => {
+        match $schema {
+            $(stringify!($ty) => $f::<$ty>($arg),)*
+            _ => None,
+        }
+    };
+}
+
+fn deserialize_primitive(schema: &str, payload: &ZBytes) -> Option<Value> {
+    fn to_json<T: zenoh_ext::Deserialize + serde::Serialize>(payload: &ZBytes) -> Option<Value> {
+        serde_json::to_value(z_deserialize::<T>(payload).ok()?).ok()
+    }
+    primitives!(
+        schema, to_json, payload, bool, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64,
+        String
+    )
+}
+
+fn serialize_primitive(schema: &str, value: &Value) -> Option<ZBytes> {
+    fn from_json<T: zenoh_ext::Serialize + serde::de::DeserializeOwned>(
+        value: &Value,
+    ) -> Option<ZBytes> {
+        Some(z_serialize(
+            &serde_json::from_value::<T>(value.clone()).ok()?,
+        ))
+    }
+    primitives!(
+        schema, from_json, value, bool, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64,
+        String
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use zenoh::bytes::{Encoding, ZBytes};
+    use zenoh_ext::{z_deserialize, z_serialize};
+
+    use super::{convert, decode, encode};
+
+    #[test]
+    fn test_decode() {
+        let cases: Vec<(ZBytes, Encoding, Option<serde_json::Value>)> = vec![
+            (
+                r#"{"a":[1,2]}"#.into(),
+                Encoding::APPLICATION_JSON,
+                Some(json!({"a": [1, 2]})),
+            ),
+            ("hello".into(), Encoding::TEXT_PLAIN, Some(json!("hello"))),
+            ("a,b".into(), Encoding::TEXT_CSV, Some(json!("a,b"))),
+            (
+                z_serialize(&42i32),
+                Encoding::ZENOH_SERIALIZED.with_schema("i32"),
+                Some(json!(42)),
+            ),
+            (
+                z_serialize(&1.5f64),
+                Encoding::ZENOH_SERIALIZED.with_schema("f64"),
+                Some(json!(1.5)),
+            ),
+            (
+                z_serialize("text"),
+                Encoding::ZENOH_SERIALIZED.with_schema("String"),
+                Some(json!("text")),
+            ),
+            (z_serialize(&42i32), Encoding::ZENOH_SERIALIZED, None),
+            (
+                z_serialize(&42i32),
+                Encoding::ZENOH_SERIALIZED.with_schema("Vec<i32>"),
+                None,
+            ),
+            (vec![1u8, 2, 3].into(), Encoding::ZENOH_BYTES, None),
+            ("not json".into(), Encoding::APPLICATION_JSON, None),
+        ];
+        for (payload, encoding, expected) in cases {
+            assert_eq!(decode(&payload, &encoding), expected, "{encoding}");
+        }
+
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&json!({"x": 1, "y": [true, null]}), &mut cbor).unwrap();
+        assert_eq!(
+            decode(&cbor.into(), &Encoding::APPLICATION_CBOR),
+            Some(json!({"x": 1, "y": [true, null]}))
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        let payload = encode(&json!(7), &Encoding::ZENOH_SERIALIZED.with_schema("u8")).unwrap();
+        assert_eq!(z_deserialize::<u8>(&payload).unwrap(), 7);
+        assert!(encode(&json!(300), &Encoding::ZENOH_SERIALIZED.with_schema("u8")).is_none());
+        assert!(encode(&json!("7"), &Encoding::ZENOH_SERIALIZED.with_schema("u8")).is_none());
+
+        let payload = encode(&json!("hello"), &Encoding::TEXT_PLAIN).unwrap();
+        assert_eq!(payload.try_to_string().unwrap(), "hello");
+        let payload = encode(&json!({"a": 1}), &Encoding::TEXT_PLAIN).unwrap();
+        assert_eq!(payload.try_to_string().unwrap(), r#"{"a":1}"#);
+        assert!(encode(&json!(1), &Encoding::IMAGE_PNG).is_none());
+        assert!(encode(&json!(1), &Encoding::TEXT_HTML).is_none());
+    }
+
+    #[test]
+    fn test_convert() {
+        let payload = z_serialize(&-3i64);
+        let encoding = Encoding::ZENOH_SERIALIZED.with_schema("i64");
+
+        let json = convert(&payload, &encoding, &Encoding::APPLICATION_JSON)
+            .unwrap()
+            .unwrap();
+        assert_eq!(json.try_to_string().unwrap(), "-3");
+
+        let cbor = convert(&payload, &encoding, &Encoding::APPLICATION_CBOR)
+            .unwrap()
+            .unwrap();
+        assert_eq!(decode(&cbor, &Encoding::APPLICATION_CBOR), Some(json!(-3)));
+
+        // Kept as is
+        assert!(convert(&payload, &encoding, &encoding).unwrap().is_none());
+        assert!(convert(&payload, &encoding, &Encoding::ZENOH_BYTES)
+            .unwrap()
+            .is_none());
+        assert!(convert(&payload, &encoding, &Encoding::from("*/*"))
+            .unwrap()
+            .is_none());
+
+        // Not convertible
+        assert!(convert(
+            &vec![0xffu8].into(),
+            &Encoding::ZENOH_BYTES,
+            &Encoding::APPLICATION_JSON
+        )
+        .is_err());
+    }
+}
@@ -30,7 +30,7 @@ use std::{
 };
 
 use base64::Engine;
-use futures::StreamExt;
+use futures::{StreamExt, TryStreamExt};
 use http_types::Method;
 use serde::{Deserialize, Serialize};
 use tide::{http::Mime, sse::Sender, Request, Response, Server, StatusCode};
@@ -38,6 +38,7 @@ use tokio::{task::JoinHandle, time::timeout};
 use zenoh::{
     bytes::{Encoding, ZBytes},
     internal::{
+        access_control::{AclMessage, Permission, UserAclEnforcer},
         bail,
         plugins::{RunningPluginTrait, ZenohPlugin},
         runtime::Runtime,
@@ -51,8 +52,19 @@ use zenoh::{
 };
 use zenoh_plugin_trait::{plugin_long_version, plugin_version, Plugin, PluginControl};
 
+mod admin;
+mod auth;
 mod config;
+mod convert;
+mod liveliness;
+mod openapi;
+mod options;
+mod sse;
+mod tls;
+mod ws;
+use auth::{AuthenticatedUser, Authenticator};
 pub use config::Config;
+use options::RequestOptions;
 use zenoh::query::ReplyError;
 
 const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
@@ -60,6 +72,16 @@ lazy_static::lazy_static! {
     static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
 }
 const RAW_KEY: &str = "_raw";
+// With `Accept: text/event-stream`, streams the replies of a query instead of subscribing
+const QUERY_KEY: &str = "_query";
+const NDJSON_MIME: &str = "application/x-ndjson";
+const CBOR_MIME: &str = "application/cbor";
+const SSE_MIME: &str = "text/event-stream";
+// The last line of a NDJSON stream of replies
+const NDJSON_END: &str = r#"{"end":true}"#;
+// The event name of the replies and of the final marker of a SSE stream of replies
+const SSE_REPLY_EVENT: &str = "reply";
+const SSE_END_EVENT: &str = "end";
 
 lazy_static::lazy_static! {
     static ref WORKER_THREAD_NUM: AtomicUsize = AtomicUsize::new(config::DEFAULT_WORK_THREAD_NUM);
@@ -106,6 +128,38 @@ where
     }
 }
 
+#[derive(Clone)]
+struct State {
+    session: Arc<Session>,
+    zid: String,
+    acl: Arc<UserAclEnforcer>,
+    config: Arc<Config>,
+    runtime: Runtime,
+    leases: Arc<liveliness::Leases>,
+}
+
+impl State {
+    /// Checks that the user, if authenticated, is allowed to perform `action` on `key_expr` by
+    /// the access control policy of the router.
+    fn is_authorized(
+        &self,
+        username: Option<&str>,
+        action: AclMessage,
+        key_expr: &KeyExpr,
+    ) -> bool {
+        self.acl.action(username, action, key_expr.as_str()) == Permission::Allow
+    }
+}
+
+impl std::fmt::Debug for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("State")
+            .field("session", &self.session)
+            .field("zid", &self.zid)
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct JSONSample {
     key: String,
@@ -123,28 +177,9 @@ fn payload_to_json(payload: &ZBytes, encoding: &Encoding) -> serde_json::Value {
     if payload.is_empty() {
         return serde_json::Value::Null;
     }
-    match encoding {
-        // If it is a JSON try to deserialize as json, if it fails fallback to base64
-        &Encoding::APPLICATION_JSON | &Encoding::TEXT_JSON | &Encoding::TEXT_JSON5 => {
-            let bytes = payload.to_bytes();
-            serde_json::from_slice(&bytes).unwrap_or_else(|e| {
-                tracing::warn!(
-                    "Encoding is JSON but data is not JSON, converting to base64, Error: {e:?}"
-                );
-                serde_json::Value::String(base64_encode(&bytes))
-            })
-        }
-        &Encoding::TEXT_PLAIN | &Encoding::ZENOH_STRING => serde_json::Value::String(
-            String::from_utf8(payload.to_bytes().into_owned()).unwrap_or_else(|e| {
-                tracing::warn!(
-                    "Encoding is String but data is not String, converting to base64, Error: {e:?}"
-                );
-                base64_encode(e.as_bytes())
-            }),
-        ),
-        // otherwise convert to JSON string
-        _ => serde_json::Value::String(base64_encode(&payload.to_bytes())),
-    }
+    // Convert the payloads that can't be decoded to base64
+    convert::decode(payload, encoding)
+        .unwrap_or_else(|| serde_json::Value::String(base64_encode(&payload.to_bytes())))
 }
 
 fn sample_to_json(sample: &Sample) -> JSONSample {
@@ -168,20 +203,67 @@ fn result_to_json(sample: Result<&Sample, &ReplyError>) -> JSONSample {
     }
 }
 
-async fn to_json(results: flume::Receiver<Reply>) -> String {
-    let values = results
+async fn to_json_samples(results: flume::Receiver<Reply>) -> Vec<JSONSample> {
+    results
         .stream()
         .filter_map(move |reply| async move { Some(result_to_json(reply.result())) })
         .collect::<Vec<JSONSample>>()
-        .await;
+        .await
+}
 
-    serde_json::to_string(&values).unwrap_or("[]".into())
+async fn to_json(results: flume::Receiver<Reply>) -> String {
+    serde_json::to_string(&to_json_samples(results).await).unwrap_or("[]".into())
 }
 
 async fn to_json_response(results: flume::Receiver<Reply>) -> Response {
     response(StatusCode::Ok, "application/json", &to_json(results).await)
 }
 
+async fn to_cbor_response(results: flume::Receiver<Reply>) -> Response {
+    let mut body = Vec::new();
+    match ciborium::into_writer(&to_json_samples(results).await, &mut body) {
+        Ok(()) => bytes_response(StatusCode::Ok, CBOR_MIME, body),
+        Err(e) => response(
+            StatusCode::InternalServerError,
+            "text/plain",
+            &e.to_string(),
+        ),
+    }
+}
+
+/// Streams the replies as newline-delimited JSON while they arrive, the last line being
+/// [`NDJSON_END`].
+fn to_ndjson_response(results: flume::Receiver<Reply>) -> Response {
+    let lines = results
+        .into_stream()
+        .map(|reply| serde_json::to_string(&result_to_json(reply.result())).unwrap_or("{}".into()))
+        .chain(futures::stream::once(futures::future::ready(
+            NDJSON_END.to_string(),
+        )))
+        .map(|line| Ok::<_, std::io::Error>(format!("{line}\n").into_bytes()));
+    let mut response = Response::new(StatusCode::Ok);
+    response.set_content_type(Mime::from_str(NDJSON_MIME).unwrap());
+    response.set_body(tide::Body::from_reader(lines.into_async_read(), None));
+    response
+}
+
+/// Streams the replies as `reply` server-sent events while they arrive, followed by an `end`
+/// event.
+fn to_sse_response(req: Request<State>, results: flume::Receiver<Reply>) -> Response {
+    tide::sse::upgrade(req, move |_req: Request<State>, sender: Sender| {
+        let results = results.clone();
+        async move {
+            while let Ok(reply) = results.recv_async().await {
+                let json_reply =
+                    serde_json::to_string(&result_to_json(reply.result())).unwrap_or("{}".into());
+                sender.send(SSE_REPLY_EVENT, json_reply, None).await?;
+            }
+            sender.send(SSE_END_EVENT, "", None).await?;
+            Ok(())
+        }
+    })
+}
+
 fn sample_to_html(sample: &Sample) -> String {
     format!(
         "<dt>{}</dt>\n<dd>{}</dd>\n",
@@ -216,21 +298,65 @@ async fn to_html_response(results: flume::Receiver<Reply>) -> Response {
     response(StatusCode::Ok, "text/html", &to_html(results).await)
 }
 
-async fn to_raw_response(results: flume::Receiver<Reply>) -> Response {
-    match results.recv_async().await {
-        Ok(reply) => match reply.result() {
-            Ok(sample) => response(
-                StatusCode::Ok,
-                Cow::from(sample.encoding()).as_ref(),
-                &sample.payload().try_to_string().unwrap_or_default(),
-            ),
-            Err(value) => response(
-                StatusCode::Ok,
-                Cow::from(value.encoding()).as_ref(),
-                &value.payload().try_to_string().unwrap_or_default(),
-            ),
-        },
-        Err(_) => response(StatusCode::Ok, "", ""),
+/// Answers with the payload of the first reply, converted to the first encoding of the `Accept`
+/// header if the plugin can produce it.
+///
+/// The schema of the accepted encoding is kept (e.g. `zenoh/serialized;i64`), but not its
+/// parameters (e.g. `application/json;q=0.9`). A payload that can't be converted is kept as is if
+/// any encoding is accepted.
+async fn to_raw_response(results: flume::Receiver<Reply>, accept: Option<&str>) -> Response {
+    let reply = match results.recv_async().await {
+        Ok(reply) => reply,
+        Err(_) => return response(StatusCode::Ok, "", ""),
+    };
+    let (payload, encoding) = match reply.result() {
+        Ok(sample) => (sample.payload(), sample.encoding()),
+        Err(value) => (value.payload(), value.encoding()),
+    };
+    let target = accept
+        .and_then(|accept| accept.split(',').next())
+        .map(|first| match first.trim().split_once(';') {
+            Some((mime, parameters)) if parameters.contains('=') => Encoding::from(mime),
+            _ => Encoding::from(first.trim()),
+        });
+    let any = accept.is_some_and(|accept| accept.contains("*/*"));
+    let converted = match &target {
+        Some(target) => convert::convert(payload, encoding, target),
+        None => Ok(None),
+    };
+    match (converted, target) {
+        (Ok(Some(converted)), Some(target)) => bytes_response(
+            StatusCode::Ok,
+            Cow::from(&target).as_ref(),
+            converted.to_bytes().into_owned(),
+        ),
+        (Err(e), _) if !any => response(StatusCode::NotAcceptable, "text/plain", &e),
+        _ => bytes_response(
+            StatusCode::Ok,
+            Cow::from(encoding).as_ref(),
+            payload.to_bytes().into_owned(),
+        ),
+    }
+}
+
+fn is_authorized(req: &Request<State>, action: AclMessage, key_expr: &KeyExpr) -> bool {
+    let username = req.ext::<AuthenticatedUser>().map(|user| user.0.as_str());
+    req.state().is_authorized(username, action, key_expr)
+}
+
+fn forbidden(action: AclMessage, key_expr: &KeyExpr) -> Response {
+    response(
+        StatusCode::Forbidden,
+        "text/plain",
+        &format!("Not authorized to {action:?} on {key_expr}"),
+    )
+}
+
+fn query_consolidation(parameters: &Parameters) -> QueryConsolidation {
+    if parameters.time_range().is_some() {
+        QueryConsolidation::from(zenoh::query::ConsolidationMode::None)
+    } else {
+        QueryConsolidation::from(zenoh::query::ConsolidationMode::Latest)
     }
 }
 
@@ -250,7 +376,6 @@ fn response<'a, S: Into<&'a str> + std::fmt::Debug>(
     tracing::trace!("Outgoing Response: {status} - {content_type:?} - body: {body}");
     let mut builder = Response::builder(status)
         .header("content-length", body.len().to_string())
-        .header("Access-Control-Allow-Origin", "*")
         .body(body);
     if let Ok(mime) = Mime::from_str(content_type.into()) {
         builder = builder.content_type(mime);
@@ -258,6 +383,18 @@ fn response<'a, S: Into<&'a str> + std::fmt::Debug>(
     builder.build()
 }
 
+fn bytes_response(status: StatusCode, content_type: &str, body: Vec<u8>) -> Response {
+    tracing::trace!(
+        "Outgoing Response: {status} - {content_type:?} - body: {} bytes",
+        body.len()
+    );
+    let mut builder = Response::builder(status).body(body);
+    if let Ok(mime) = Mime::from_str(content_type) {
+        builder = builder.content_type(mime);
+    }
+    builder.build()
+}
+
 #[cfg(feature = "dynamic_plugin")]
 zenoh_plugin_trait::declare_plugin!(RestPlugin);
 
@@ -291,6 +428,8 @@ impl Plugin for RestPlugin {
             .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
         WORKER_THREAD_NUM.store(conf.work_thread_num, Ordering::SeqCst);
         MAX_BLOCK_THREAD_NUM.store(conf.max_block_thread_num, Ordering::SeqCst);
+        // The configuration is read again by the server to set up the access control.
+        drop(runtime_conf);
 
         let task = run(runtime.clone(), conf.clone());
         let task =
@@ -355,9 +494,13 @@ fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     result
 }
 
-async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+async fn query(mut req: Request<State>) -> tide::Result<Response> {
     tracing::trace!("Incoming GET request: {:?}", req);
 
+    if ws::is_upgrade(&req) {
+        return ws::upgrade(req).await;
+    }
+
     let first_accept = match req.header("accept") {
         Some(accept) => accept[0]
             .to_string()
@@ -370,59 +513,14 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
             .to_string(),
         None => "application/json".to_string(),
     };
-    if first_accept == "text/event-stream" {
-        Ok(tide::sse::upgrade(
-            req,
-            move |req: Request<(Arc<Session>, String)>, sender: Sender| async move {
-                let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
-                    Ok(ke) => ke.into_owned(),
-                    Err(e) => {
-                        return Err(tide::Error::new(
-                            tide::StatusCode::BadRequest,
-                            anyhow::anyhow!("{}", e),
-                        ))
-                    }
-                };
-                spawn_runtime(async move {
-                    tracing::debug!("Subscribe to {} for SSE stream", key_expr);
-                    let sender = &sender;
-                    let sub = req.state().0.declare_subscriber(&key_expr).await.unwrap();
-                    loop {
-                        let sample = sub.recv_async().await.unwrap();
-                        let json_sample =
-                            serde_json::to_string(&sample_to_json(&sample)).unwrap_or("{}".into());
-
-                        match timeout(
-                            std::time::Duration::new(10, 0),
-                            sender.send(&sample.kind().to_string(), json_sample, None),
-                        )
-                        .await
-                        {
-                            Ok(Ok(_)) => {}
-                            Ok(Err(e)) => {
-                                tracing::debug!("SSE error ({})! Unsubscribe and terminate", e);
-                                if let Err(e) = sub.undeclare().await {
-                                    tracing::error!("Error undeclaring subscriber: {}", e);
-                                }
-                                break;
-                            }
-                            Err(_) => {
-                                tracing::debug!("SSE timeout! Unsubscribe and terminate",);
-                                if let Err(e) = sub.undeclare().await {
-                                    tracing::error!("Error undeclaring subscriber: {}", e);
-                                }
-                                break;
-                            }
-                        }
-                    }
-                });
-                Ok(())
-            },
-        ))
+    let stream_query =
+        Parameters::from(req.url().query().unwrap_or_default()).contains_key(QUERY_KEY);
+    if first_accept == SSE_MIME && !stream_query {
+        sse::subscribe(req).await
     } else {
         let body = req.body_bytes().await.unwrap_or_default();
         let url = req.url();
-        let key_expr = match path_to_key_expr(url.path(), &req.state().1) {
+        let key_expr = match path_to_key_expr(url.path(), &req.state().zid) {
             Ok(ke) => ke,
             Err(e) => {
                 return Ok(response(
@@ -432,20 +530,47 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
                 ))
             }
         };
+        if !is_authorized(&req, AclMessage::Query, &key_expr) {
+            return Ok(forbidden(AclMessage::Query, &key_expr));
+        }
         let query_part = url.query();
-        let parameters = Parameters::from(query_part.unwrap_or_default());
-        let consolidation = if parameters.time_range().is_some() {
-            QueryConsolidation::from(zenoh::query::ConsolidationMode::None)
-        } else {
-            QueryConsolidation::from(zenoh::query::ConsolidationMode::Latest)
+        let mut parameters = Parameters::from(query_part.unwrap_or_default());
+        let options = match RequestOptions::extract(&req, &mut parameters) {
+            Ok(options) => options,
+            Err(e) => {
+                return Ok(response(
+                    StatusCode::BadRequest,
+                    "text/plain",
+                    &e.to_string(),
+                ))
+            }
         };
-        let raw = parameters.contains_key(RAW_KEY);
+        let streaming = first_accept == SSE_MIME || first_accept == NDJSON_MIME;
+        let consolidation = match options.consolidation {
+            Some(consolidation) => QueryConsolidation::from(consolidation),
+            // The latest consolidation holds back the replies until the query completes
+            None if streaming && parameters.time_range().is_none() => {
+                QueryConsolidation::from(zenoh::query::ConsolidationMode::Monotonic)
+            }
+            None => query_consolidation(&parameters),
+        };
+        // The keys only meaningful to the plugin are not forwarded to the queryables
+        let raw = parameters.remove(RAW_KEY).is_some();
+        parameters.remove(QUERY_KEY);
         let mut query = req
             .state()
-            .0
+            .session
             .get(Selector::borrowed(&key_expr, &parameters))
-            .consolidation(consolidation)
-            .with(flume::unbounded());
+            .consolidation(consolidation);
+        if let Some(target) = options.target {
+            query = query.target(target);
+        }
+        if let Some(timeout) = options.timeout {
+            query = query.timeout(timeout);
+        }
+        query = options.apply_qos(query);
+        query = options.apply_attachment(query);
+        let mut query = query.with(flume::unbounded());
         if !body.is_empty() {
             let encoding: Encoding = req
                 .content_type()
@@ -456,9 +581,16 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
         match query.await {
             Ok(receiver) => {
                 if raw {
-                    Ok(to_raw_response(receiver).await)
+                    let accept = req.header("accept").map(|accept| accept[0].as_str());
+                    Ok(to_raw_response(receiver, accept).await)
+                } else if first_accept == SSE_MIME {
+                    Ok(to_sse_response(req, receiver))
+                } else if streaming {
+                    Ok(to_ndjson_response(receiver))
                 } else if first_accept == "text/html" {
                     Ok(to_html_response(receiver).await)
+                } else if first_accept == CBOR_MIME {
+                    Ok(to_cbor_response(receiver).await)
                 } else {
                     Ok(to_json_response(receiver).await)
                 }
@@ -472,11 +604,11 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
     }
 }
 
-async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+async fn write(mut req: Request<State>) -> tide::Result<Response> {
     tracing::trace!("Incoming PUT request: {:?}", req);
     match req.body_bytes().await {
         Ok(bytes) => {
-            let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
+            let key_expr = match path_to_key_expr(req.url().path(), &req.state().zid) {
                 Ok(ke) => ke,
                 Err(e) => {
                     return Ok(response(
@@ -487,16 +619,46 @@ async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
                 }
             };
 
+            let kind = method_to_kind(req.method());
+            let action = match kind {
+                SampleKind::Put => AclMessage::Put,
+                SampleKind::Delete => AclMessage::Delete,
+            };
+            if !is_authorized(&req, action, &key_expr) {
+                return Ok(forbidden(action, &key_expr));
+            }
+
             let encoding: Encoding = req
                 .content_type()
                 .map(|m| Encoding::from(m.to_string()))
                 .unwrap_or_default();
 
-            // @TODO: Define the right congestion control value
-            let session = &req.state().0;
-            let res = match method_to_kind(req.method()) {
-                SampleKind::Put => session.put(&key_expr, bytes).encoding(encoding).await,
-                SampleKind::Delete => session.delete(&key_expr).await,
+            let mut parameters = Parameters::from(req.url().query().unwrap_or_default());
+            let options = match RequestOptions::extract(&req, &mut parameters)
+                .and_then(|options| options.check_publication().map(|()| options))
+            {
+                Ok(options) => options,
+                Err(e) => {
+                    return Ok(response(
+                        StatusCode::BadRequest,
+                        "text/plain",
+                        &e.to_string(),
+                    ))
+                }
+            };
+
+            let session = &req.state().session;
+            let res = match kind {
+                SampleKind::Put => {
+                    let put = session.put(&key_expr, bytes).encoding(encoding);
+                    let put = options.apply_timestamp(options.apply_attachment(put));
+                    options.apply_qos(put).await
+                }
+                SampleKind::Delete => {
+                    let delete = session.delete(&key_expr);
+                    let delete = options.apply_timestamp(options.apply_attachment(delete));
+                    options.apply_qos(delete).await
+                }
             };
             match res {
                 Ok(_) => Ok(Response::new(StatusCode::Ok)),
@@ -522,9 +684,30 @@ pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
     zenoh::init_log_from_env_or("error");
 
     let zid = runtime.zid().to_string();
-    let session = zenoh::session::init(runtime).await.unwrap();
-
-    let mut app = Server::with_state((Arc::new(session), zid));
+    let acl = UserAclEnforcer::new(runtime.config().lock().access_control())?;
+    let session = zenoh::session::init(runtime.clone()).await.unwrap();
+
+    let mut app = Server::with_state(State {
+        session: Arc::new(session),
+        zid,
+        acl: Arc::new(acl),
+        config: Arc::new(conf.clone()),
+        runtime,
+        leases: Arc::default(),
+    });
+
+    // NOTE: Credentials are only allowed for explicitly listed origins, browsers rejecting them
+    //       when any origin is allowed.
+    let any_origin = conf
+        .cors
+        .allowed_origins
+        .iter()
+        .any(|origin| origin == config::ANY_ORIGIN);
+    let allowed_origin = if any_origin {
+        tide::security::Origin::from(config::ANY_ORIGIN)
+    } else {
+        tide::security::Origin::from(conf.cors.allowed_origins.clone())
+    };
     app.with(
         tide::security::CorsMiddleware::new()
             .allow_methods(
@@ -532,10 +715,34 @@ pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
                     .parse::<http_types::headers::HeaderValue>()
                     .unwrap(),
             )
-            .allow_origin(tide::security::Origin::from("*"))
-            .allow_credentials(false),
+            .allow_headers(
+                "*, Authorization"
+                    .parse::<http_types::headers::HeaderValue>()
+                    .unwrap(),
+            )
+            .allow_origin(allowed_origin)
+            .allow_credentials(!any_origin),
     );
+    if let Some(auth) = &conf.auth {
+        app.with(Authenticator::new(auth)?);
+    }
 
+    let openapi = openapi::document(&conf).to_string();
+    app.at(openapi::OPENAPI_PATH).get(move |_| {
+        let openapi = openapi.clone();
+        async move { Ok(response(StatusCode::Ok, "application/json", &openapi)) }
+    });
+    app.at(&format!("{}/*", liveliness::TOKENS_PATH))
+        .get(liveliness::get_tokens)
+        .post(liveliness::declare_token);
+    app.at(&format!("{}/:id", liveliness::LEASES_PATH))
+        .put(liveliness::renew_lease)
+        .delete(liveliness::undeclare_token);
+    app.at(admin::CONFIG_PATH).get(admin::get_config);
+    app.at(&format!("{}/*", admin::CONFIG_PATH))
+        .get(admin::get_config)
+        .put(admin::put_config)
+        .delete(admin::delete_config);
     app.at("/")
         .get(query)
         .post(query)
@@ -549,7 +756,12 @@ pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
         .patch(write)
         .delete(write);
 
-    if let Err(e) = app.listen(conf.http_port).await {
+    if let Some(tls) = &conf.tls {
+        if let Err(e) = tls::listen(app, &conf.http_port, tls).await {
+            tracing::error!("Unable to start https server for REST: {:?}", e);
+            return Err(e);
+        }
+    } else if let Err(e) = app.listen(conf.http_port).await {
         tracing::error!("Unable to start http server for REST: {:?}", e);
         return Err(e.into());
     }
@@ -0,0 +1,266 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The liveliness tokens of the HTTP clients, which can't keep a Zenoh session open to announce
+//! their presence:
+//!
+//! - `GET /@/liveliness/tokens/<key_expr>` answers with the key expressions of the alive tokens
+//!   intersecting `<key_expr>`;
+//! - `POST /@/liveliness/tokens/<key_expr>[?_lease=<seconds>]` declares a token, answering with
+//!   the id of its lease;
+//! - `PUT /@/liveliness/leases/<id>` renews the lease of a token, which is undeclared if its lease
+//!   is not renewed in time;
+//! - `DELETE /@/liveliness/leases/<id>` undeclares a token.
+//!
+//! The leases are owned by the user who declared them, if authenticated.
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
+
+use serde::Serialize;
+use tide::{Request, Response, StatusCode};
+use tokio::{
+    sync::Mutex,
+    time::{sleep_until, Instant},
+};
+use zenoh::{
+    internal::access_control::AclMessage,
+    key_expr::KeyExpr,
+    liveliness::LivelinessToken,
+    query::{Parameters, Reply},
+};
+
+use crate::{
+    auth::AuthenticatedUser, forbidden, is_authorized, options::RequestOptions, path_to_key_expr,
+    response, spawn_runtime, State,
+};
+
+pub(crate) const TOKENS_PATH: &str = "/@/liveliness/tokens";
+pub(crate) const LEASES_PATH: &str = "/@/liveliness/leases";
+pub(crate) const LEASE_KEY: &str = "_lease";
+
+struct Lease {
+    key_expr: KeyExpr<'static>,
+    username: Option<String>,
+    duration: Duration,
+    deadline: Instant,
+    _token: LivelinessToken,
+}
+
+/// The leases of the tokens declared through the REST API, by id.
+#[derive(Default)]
+pub(crate) struct Leases(Mutex<HashMap<String, Lease>>);
+
+#[derive(Serialize)]
+struct LeaseInfo<'a> {
+    id: &'a str,
+    key_expr: &'a str,
+    lease: u64,
+}
+
+impl LeaseInfo<'_> {
+    fn response(&self, status: StatusCode) -> Response {
+        let mut response = response(
+            status,
+            "application/json",
+            &serde_json::to_string(self).unwrap_or_default(),
+        );
+        response.insert_header("Location", format!("{LEASES_PATH}/{}", self.id));
+        response
+    }
+}
+
+fn username(req: &Request<State>) -> Option<String> {
+    req.ext::<AuthenticatedUser>().map(|user| user.0.clone())
+}
+
+fn bad_request(message: &str) -> Response {
+    response(StatusCode::BadRequest, "text/plain", message)
+}
+
+fn not_found(id: &str) -> Response {
+    response(
+        StatusCode::NotFound,
+        "text/plain",
+        &format!("No lease with id '{id}'"),
+    )
+}
+
+fn tokens_key_expr(req: &Request<State>) -> Result<KeyExpr<'static>, Response> {
+    let path = req.url().path();
+    let suffix = path.strip_prefix(TOKENS_PATH).unwrap_or(path);
+    path_to_key_expr(suffix, &req.state().zid)
+        .map(KeyExpr::into_owned)
+        .map_err(|e| bad_request(&e.to_string()))
+}
+
+/// Answers with the key expressions of the alive tokens intersecting the key expression of the
+/// path.
+pub(crate) async fn get_tokens(req: Request<State>) -> tide::Result<Response> {
+    let key_expr = match tokens_key_expr(&req) {
+        Ok(key_expr) => key_expr,
+        Err(response) => return Ok(response),
+    };
+    if !is_authorized(&req, AclMessage::LivelinessQuery, &key_expr) {
+        return Ok(forbidden(AclMessage::LivelinessQuery, &key_expr));
+    }
+    let mut parameters = Parameters::from(req.url().query().unwrap_or_default()).into_owned();
+    let options = match RequestOptions::extract(&req, &mut parameters) {
+        Ok(options) => options,
+        Err(e) => return Ok(bad_request(&e.to_string())),
+    };
+
+    let mut get = req.state().session.liveliness().get(&key_expr);
+    if let Some(timeout) = options.timeout {
+        get = get.timeout(timeout);
+    }
+    let replies = match get.await {
+        Ok(replies) => replies,
+        Err(e) => {
+            return Ok(response(
+                StatusCode::InternalServerError,
+                "text/plain",
+                &e.to_string(),
+            ))
+        }
+    };
+    let mut keys = Vec::new();
+    while let Ok(reply) = replies.recv_async().await {
+        match Reply::into_result(reply) {
+            Ok(sample) => keys.push(sample.key_expr().to_string()),
+            Err(e) => tracing::debug!("Error reply to liveliness query: {:?}", e),
+        }
+    }
+    Ok(response(
+        StatusCode::Ok,
+        "application/json",
+        &serde_json::to_string(&keys).unwrap_or_default(),
+    ))
+}
+
+/// Declares a token on the key expression of the path, leased for the `_lease` parameter or else
+/// the default lease of the configuration.
+pub(crate) async fn declare_token(req: Request<State>) -> tide::Result<Response> {
+    let key_expr = match tokens_key_expr(&req) {
+        Ok(key_expr) => key_expr,
+        Err(response) => return Ok(response),
+    };
+    if !is_authorized(&req, AclMessage::LivelinessToken, &key_expr) {
+        return Ok(forbidden(AclMessage::LivelinessToken, &key_expr));
+    }
+    let state = req.state();
+    let conf = &state.config.liveliness;
+    let lease = match Parameters::from(req.url().query().unwrap_or_default()).get(LEASE_KEY) {
+        Some(lease) => match u64::from_str(lease) {
+            Ok(lease) if lease > 0 && lease <= conf.max_lease => lease,
+            _ => {
+                return Ok(bad_request(&format!(
+                    "Invalid {LEASE_KEY} '{lease}': expected a number of seconds from 1 to {}",
+                    conf.max_lease
+                )))
+            }
+        },
+        None => conf.lease.clamp(1, conf.max_lease.max(1)),
+    };
+
+    let token = match state.session.liveliness().declare_token(&key_expr).await {
+        Ok(token) => token,
+        Err(e) => {
+            return Ok(response(
+                StatusCode::InternalServerError,
+                "text/plain",
+                &e.to_string(),
+            ))
+        }
+    };
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let duration = Duration::from_secs(lease);
+    let response = LeaseInfo {
+        id: &id,
+        key_expr: key_expr.as_str(),
+        lease,
+    }
+    .response(StatusCode::Created);
+    tracing::debug!("Declare liveliness token {} with lease {}", key_expr, id);
+    state.leases.0.lock().await.insert(
+        id.clone(),
+        Lease {
+            key_expr,
+            username: username(&req),
+            duration,
+            deadline: Instant::now() + duration,
+            _token: token,
+        },
+    );
+    spawn_runtime(expire(state.leases.clone(), id));
+    Ok(response)
+}
+
+/// Undeclares the token of a lease when its deadline passes without renewal.
+async fn expire(leases: Arc<Leases>, id: String) {
+    loop {
+        let deadline = match leases.0.lock().await.get(&id) {
+            Some(lease) => lease.deadline,
+            None => return,
+        };
+        sleep_until(deadline).await;
+        let mut leases = leases.0.lock().await;
+        if leases
+            .get(&id)
+            .is_some_and(|lease| lease.deadline <= Instant::now())
+        {
+            if let Some(lease) = leases.remove(&id) {
+                tracing::debug!(
+                    "Lease {} of liveliness token {} expired",
+                    id,
+                    lease.key_expr
+                );
+            }
+            return;
+        }
+    }
+}
+
+/// Renews the lease of the path.
+pub(crate) async fn renew_lease(req: Request<State>) -> tide::Result<Response> {
+    let id = req.param("id")?;
+    let username = username(&req);
+    let mut leases = req.state().leases.0.lock().await;
+    match leases.get_mut(id) {
+        Some(lease) if lease.username == username => {
+            lease.deadline = Instant::now() + lease.duration;
+            Ok(LeaseInfo {
+                id,
+                key_expr: lease.key_expr.as_str(),
+                lease: lease.duration.as_secs(),
+            }
+            .response(StatusCode::Ok))
+        }
+        _ => Ok(not_found(id)),
+    }
+}
+
+/// Undeclares the token of the lease of the path.
+pub(crate) async fn undeclare_token(req: Request<State>) -> tide::Result<Response> {
+    let id = req.param("id")?;
+    let username = username(&req);
+    let mut leases = req.state().leases.0.lock().await;
+    match leases.get(id) {
+        Some(lease) if lease.username == username => {
+            let lease = leases.remove(id);
+            drop(leases);
+            drop(lease);
+            Ok(Response::new(StatusCode::NoContent))
+        }
+        _ => Ok(not_found(id)),
+    }
+}
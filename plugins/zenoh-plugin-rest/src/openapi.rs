@@ -0,0 +1,488 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde_json::{json, Map, Value};
+
+use crate::{
+    admin::CONFIG_PATH,
+    config::Config,
+    liveliness::{LEASES_PATH, LEASE_KEY, TOKENS_PATH},
+    options::{
+        ATTACHMENT, CONGESTION_CONTROL, CONSOLIDATION, EXPRESS, PRIORITY, TARGET, TIMEOUT,
+        TIMESTAMP,
+    },
+    sse::HISTORY_KEY,
+    NDJSON_MIME, QUERY_KEY, RAW_KEY, SSE_MIME,
+};
+
+/// The path of the OpenAPI document describing the REST API.
+pub(crate) const OPENAPI_PATH: &str = "/@/openapi.json";
+
+const QUERY_OPTIONS: [(&str, &str, &str); 3] = [
+    (
+        TARGET,
+        "The queryables targeted by the query: `BestMatching`, `All` or `AllComplete`.",
+        "string",
+    ),
+    (
+        CONSOLIDATION,
+        "The consolidation of the replies: `Auto`, `None`, `Monotonic` or `Latest`.",
+        "string",
+    ),
+    (
+        TIMEOUT,
+        "The timeout of the query, in milliseconds.",
+        "integer",
+    ),
+];
+
+const QOS_OPTIONS: [(&str, &str, &str); 4] = [
+    (
+        PRIORITY,
+        "`RealTime`, `InteractiveHigh`, `InteractiveLow`, `DataHigh`, `Data`, `DataLow`, \
+         `Background`, or the corresponding number from 1 to 7.",
+        "string",
+    ),
+    (CONGESTION_CONTROL, "`Drop` or `Block`.", "string"),
+    (EXPRESS, "Whether to batch the message.", "boolean"),
+    (ATTACHMENT, "The attachment of the message.", "string"),
+];
+
+const PUBLICATION_OPTIONS: [(&str, &str, &str); 1] = [(
+    TIMESTAMP,
+    "The timestamp of the publication, formatted as the timestamps of the samples.",
+    "string",
+)];
+
+/// Returns the reserved query parameter and the header setting an option.
+fn option_parameters(name: &str, description: &str, schema: &str) -> [Value; 2] {
+    let header = name
+        .split('_')
+        .map(|word| word[..1].to_uppercase() + &word[1..])
+        .collect::<Vec<_>>()
+        .join("-");
+    [
+        json!({
+            "name": format!("_{name}"),
+            "in": "query",
+            "description": description,
+            "schema": { "type": schema },
+        }),
+        json!({
+            "name": format!("X-Zenoh-{header}"),
+            "in": "header",
+            "description": format!("{description} Overridden by the `_{name}` parameter."),
+            "schema": { "type": schema },
+        }),
+    ]
+}
+
+fn flag_parameter(name: &str, description: &str) -> Value {
+    json!({
+        "name": name,
+        "in": "query",
+        "description": description,
+        "allowEmptyValue": true,
+        "schema": { "type": "boolean" },
+    })
+}
+
+fn query_operation(summary: &str, with_payload: bool) -> Value {
+    let mut parameters: Vec<Value> = QUERY_OPTIONS
+        .iter()
+        .chain(QOS_OPTIONS.iter())
+        .flat_map(|(name, description, schema)| option_parameters(name, description, schema))
+        .collect();
+    parameters.extend([
+        flag_parameter(
+            RAW_KEY,
+            "Answer with the payload of the first reply instead of the list of the replies, \
+             converted to the accepted encoding when the plugin can convert it.",
+        ),
+        flag_parameter(
+            QUERY_KEY,
+            "With `Accept: text/event-stream`, stream the replies of a query instead of \
+             subscribing.",
+        ),
+        flag_parameter(
+            HISTORY_KEY,
+            "With `Accept: text/event-stream`, first send the samples returned by a query on the \
+             key expression.",
+        ),
+        json!({
+            "name": "_time",
+            "in": "query",
+            "description": "The time range of the samples to query, e.g. `[now(-2h)..now()]`.",
+            "schema": { "type": "string" },
+        }),
+        json!({
+            "name": "Last-Event-ID",
+            "in": "header",
+            "description": "With `Accept: text/event-stream`, resume the stream of a \
+                            subscription from the id of its last event: the timestamps of \
+                            the last samples sent from each source, separated by commas.",
+            "schema": { "type": "string" },
+        }),
+    ]);
+
+    let mut operation = json!({
+        "summary": summary,
+        "description": "The representation of the replies depends on the `Accept` header:\n\
+            - `application/json` (default): the list of the replies, whose values are decoded \
+              when possible and base64-encoded otherwise\n\
+            - `application/cbor`: the same list, encoded in CBOR\n\
+            - `application/x-ndjson`: one reply per line as it arrives, the last line being \
+              `{\"end\":true}`\n\
+            - `text/event-stream`: the samples of a subscription, or with `_query` the replies \
+              as `reply` events followed by an `end` event\n\
+            - `text/html`: the replies as a description list\n\n\
+            A WebSocket upgrade request opens the WebSocket JSON API.",
+        "parameters": parameters,
+        "responses": {
+            "200": {
+                "description": "The replies.",
+                "content": {
+                    "application/json": {
+                        "schema": {
+                            "type": "array",
+                            "items": { "$ref": "#/components/schemas/Sample" },
+                        },
+                    },
+                    "application/cbor": {
+                        "schema": {
+                            "type": "array",
+                            "items": { "$ref": "#/components/schemas/Sample" },
+                        },
+                    },
+                    NDJSON_MIME: { "schema": { "$ref": "#/components/schemas/Sample" } },
+                    SSE_MIME: { "schema": { "type": "string" } },
+                    "text/html": { "schema": { "type": "string" } },
+                },
+            },
+            "101": { "description": "The connection is upgraded to the WebSocket JSON API." },
+            "400": { "$ref": "#/components/responses/BadRequest" },
+            "403": { "$ref": "#/components/responses/Forbidden" },
+            "406": {
+                "description": "The payload can't be converted to the accepted encoding.",
+            },
+            "500": { "description": "The query failed." },
+        },
+    });
+    if with_payload {
+        operation["requestBody"] = json!({
+            "description": "The payload of the query, whose encoding is the `Content-Type`.",
+            "content": { "*/*": { "schema": {} } },
+        });
+    }
+    operation
+}
+
+fn write_operation(summary: &str, with_payload: bool) -> Value {
+    let parameters: Vec<Value> = QOS_OPTIONS
+        .iter()
+        .chain(PUBLICATION_OPTIONS.iter())
+        .flat_map(|(name, description, schema)| option_parameters(name, description, schema))
+        .collect();
+    let mut operation = json!({
+        "summary": summary,
+        "parameters": parameters,
+        "responses": {
+            "200": { "description": "The sample is published." },
+            "400": { "$ref": "#/components/responses/BadRequest" },
+            "403": { "$ref": "#/components/responses/Forbidden" },
+        },
+    });
+    if with_payload {
+        operation["requestBody"] = json!({
+            "description": "The payload of the publication, whose encoding is the \
+                            `Content-Type`.",
+            "required": true,
+            "content": { "*/*": { "schema": {} } },
+        });
+    }
+    operation
+}
+
+fn path_parameter(name: &str, description: &str) -> Value {
+    json!({
+        "name": name,
+        "in": "path",
+        "required": true,
+        "description": description,
+        "schema": { "type": "string" },
+    })
+}
+
+fn liveliness_paths(paths: &mut Map<String, Value>) {
+    let lease = json!({
+        "description": "The lease of the token.",
+        "headers": {
+            "Location": {
+                "description": "The path of the lease.",
+                "schema": { "type": "string" },
+            },
+        },
+        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Lease" } } },
+    });
+    let not_found = json!({ "description": "There is no lease with this id for the user." });
+    paths.insert(
+        format!("{TOKENS_PATH}/{{key_expr}}"),
+        json!({
+            "parameters": [path_parameter("key_expr", "The key expression of the tokens.")],
+            "get": {
+                "summary": "The alive liveliness tokens",
+                "parameters": option_parameters(TIMEOUT, QUERY_OPTIONS[2].1, "integer"),
+                "responses": {
+                    "200": {
+                        "description": "The key expressions of the tokens.",
+                        "content": {
+                            "application/json": {
+                                "schema": { "type": "array", "items": { "type": "string" } },
+                            },
+                        },
+                    },
+                    "400": { "$ref": "#/components/responses/BadRequest" },
+                    "403": { "$ref": "#/components/responses/Forbidden" },
+                },
+            },
+            "post": {
+                "summary": "Declare a liveliness token, undeclared if its lease is not renewed",
+                "parameters": [{
+                    "name": LEASE_KEY,
+                    "in": "query",
+                    "description": "The lease of the token, in seconds.",
+                    "schema": { "type": "integer" },
+                }],
+                "responses": {
+                    "201": lease,
+                    "400": { "$ref": "#/components/responses/BadRequest" },
+                    "403": { "$ref": "#/components/responses/Forbidden" },
+                },
+            },
+        }),
+    );
+    paths.insert(
+        format!("{LEASES_PATH}/{{id}}"),
+        json!({
+            "parameters": [path_parameter("id", "The id of the lease.")],
+            "put": {
+                "summary": "Renew the lease of a liveliness token",
+                "responses": { "200": lease, "404": not_found },
+            },
+            "delete": {
+                "summary": "Undeclare a liveliness token",
+                "responses": {
+                    "204": { "description": "The token is undeclared." },
+                    "404": not_found,
+                },
+            },
+        }),
+    );
+}
+
+fn config_paths(paths: &mut Map<String, Value>) {
+    let value = json!({
+        "description": "The value of the configuration, without its private values.",
+        "content": { "application/json": { "schema": {} } },
+    });
+    let forbidden = json!({
+        "description": "The user is not an admin, or the access control policy or the \
+                        adminspace permissions of the router deny the operation.",
+    });
+    paths.insert(
+        CONFIG_PATH.into(),
+        json!({
+            "get": {
+                "summary": "The configuration of the router",
+                "responses": { "200": value, "403": forbidden },
+            },
+        }),
+    );
+    paths.insert(
+        format!("{CONFIG_PATH}/{{path}}"),
+        json!({
+            "parameters": [
+                path_parameter("path", "The path of the value, e.g. `plugins/rest/http_port`."),
+            ],
+            "get": {
+                "summary": "A value of the configuration of the router",
+                "responses": {
+                    "200": value,
+                    "403": forbidden,
+                    "404": { "description": "There is no value at this path." },
+                },
+            },
+            "put": {
+                "summary": "Set a value of the configuration of the router",
+                "requestBody": {
+                    "description": "The JSON5 value.",
+                    "required": true,
+                    "content": { "application/json": { "schema": {} } },
+                },
+                "responses": {
+                    "200": value,
+                    "400": { "description": "The value is invalid." },
+                    "403": forbidden,
+                },
+            },
+            "delete": {
+                "summary": "Remove a value of the configuration of the plugins",
+                "responses": {
+                    "204": { "description": "The value is removed." },
+                    "400": { "description": "The value can't be removed." },
+                    "403": forbidden,
+                },
+            },
+        }),
+    );
+}
+
+/// Returns the OpenAPI document describing the REST API as configured by `conf`.
+pub(crate) fn document(conf: &Config) -> Value {
+    let mut document = json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Zenoh REST API",
+            "description": "Queries, subscriptions and publications on the key expressions of \
+                            a Zenoh network. The key expression is the path of the URL, \
+                            `@/local` standing for the adminspace of the local router.",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/{key_expr}": {
+                "parameters": [{
+                    "name": "key_expr",
+                    "in": "path",
+                    "required": true,
+                    "description": "The key expression, which may contain `/`.",
+                    "schema": { "type": "string" },
+                }],
+                "get": query_operation("Query, subscribe or open a WebSocket", false),
+                "post": query_operation("Query with a payload", true),
+                "put": write_operation("Publish a sample", true),
+                "patch": write_operation("Publish a sample", true),
+                "delete": write_operation("Publish a deletion", false),
+            },
+            OPENAPI_PATH: {
+                "get": {
+                    "summary": "This document",
+                    "responses": {
+                        "200": {
+                            "description": "The OpenAPI document of the REST API.",
+                            "content": { "application/json": { "schema": { "type": "object" } } },
+                        },
+                    },
+                },
+            },
+        },
+        "components": {
+            "schemas": {
+                "Sample": {
+                    "type": "object",
+                    "required": ["key", "value", "encoding"],
+                    "properties": {
+                        "key": {
+                            "type": "string",
+                            "description": "The key expression of the sample, `ERROR` for an \
+                                            error reply.",
+                        },
+                        "value": {
+                            "description": "The decoded payload (JSON, CBOR, text and \
+                                            zenoh-ext serialized primitives), base64-encoded \
+                                            if it can't be decoded.",
+                        },
+                        "encoding": { "type": "string" },
+                        "timestamp": { "type": "string", "nullable": true },
+                    },
+                },
+                "Lease": {
+                    "type": "object",
+                    "required": ["id", "key_expr", "lease"],
+                    "properties": {
+                        "id": { "type": "string" },
+                        "key_expr": { "type": "string" },
+                        "lease": { "type": "integer", "description": "In seconds." },
+                    },
+                },
+            },
+            "responses": {
+                "BadRequest": {
+                    "description": "The key expression or an option is invalid.",
+                    "content": { "text/plain": { "schema": { "type": "string" } } },
+                },
+                "Forbidden": {
+                    "description": "The access control policy of the router denies the \
+                                    operation to the user.",
+                    "content": { "text/plain": { "schema": { "type": "string" } } },
+                },
+            },
+        },
+    });
+    if let Some(paths) = document["paths"].as_object_mut() {
+        liveliness_paths(paths);
+        config_paths(paths);
+    }
+
+    if conf.auth.is_some() {
+        let mut schemes = Map::new();
+        schemes.insert("basic".into(), json!({ "type": "http", "scheme": "basic" }));
+        schemes.insert(
+            "bearer".into(),
+            json!({ "type": "http", "scheme": "bearer" }),
+        );
+        document["components"]["securitySchemes"] = Value::Object(schemes);
+        document["security"] = json!([{ "basic": [] }, { "bearer": [] }]);
+    }
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{document, OPENAPI_PATH};
+    use crate::config::Config;
+
+    #[test]
+    fn test_document() {
+        let conf = serde_json::from_str::<Config>(r#"{"http_port": 8080}"#).unwrap();
+        let openapi = document(&conf);
+        let operations = &openapi["paths"]["/{key_expr}"];
+        for method in ["get", "post", "put", "patch", "delete"] {
+            assert!(operations[method].is_object(), "{method}");
+        }
+        assert!(openapi["paths"][OPENAPI_PATH].is_object());
+        assert!(openapi["paths"]["/@/liveliness/tokens/{key_expr}"]["post"].is_object());
+        assert!(openapi["paths"]["/@/liveliness/leases/{id}"]["put"].is_object());
+        assert!(openapi["paths"]["/@/config/{path}"]["put"].is_object());
+        let parameters = operations["get"]["parameters"].as_array().unwrap();
+        for name in [
+            "_target",
+            "X-Zenoh-Target",
+            "X-Zenoh-Congestion-Control",
+            "_raw",
+        ] {
+            assert!(
+                parameters.iter().any(|parameter| parameter["name"] == name),
+                "{name}"
+            );
+        }
+        assert!(openapi.get("security").is_none());
+
+        let conf = serde_json::from_str::<Config>(
+            r#"{"http_port": 8080, "auth": { "users": [{ "username": "a", "password": "b" }] }}"#,
+        )
+        .unwrap();
+        let openapi = document(&conf);
+        assert!(openapi["components"]["securitySchemes"]["basic"].is_object());
+        assert!(openapi["security"].is_array());
+    }
+}
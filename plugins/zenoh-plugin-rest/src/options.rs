@@ -0,0 +1,298 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{str::FromStr, time::Duration};
+
+use serde::de::DeserializeOwned;
+use tide::Request;
+use zenoh::{
+    internal::{
+        bail,
+        traits::{QoSBuilderTrait, SampleBuilderTrait, TimestampBuilderTrait},
+        zerror,
+    },
+    qos::{CongestionControl, Priority},
+    query::{ConsolidationMode, Parameters, QueryTarget},
+    time::Timestamp,
+    Result as ZResult,
+};
+
+pub(crate) const TARGET: &str = "target";
+pub(crate) const CONSOLIDATION: &str = "consolidation";
+pub(crate) const TIMEOUT: &str = "timeout";
+pub(crate) const PRIORITY: &str = "priority";
+pub(crate) const CONGESTION_CONTROL: &str = "congestion_control";
+pub(crate) const EXPRESS: &str = "express";
+pub(crate) const TIMESTAMP: &str = "timestamp";
+pub(crate) const ATTACHMENT: &str = "attachment";
+
+/// The options of a request, each one being set either through a reserved query parameter
+/// prefixed with `_` (e.g. `_congestion_control=Block`) or through a header prefixed with
+/// `X-Zenoh-` (e.g. `X-Zenoh-Congestion-Control: Block`), the query parameter taking precedence.
+///
+/// - `target`: `BestMatching`, `All` or `AllComplete` (queries only)
+/// - `consolidation`: `Auto`, `None`, `Monotonic` or `Latest` (queries only)
+/// - `timeout`: in milliseconds (queries only)
+/// - `priority`: `RealTime`, `InteractiveHigh`, `InteractiveLow`, `DataHigh`, `Data`, `DataLow`,
+///   `Background` or the corresponding number from 1 to 7
+/// - `congestion_control`: `Drop` or `Block`
+/// - `express`: `true` or `false`
+/// - `timestamp`: formatted as the timestamps of the samples returned by the plugin (publications
+///   only)
+/// - `attachment`: sent as is
+///
+/// A publication carrying an option that only applies to queries is rejected.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct RequestOptions {
+    pub(crate) target: Option<QueryTarget>,
+    pub(crate) consolidation: Option<ConsolidationMode>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) priority: Option<Priority>,
+    pub(crate) congestion_control: Option<CongestionControl>,
+    pub(crate) express: Option<bool>,
+    pub(crate) timestamp: Option<Timestamp>,
+    pub(crate) attachment: Option<String>,
+}
+
+impl RequestOptions {
+    /// Extracts the options of the request, removing their query parameters from `parameters` so
+    /// that they are not forwarded to the queryables.
+    pub(crate) fn extract<State>(
+        req: &Request<State>,
+        parameters: &mut Parameters,
+    ) -> ZResult<Self> {
+        Self::extract_with(parameters, |name| {
+            let header = format!("x-zenoh-{}", name.replace('_', "-"));
+            req.header(header.as_str())
+                .map(|values| values.last().as_str().to_string())
+        })
+    }
+
+    /// Extracts the options set through the reserved query parameters only, for the requests that
+    /// do not have headers (e.g. the operations sent over a WebSocket).
+    pub(crate) fn from_parameters(parameters: &mut Parameters) -> ZResult<Self> {
+        Self::extract_with(parameters, |_| None)
+    }
+
+    fn extract_with<H: Fn(&str) -> Option<String>>(
+        parameters: &mut Parameters,
+        header: H,
+    ) -> ZResult<Self> {
+        let mut option = |name: &str| -> Option<String> {
+            parameters
+                .remove(format!("_{name}"))
+                .or_else(|| header(name))
+        };
+
+        Ok(Self {
+            target: option(TARGET)
+                .map(|value| parse_variant(TARGET, &value))
+                .transpose()?,
+            consolidation: option(CONSOLIDATION)
+                .map(|value| parse_variant(CONSOLIDATION, &value))
+                .transpose()?,
+            timeout: option(TIMEOUT)
+                .map(|value| parse::<u64>(TIMEOUT, &value).map(Duration::from_millis))
+                .transpose()?,
+            priority: option(PRIORITY)
+                .map(|value| parse_priority(&value))
+                .transpose()?,
+            congestion_control: option(CONGESTION_CONTROL)
+                .map(|value| parse_variant(CONGESTION_CONTROL, &value))
+                .transpose()?,
+            express: option(EXPRESS)
+                .map(|value| parse(EXPRESS, &value))
+                .transpose()?,
+            timestamp: option(TIMESTAMP)
+                .map(|value| parse(TIMESTAMP, &value))
+                .transpose()?,
+            attachment: option(ATTACHMENT),
+        })
+    }
+
+    /// Fails if options that only apply to queries are set, as they would be ignored by a
+    /// publication.
+    pub(crate) fn check_publication(&self) -> ZResult<()> {
+        for (name, is_set) in [
+            (TARGET, self.target.is_some()),
+            (CONSOLIDATION, self.consolidation.is_some()),
+            (TIMEOUT, self.timeout.is_some()),
+        ] {
+            if is_set {
+                bail!("The {name} option only applies to queries");
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn apply_qos<B: QoSBuilderTrait>(&self, mut builder: B) -> B {
+        if let Some(priority) = self.priority {
+            builder = builder.priority(priority);
+        }
+        if let Some(congestion_control) = self.congestion_control {
+            builder = builder.congestion_control(congestion_control);
+        }
+        if let Some(express) = self.express {
+            builder = builder.express(express);
+        }
+        builder
+    }
+
+    pub(crate) fn apply_attachment<B: SampleBuilderTrait>(&self, builder: B) -> B {
+        match &self.attachment {
+            Some(attachment) => builder.attachment(attachment.clone()),
+            None => builder,
+        }
+    }
+
+    pub(crate) fn apply_timestamp<B: TimestampBuilderTrait>(&self, builder: B) -> B {
+        match self.timestamp {
+            Some(timestamp) => builder.timestamp(timestamp),
+            None => builder,
+        }
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> ZResult<T>
+where
+    T::Err: std::fmt::Debug,
+{
+    value
+        .parse()
+        .map_err(|e| zerror!("Invalid {name} '{value}': {e:?}").into())
+}
+
+/// Parses the name of a variant of the enums that zenoh deserializes from its configuration.
+fn parse_variant<T: DeserializeOwned>(name: &str, value: &str) -> ZResult<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|e| zerror!("Invalid {name} '{value}': {e}").into())
+}
+
+fn parse_priority(value: &str) -> ZResult<Priority> {
+    match value.parse::<u8>() {
+        Ok(priority) => Priority::try_from(priority)
+            .map_err(|e| zerror!("Invalid {PRIORITY} '{value}': {e}").into()),
+        Err(_) => parse_variant(PRIORITY, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tide::http::{Method, Request, Url};
+    use zenoh::{
+        qos::{CongestionControl, Priority},
+        query::{ConsolidationMode, Parameters, QueryTarget},
+    };
+
+    use super::RequestOptions;
+
+    fn request(url: &str, headers: &[(&str, &str)]) -> tide::Request<()> {
+        let mut req = Request::new(Method::Get, Url::parse(url).unwrap());
+        for (name, value) in headers {
+            req.insert_header(*name, *value);
+        }
+        req.into()
+    }
+
+    #[test]
+    fn test_query_options() {
+        let req = request(
+            "http://localhost/demo/**?arg=1;_target=All;_timeout=30000;_priority=2",
+            &[
+                ("X-Zenoh-Target", "AllComplete"),
+                ("X-Zenoh-Consolidation", "None"),
+                ("X-Zenoh-Congestion-Control", "Block"),
+                ("X-Zenoh-Attachment", "meta"),
+            ],
+        );
+        let mut parameters = Parameters::from(req.url().query().unwrap_or_default());
+        let options = RequestOptions::extract(&req, &mut parameters).unwrap();
+
+        assert_eq!(
+            options,
+            RequestOptions {
+                target: Some(QueryTarget::All),
+                consolidation: Some(ConsolidationMode::None),
+                timeout: Some(Duration::from_secs(30)),
+                priority: Some(Priority::InteractiveHigh),
+                congestion_control: Some(CongestionControl::Block),
+                attachment: Some("meta".to_string()),
+                ..Default::default()
+            }
+        );
+        // Only the options are removed from the parameters
+        assert_eq!(parameters.as_str(), "arg=1");
+    }
+
+    #[test]
+    fn test_parameters_options() {
+        let mut parameters = Parameters::from("arg=1;_target=All;_timeout=500;_consolidation=None");
+        let options = RequestOptions::from_parameters(&mut parameters).unwrap();
+
+        assert_eq!(
+            options,
+            RequestOptions {
+                target: Some(QueryTarget::All),
+                consolidation: Some(ConsolidationMode::None),
+                timeout: Some(Duration::from_millis(500)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(parameters.as_str(), "arg=1");
+    }
+
+    #[test]
+    fn test_publication_options() {
+        let req = request(
+            "http://localhost/demo?_express=true",
+            &[
+                ("X-Zenoh-Priority", "Data"),
+                (
+                    "X-Zenoh-Timestamp",
+                    "7054123566570568799/BC779A06D7E049BD88C3FF3DB0C17FCC",
+                ),
+            ],
+        );
+        let mut parameters = Parameters::from(req.url().query().unwrap_or_default());
+        let options = RequestOptions::extract(&req, &mut parameters).unwrap();
+        assert!(options.check_publication().is_ok());
+
+        for (url, headers) in [
+            ("http://localhost/demo?_target=All", &[][..]),
+            ("http://localhost/demo?_consolidation=None", &[][..]),
+            ("http://localhost/demo", &[("X-Zenoh-Timeout", "1000")][..]),
+        ] {
+            let req = request(url, headers);
+            let mut parameters = Parameters::from(req.url().query().unwrap_or_default());
+            let options = RequestOptions::extract(&req, &mut parameters).unwrap();
+            assert!(options.check_publication().is_err());
+        }
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        for url in [
+            "http://localhost/demo?_target=Any",
+            "http://localhost/demo?_timeout=30s",
+            "http://localhost/demo?_priority=0",
+            "http://localhost/demo?_express=yes",
+            "http://localhost/demo?_timestamp=now",
+        ] {
+            let req = request(url, &[]);
+            let mut parameters = Parameters::from(req.url().query().unwrap_or_default());
+            assert!(RequestOptions::extract(&req, &mut parameters).is_err());
+        }
+    }
+}
@@ -0,0 +1,405 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The server-sent events streams of the subscriptions, opened by a `GET` request on a key
+//! expression with `Accept: text/event-stream`.
+//!
+//! Each sample is sent as an event named after its kind (`PUT` or `DELETE`), with the JSON
+//! sample as data and, if the sample is timestamped, an [`EventId`] as event id:
+//!
+//! - the `_history` parameter first sends the samples returned by a query on the key expression,
+//!   as a `querying_subscriber` would;
+//! - the `Last-Event-ID` header, set by the browsers when they reconnect a stream, resumes it
+//!   with the samples of each source timestamped after the last one sent from it, queried with a
+//!   time range then received by the subscription;
+//! - the samples that a client does not read in time are handled by the `slow_client` policy of
+//!   the configuration;
+//! - heartbeat comments are sent on the idle streams, so that the proxies keep them open.
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt, io,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use futures::{StreamExt, TryStreamExt};
+use tide::{http::Mime, Body, Request, Response, StatusCode};
+use tokio::time::timeout;
+use zenoh::{
+    handlers::{RingChannel, RingChannelHandler},
+    internal::{access_control::AclMessage, bail, zerror},
+    key_expr::KeyExpr,
+    pubsub::Subscriber,
+    query::{Parameters, Selector, TimeBound, TimeExpr, TimeRange, ZenohParameters},
+    sample::Sample,
+    session::Session,
+    time::{Timestamp, TimestampId},
+    Result as ZResult,
+};
+
+use crate::{
+    config::{SlowClientPolicy, SseConfig},
+    forbidden, is_authorized, path_to_key_expr, query_consolidation, response, sample_to_json,
+    spawn_runtime, State, SSE_MIME,
+};
+
+pub(crate) const HISTORY_KEY: &str = "_history";
+const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+const SUBSCRIBED: &str = ": subscribed\n\n";
+const HEARTBEAT: &str = ": heartbeat\n\n";
+// The number of events buffered between a subscription and its connection, the samples
+// being queued by the subscription itself
+const EVENTS_BUFFER_SIZE: usize = 16;
+
+/// The samples received by the subscription of a stream, queued according to the slow client
+/// policy.
+enum SampleQueue {
+    DropOldest(Subscriber<RingChannelHandler<Sample>>),
+    Disconnect {
+        _subscriber: Subscriber<()>,
+        receiver: flume::Receiver<Sample>,
+        overflowed: Arc<AtomicBool>,
+    },
+}
+
+impl SampleQueue {
+    async fn declare(
+        session: &Session,
+        key_expr: &KeyExpr<'static>,
+        config: &SseConfig,
+    ) -> ZResult<Self> {
+        let queue_size = config.queue_size.max(1);
+        match config.slow_client {
+            SlowClientPolicy::DropOldest => Ok(Self::DropOldest(
+                session
+                    .declare_subscriber(key_expr)
+                    .with(RingChannel::new(queue_size))
+                    .await?,
+            )),
+            SlowClientPolicy::Disconnect => {
+                let (sender, receiver) = flume::bounded(queue_size);
+                let overflowed = Arc::new(AtomicBool::new(false));
+                let subscriber = session
+                    .declare_subscriber(key_expr)
+                    .callback({
+                        let overflowed = overflowed.clone();
+                        move |sample| {
+                            if sender.try_send(sample).is_err() {
+                                overflowed.store(true, Ordering::Relaxed);
+                            }
+                        }
+                    })
+                    .await?;
+                Ok(Self::Disconnect {
+                    _subscriber: subscriber,
+                    receiver,
+                    overflowed,
+                })
+            }
+        }
+    }
+
+    async fn recv(&self) -> ZResult<Sample> {
+        match self {
+            Self::DropOldest(subscriber) => subscriber.recv_async().await,
+            Self::Disconnect {
+                receiver,
+                overflowed,
+                ..
+            } => {
+                if overflowed.load(Ordering::Relaxed) {
+                    bail!("The client did not read its stream in time");
+                }
+                receiver.recv_async().await.map_err(|e| zerror!(e).into())
+            }
+        }
+    }
+}
+
+/// The id of the events of a stream: the timestamp of the last sample sent from each source,
+/// separated by commas.
+///
+/// NOTE: The timestamps of different sources can't be compared, their clocks not being
+///       synchronized: a single timestamp would make a resumed stream skip the samples of a
+///       source whose clock is behind the one of the last sample sent.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct EventId(BTreeMap<TimestampId, Timestamp>);
+
+impl EventId {
+    /// Returns `true` if a sample with this timestamp, or a later one from its source, was sent.
+    fn contains(&self, timestamp: &Timestamp) -> bool {
+        self.0
+            .get(timestamp.get_id())
+            .is_some_and(|last| timestamp <= last)
+    }
+
+    fn insert(&mut self, timestamp: Timestamp) {
+        let last = self.0.entry(*timestamp.get_id()).or_insert(timestamp);
+        if *last < timestamp {
+            *last = timestamp;
+        }
+    }
+
+    /// Returns the earliest timestamp, from which the stream is resumed.
+    fn start(&self) -> Option<&Timestamp> {
+        self.0.values().min()
+    }
+}
+
+impl FromStr for EventId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut event_id = EventId::default();
+        for timestamp in s.split(',') {
+            event_id.insert(Timestamp::from_str(timestamp.trim()).map_err(|e| format!("{e:?}"))?);
+        }
+        Ok(event_id)
+    }
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, timestamp) in self.0.values().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{timestamp}")?;
+        }
+        Ok(())
+    }
+}
+
+fn event(sample: &Sample, event_id: &EventId) -> String {
+    let data = serde_json::to_string(&sample_to_json(sample)).unwrap_or("{}".into());
+    match sample.timestamp() {
+        Some(_) => format!("id: {event_id}\nevent: {}\ndata: {data}\n\n", sample.kind()),
+        None => format!("event: {}\ndata: {data}\n\n", sample.kind()),
+    }
+}
+
+/// Answers a `GET` request with `Accept: text/event-stream` with the stream of the samples
+/// published on its key expression.
+pub(crate) async fn subscribe(req: Request<State>) -> tide::Result<Response> {
+    let key_expr = match path_to_key_expr(req.url().path(), &req.state().zid) {
+        Ok(key_expr) => key_expr.into_owned(),
+        Err(e) => {
+            return Ok(response(
+                StatusCode::BadRequest,
+                "text/plain",
+                &e.to_string(),
+            ))
+        }
+    };
+    if !is_authorized(&req, AclMessage::DeclareSubscriber, &key_expr) {
+        return Ok(forbidden(AclMessage::DeclareSubscriber, &key_expr));
+    }
+
+    let history = Parameters::from(req.url().query().unwrap_or_default()).contains_key(HISTORY_KEY);
+    let last_event_id = match req.header(LAST_EVENT_ID_HEADER) {
+        Some(values) => match EventId::from_str(values.last().as_str()) {
+            Ok(event_id) => Some(event_id),
+            Err(e) => {
+                return Ok(response(
+                    StatusCode::BadRequest,
+                    "text/plain",
+                    &format!("Invalid Last-Event-ID '{}': {e}", values.last()),
+                ))
+            }
+        },
+        None => None,
+    };
+    if (history || last_event_id.is_some()) && !is_authorized(&req, AclMessage::Query, &key_expr) {
+        return Ok(forbidden(AclMessage::Query, &key_expr));
+    }
+
+    let state = req.state().clone();
+    let queue = match SampleQueue::declare(&state.session, &key_expr, &state.config.sse).await {
+        Ok(queue) => queue,
+        Err(e) => {
+            return Ok(response(
+                StatusCode::InternalServerError,
+                "text/plain",
+                &e.to_string(),
+            ))
+        }
+    };
+    tracing::debug!("Subscribe to {} for SSE stream", key_expr);
+
+    let (events, receiver) = flume::bounded(EVENTS_BUFFER_SIZE);
+    // Send the headers without waiting for the first sample
+    let _ = events.try_send(SUBSCRIBED.to_string());
+    spawn_runtime(async move {
+        let heartbeat = (state.config.sse.heartbeat_interval > 0)
+            .then(|| Duration::from_secs(state.config.sse.heartbeat_interval));
+        let stream = Stream {
+            session: &state.session,
+            key_expr: &key_expr,
+            events,
+            heartbeat,
+        };
+        if let Err(e) = stream.run(queue, history, last_event_id).await {
+            tracing::debug!("SSE stream on {} terminated: {}", key_expr, e);
+        }
+    });
+
+    let body = receiver
+        .into_stream()
+        .map(|event| Ok::<_, io::Error>(event.into_bytes()))
+        .into_async_read();
+    let mut response = Response::new(StatusCode::Ok);
+    response.set_content_type(Mime::from_str(SSE_MIME).unwrap());
+    response.insert_header("Cache-Control", "no-cache");
+    response.set_body(Body::from_reader(body, None));
+    Ok(response)
+}
+
+struct Stream<'a> {
+    session: &'a Session,
+    key_expr: &'a KeyExpr<'static>,
+    events: flume::Sender<String>,
+    heartbeat: Option<Duration>,
+}
+
+impl Stream<'_> {
+    /// Forwards the samples to the client until it closes the stream or, with the `disconnect`
+    /// policy, until it does not read it in time.
+    async fn run(
+        &self,
+        queue: SampleQueue,
+        history: bool,
+        last_event_id: Option<EventId>,
+    ) -> ZResult<()> {
+        // The id of the last event sent, which resumes the stream where `last_event_id` left it
+        let mut event_id = last_event_id.clone().unwrap_or_default();
+        // The samples sent from the history, which the subscription may also have received
+        let mut replayed = HashSet::new();
+        if history || last_event_id.is_some() {
+            for sample in self.history(last_event_id.as_ref()).await? {
+                if let Some(timestamp) = sample.timestamp() {
+                    replayed.insert((sample.key_expr().to_string(), *timestamp));
+                    event_id.insert(*timestamp);
+                }
+                self.send(event(&sample, &event_id)).await?;
+            }
+        }
+
+        loop {
+            let sample = match self.heartbeat {
+                Some(heartbeat) => match timeout(heartbeat, queue.recv()).await {
+                    Ok(sample) => sample?,
+                    Err(_) => {
+                        self.send(HEARTBEAT.to_string()).await?;
+                        continue;
+                    }
+                },
+                None => queue.recv().await?,
+            };
+            if let Some(timestamp) = sample.timestamp() {
+                if last_event_id
+                    .as_ref()
+                    .is_some_and(|last_event_id| last_event_id.contains(timestamp))
+                    || replayed.remove(&(sample.key_expr().to_string(), *timestamp))
+                {
+                    continue;
+                }
+                event_id.insert(*timestamp);
+            }
+            self.send(event(&sample, &event_id)).await?;
+        }
+    }
+
+    /// Queries the samples to send before the ones of the subscription, ordered by timestamp:
+    /// the samples of each source timestamped after the one of `last_event_id` if any, or else all
+    /// the stored samples.
+    ///
+    /// NOTE: The samples of the sources absent from `last_event_id` are resumed from its earliest
+    ///       timestamp.
+    async fn history(&self, last_event_id: Option<&EventId>) -> ZResult<Vec<Sample>> {
+        let mut parameters = Parameters::empty();
+        if let Some(start) = last_event_id.and_then(EventId::start) {
+            parameters.set_time_range(TimeRange {
+                start: TimeBound::Inclusive(TimeExpr::Fixed(start.get_time().to_system_time())),
+                end: TimeBound::Unbounded,
+            });
+        }
+        let replies = self
+            .session
+            .get(Selector::borrowed(self.key_expr, &parameters))
+            .consolidation(query_consolidation(&parameters))
+            .await?;
+
+        let mut samples = Vec::new();
+        while let Ok(reply) = replies.recv_async().await {
+            match reply.into_result() {
+                Ok(sample) => match (last_event_id, sample.timestamp()) {
+                    (Some(last_event_id), Some(timestamp)) if last_event_id.contains(timestamp) => {
+                    }
+                    // The samples which can't be ordered are only sent with the whole history
+                    (Some(_), None) => {}
+                    _ => samples.push(sample),
+                },
+                Err(e) => tracing::debug!("Error reply to SSE history query: {:?}", e),
+            }
+        }
+        samples.sort_by_key(|sample| sample.timestamp().copied());
+        Ok(samples)
+    }
+
+    async fn send(&self, event: String) -> ZResult<()> {
+        self.events
+            .send_async(event)
+            .await
+            .map_err(|_| zerror!("The client closed the stream").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use zenoh::time::{Timestamp, TimestampId, NTP64};
+
+    use super::EventId;
+
+    #[test]
+    fn test_event_id() {
+        let a = TimestampId::try_from(1u8).unwrap();
+        let b = TimestampId::try_from(2u8).unwrap();
+
+        let mut event_id = EventId::default();
+        event_id.insert(Timestamp::new(NTP64(20), a));
+        event_id.insert(Timestamp::new(NTP64(10), a));
+        event_id.insert(Timestamp::new(NTP64(5), b));
+
+        // Each source is compared with its own last timestamp
+        assert!(event_id.contains(&Timestamp::new(NTP64(20), a)));
+        assert!(!event_id.contains(&Timestamp::new(NTP64(21), a)));
+        assert!(!event_id.contains(&Timestamp::new(NTP64(10), b)));
+        assert!(!event_id.contains(&Timestamp::new(NTP64(1), TimestampId::rand())));
+        assert_eq!(event_id.start(), Some(&Timestamp::new(NTP64(5), b)));
+
+        assert_eq!(EventId::from_str(&event_id.to_string()), Ok(event_id));
+        // The id of a stream resumed by a single timestamp
+        let timestamp = Timestamp::new(NTP64(20), a);
+        assert!(EventId::from_str(&timestamp.to_string())
+            .unwrap()
+            .contains(&timestamp));
+        assert!(EventId::from_str("yesterday").is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    io::{self, Cursor},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use futures::{AsyncRead, AsyncWrite};
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer},
+    ServerConfig,
+};
+use tide::Server;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::{server::TlsStream, TlsAcceptor};
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt};
+use zenoh::{
+    internal::{zerror, zlock},
+    Result as ZResult,
+};
+
+use crate::{config::TlsConfig, spawn_runtime};
+
+/// A TLS stream that can be cloned, as required by `async_h1`, all the clones sharing the same
+/// underlying stream.
+#[derive(Clone)]
+struct SharedTlsStream(Arc<Mutex<Compat<TlsStream<TcpStream>>>>);
+
+impl AsyncRead for SharedTlsStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut *zlock!(self.0)).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for SharedTlsStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut *zlock!(self.0)).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *zlock!(self.0)).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *zlock!(self.0)).poll_close(cx)
+    }
+}
+
+fn server_config(config: &TlsConfig) -> ZResult<ServerConfig> {
+    let certificate = std::fs::read(&config.listen_certificate).map_err(|e| {
+        zerror!(
+            "Failed to read certificate '{}': {e}",
+            config.listen_certificate
+        )
+    })?;
+    let certificates: Vec<CertificateDer> = rustls_pemfile::certs(&mut Cursor::new(&certificate))
+        .collect::<Result<_, _>>()
+        .map_err(|e| zerror!("Error processing server certificate: {e}."))?;
+
+    let private_key = std::fs::read(&config.listen_private_key).map_err(|e| {
+        zerror!(
+            "Failed to read private key '{}': {e}",
+            config.listen_private_key
+        )
+    })?;
+    let private_key: PrivateKeyDer = rustls_pemfile::private_key(&mut Cursor::new(&private_key))
+        .map_err(|e| zerror!("Error processing server key: {e}."))?
+        .ok_or_else(|| zerror!("No private key found in '{}'", config.listen_private_key))?;
+
+    let server_config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| zerror!(e))?
+            .with_no_client_auth()
+            .with_single_cert(certificates, private_key)
+            .map_err(|e| zerror!(e))?;
+
+    Ok(server_config)
+}
+
+/// Serves the `app` over HTTPS on `address`.
+///
+/// This function only returns if the TLS configuration is invalid or if the listener could not be
+/// bound, each connection being served in its own task.
+pub(crate) async fn listen<State: Clone + Send + Sync + 'static>(
+    app: Server<State>,
+    address: &str,
+    config: &TlsConfig,
+) -> ZResult<()> {
+    let acceptor = TlsAcceptor::from(Arc::new(server_config(config)?));
+    let listener = TcpListener::bind(address).await?;
+    tracing::info!("REST server listening on https://{address}");
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                tracing::warn!("Failed to accept HTTPS connection: {e}");
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        spawn_runtime(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => SharedTlsStream(Arc::new(Mutex::new(stream.compat()))),
+                Err(e) => {
+                    tracing::debug!("TLS handshake with {peer} failed: {e}");
+                    return;
+                }
+            };
+
+            let result = async_h1::accept(stream, |mut req| {
+                let app = app.clone();
+                async move {
+                    req.set_peer_addr(Some(peer));
+                    app.respond::<_, http_types::Response>(req).await
+                }
+            })
+            .await;
+            if let Err(e) = result {
+                tracing::debug!("HTTPS connection with {peer} failed: {e}");
+            }
+        });
+    }
+}
@@ -0,0 +1,578 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The WebSocket API of the REST plugin.
+//!
+//! A client opens a WebSocket by sending a GET request with the `Upgrade: websocket` header on any
+//! path. It can then multiplex subscriptions, publications, queries and liveliness tokens over
+//! that single connection, each operation being a JSON text message:
+//!
+//! ```text
+//! {"op": "subscribe", "id": 1, "key_expr": "demo/**"}
+//! {"op": "declare_token", "id": 2, "key_expr": "group/member/1"}
+//! {"op": "undeclare", "id": 1}
+//! {"op": "put", "id": 3, "key_expr": "demo/a", "value": "hello", "encoding": "text/plain"}
+//! {"op": "delete", "id": 4, "key_expr": "demo/a"}
+//! {"op": "get", "id": 5, "selector": "demo/**?arg=1;_timeout=5000", "value": {"some": "json"}}
+//! ```
+//!
+//! The options of a query (e.g. `_target`, `_consolidation` or `_timeout`) are set through the
+//! same reserved parameters of its selector as over HTTP, and are not forwarded to the queryables.
+//!
+//! The `id` is chosen by the client and identifies the declaration (to undeclare it) or the
+//! operation. Every message of the server refers to the `id` of an operation:
+//!
+//! ```text
+//! {"type": "ok", "id": 3}
+//! {"type": "error", "id": 4, "message": "..."}
+//! {"type": "sample", "id": 1, "kind": "PUT", "key": "demo/a", "value": "hello", ...}
+//! {"type": "reply", "id": 5, "key": "demo/b", "value": ..., ...}
+//! {"type": "end", "id": 5}
+//! ```
+//!
+//! Declarations are acknowledged with `ok`, the replies of a query are streamed as they arrive
+//! and followed by an `end` message. All the declarations are undeclared when the connection is
+//! closed.
+//!
+//! A browser can only open a WebSocket from an origin allowed by the CORS policy. At most
+//! `ws.queue_size` messages are queued for a client that does not read its connection, which is
+//! closed once that limit is reached.
+use std::{collections::HashMap, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tide::{Request, Response, StatusCode};
+use tokio_tungstenite::{
+    tungstenite::{
+        handshake::derive_accept_key,
+        protocol::{frame::coding::CloseCode, CloseFrame, Role},
+        Message,
+    },
+    WebSocketStream,
+};
+use tokio_util::{compat::FuturesAsyncReadCompatExt, sync::CancellationToken};
+use zenoh::{
+    bytes::Encoding,
+    internal::{access_control::AclMessage, bail},
+    key_expr::KeyExpr,
+    liveliness::LivelinessToken,
+    pubsub::Subscriber,
+    query::{Parameters, QueryConsolidation, Selector},
+    Result as ZResult,
+};
+
+use crate::{
+    auth::AuthenticatedUser, config::ANY_ORIGIN, options::RequestOptions, path_to_key_expr,
+    query_consolidation, result_to_json, sample_to_json, spawn_runtime, JSONSample, State,
+};
+
+/// How long the Close frame of a connection that overflowed may take to be sent.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Returns `true` if the request asks for its connection to be upgraded to a WebSocket.
+pub(crate) fn is_upgrade(req: &Request<State>) -> bool {
+    req.header("upgrade")
+        .is_some_and(|values| values.last().as_str().eq_ignore_ascii_case("websocket"))
+}
+
+/// Returns `true` if the `Origin` of the request, if any, is allowed by the CORS policy.
+///
+/// Browsers do not apply the CORS policy to WebSockets: without this check, any page could use the
+/// credentials of its visitors to open a WebSocket. Clients that are not browsers usually do not
+/// send an `Origin`, their requests are accepted.
+fn is_origin_allowed(req: &Request<State>) -> bool {
+    let Some(origin) = req.header("origin") else {
+        return true;
+    };
+    let origin = origin.last().as_str();
+    req.state()
+        .config
+        .cors
+        .allowed_origins
+        .iter()
+        .any(|allowed| allowed == ANY_ORIGIN || allowed == origin)
+}
+
+/// Answers the upgrade request and serves the WebSocket API on the upgraded connection.
+pub(crate) async fn upgrade(req: Request<State>) -> tide::Result<Response> {
+    if !is_origin_allowed(&req) {
+        return Ok(Response::builder(StatusCode::Forbidden)
+            .body("Origin not allowed")
+            .build());
+    }
+
+    let Some(key) = req.header("sec-websocket-key") else {
+        return Ok(Response::builder(StatusCode::BadRequest)
+            .body("Missing Sec-WebSocket-Key header")
+            .build());
+    };
+
+    let mut response = Response::builder(StatusCode::SwitchingProtocols)
+        .header("upgrade", "websocket")
+        .header("connection", "Upgrade")
+        .header(
+            "sec-websocket-accept",
+            derive_accept_key(key.last().as_str().as_bytes()),
+        )
+        .build();
+
+    let state = req.state().clone();
+    let username = req.ext::<AuthenticatedUser>().map(|user| user.0.clone());
+    let http_response: &mut http_types::Response = response.as_mut();
+    let connection = http_response.recv_upgrade().await;
+
+    spawn_runtime(async move {
+        match connection.await {
+            Some(connection) => {
+                let stream =
+                    WebSocketStream::from_raw_socket(connection.compat(), Role::Server, None).await;
+                WsSession::serve(state, username, stream).await;
+            }
+            None => tracing::debug!("WebSocket upgrade failed"),
+        }
+    });
+
+    Ok(response)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+enum WsRequest {
+    Subscribe {
+        id: u64,
+        key_expr: String,
+    },
+    DeclareToken {
+        id: u64,
+        key_expr: String,
+    },
+    Undeclare {
+        id: u64,
+    },
+    Put {
+        id: u64,
+        key_expr: String,
+        value: serde_json::Value,
+        encoding: Option<String>,
+    },
+    Delete {
+        id: u64,
+        key_expr: String,
+    },
+    Get {
+        id: u64,
+        selector: String,
+        value: Option<serde_json::Value>,
+        encoding: Option<String>,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WsResponse {
+    Ok {
+        id: u64,
+    },
+    Error {
+        id: Option<u64>,
+        message: String,
+    },
+    Sample {
+        id: u64,
+        kind: String,
+        #[serde(flatten)]
+        sample: JSONSample,
+    },
+    Reply {
+        id: u64,
+        #[serde(flatten)]
+        sample: JSONSample,
+    },
+    End {
+        id: u64,
+    },
+}
+
+impl WsResponse {
+    fn to_message(&self) -> Message {
+        Message::Text(serde_json::to_string(self).unwrap_or("{}".into()))
+    }
+}
+
+enum Declaration {
+    Subscriber(Subscriber<()>),
+    Token(LivelinessToken),
+}
+
+/// The bounded queue of the messages to send to the client.
+///
+/// Once it is full, the connection is `overflowed`: it is closed instead of queueing more messages
+/// for a client that does not read them.
+#[derive(Clone)]
+struct Outgoing {
+    sender: flume::Sender<Message>,
+    overflowed: CancellationToken,
+}
+
+impl Outgoing {
+    /// Queues the `response`, returning `false` if the connection is closing.
+    fn send(&self, response: &WsResponse) -> bool {
+        match self.sender.try_send(response.to_message()) {
+            Ok(()) => true,
+            Err(flume::TrySendError::Full(_)) => {
+                if !self.overflowed.is_cancelled() {
+                    tracing::debug!("WebSocket queue full! Closing connection");
+                    self.overflowed.cancel();
+                }
+                false
+            }
+            Err(flume::TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// The state of a WebSocket connection: the declarations made by the client and the queue of the
+/// messages to send to the client.
+struct WsSession {
+    state: State,
+    username: Option<String>,
+    declarations: HashMap<u64, Declaration>,
+    outgoing: Outgoing,
+}
+
+impl WsSession {
+    async fn serve<S>(state: State, username: Option<String>, stream: WebSocketStream<S>)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut sink, mut stream) = stream.split();
+
+        let (sender, outgoing_rx) = flume::bounded(state.config.ws.queue_size.max(1));
+        let overflowed = CancellationToken::new();
+        let mut session = Self {
+            state,
+            username,
+            declarations: HashMap::new(),
+            outgoing: Outgoing {
+                sender,
+                overflowed: overflowed.clone(),
+            },
+        };
+
+        let writer = spawn_runtime({
+            let overflowed = overflowed.clone();
+            async move {
+                // NOTE: Once the connection overflowed, the client is not reading it: the sending
+                //       of any pending message is abandoned and the Close frame is sent on a best
+                //       effort basis.
+                loop {
+                    let message = tokio::select! {
+                        biased;
+                        _ = overflowed.cancelled() => break,
+                        message = outgoing_rx.recv_async() => match message {
+                            Ok(message) => message,
+                            Err(_) => break,
+                        },
+                    };
+                    let sent = tokio::select! {
+                        biased;
+                        _ = overflowed.cancelled() => break,
+                        sent = sink.send(message) => sent,
+                    };
+                    if let Err(e) = sent {
+                        tracing::debug!("WebSocket send error ({e})! Closing connection");
+                        break;
+                    }
+                }
+
+                if overflowed.is_cancelled() {
+                    let close = Message::Close(Some(CloseFrame {
+                        code: CloseCode::Again,
+                        reason: "Too many queued messages".into(),
+                    }));
+                    let _ = tokio::time::timeout(CLOSE_TIMEOUT, sink.send(close)).await;
+                }
+            }
+        });
+
+        loop {
+            let message = tokio::select! {
+                message = stream.next() => match message {
+                    Some(message) => message,
+                    None => break,
+                },
+                _ = overflowed.cancelled() => break,
+            };
+            let response = match message {
+                Ok(Message::Text(text)) => match serde_json::from_str::<WsRequest>(&text) {
+                    Ok(request) => session.handle(request).await,
+                    Err(e) => Some(WsResponse::Error {
+                        id: None,
+                        message: format!("Invalid request: {e}"),
+                    }),
+                },
+                Ok(Message::Binary(_)) => Some(WsResponse::Error {
+                    id: None,
+                    message: "Binary messages are not supported".to_string(),
+                }),
+                Ok(Message::Close(_)) => break,
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::debug!("WebSocket receive error ({e})! Closing connection");
+                    break;
+                }
+            };
+            if let Some(response) = response {
+                session.outgoing.send(&response);
+            }
+        }
+
+        // Dropping the session undeclares all its declarations and, once the pending queries
+        // are answered, terminates the writer.
+        drop(session);
+        if let Err(e) = writer.await {
+            tracing::debug!("WebSocket writer failed: {e}");
+        }
+    }
+
+    /// Performs the operation requested by the client and returns the message acknowledging it,
+    /// if any.
+    async fn handle(&mut self, request: WsRequest) -> Option<WsResponse> {
+        tracing::trace!("Incoming WebSocket request: {request:?}");
+        let (id, result) = match request {
+            WsRequest::Subscribe { id, key_expr } => (id, self.subscribe(id, &key_expr).await),
+            WsRequest::DeclareToken { id, key_expr } => {
+                (id, self.declare_token(id, &key_expr).await)
+            }
+            WsRequest::Undeclare { id } => (id, self.undeclare(id).await),
+            WsRequest::Put {
+                id,
+                key_expr,
+                value,
+                encoding,
+            } => (id, self.put(&key_expr, value, encoding).await),
+            WsRequest::Delete { id, key_expr } => (id, self.delete(&key_expr).await),
+            WsRequest::Get {
+                id,
+                selector,
+                value,
+                encoding,
+            } => match self.get(id, &selector, value, encoding).await {
+                // The replies and the final marker are sent by the task forwarding them.
+                Ok(()) => return None,
+                Err(e) => (id, Err(e)),
+            },
+        };
+
+        Some(match result {
+            Ok(()) => WsResponse::Ok { id },
+            Err(e) => WsResponse::Error {
+                id: Some(id),
+                message: e.to_string(),
+            },
+        })
+    }
+
+    fn key_expr<'a>(&self, key_expr: &'a str, action: AclMessage) -> ZResult<KeyExpr<'a>> {
+        let key_expr = path_to_key_expr(key_expr, &self.state.zid)?;
+        if !self
+            .state
+            .is_authorized(self.username.as_deref(), action, &key_expr)
+        {
+            bail!("Not authorized to {action:?} on {key_expr}");
+        }
+        Ok(key_expr)
+    }
+
+    fn check_id(&self, id: u64) -> ZResult<()> {
+        if self.declarations.contains_key(&id) {
+            bail!("A declaration with id {id} already exists");
+        }
+        Ok(())
+    }
+
+    async fn subscribe(&mut self, id: u64, key_expr: &str) -> ZResult<()> {
+        self.check_id(id)?;
+        let key_expr = self.key_expr(key_expr, AclMessage::DeclareSubscriber)?;
+
+        let outgoing = self.outgoing.clone();
+        let subscriber = self
+            .state
+            .session
+            .declare_subscriber(key_expr)
+            .callback(move |sample| {
+                let response = WsResponse::Sample {
+                    id,
+                    kind: sample.kind().to_string(),
+                    sample: sample_to_json(&sample),
+                };
+                outgoing.send(&response);
+            })
+            .await?;
+        self.declarations
+            .insert(id, Declaration::Subscriber(subscriber));
+        Ok(())
+    }
+
+    async fn declare_token(&mut self, id: u64, key_expr: &str) -> ZResult<()> {
+        self.check_id(id)?;
+        let key_expr = self.key_expr(key_expr, AclMessage::LivelinessToken)?;
+
+        let token = self
+            .state
+            .session
+            .liveliness()
+            .declare_token(key_expr)
+            .await?;
+        self.declarations.insert(id, Declaration::Token(token));
+        Ok(())
+    }
+
+    async fn undeclare(&mut self, id: u64) -> ZResult<()> {
+        match self.declarations.remove(&id) {
+            Some(Declaration::Subscriber(subscriber)) => subscriber.undeclare().await,
+            Some(Declaration::Token(token)) => token.undeclare().await,
+            None => bail!("No declaration with id {id}"),
+        }
+    }
+
+    async fn put(
+        &self,
+        key_expr: &str,
+        value: serde_json::Value,
+        encoding: Option<String>,
+    ) -> ZResult<()> {
+        let key_expr = self.key_expr(key_expr, AclMessage::Put)?;
+        let (payload, encoding) = json_to_payload(value, encoding)?;
+        self.state
+            .session
+            .put(key_expr, payload)
+            .encoding(encoding)
+            .await
+    }
+
+    async fn delete(&self, key_expr: &str) -> ZResult<()> {
+        let key_expr = self.key_expr(key_expr, AclMessage::Delete)?;
+        self.state.session.delete(key_expr).await
+    }
+
+    async fn get(
+        &self,
+        id: u64,
+        selector: &str,
+        value: Option<serde_json::Value>,
+        encoding: Option<String>,
+    ) -> ZResult<()> {
+        let (key_expr, parameters) = selector.split_once('?').unwrap_or((selector, ""));
+        let key_expr = self.key_expr(key_expr, AclMessage::Query)?;
+        let mut parameters = Parameters::from(parameters);
+        let options = RequestOptions::from_parameters(&mut parameters)?;
+
+        let consolidation = match options.consolidation {
+            Some(consolidation) => QueryConsolidation::from(consolidation),
+            None => query_consolidation(&parameters),
+        };
+        let mut query = self
+            .state
+            .session
+            .get(Selector::borrowed(&key_expr, &parameters))
+            .consolidation(consolidation);
+        if let Some(target) = options.target {
+            query = query.target(target);
+        }
+        if let Some(timeout) = options.timeout {
+            query = query.timeout(timeout);
+        }
+        query = options.apply_qos(query);
+        query = options.apply_attachment(query);
+        let mut query = query.with(flume::unbounded());
+        if let Some(value) = value {
+            let (payload, encoding) = json_to_payload(value, encoding)?;
+            query = query.payload(payload).encoding(encoding);
+        }
+        let replies = query.await?;
+
+        let outgoing = self.outgoing.clone();
+        spawn_runtime(async move {
+            while let Ok(reply) = replies.recv_async().await {
+                let response = WsResponse::Reply {
+                    id,
+                    sample: result_to_json(reply.result()),
+                };
+                if !outgoing.send(&response) {
+                    return;
+                }
+            }
+            outgoing.send(&WsResponse::End { id });
+        });
+        Ok(())
+    }
+}
+
+/// Converts the value of a request into a payload: a string is sent as is, any other JSON value
+/// is serialized and defaults to the `application/json` encoding.
+fn json_to_payload(
+    value: serde_json::Value,
+    encoding: Option<String>,
+) -> ZResult<(Vec<u8>, Encoding)> {
+    let encoding = encoding.map(Encoding::from);
+    match value {
+        serde_json::Value::String(value) => Ok((value.into_bytes(), encoding.unwrap_or_default())),
+        value => Ok((
+            serde_json::to_vec(&value)?,
+            encoding.unwrap_or(Encoding::APPLICATION_JSON),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zenoh::bytes::Encoding;
+
+    use super::{json_to_payload, WsRequest};
+
+    #[test]
+    fn test_requests() {
+        let request = serde_json::from_str::<WsRequest>(
+            r#"{"op": "put", "id": 3, "key_expr": "demo/a", "value": "hello"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            request,
+            WsRequest::Put { id: 3, ref key_expr, encoding: None, .. } if key_expr == "demo/a"
+        ));
+
+        let request = serde_json::from_str::<WsRequest>(r#"{"op": "undeclare", "id": 1}"#).unwrap();
+        assert!(matches!(request, WsRequest::Undeclare { id: 1 }));
+
+        assert!(serde_json::from_str::<WsRequest>(r#"{"op": "publish", "id": 1}"#).is_err());
+        assert!(serde_json::from_str::<WsRequest>(r#"{"op": "subscribe", "id": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_json_to_payload() {
+        let (payload, encoding) = json_to_payload("hello".into(), None).unwrap();
+        assert_eq!(payload, b"hello");
+        assert_eq!(encoding, Encoding::default());
+
+        let (payload, encoding) =
+            json_to_payload("hello".into(), Some("text/plain".to_string())).unwrap();
+        assert_eq!(payload, b"hello");
+        assert_eq!(encoding, Encoding::TEXT_PLAIN);
+
+        let (payload, encoding) = json_to_payload(serde_json::json!({"a": 1}), None).unwrap();
+        assert_eq!(payload, br#"{"a":1}"#);
+        assert_eq!(encoding, Encoding::APPLICATION_JSON);
+    }
+}
@@ -0,0 +1,112 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the conversions of the payloads by the REST plugin -
+// 1. the JSON replies carry the decoded value of a zenoh-ext serialized number
+// 2. `_raw` converts the payload to the accepted encoding, or keeps it as is
+// 3. `Accept: application/cbor` encodes the replies in CBOR
+// 4. the OpenAPI document is served
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use zenoh::{bytes::Encoding, Config, Wait};
+use zenoh_ext::z_serialize;
+use zenoh_plugin_trait::Plugin;
+
+const SLEEP: Duration = Duration::from_secs(1);
+
+/// Returns the status line, the headers and the body of the response.
+async fn get(path: &str, accept: &str) -> (String, Vec<u8>) {
+    let mut stream = TcpStream::connect("127.0.0.1:18004").await.unwrap();
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: localhost\r\nAccept: {accept}\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    let separator = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .unwrap();
+    let head = String::from_utf8(response[..separator].to_vec()).unwrap();
+    println!("Received: {head}");
+    (head, response[separator + 4..].to_vec())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_convert() {
+    let mut config = Config::default();
+    config
+        .insert_json5("plugins/rest", r#"{ http_port: 18004 }"#)
+        .unwrap();
+    config
+        .insert_json5("scouting/multicast/enabled", "false")
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let rest = zenoh_plugin_rest::RestPlugin::start("rest", &runtime).unwrap();
+    let session = zenoh::session::init(runtime).await.unwrap();
+    let _queryable = session
+        .declare_queryable("convert/test")
+        .callback(|query| {
+            query
+                .reply("convert/test", z_serialize(&42i64))
+                .encoding(Encoding::ZENOH_SERIALIZED.with_schema("i64"))
+                .wait()
+                .unwrap()
+        })
+        .await
+        .unwrap();
+
+    tokio::time::sleep(SLEEP).await;
+
+    let (head, body) = get("/convert/test", "application/json").await;
+    assert!(head.contains("200"));
+    let replies: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(replies[0]["value"], 42);
+    assert_eq!(replies[0]["encoding"], "zenoh/serialized;i64");
+
+    let (head, body) = get("/convert/test?_raw", "application/json").await;
+    assert!(head.contains("application/json"));
+    assert_eq!(body, b"42");
+
+    let (_, body) = get("/convert/test?_raw", "text/plain").await;
+    assert_eq!(body, b"42");
+
+    let (_, body) = get("/convert/test?_raw", "zenoh/serialized;u8").await;
+    assert_eq!(body, [42]);
+
+    let (_, body) = get("/convert/test?_raw", "*/*").await;
+    assert_eq!(body, z_serialize(&42i64).to_bytes().as_ref());
+
+    let (head, body) = get("/convert/test", "application/cbor").await;
+    assert!(head.contains("application/cbor"));
+    let replies: Value = ciborium::from_reader(body.as_slice()).unwrap();
+    assert_eq!(replies[0]["value"], json!(42));
+
+    let (head, body) = get("/@/openapi.json", "application/json").await;
+    assert!(head.contains("200"));
+    let openapi: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(openapi["openapi"], "3.0.3");
+
+    drop(rest);
+}
@@ -0,0 +1,154 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the liveliness tokens and the configuration routes of the REST plugin -
+// 1. a token declared with a lease is alive until its lease expires, unless renewed
+// 2. a lease can only be renewed or undeclared by its owner
+// 3. the configuration of the router is only available to the admins
+use std::time::Duration;
+
+use base64::Engine;
+use serde_json::Value;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use zenoh::Config;
+use zenoh_plugin_trait::Plugin;
+
+const SLEEP: Duration = Duration::from_secs(1);
+
+/// Returns the status code and the body of the response.
+async fn request(method: &str, path: &str, user: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect("127.0.0.1:18005").await.unwrap();
+    let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{user}:secret"));
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nAuthorization: Basic {credentials}\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    println!("Received: {response}");
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+async fn tokens(user: &str) -> Value {
+    let (status, body) = request("GET", "/@/liveliness/tokens/device/**", user, "").await;
+    assert_eq!(status, 200);
+    serde_json::from_str(&body).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_liveliness() {
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/rest",
+            r#"{
+                http_port: 18005,
+                auth: {
+                    users: [
+                        { username: "alice", password: "secret" },
+                        { username: "bob", password: "secret" },
+                    ],
+                    admins: ["alice"],
+                },
+            }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5("scouting/multicast/enabled", "false")
+        .unwrap();
+    config
+        .insert_json5(
+            "adminspace",
+            r#"{ enabled: true, permissions: { read: true, write: true } }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let rest = zenoh_plugin_rest::RestPlugin::start("rest", &runtime).unwrap();
+
+    tokio::time::sleep(SLEEP).await;
+
+    let (status, body) = request("POST", "/@/liveliness/tokens/device/1?_lease=2", "bob", "").await;
+    assert_eq!(status, 201);
+    let lease: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(lease["key_expr"], "device/1");
+    assert_eq!(lease["lease"], 2);
+    let lease_path = format!("/@/liveliness/leases/{}", lease["id"].as_str().unwrap());
+    assert_eq!(tokens("bob").await, serde_json::json!(["device/1"]));
+
+    // Renewed by its owner only
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(request("PUT", &lease_path, "alice", "").await.0, 404);
+    assert_eq!(request("PUT", &lease_path, "bob", "").await.0, 200);
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(tokens("bob").await, serde_json::json!(["device/1"]));
+
+    // Expired
+    tokio::time::sleep(3 * SLEEP).await;
+    assert_eq!(tokens("bob").await, serde_json::json!([]));
+    assert_eq!(request("PUT", &lease_path, "bob", "").await.0, 404);
+
+    // Undeclared
+    let (_, body) = request("POST", "/@/liveliness/tokens/device/2", "bob", "").await;
+    let lease: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(lease["lease"], 30);
+    let lease_path = format!("/@/liveliness/leases/{}", lease["id"].as_str().unwrap());
+    assert_eq!(tokens("bob").await, serde_json::json!(["device/2"]));
+    assert_eq!(request("DELETE", &lease_path, "bob", "").await.0, 204);
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(tokens("bob").await, serde_json::json!([]));
+
+    let (status, _) = request("POST", "/@/liveliness/tokens/device/3?_lease=0", "bob", "").await;
+    assert_eq!(status, 400);
+
+    // Configuration
+    let path = "/@/config/plugins/rest/http_port";
+    assert_eq!(request("GET", path, "bob", "").await.0, 403);
+    assert_eq!(
+        request("GET", path, "alice", "").await,
+        (200, "18005".into())
+    );
+    let (status, body) = request("GET", "/@/config/plugins/rest/auth", "alice", "").await;
+    assert_eq!(status, 200);
+    assert!(!body.contains("secret"), "{body}");
+
+    let path = "/@/config/metadata";
+    let (status, body) = request("PUT", path, "alice", r#"{ name: "gateway" }"#).await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        serde_json::from_str::<Value>(&body).unwrap(),
+        serde_json::json!({ "name": "gateway" })
+    );
+    assert_eq!(request("PUT", path, "bob", "{}").await.0, 403);
+    assert_eq!(request("PUT", path, "alice", "{").await.0, 400);
+    assert_eq!(request("DELETE", path, "alice", "").await.0, 400);
+    assert_eq!(
+        request("GET", "/@/config/nothing/here", "alice", "")
+            .await
+            .0,
+        404
+    );
+
+    drop(rest);
+}
@@ -0,0 +1,200 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the server-sent events streams of the subscriptions of the REST plugin -
+// 1. `_history` sends the replies of a query, ordered by timestamp, before the publications
+// 2. `Last-Event-ID` resumes a stream after the last timestamp of each source
+// 3. heartbeat comments are sent on an idle stream
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    time::timeout,
+};
+use zenoh::{
+    internal::plugins::RunningPlugin,
+    time::{Timestamp, TimestampId, NTP64},
+    Config, Session, Wait,
+};
+use zenoh_plugin_trait::Plugin;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const SLEEP: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default)]
+struct Event {
+    id: Option<String>,
+    name: String,
+    data: String,
+}
+
+async fn subscribe(port: u16, path: &str, last_event_id: Option<&str>) -> BufReader<TcpStream> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let last_event_id = last_event_id
+        .map(|id| format!("Last-Event-ID: {id}\r\n"))
+        .unwrap_or_default();
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: localhost\r\nAccept: text/event-stream\r\n{last_event_id}\r\n"
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    BufReader::new(stream)
+}
+
+/// Returns the next event, or the next comment as an event without name.
+async fn next_event(reader: &mut BufReader<TcpStream>) -> Event {
+    let mut event = Event::default();
+    loop {
+        let mut line = String::new();
+        timeout(TIMEOUT, reader.read_line(&mut line))
+            .await
+            .unwrap()
+            .unwrap();
+        let line = line.trim_end();
+        if let Some(id) = line.strip_prefix("id: ") {
+            event.id = Some(id.to_string());
+        } else if let Some(name) = line.strip_prefix("event: ") {
+            event.name = name.to_string();
+        } else if let Some(data) = line.strip_prefix("data: ") {
+            event.data = data.to_string();
+            println!("Received: {event:?}");
+            return event;
+        } else if let Some(comment) = line.strip_prefix(": ") {
+            event.data = comment.to_string();
+            println!("Received: {event:?}");
+            return event;
+        }
+    }
+}
+
+async fn next_sample(reader: &mut BufReader<TcpStream>) -> (String, serde_json::Value) {
+    loop {
+        let event = next_event(reader).await;
+        if !event.name.is_empty() {
+            return (
+                event.id.unwrap(),
+                serde_json::from_str(&event.data).unwrap(),
+            );
+        }
+    }
+}
+
+async fn start(port: u16) -> (Session, RunningPlugin) {
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/rest",
+            &format!("{{ http_port: {port}, sse: {{ heartbeat_interval: 1 }} }}"),
+        )
+        .unwrap();
+    config
+        .insert_json5("scouting/multicast/enabled", "false")
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let rest = zenoh_plugin_rest::RestPlugin::start("rest", &runtime).unwrap();
+    let session = zenoh::session::init(runtime).await.unwrap();
+    (session, rest)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_sse_history_and_resume() {
+    let (session, rest) = start(18002).await;
+    let first = session.new_timestamp();
+    let second = session.new_timestamp();
+    let _queryable = session
+        .declare_queryable("sse/test/**")
+        .callback(move |query| {
+            // Replied in reverse order, the samples being sorted by the plugin
+            query
+                .reply("sse/test/b", "second")
+                .timestamp(second)
+                .wait()
+                .unwrap();
+            query
+                .reply("sse/test/a", "first")
+                .timestamp(first)
+                .wait()
+                .unwrap();
+        })
+        .await
+        .unwrap();
+
+    tokio::time::sleep(SLEEP).await;
+
+    let mut reader = subscribe(18002, "/sse/test/**?_history", None).await;
+    let (id, sample) = next_sample(&mut reader).await;
+    assert_eq!(id, first.to_string());
+    assert_eq!(sample["key"], "sse/test/a");
+    let (id, sample) = next_sample(&mut reader).await;
+    assert_eq!(id, second.to_string());
+    assert_eq!(sample["key"], "sse/test/b");
+
+    tokio::time::sleep(SLEEP).await;
+    let third = session.new_timestamp();
+    session
+        .put("sse/test/c", "third")
+        .timestamp(third)
+        .await
+        .unwrap();
+    let (id, sample) = next_sample(&mut reader).await;
+    assert_eq!(id, third.to_string());
+    assert_eq!(sample["key"], "sse/test/c");
+    drop(reader);
+
+    let mut reader = subscribe(18002, "/sse/test/**", Some(&first.to_string())).await;
+    let (id, sample) = next_sample(&mut reader).await;
+    assert_eq!(id, second.to_string());
+    assert_eq!(sample["key"], "sse/test/b");
+
+    // A source whose clock is ahead does not hide the samples of the others
+    let ahead = Timestamp::new(
+        NTP64::from(
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + Duration::from_secs(3600),
+        ),
+        TimestampId::try_from(1u8).unwrap(),
+    );
+    let mut reader = subscribe(18002, "/sse/test/**", Some(&format!("{first},{ahead}"))).await;
+    let (id, sample) = next_sample(&mut reader).await;
+    assert!(
+        id.contains(&second.to_string()) && id.contains(&ahead.to_string()),
+        "{id}"
+    );
+    assert_eq!(sample["key"], "sse/test/b");
+
+    let mut reader = subscribe(18002, "/sse/test/**", Some("yesterday")).await;
+    let mut status = String::new();
+    reader.read_line(&mut status).await.unwrap();
+    assert!(status.contains("400"), "{status}");
+
+    drop(rest);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_sse_heartbeat() {
+    let (_session, rest) = start(18003).await;
+
+    tokio::time::sleep(SLEEP).await;
+
+    let mut reader = subscribe(18003, "/sse/heartbeat", None).await;
+    assert_eq!(next_event(&mut reader).await.data, "subscribed");
+    assert_eq!(next_event(&mut reader).await.data, "heartbeat");
+    assert_eq!(next_event(&mut reader).await.data, "heartbeat");
+
+    drop(rest);
+}
@@ -0,0 +1,148 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the streaming of the replies of a query by the REST plugin -
+// 1. with `Accept: application/x-ndjson`, a reply is received before the query completes
+// 2. the NDJSON stream ends with the final marker
+// 3. with `Accept: text/event-stream` and `_query`, the replies are `reply` events followed by
+//    an `end` event
+// 4. the parameters reserved to the plugin are not forwarded to the queryables
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::Value;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    time::timeout,
+};
+use zenoh::{Config, Wait};
+use zenoh_plugin_trait::Plugin;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const SLEEP: Duration = Duration::from_secs(1);
+const REPLY_DELAY: Duration = Duration::from_secs(3);
+
+async fn request(path: &str, accept: &str) -> BufReader<TcpStream> {
+    let mut stream = TcpStream::connect("127.0.0.1:18001").await.unwrap();
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: localhost\r\nAccept: {accept}\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    BufReader::new(stream)
+}
+
+/// Returns the next line of the response that satisfies `filter`, skipping the headers and the
+/// chunk sizes.
+async fn next_line<F: Fn(&str) -> bool>(
+    reader: &mut BufReader<TcpStream>,
+    delay: Duration,
+    filter: F,
+) -> Option<String> {
+    loop {
+        let mut line = String::new();
+        match timeout(delay, reader.read_line(&mut line)).await {
+            Ok(Ok(0)) | Err(_) => return None,
+            Ok(Ok(_)) => {
+                let line = line.trim_end();
+                if filter(line) {
+                    println!("Received: {line}");
+                    return Some(line.to_string());
+                }
+            }
+            Ok(Err(e)) => panic!("{e}"),
+        }
+    }
+}
+
+async fn next_json(reader: &mut BufReader<TcpStream>, delay: Duration) -> Option<Value> {
+    next_line(reader, delay, |line| line.starts_with('{'))
+        .await
+        .map(|line| serde_json::from_str(&line).unwrap())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_stream_replies() {
+    let mut config = Config::default();
+    config
+        .insert_json5("plugins/rest", r#"{ http_port: 18001 }"#)
+        .unwrap();
+    config
+        .insert_json5("scouting/multicast/enabled", "false")
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let rest = zenoh_plugin_rest::RestPlugin::start("rest", &runtime).unwrap();
+    let session = zenoh::session::init(runtime).await.unwrap();
+    let handle = tokio::runtime::Handle::current();
+    let parameters = Arc::new(Mutex::new(Vec::new()));
+    let queried_parameters = parameters.clone();
+    let _queryable = session
+        .declare_queryable("stream/test/**")
+        .callback(move |query| {
+            queried_parameters
+                .lock()
+                .unwrap()
+                .push(query.parameters().to_string());
+            query.reply("stream/test/a", "first").wait().unwrap();
+            handle.spawn(async move {
+                tokio::time::sleep(REPLY_DELAY).await;
+                query.reply("stream/test/b", "second").await.unwrap();
+            });
+        })
+        .await
+        .unwrap();
+
+    tokio::time::sleep(SLEEP).await;
+
+    let mut reader = request("/stream/test/**", "application/x-ndjson").await;
+    let first = next_json(&mut reader, REPLY_DELAY - SLEEP).await.unwrap();
+    assert_eq!(first["key"], "stream/test/a");
+    assert_eq!(first["value"], "Zmlyc3Q="); // "first" in base64
+    let second = next_json(&mut reader, TIMEOUT).await.unwrap();
+    assert_eq!(second["key"], "stream/test/b");
+    assert_eq!(
+        next_json(&mut reader, TIMEOUT).await.unwrap(),
+        serde_json::json!({"end": true})
+    );
+
+    let mut reader = request(
+        "/stream/test/**?_query;arg=1;_timeout=10000",
+        "text/event-stream",
+    )
+    .await;
+    let is_event = |line: &str| line.starts_with("event:") || line.starts_with("data:");
+    let mut events = vec![];
+    while let Some(line) = next_line(&mut reader, TIMEOUT, is_event).await {
+        let end = line == "event:end";
+        events.push(line);
+        if end {
+            break;
+        }
+    }
+    let names: Vec<&str> = events
+        .iter()
+        .filter_map(|line| line.strip_prefix("event:"))
+        .collect();
+    assert_eq!(names, ["reply", "reply", "end"]);
+    assert_eq!(parameters.lock().unwrap().last().unwrap(), "arg=1");
+
+    drop(rest);
+}
@@ -0,0 +1,250 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the WebSocket API of the REST plugin -
+// 1. a subscription receives the publications made over the same connection
+// 2. a liveliness token declared over the connection is visible to the other sessions
+// 3. the replies of a query are followed by the final marker, and the query is bounded by its
+//    `_timeout` option
+// 4. the declarations are undeclared with the connection
+// 5. a WebSocket can only be opened from an origin allowed by the CORS policy
+// 6. the connection of a client that does not read it is closed once its queue is full
+
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::{net::TcpStream, time::timeout};
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+use zenoh::{Config, Session, Wait};
+use zenoh_plugin_trait::Plugin;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const SLEEP: Duration = Duration::from_secs(1);
+
+type Ws = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn send(ws: &mut Ws, request: Value) {
+    ws.send(Message::Text(request.to_string())).await.unwrap();
+}
+
+async fn recv(ws: &mut Ws) -> Value {
+    loop {
+        let message = timeout(TIMEOUT, ws.next()).await.unwrap().unwrap().unwrap();
+        if let Message::Text(text) = message {
+            println!("Received: {text}");
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+async fn tokens(session: &Session, key_expr: &str) -> usize {
+    session
+        .liveliness()
+        .get(key_expr)
+        .await
+        .unwrap()
+        .into_iter()
+        .count()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_ws() {
+    let mut config = Config::default();
+    config
+        .insert_json5("plugins/rest", r#"{ http_port: 18000 }"#)
+        .unwrap();
+    config
+        .insert_json5("scouting/multicast/enabled", "false")
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let rest = zenoh_plugin_rest::RestPlugin::start("rest", &runtime).unwrap();
+    let session = zenoh::session::init(runtime).await.unwrap();
+    let _queryable = session
+        .declare_queryable("ws/test/**")
+        .callback(|query| query.reply("ws/test/q", "answer").wait().unwrap())
+        .await
+        .unwrap();
+    // A queryable that holds the queries, without replying, longer than their timeout
+    let handle = tokio::runtime::Handle::current();
+    let _slow_queryable = session
+        .declare_queryable("ws/slow/**")
+        .callback(move |query| {
+            handle.spawn(async move {
+                tokio::time::sleep(TIMEOUT).await;
+                drop(query);
+            });
+        })
+        .await
+        .unwrap();
+
+    tokio::time::sleep(SLEEP).await;
+
+    let (mut ws, _) = tokio_tungstenite::connect_async("ws://127.0.0.1:18000/")
+        .await
+        .unwrap();
+
+    send(
+        &mut ws,
+        json!({"op": "subscribe", "id": 1, "key_expr": "ws/test/**"}),
+    )
+    .await;
+    assert_eq!(recv(&mut ws).await, json!({"type": "ok", "id": 1}));
+
+    send(
+        &mut ws,
+        json!({"op": "put", "id": 2, "key_expr": "ws/test/a", "value": {"x": 1}}),
+    )
+    .await;
+    let mut messages = vec![recv(&mut ws).await, recv(&mut ws).await];
+    messages.sort_by_key(|message| message["type"].as_str().unwrap().to_string());
+    assert_eq!(messages[0], json!({"type": "ok", "id": 2}));
+    assert_eq!(messages[1]["type"], "sample");
+    assert_eq!(messages[1]["id"], 1);
+    assert_eq!(messages[1]["kind"], "PUT");
+    assert_eq!(messages[1]["key"], "ws/test/a");
+    assert_eq!(messages[1]["value"], json!({"x": 1}));
+
+    send(
+        &mut ws,
+        json!({"op": "declare_token", "id": 3, "key_expr": "ws/token"}),
+    )
+    .await;
+    assert_eq!(recv(&mut ws).await, json!({"type": "ok", "id": 3}));
+    assert_eq!(tokens(&session, "ws/token").await, 1);
+
+    send(
+        &mut ws,
+        json!({"op": "declare_token", "id": 3, "key_expr": "ws/token"}),
+    )
+    .await;
+    assert_eq!(recv(&mut ws).await["type"], "error");
+
+    send(
+        &mut ws,
+        json!({"op": "get", "id": 4, "selector": "ws/test/**"}),
+    )
+    .await;
+    let reply = recv(&mut ws).await;
+    assert_eq!(reply["type"], "reply");
+    assert_eq!(reply["id"], 4);
+    assert_eq!(reply["key"], "ws/test/q");
+    assert_eq!(recv(&mut ws).await, json!({"type": "end", "id": 4}));
+
+    send(
+        &mut ws,
+        json!({"op": "get", "id": 5, "selector": "ws/slow/**?_timeout=500"}),
+    )
+    .await;
+    let reply = timeout(TIMEOUT / 2, recv(&mut ws)).await.unwrap();
+    assert_eq!(reply["type"], "reply");
+    assert_eq!(reply["key"], "ERROR");
+    assert_eq!(recv(&mut ws).await, json!({"type": "end", "id": 5}));
+
+    send(
+        &mut ws,
+        json!({"op": "get", "id": 6, "selector": "ws/test/**?_target=Any"}),
+    )
+    .await;
+    assert_eq!(recv(&mut ws).await["type"], "error");
+
+    send(&mut ws, json!({"op": "undeclare", "id": 1})).await;
+    assert_eq!(recv(&mut ws).await, json!({"type": "ok", "id": 1}));
+    send(&mut ws, json!({"op": "undeclare", "id": 1})).await;
+    assert_eq!(recv(&mut ws).await["type"], "error");
+
+    ws.close(None).await.unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(tokens(&session, "ws/token").await, 0);
+
+    drop(rest);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_ws_origin_and_overflow() {
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/rest",
+            r#"{
+                http_port: 18001,
+                cors: { allowed_origins: ["https://allowed.example"] },
+                ws: { queue_size: 1 },
+            }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5("scouting/multicast/enabled", "false")
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let rest = zenoh_plugin_rest::RestPlugin::start("rest", &runtime).unwrap();
+    let session = zenoh::session::init(runtime).await.unwrap();
+
+    tokio::time::sleep(SLEEP).await;
+
+    let request = |origin: &str| {
+        let mut request = "ws://127.0.0.1:18001/".into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("origin", origin.parse().unwrap());
+        request
+    };
+
+    // the origin must be allowed by the CORS policy
+    assert!(
+        tokio_tungstenite::connect_async(request("https://denied.example"))
+            .await
+            .is_err()
+    );
+    let (mut ws, _) = tokio_tungstenite::connect_async(request("https://allowed.example"))
+        .await
+        .unwrap();
+
+    send(
+        &mut ws,
+        json!({"op": "subscribe", "id": 1, "key_expr": "ws/overflow/**"}),
+    )
+    .await;
+    assert_eq!(recv(&mut ws).await, json!({"type": "ok", "id": 1}));
+
+    // the connection is closed once the client falls behind
+    const PUBLICATIONS: usize = 200;
+    let payload = "x".repeat(100_000);
+    for _ in 0..PUBLICATIONS {
+        session.put("ws/overflow/a", &payload).await.unwrap();
+    }
+
+    let mut samples = 0;
+    timeout(TIMEOUT, async {
+        while let Some(Ok(Message::Text(_))) = ws.next().await {
+            samples += 1;
+        }
+    })
+    .await
+    .unwrap();
+    assert!(samples < PUBLICATIONS);
+
+    drop(rest);
+}
@@ -18,7 +18,7 @@ use std::{
 };
 
 use zenoh::{internal::bail, key_expr::OwnedKeyExpr, time::Timestamp, Result};
-use zenoh_backend_traits::config::ReplicaConfig;
+use zenoh_backend_traits::{config::ReplicaConfig, History};
 
 use super::{
     classification::{IntervalIdx, SubIntervalIdx},
@@ -33,6 +33,9 @@ use super::{
 /// a Replica active on "replication/**" to receive and process the Digests emitted by a Replica
 /// active on "replication/a/*".
 ///
+/// The `history` capability of the Storage is also part of the fingerprint: a Replica keeping all
+/// the versions of a key expression cannot align with a Replica only keeping the latest one.
+///
 /// Using the newtype pattern allows us to add methods to compute the time classification of
 /// events.
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -40,6 +43,7 @@ pub(crate) struct Configuration {
     storage_key_expr: OwnedKeyExpr,
     prefix: Option<OwnedKeyExpr>,
     replica_config: ReplicaConfig,
+    history: History,
     fingerprint: Fingerprint,
 }
 
@@ -59,6 +63,7 @@ impl Configuration {
         storage_key_expr: OwnedKeyExpr,
         prefix: Option<OwnedKeyExpr>,
         replica_config: ReplicaConfig,
+        history: History,
     ) -> Self {
         let mut hasher = xxhash_rust::xxh3::Xxh3::default();
         hasher.update(storage_key_expr.as_bytes());
@@ -70,11 +75,17 @@ impl Configuration {
         hasher.update(&replica_config.hot.to_le_bytes());
         hasher.update(&replica_config.warm.to_le_bytes());
         hasher.update(&replica_config.propagation_delay.as_millis().to_le_bytes());
+        // NOTE: The `history` is only hashed when it differs from the default such that the
+        //       fingerprint of the Replicas keeping only the latest version is left unchanged.
+        if history == History::All {
+            hasher.update(&[1]);
+        }
 
         Self {
             storage_key_expr,
             prefix,
             replica_config,
+            history,
             fingerprint: Fingerprint::from(hasher.digest()),
         }
     }
@@ -88,6 +99,14 @@ impl Configuration {
         self.prefix.as_ref()
     }
 
+    /// Returns the `history` capability of the Storage.
+    ///
+    /// If it is set to `History::All`, every version of a key expression is tracked in the
+    /// Replication Log, provided it was published in the *Hot* or *Warm* Era.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
     /// Returns the [Fingerprint] of the `Configuration`.
     ///
     /// The fingerprint is the hash of all its fields, using the `xxhash_rust` crate.
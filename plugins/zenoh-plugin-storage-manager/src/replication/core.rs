@@ -25,21 +25,29 @@ use rand::Rng;
 use tokio::{sync::RwLock, task::JoinHandle};
 use tracing::{debug_span, Instrument};
 use zenoh::{
+    handlers::FifoChannelHandler,
+    internal::{bail, zerror},
     key_expr::{
         format::{kedefine, keformat},
         OwnedKeyExpr,
     },
-    query::{ConsolidationMode, Selector},
+    query::{ConsolidationMode, Reply, Selector},
     sample::{Locality, SampleKind},
+    session::ZenohId,
     time::Timestamp,
-    Session,
+    Result as ZResult, Session,
 };
+use zenoh_backend_traits::History;
 
 use self::aligner_reply::AlignmentReply;
-use super::{digest::Digest, log::LogLatest, Action, Event, LogLatestKey};
+use super::{
+    digest::Digest,
+    log::{EventMetadata, LogLatest},
+    Action, Event, LogLatestKey,
+};
 use crate::{
     replication::core::aligner_query::AlignmentQuery,
-    storages_mgt::{LatestUpdates, StorageService},
+    storages_mgt::{LatestUpdates, StorageService, ALIGN_OPERATION},
 };
 
 kedefine!(
@@ -111,12 +119,28 @@ impl Replication {
         }
     }
 
+    /// Restores the `versioned` flag, which is not serialised, of the [EventMetadata] received from
+    /// a Replica.
+    ///
+    /// As Replicas only align if they share the same configuration, the flag is set if and only if
+    /// this Storage has the capability `History::All`.
+    pub(crate) async fn restore_versioned<'a>(
+        &self,
+        events: impl IntoIterator<Item = &'a mut EventMetadata>,
+    ) {
+        let versioned = *self.replication_log.read().await.configuration.history() == History::All;
+        events
+            .into_iter()
+            .for_each(|event| event.versioned = versioned);
+    }
+
     /// Spawns a task that periodically publishes the [Digest] of the Replication [Log].
     ///
     /// This task will perform the following steps:
     /// 1. It will swap the `latest_updates` structure with an empty one -- with the sole purpose of
     ///    minimising the contention on the StorageService.
-    /// 2. With the content from the `latest_updates`, it will update the Replication [Log].
+    /// 2. With the content from the `latest_updates`, it will update the Replication [Log] and, for
+    ///    Storage keeping all versions, remove the ones older than the *Warm* Era.
     /// 3. It will recompute the [Digest].
     /// 4. It will publish the [Digest]. The periodicity of this publication is dictated by the
     ///    `interval` configuration option.
@@ -217,6 +241,15 @@ impl Replication {
                 {
                     let mut replication_guard = replication.replication_log.write().await;
                     replication_guard.update(events.drain().map(|(_, event)| event));
+                    match configuration.last_elapsed_interval() {
+                        Ok(idx) => replication_guard.remove_events_outside_window(idx),
+                        Err(e) => {
+                            tracing::error!(
+                                "Fatal error, call to `last_elapsed_interval` failed with: {e:?}"
+                            );
+                            return;
+                        }
+                    }
                     digest = match replication_guard.digest() {
                         Ok(digest) => digest,
                         Err(e) => {
@@ -468,9 +501,89 @@ impl Replication {
         })
     }
 
+    /// Spawns a new task to retrieve all the entries of the provided Replica, regardless of the
+    /// Digests.
+    ///
+    /// The outcome of this alignment is recorded as the progress of the `align` operation of the
+    /// Storage.
+    pub(crate) fn spawn_full_alignment(&self, replica: ZenohId) -> JoinHandle<()> {
+        let replication = self.clone();
+        tokio::task::spawn(async move {
+            let error = replication
+                .full_alignment(replica)
+                .await
+                .err()
+                .map(|e| e.to_string());
+            replication
+                .storage_service
+                .finish_operation(ALIGN_OPERATION, error)
+                .await;
+        })
+    }
+
+    /// Retrieves all the entries of the provided Replica, returning once all of them were
+    /// processed.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the Replica could not be found (it is not reachable or
+    /// its configuration differs) or if any of the queries sent to its Aligner failed.
+    async fn full_alignment(&self, replica: ZenohId) -> ZResult<()> {
+        let replica_aligner_ke = keformat!(
+            aligner_key_expr_formatter::formatter(),
+            hash_configuration = *self
+                .replication_log
+                .read()
+                .await
+                .configuration
+                .fingerprint(),
+            zid = replica,
+        )?;
+
+        // A Replica with nothing to align does not reply to an `AlignmentQuery::All`: the Replica
+        // is first discovered to distinguish it from a Replica that does not exist.
+        let discovery_replies = self
+            .send_alignment_query(&replica_aligner_ke, &AlignmentQuery::Discovery)
+            .await?;
+        if !discovery_replies
+            .recv_async()
+            .await
+            .is_ok_and(|reply| reply.result().is_ok())
+        {
+            bail!(
+                "Replica < {replica} > not found: either it is not reachable or its configuration \
+                 differs"
+            );
+        }
+
+        tracing::debug!("Performing full alignment with < {replica_aligner_ke} >");
+        self.query_replica_aligner(replica_aligner_ke, AlignmentQuery::All)
+            .await
+    }
+
     /// Spawns a new task to query the Aligner of the remote Replica which potentially has data this
     /// Storage is missing.
     ///
+    /// See [Replication::query_replica_aligner] for the details, any error is logged.
+    pub(crate) fn spawn_query_replica_aligner(
+        &self,
+        replica_aligner_ke: OwnedKeyExpr,
+        alignment_query: AlignmentQuery,
+    ) -> JoinHandle<()> {
+        let replication = self.clone();
+        tokio::task::spawn(async move {
+            if let Err(e) = replication
+                .query_replica_aligner(replica_aligner_ke, alignment_query)
+                .await
+            {
+                tracing::error!("{e:?}");
+            }
+        })
+    }
+
+    /// Queries the Aligner of the remote Replica which potentially has data this Storage is
+    /// missing.
+    ///
     /// This method will:
     /// 1. Serialise the AlignmentQuery.
     /// 2. Send a Query to the Aligner of the Replica, adding the serialised AlignmentQuery as an
@@ -481,99 +594,102 @@ impl Replication {
     /// information), consequently spawning a new task.
     ///
     /// This process is stateless and all the required information are carried in the query / reply.
-    pub(crate) fn spawn_query_replica_aligner(
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the Query could not be sent or if the Replica replied
+    /// with an error. In the latter case, the other replies are still processed.
+    pub(crate) async fn query_replica_aligner(
         &self,
         replica_aligner_ke: OwnedKeyExpr,
         alignment_query: AlignmentQuery,
-    ) -> JoinHandle<()> {
-        let replication = self.clone();
-        tokio::task::spawn(async move {
-            let attachment = match bincode::serialize(&alignment_query) {
-                Ok(attachment) => attachment,
+    ) -> ZResult<()> {
+        let reply_receiver = self
+            .send_alignment_query(&replica_aligner_ke, &alignment_query)
+            .await?;
+
+        let mut error = None;
+        while let Ok(reply) = reply_receiver.recv_async().await {
+            let sample = match reply.into_result() {
+                Ok(sample) => sample,
                 Err(e) => {
-                    tracing::error!("Failed to serialize AlignmentQuery: {e:?}");
-                    return;
+                    tracing::warn!("Skipping reply to query to < {replica_aligner_ke} >: {e:?}");
+                    error.get_or_insert_with(|| {
+                        zerror!("Aligner < {replica_aligner_ke} > replied with an error: {e:?}")
+                    });
+                    continue;
                 }
             };
 
-            // NOTE: We need to put the Consolidation to `None` as otherwise if multiple replies are
-            //       sent, they will be "consolidated" and only one of them will make it through.
-            //
-            //       When we retrieve Samples from a Replica, each Sample is sent in a separate
-            //       reply. Hence the need to have no consolidation.
-            let mut consolidation = ConsolidationMode::None;
+            let alignment_reply = match sample.attachment() {
+                None => {
+                    tracing::debug!("Skipping reply without attachment");
+                    continue;
+                }
+                Some(attachment) => {
+                    match bincode::deserialize::<AlignmentReply>(&attachment.to_bytes()) {
+                        Err(e) => {
+                            tracing::error!(
+                                "Failed to deserialize attachment as AlignmentReply: {e:?}"
+                            );
+                            continue;
+                        }
+                        Ok(alignment_reply) => alignment_reply,
+                    }
+                }
+            };
+
+            self.process_alignment_reply(replica_aligner_ke.clone(), alignment_reply, sample)
+                .await;
 
+            // The consolidation mode `Monotonic`, used for sending out an
+            // `AlignmentQuery::Discovery`, will keep on sending replies. We only want to discover /
+            // align with a single Replica so we break here.
             if matches!(alignment_query, AlignmentQuery::Discovery) {
-                // NOTE: `Monotonic` means that Zenoh will forward the first answer it receives (and
-                //       ensure that later answers are with a higher timestamp — we do not care
-                //       about that last aspect).
-                //
-                //       By setting the consolidation to this value when performing the initial
-                //       alignment, we select the most reactive Replica (hopefully the closest as
-                //       well).
-                consolidation = ConsolidationMode::Monotonic;
+                break;
             }
+        }
 
-            match replication
-                .zenoh_session
-                .get(Into::<Selector>::into(replica_aligner_ke.clone()))
-                .attachment(attachment)
-                .consolidation(consolidation)
-                .await
-            {
-                Err(e) => {
-                    tracing::error!("Failed to query Aligner < {replica_aligner_ke} >: {e:?}");
-                }
-                Ok(reply_receiver) => {
-                    while let Ok(reply) = reply_receiver.recv_async().await {
-                        let sample = match reply.into_result() {
-                            Ok(sample) => sample,
-                            Err(e) => {
-                                tracing::warn!(
-                                    "Skipping reply to query to < {replica_aligner_ke} >: {e:?}"
-                                );
-                                continue;
-                            }
-                        };
+        match error {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
 
-                        let alignment_reply = match sample.attachment() {
-                            None => {
-                                tracing::debug!("Skipping reply without attachment");
-                                continue;
-                            }
-                            Some(attachment) => {
-                                match bincode::deserialize::<AlignmentReply>(&attachment.to_bytes())
-                                {
-                                    Err(e) => {
-                                        tracing::error!(
-                                            "Failed to deserialize attachment as AlignmentReply: \
-                                             {e:?}"
-                                        );
-                                        continue;
-                                    }
-                                    Ok(alignment_reply) => alignment_reply,
-                                }
-                            }
-                        };
+    /// Sends the [AlignmentQuery], serialised as an attachment, to the Aligner of the remote
+    /// Replica and returns the receiver of its replies.
+    async fn send_alignment_query(
+        &self,
+        replica_aligner_ke: &OwnedKeyExpr,
+        alignment_query: &AlignmentQuery,
+    ) -> ZResult<FifoChannelHandler<Reply>> {
+        let attachment = bincode::serialize(alignment_query)
+            .map_err(|e| zerror!("Failed to serialize AlignmentQuery: {e:?}"))?;
+
+        // NOTE: We need to put the Consolidation to `None` as otherwise if multiple replies are
+        //       sent, they will be "consolidated" and only one of them will make it through.
+        //
+        //       When we retrieve Samples from a Replica, each Sample is sent in a separate
+        //       reply. Hence the need to have no consolidation.
+        let mut consolidation = ConsolidationMode::None;
+
+        if matches!(alignment_query, AlignmentQuery::Discovery) {
+            // NOTE: `Monotonic` means that Zenoh will forward the first answer it receives (and
+            //       ensure that later answers are with a higher timestamp — we do not care
+            //       about that last aspect).
+            //
+            //       By setting the consolidation to this value when performing the initial
+            //       alignment, we select the most reactive Replica (hopefully the closest as
+            //       well).
+            consolidation = ConsolidationMode::Monotonic;
+        }
 
-                        replication
-                            .process_alignment_reply(
-                                replica_aligner_ke.clone(),
-                                alignment_reply,
-                                sample,
-                            )
-                            .await;
-
-                        // The consolidation mode `Monotonic`, used for sending out an
-                        // `AlignmentQuery::Discovery`, will keep on sending replies. We only want
-                        // to discover / align with a single Replica so we break here.
-                        if matches!(alignment_query, AlignmentQuery::Discovery) {
-                            return;
-                        }
-                    }
-                }
-            }
-        })
+        self.zenoh_session
+            .get(Into::<Selector>::into(replica_aligner_ke.clone()))
+            .attachment(attachment)
+            .consolidation(consolidation)
+            .await
+            .map_err(|e| zerror!("Failed to query Aligner < {replica_aligner_ke} >: {e:?}").into())
     }
 }
 
@@ -12,13 +12,17 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, UNIX_EPOCH},
+};
 
 use serde::{Deserialize, Serialize};
 use zenoh::{
     bytes::{Encoding, ZBytes},
     key_expr::keyexpr_tree::IKeyExprTree,
-    query::Query,
+    query::{Query, TimeBound, TimeExpr, TimeRange},
+    time::Timestamp,
 };
 
 use super::aligner_reply::AlignmentReply;
@@ -162,8 +166,9 @@ impl Replication {
                         .await;
                 }
             }
-            AlignmentQuery::Events(events_to_retrieve) => {
+            AlignmentQuery::Events(mut events_to_retrieve) => {
                 tracing::trace!("Processing `AlignmentQuery::Events`");
+                self.restore_versioned(&mut events_to_retrieve).await;
                 for event_to_retrieve in events_to_retrieve {
                     self.reply_event_retrieval(&query, event_to_retrieve).await;
                 }
@@ -278,10 +283,18 @@ impl Replication {
             Action::Delete | Action::WildcardDelete(_) => None,
             // For a Put we need to retrieve the `Value` in the Storage.
             Action::Put => {
+                // A Storage keeping all the versions of a key expression only returns the one
+                // we are looking for if its time range is selected.
+                let parameters = if event_to_retrieve.versioned {
+                    version_time_range(event_to_retrieve.timestamp())
+                } else {
+                    String::default()
+                };
+
                 let stored_data = {
                     let mut storage = self.storage_service.storage.lock().await;
                     match storage
-                        .get(event_to_retrieve.stripped_key.clone(), "")
+                        .get(event_to_retrieve.stripped_key.clone(), &parameters)
                         .await
                     {
                         Ok(stored_data) => stored_data,
@@ -335,6 +348,27 @@ impl Replication {
     }
 }
 
+/// Returns the `_time` selector parameter of the second containing the provided [Timestamp].
+///
+/// A time range is expressed with a precision of a second, the version with the exact [Timestamp]
+/// still has to be looked up among the ones returned by the Storage.
+fn version_time_range(timestamp: &Timestamp) -> String {
+    let secs_since_epoch = timestamp
+        .get_time()
+        .to_system_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let start = UNIX_EPOCH + Duration::from_secs(secs_since_epoch);
+
+    let time_range = TimeRange {
+        start: TimeBound::Inclusive(TimeExpr::Fixed(start)),
+        end: TimeBound::Exclusive(TimeExpr::Fixed(start + Duration::from_secs(1))),
+    };
+
+    format!("_time={time_range}")
+}
+
 /// Replies to a Query, adding the [AlignmentReply] as an attachment and, if provided, the payload
 /// with the corresponding [zenoh::bytes::Encoding].
 async fn reply_to_query(query: &Query, reply: AlignmentReply, value: Option<(ZBytes, Encoding)>) {
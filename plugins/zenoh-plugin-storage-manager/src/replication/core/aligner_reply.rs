@@ -198,8 +198,9 @@ impl Replication {
                     );
                 }
             }
-            AlignmentReply::EventsMetadata(replica_events) => {
+            AlignmentReply::EventsMetadata(mut replica_events) => {
                 tracing::trace!("Processing `AlignmentReply::EventsMetadata`");
+                self.restore_versioned(&mut replica_events).await;
                 let mut diff_events = Vec::default();
 
                 for replica_event in replica_events {
@@ -222,7 +223,8 @@ impl Replication {
                     );
                 }
             }
-            AlignmentReply::Retrieval(replica_event) => {
+            AlignmentReply::Retrieval(mut replica_event) => {
+                self.restore_versioned([&mut replica_event]).await;
                 self.process_event_retrieval(replica_event, sample).await;
             }
         }
@@ -241,6 +243,10 @@ impl Replication {
     /// See the [needs_further_processing] function for more information on the specific cases we
     /// need to be aware of.
     async fn process_event_metadata(&self, replica_event: EventMetadata) -> Option<EventMetadata> {
+        if replica_event.versioned {
+            return self.process_version_metadata(replica_event).await;
+        }
+
         if self
             .latest_updates
             .read()
@@ -285,6 +291,13 @@ impl Replication {
                                 .await
                                 .delete(replica_event.stripped_key.clone(), replica_event.timestamp)
                                 .await;
+                            self.storage_service
+                                .register_aligned_expiration(
+                                    replica_event.stripped_key.clone(),
+                                    SampleKind::Delete,
+                                    replica_event.timestamp,
+                                )
+                                .await;
                         }
                     }
                 }
@@ -321,6 +334,11 @@ impl Replication {
     async fn process_event_retrieval(&self, replica_event: EventMetadata, sample: Sample) {
         tracing::trace!("Processing `AlignmentReply::Retrieval` for < {replica_event:?} >");
 
+        if replica_event.versioned {
+            self.process_version_retrieval(replica_event, sample).await;
+            return;
+        }
+
         if self
             .latest_updates
             .read()
@@ -342,16 +360,36 @@ impl Replication {
 
         // The Event is newer than what we have and is not overridden by a Wildcard Update, we
         // need to process it.
-        replication_log_guard.remove_older(&replica_event);
+        let removal = replication_log_guard.remove_older(&replica_event);
 
         match &replica_event.action {
             // NOTE: This code can only be called with `action` set to `Delete` or `WildcardDelete`
-            // on an initial alignment, in which case the Storage of the receiving Replica is empty
-            // => there is no need to actually call `storage.delete`.
-            //
-            // Outside of an initial alignment, the `Delete` or `WildcardDelete` actions will be
+            // on an initial alignment or on a full alignment requested through the adminspace.
+            // Outside of these alignments, the `Delete` or `WildcardDelete` actions will be
             // performed at the step above, in `AlignmentReply::EventsMetadata`.
-            Action::Delete => {}
+            //
+            // The Storage of the receiving Replica is empty on an initial alignment but it is not
+            // necessarily the case on a full alignment: the older entry, if any, is deleted.
+            Action::Delete => {
+                if let EventRemoval::RemovedOlder(older_event) = removal {
+                    if older_event.action == Action::Put {
+                        let _ = self
+                            .storage_service
+                            .storage
+                            .lock()
+                            .await
+                            .delete(replica_event.stripped_key.clone(), replica_event.timestamp)
+                            .await;
+                        self.storage_service
+                            .register_aligned_expiration(
+                                replica_event.stripped_key.clone(),
+                                SampleKind::Delete,
+                                replica_event.timestamp,
+                            )
+                            .await;
+                    }
+                }
+            }
             Action::WildcardDelete(wildcard_delete_ke) => {
                 self.storage_service
                     .register_wildcard_update(
@@ -388,6 +426,13 @@ impl Replication {
                     // In that scenario the Storage should either return an error or `Outdated`.
                     return;
                 }
+                self.storage_service
+                    .register_aligned_expiration(
+                        replica_event.stripped_key.clone(),
+                        SampleKind::Put,
+                        replica_event.timestamp,
+                    )
+                    .await;
             }
             Action::WildcardPut(_) => {
                 let SampleFields {
@@ -408,6 +453,130 @@ impl Replication {
         replication_log_guard.insert_event_unchecked(replica_event.into());
     }
 
+    /// Processes the `versioned` [EventMetadata] sent by the remote Replica, i.e. a version of a key
+    /// expression kept by a Storage with the capability `History::All`.
+    ///
+    /// Versions are independent from one another: a version is missing if this Replica has no
+    /// Event with the same key expression and [Timestamp]. Wildcard Updates do not need to be
+    /// considered as they are not tracked for such Storage, only the versions they produce are.
+    ///
+    /// If we need to retrieve the payload then this [EventMetadata] is returned. If we don't,
+    /// `None` is returned.
+    async fn process_version_metadata(
+        &self,
+        replica_event: EventMetadata,
+    ) -> Option<EventMetadata> {
+        if self
+            .latest_updates
+            .read()
+            .await
+            .contains_key(&replica_event.log_key())
+        {
+            return None;
+        }
+
+        let mut replication_log_guard = self.replication_log.write().await;
+        if replication_log_guard.lookup_newer(&replica_event).is_some() {
+            return None;
+        }
+
+        match &replica_event.action {
+            Action::Put => return Some(replica_event),
+            Action::Delete => {
+                // NOTE: See the comment in `process_event_metadata`, an error cannot be
+                //       distinguished from a missing key.
+                let _ = self
+                    .storage_service
+                    .storage
+                    .lock()
+                    .await
+                    .delete(replica_event.stripped_key.clone(), replica_event.timestamp)
+                    .await;
+                self.storage_service
+                    .register_aligned_expiration(
+                        replica_event.stripped_key.clone(),
+                        SampleKind::Delete,
+                        replica_event.timestamp,
+                    )
+                    .await;
+            }
+            Action::WildcardPut(_) | Action::WildcardDelete(_) => {
+                tracing::warn!("Ignoring versioned Wildcard Update < {replica_event:?} >");
+                return None;
+            }
+        }
+
+        replication_log_guard.insert_event_unchecked(replica_event.into());
+        None
+    }
+
+    /// Processes the `versioned` [EventMetadata] and [Sample] sent by the remote Replica, adding
+    /// this version to our Storage if it is missing.
+    ///
+    /// As for [process_event_retrieval], a `Delete` can only be received here during the initial
+    /// alignment.
+    async fn process_version_retrieval(&self, replica_event: EventMetadata, sample: Sample) {
+        if self
+            .latest_updates
+            .read()
+            .await
+            .contains_key(&replica_event.log_key())
+        {
+            return;
+        }
+
+        let mut replication_log_guard = self.replication_log.write().await;
+        if replication_log_guard.lookup_newer(&replica_event).is_some() {
+            return;
+        }
+
+        let mut storage = self.storage_service.storage.lock().await;
+        match &replica_event.action {
+            Action::Put => {
+                let SampleFields {
+                    payload, encoding, ..
+                } = sample.into();
+                if matches!(
+                    storage
+                        .put(
+                            replica_event.stripped_key.clone(),
+                            payload,
+                            encoding,
+                            replica_event.timestamp,
+                        )
+                        .await,
+                    Ok(StorageInsertionResult::Outdated) | Err(_)
+                ) {
+                    return;
+                }
+            }
+            Action::Delete => {
+                let _ = storage
+                    .delete(replica_event.stripped_key.clone(), replica_event.timestamp)
+                    .await;
+            }
+            Action::WildcardPut(_) | Action::WildcardDelete(_) => {
+                tracing::warn!("Ignoring versioned Wildcard Update < {replica_event:?} >");
+                return;
+            }
+        }
+        drop(storage);
+
+        let kind = match replica_event.action {
+            Action::Put => SampleKind::Put,
+            _ => SampleKind::Delete,
+        };
+        self.storage_service
+            .register_aligned_expiration(
+                replica_event.stripped_key.clone(),
+                kind,
+                replica_event.timestamp,
+            )
+            .await;
+
+        replication_log_guard.insert_event_unchecked(replica_event.into());
+    }
+
     /// Returns `true` if the provided `replica_event` requires more processing.
     ///
     /// This method will:
@@ -495,6 +664,13 @@ impl Replication {
                         .await
                         .delete(replica_event.stripped_key.clone(), *log_event.timestamp())
                         .await;
+                    self.storage_service
+                        .register_aligned_expiration(
+                            replica_event.stripped_key.clone(),
+                            SampleKind::Delete,
+                            *log_event.timestamp(),
+                        )
+                        .await;
                 }
 
                 let log_event_metadata = log_event.into();
@@ -635,6 +811,13 @@ impl Replication {
                     ) {
                         continue;
                     }
+                    self.storage_service
+                        .register_aligned_expiration(
+                            overridden_event.key_expr().clone(),
+                            SampleKind::Put,
+                            replica_event.timestamp,
+                        )
+                        .await;
                 }
                 (Action::Put, SampleKind::Delete) => {
                     if matches!(
@@ -651,6 +834,13 @@ impl Replication {
                     ) {
                         continue;
                     }
+                    self.storage_service
+                        .register_aligned_expiration(
+                            overridden_event.key_expr().clone(),
+                            SampleKind::Delete,
+                            *overridden_event.timestamp(),
+                        )
+                        .await;
                 }
 
                 // We are overriding a Wildcard Update with another Wildcard Update, there is no
@@ -799,6 +989,9 @@ impl Replication {
             );
             return;
         }
+        self.storage_service
+            .register_aligned_expiration(replica_event.stripped_key.clone(), kind, timestamp)
+            .await;
 
         // First create an Event with the metadata sent by the Replica: we want to keep the
         // `timestamp_last_non_wildcard_update`.
@@ -20,7 +20,7 @@ use std::{
 use bloomfilter::Bloom;
 use serde::{Deserialize, Serialize};
 use zenoh::{key_expr::OwnedKeyExpr, sample::SampleKind, time::Timestamp, Result as ZResult};
-use zenoh_backend_traits::config::ReplicaConfig;
+use zenoh_backend_traits::{config::ReplicaConfig, History};
 
 use super::{
     classification::{EventLookup, EventRemoval, Interval, IntervalIdx},
@@ -97,12 +97,25 @@ impl From<&Action> for ActionKind {
 
 /// The `EventMetadata` structure contains all the information needed by a replica to assess if it
 /// is missing an [Event] in its log.
+///
+/// An Event is `versioned` when it was produced by a Storage with the capability `History::All`:
+/// each of its versions is then tracked separately in the Replication Log.
+///
+/// The `versioned` flag is not serialised, such that the format exchanged between Replicas is
+/// unchanged. It is instead restored upon reception, see [Replication::restore_versioned]: only
+/// Replicas with the same [Configuration] align and, as the `history` capability is part of its
+/// fingerprint, the flag of the received Events is the same as the one of the local Events.
+///
+/// [Replication::restore_versioned]: super::core::Replication::restore_versioned
+/// [Configuration]: super::configuration::Configuration
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Hash)]
 pub struct EventMetadata {
     pub(crate) stripped_key: Option<OwnedKeyExpr>,
     pub(crate) timestamp: Timestamp,
     pub(crate) timestamp_last_non_wildcard_update: Option<Timestamp>,
     pub(crate) action: Action,
+    #[serde(skip)]
+    pub(crate) versioned: bool,
 }
 
 impl EventMetadata {
@@ -119,10 +132,13 @@ impl EventMetadata {
     }
 
     /// Returns the [LogLatestKey] corresponding to this [Event].
+    ///
+    /// The [Timestamp] is part of the key of a `versioned` Event.
     pub fn log_key(&self) -> LogLatestKey {
         LogLatestKey {
             maybe_stripped_key: self.stripped_key.clone(),
             action: (&self.action).into(),
+            version: self.versioned.then_some(self.timestamp),
         }
     }
 }
@@ -134,6 +150,7 @@ impl From<&Event> for EventMetadata {
             timestamp: event.timestamp,
             timestamp_last_non_wildcard_update: event.timestamp_last_non_wildcard_update,
             action: event.action.clone(),
+            versioned: event.versioned,
         }
     }
 }
@@ -219,10 +236,18 @@ impl Event {
                 timestamp,
                 timestamp_last_non_wildcard_update,
                 action: actual_action,
+                versioned: false,
             },
         }
     }
 
+    /// Marks this [Event] as `versioned`, i.e. as one of the versions of its key expression kept
+    /// by a Storage with the capability `History::All`.
+    pub fn into_versioned(mut self) -> Self {
+        self.metadata.versioned = true;
+        self
+    }
+
     /// Computes the [Fingerprint] of the [Event], which is equal to the hash of its fields
     /// `timestamp` and `maybe_stripped_key`.
     ///
@@ -286,10 +311,11 @@ pub enum EventInsertion {
 
 /// The `LogLatest` keeps track of the last publication that happened on a key expression.
 ///
-/// By definition, the `LogLatest` is only compatible with storage that have the capability
-/// `History::Latest` (the default value). For instance, that means that it will *not* work for
-/// time-series storage that keep track of all the publications that happen for a given key
-/// expression.
+/// For Storage that have the capability `History::All` (i.e. time-series storage that keep track
+/// of all the publications that happen for a given key expression), the `LogLatest` instead keeps
+/// track of every version: their [Event]s are `versioned` and their [Timestamp] is part of their
+/// [LogLatestKey]. As keeping all versions forever is not an option, only the versions published
+/// in the *Hot* and *Warm* Eras are kept, see [LogLatest::remove_events_outside_window].
 ///
 /// Internally, the `LogLatest` groups publications (i.e. [Event]s) according to their [Timestamp]
 /// in [Interval]s and [SubInterval]s. The purpose of this grouping is to facilitate the alignment
@@ -313,6 +339,7 @@ pub struct LogLatest {
 pub(crate) struct LogLatestKey {
     maybe_stripped_key: Option<OwnedKeyExpr>,
     action: ActionKind,
+    version: Option<Timestamp>,
 }
 
 impl LogLatest {
@@ -339,9 +366,10 @@ impl LogLatest {
         storage_key_expr: OwnedKeyExpr,
         prefix: Option<OwnedKeyExpr>,
         replica_config: ReplicaConfig,
+        history: History,
     ) -> Self {
         Self {
-            configuration: Configuration::new(storage_key_expr, prefix, replica_config),
+            configuration: Configuration::new(storage_key_expr, prefix, replica_config, history),
             intervals: BTreeMap::default(),
             // TODO Should these be configurable?
             //
@@ -533,6 +561,22 @@ impl LogLatest {
         });
     }
 
+    /// Removes the Intervals that are older than the *Warm* Era, assuming that the *Hot* Era ends
+    /// at the provided `hot_era_upper_bound`.
+    ///
+    /// This method only has an effect if the Storage has the capability `History::All`: the
+    /// versions of a key expression are then only aligned if they were published in the *Hot* or
+    /// *Warm* Era. As all Replicas call this method before computing their [Digest], the
+    /// fingerprint of their *Cold* Era is always empty.
+    pub(crate) fn remove_events_outside_window(&mut self, hot_era_upper_bound: IntervalIdx) {
+        if *self.configuration.history() != History::All {
+            return;
+        }
+
+        let warm_era_lower_bound = self.configuration.warm_era_lower_bound(hot_era_upper_bound);
+        self.intervals = self.intervals.split_off(&warm_era_lower_bound);
+    }
+
     /// Retrieves the latest [Digest], assuming that the hot era starts at the last elapsed
     /// interval.
     ///
@@ -15,8 +15,10 @@
 //! This module exposes the [ReplicationService] structure needed by the storage manager to
 //! replicate the content of storage across a Zenoh network.
 //!
-//! This structure, and thus the replication, only works for storage that have the [History::Latest]
-//! capability.
+//! For storage that have the [History::Latest] capability, the replication aligns the latest value
+//! of each key expression. For storage that have the [History::All] capability, it aligns every
+//! version of each key expression published during the "hot" and "warm" eras: older versions are
+//! not tracked and hence are not transferred.
 //!
 //! From a high-level, the replication works by generating a concise view of the state of the
 //! storage at regular time intervals. To do so, the time is divided in 'intervals' (themselves
@@ -25,6 +27,7 @@
 //! comparison.
 //!
 //! [History::Latest]: zenoh_backend_traits::History::Latest
+//! [History::All]: zenoh_backend_traits::History::All
 
 mod classification;
 mod configuration;
@@ -15,10 +15,16 @@
 use std::sync::Arc;
 
 use tokio::{
-    sync::{broadcast::Receiver, RwLock},
+    sync::{
+        broadcast::{error::RecvError, Receiver},
+        mpsc, RwLock,
+    },
     task::JoinHandle,
 };
-use zenoh::{key_expr::OwnedKeyExpr, session::Session};
+use zenoh::{
+    key_expr::OwnedKeyExpr,
+    session::{Session, ZenohId},
+};
 
 use super::{core::Replication, LogLatest};
 use crate::storages_mgt::{LatestUpdates, StorageMessage, StorageService};
@@ -46,7 +52,8 @@ impl ReplicationService {
     /// 3. One to receive alignment queries of other Replica.
     /// 4. One to wait on the provided [Receiver] in order to stop the Replication Service,
     ///    attempting to abort all the tasks that were spawned, once a Stop message has been
-    ///    received.
+    ///    received. This task also starts the full alignments requested through the adminspace,
+    ///    received on the provided [mpsc::Receiver].
     pub async fn spawn_start(
         zenoh_session: Arc<Session>,
         storage_service: Arc<StorageService>,
@@ -54,6 +61,7 @@ impl ReplicationService {
         replication_log: Arc<RwLock<LogLatest>>,
         latest_updates: Arc<RwLock<LatestUpdates>>,
         mut rx: Receiver<StorageMessage>,
+        mut align_rx: mpsc::Receiver<ZenohId>,
     ) {
         let replication = Replication {
             zenoh_session,
@@ -80,10 +88,20 @@ impl ReplicationService {
                 aligner_queryable_handle: replication.spawn_aligner_queryable(),
             };
 
-            while let Ok(storage_message) = rx.recv().await {
-                if matches!(storage_message, StorageMessage::Stop) {
-                    replication_service.stop();
-                    return;
+            loop {
+                tokio::select! {
+                    storage_message = rx.recv() => match storage_message {
+                        Ok(StorageMessage::Stop) | Err(RecvError::Closed) => {
+                            replication_service.stop();
+                            return;
+                        }
+                        // NOTE: Only the Stop message matters here, missing the other ones is not
+                        //       an issue.
+                        Ok(StorageMessage::GetStatus(_)) | Err(RecvError::Lagged(_)) => {}
+                    },
+                    Some(replica) = align_rx.recv() => {
+                        replication.spawn_full_alignment(replica);
+                    }
                 }
             }
         });
@@ -30,6 +30,7 @@ fn test_lower_bounds() {
             warm: 5,
             propagation_delay: Duration::from_millis(250),
         },
+        History::Latest,
     );
 
     assert_eq!(
@@ -56,12 +57,14 @@ fn test_difference() {
         OwnedKeyExpr::from_str("replication/test/**").unwrap(),
         None,
         identical_replica_config.clone(),
+        History::Latest,
     );
 
     let configuration_b = Configuration::new(
         OwnedKeyExpr::from_str("replication/test/a/*").unwrap(),
         None,
         identical_replica_config.clone(),
+        History::Latest,
     );
 
     assert_ne!(configuration_a.fingerprint, configuration_b.fingerprint);
@@ -70,9 +73,19 @@ fn test_difference() {
         configuration_a.storage_key_expr,
         Some(OwnedKeyExpr::from_str("replication/test").unwrap()),
         identical_replica_config,
+        History::Latest,
     );
 
     assert_ne!(configuration_a.fingerprint, configuration_c.fingerprint);
+
+    let configuration_d = Configuration::new(
+        configuration_c.storage_key_expr.clone(),
+        configuration_c.prefix.clone(),
+        configuration_c.replica_config.clone(),
+        History::All,
+    );
+
+    assert_ne!(configuration_c.fingerprint, configuration_d.fingerprint);
 }
 
 #[test]
@@ -87,6 +100,7 @@ fn test_get_classification() {
             warm: 5,
             propagation_delay: Duration::from_millis(250),
         },
+        History::Latest,
     );
 
     let hlc = HLC::default();
@@ -16,7 +16,7 @@ use std::{collections::HashMap, str::FromStr, time::Duration};
 
 use uhlc::{Timestamp, HLC, NTP64};
 use zenoh::key_expr::OwnedKeyExpr;
-use zenoh_backend_traits::config::ReplicaConfig;
+use zenoh_backend_traits::{config::ReplicaConfig, History};
 
 use super::{Event, EventMetadata, LogLatest};
 use crate::replication::{
@@ -55,6 +55,7 @@ fn test_insert() {
             warm: 5,
             propagation_delay: Duration::from_millis(250),
         },
+        History::Latest,
     );
 
     let event_10_0_0 = Event::new(
@@ -119,6 +120,7 @@ fn test_digest() {
             warm: 5,
             propagation_delay: Duration::from_millis(250),
         },
+        History::Latest,
     );
 
     let event_warm_5_1_0 = Event::new(
@@ -297,6 +299,103 @@ fn test_digest() {
     assert_eq!(expected_digest, log.digest_from(IntervalIdx(12)));
 }
 
+#[test]
+fn test_versioned() {
+    let hlc = HLC::default();
+    let mut log = LogLatest::new(
+        OwnedKeyExpr::from_str("replication/test/**").unwrap(),
+        None,
+        ReplicaConfig {
+            interval: Duration::from_secs(10),
+            sub_intervals: 5,
+            hot: 1,
+            warm: 5,
+            propagation_delay: Duration::from_millis(250),
+        },
+        History::All,
+    );
+
+    let ke = Some(OwnedKeyExpr::from_str("test/key").unwrap());
+    let event_4_0_0 = Event::new(
+        ke.clone(),
+        generate_timestamp_matching(&log, &hlc, 4, 0, 0),
+        &Action::Put,
+    )
+    .into_versioned();
+    let event_10_0_0 = Event::new(
+        ke.clone(),
+        generate_timestamp_matching(&log, &hlc, 10, 0, 0),
+        &Action::Put,
+    )
+    .into_versioned();
+    let event_10_1_0 = Event::new(
+        ke.clone(),
+        generate_timestamp_matching(&log, &hlc, 10, 1, 0),
+        &Action::Delete,
+    )
+    .into_versioned();
+
+    // All the versions of the same key expression are kept.
+    for event in [&event_10_1_0, &event_10_0_0, &event_4_0_0] {
+        assert_eq!(
+            EventInsertion::New(event.clone()),
+            log.insert_event(event.clone())
+        );
+    }
+    assert_eq!(
+        EventInsertion::NotInsertedAsOlder,
+        log.insert_event(event_10_0_0.clone())
+    );
+
+    assert_eq!(
+        Some(&event_10_0_0),
+        log.lookup_newer(&(&event_10_0_0).into())
+    );
+    let event_10_2_0 = Event::new(
+        ke,
+        generate_timestamp_matching(&log, &hlc, 10, 2, 0),
+        &Action::Put,
+    )
+    .into_versioned();
+    assert!(log.lookup_newer(&(&event_10_2_0).into()).is_none());
+
+    // The `versioned` flag is not part of the format exchanged between Replicas.
+    let metadata: EventMetadata = (&event_10_2_0).into();
+    let serialized = bincode::serialize(&metadata).unwrap();
+    assert_eq!(
+        serialized,
+        bincode::serialize(&EventMetadata {
+            versioned: false,
+            ..metadata.clone()
+        })
+        .unwrap()
+    );
+    assert!(
+        !bincode::deserialize::<EventMetadata>(&serialized)
+            .unwrap()
+            .versioned
+    );
+
+    // With 10 as the upper bound of the Hot Era, the Warm Era starts at 5: the version published
+    // in the Interval 4 is removed.
+    log.remove_events_outside_window(IntervalIdx(10));
+    assert!(!log.intervals.contains_key(&IntervalIdx(4)));
+
+    let expected_digest = Digest {
+        configuration_fingerprint: log.configuration.fingerprint(),
+        cold_era_fingerprint: Fingerprint::default(),
+        warm_era_fingerprints: HashMap::default(),
+        hot_era_fingerprints: HashMap::from([(
+            IntervalIdx(10),
+            HashMap::from([
+                (SubIntervalIdx(0), event_10_0_0.fingerprint()),
+                (SubIntervalIdx(1), event_10_1_0.fingerprint()),
+            ]),
+        )]),
+    };
+    assert_eq!(expected_digest, log.digest_from(IntervalIdx(10)));
+}
+
 #[test]
 fn test_event() {
     let hlc = HLC::default();
@@ -310,6 +409,7 @@ fn test_event() {
             timestamp: ts,
             timestamp_last_non_wildcard_update: Some(ts),
             action: Action::Put,
+            versioned: false,
         },
         fingerprint: Event::compute_fingerprint(&Some(ke.clone()), &ts),
     };
@@ -327,6 +427,7 @@ fn test_event() {
             timestamp: wildcard_ts,
             timestamp_last_non_wildcard_update: None,
             action: Action::WildcardPut(wildcard_ke.clone()),
+            versioned: false,
         },
         fingerprint: Event::compute_fingerprint(&Some(wildcard_ke.clone()), &wildcard_ts),
     };
@@ -353,6 +454,7 @@ fn test_convert_metadata_to_event() {
         timestamp: wildcard_timestamp,
         timestamp_last_non_wildcard_update: None,
         action: Action::WildcardPut(wildcard_ke.clone()),
+        versioned: false,
     };
 
     let expected_wildcard_event = Event::new(
@@ -373,6 +475,7 @@ fn test_convert_metadata_to_event() {
         timestamp: put_timestamp,
         timestamp_last_non_wildcard_update: Some(put_timestamp),
         action: Action::Put,
+        versioned: false,
     };
 
     let expected_put_event = Event::new(Some(put_ke.clone()), put_timestamp, &Action::Put);
@@ -389,6 +492,7 @@ fn test_convert_metadata_to_event() {
         timestamp: overridden_timestamp,
         timestamp_last_non_wildcard_update: Some(put_timestamp),
         action: Action::Put,
+        versioned: false,
     };
 
     let expected_put_event = Event {
@@ -0,0 +1,184 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    key_expr::{keyexpr, OwnedKeyExpr},
+    pubsub::Publisher,
+    sample::SampleKind,
+    session::Session,
+    time::Timestamp,
+    Result as ZResult,
+};
+use zenoh_backend_traits::{Storage, StorageInsertionResult, StoredData};
+
+/// The chunk, appended to the admin key of a Storage, on which its changes are published.
+///
+/// A liveliness token is also declared on that key expression while the Storage is running.
+pub(crate) const CHANGES_CHUNK: &str = "changes";
+
+/// A `Change` describes the outcome of an operation performed on a Storage.
+///
+/// It is published, serialised in JSON, on `<admin_key>/changes` after every `put` or `delete`,
+/// whether it comes from a publication, a time-to-live expiration, an import or the Replication.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Change {
+    key_expr: String,
+    kind: &'static str,
+    timestamp: String,
+    result: &'static str,
+}
+
+impl Change {
+    fn new(
+        key_expr: &OwnedKeyExpr,
+        kind: SampleKind,
+        timestamp: &Timestamp,
+        result: &StorageInsertionResult,
+    ) -> Self {
+        let result = match result {
+            StorageInsertionResult::Outdated => "outdated",
+            StorageInsertionResult::Inserted => "inserted",
+            StorageInsertionResult::Replaced => "replaced",
+            StorageInsertionResult::Deleted => "deleted",
+        };
+
+        let kind = match kind {
+            SampleKind::Put => "put",
+            SampleKind::Delete => "delete",
+        };
+
+        Self {
+            key_expr: key_expr.to_string(),
+            kind,
+            timestamp: timestamp.to_string(),
+            result,
+        }
+    }
+}
+
+/// The `ChangeFeed` wraps the [Storage] created by a backend and publishes a [Change] for each
+/// `put` and `delete` it processes.
+///
+/// Wrapping the [Storage] guarantees that no operation is missed, regardless of the component of
+/// the Storage Manager that performed it.
+pub(crate) struct ChangeFeed {
+    storage: Box<dyn Storage>,
+    publisher: Publisher<'static>,
+    strip_prefix: Option<OwnedKeyExpr>,
+}
+
+impl ChangeFeed {
+    /// Declares the [Publisher] of the changes of the Storage and wraps it.
+    pub(crate) async fn new(
+        session: &Arc<Session>,
+        admin_key: &OwnedKeyExpr,
+        strip_prefix: Option<OwnedKeyExpr>,
+        storage: Box<dyn Storage>,
+    ) -> ZResult<Self> {
+        let publisher = session
+            .declare_publisher(admin_key / keyexpr::new(CHANGES_CHUNK)?)
+            .encoding(Encoding::APPLICATION_JSON)
+            .await?;
+
+        Ok(Self {
+            storage,
+            publisher,
+            strip_prefix,
+        })
+    }
+
+    async fn publish(
+        &self,
+        stripped_key: &Option<OwnedKeyExpr>,
+        kind: SampleKind,
+        timestamp: &Timestamp,
+        result: &ZResult<StorageInsertionResult>,
+    ) {
+        let Ok(result) = result else {
+            return;
+        };
+
+        let key_expr = match crate::prefix(self.strip_prefix.as_ref(), stripped_key.as_ref()) {
+            Ok(key_expr) => key_expr,
+            Err(e) => {
+                tracing::error!("Failed to publish change: {e:?}");
+                return;
+            }
+        };
+
+        let change = Change::new(&key_expr, kind, timestamp, result);
+        let payload = match serde_json::to_vec(&change) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!("Failed to serialise change < {change:?} >: {e:?}");
+                return;
+            }
+        };
+
+        if let Err(e) = self.publisher.put(payload).await {
+            tracing::warn!("Failed to publish change < {change:?} >: {e:?}");
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for ChangeFeed {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.storage.get_admin_status()
+    }
+
+    async fn put(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        payload: ZBytes,
+        encoding: Encoding,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        let result = self
+            .storage
+            .put(key.clone(), payload, encoding, timestamp)
+            .await;
+        self.publish(&key, SampleKind::Put, &timestamp, &result)
+            .await;
+        result
+    }
+
+    async fn delete(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        let result = self.storage.delete(key.clone(), timestamp).await;
+        self.publish(&key, SampleKind::Delete, &timestamp, &result)
+            .await;
+        result
+    }
+
+    async fn get(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        parameters: &str,
+    ) -> ZResult<Vec<StoredData>> {
+        self.storage.get(key, parameters).await
+    }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+        self.storage.get_all_entries().await
+    }
+}
@@ -15,13 +15,20 @@
 use std::{collections::HashMap, sync::Arc};
 
 use tokio::sync::{broadcast::Sender, Mutex, RwLock};
-use zenoh::{internal::bail, session::Session, Result as ZResult};
-use zenoh_backend_traits::{config::StorageConfig, History, VolumeInstance};
+use zenoh::{internal::bail, key_expr::OwnedKeyExpr, session::Session, Result as ZResult};
+use zenoh_backend_traits::{config::StorageConfig, History, Storage, VolumeInstance};
 
 use crate::replication::{Action, Event, LogLatest, LogLatestKey, ReplicationService};
 
 pub(crate) mod service;
 pub(crate) use service::StorageService;
+mod change_feed;
+mod operations;
+mod snapshot;
+use change_feed::ChangeFeed;
+pub(crate) use change_feed::CHANGES_CHUNK;
+pub(crate) use operations::ALIGN_OPERATION;
+use snapshot::Snapshot;
 
 #[derive(Clone)]
 pub enum StorageMessage {
@@ -65,15 +72,36 @@ pub(crate) async fn create_and_start_storage(
     let uuid = parts[2];
     let storage_name = parts[7];
     let name = format!("{uuid}/{storage_name}");
+    let admin_key = OwnedKeyExpr::try_from(admin_key)?;
+    let storage = ChangeFeed::new(
+        &zenoh_session,
+        &admin_key,
+        config.strip_prefix.clone(),
+        storage,
+    )
+    .await?;
+
+    // The snapshot is read before spawning the Storage such that an invalid snapshot prevents it
+    // from starting, as would any other invalid configuration.
+    let snapshot = match &config.import_snapshot {
+        Some(path) => Some(Snapshot::read_from(path.clone()).await?),
+        None => None,
+    };
 
     let (tx, rx_storage) = tokio::sync::broadcast::channel(1);
     let rx_replication = tx.subscribe();
+    // The full alignments requested through the adminspace have their own channel: as only one
+    // can run at a time, a single slot is enough.
+    let (align_tx, align_rx) = tokio::sync::mpsc::channel(1);
 
     let mut entries = match storage.get_all_entries().await {
         Ok(entries) => entries
             .into_iter()
             .map(|(stripped_key, ts)| {
-                let event = Event::new(stripped_key, ts, &Action::Put);
+                let mut event = Event::new(stripped_key, ts, &Action::Put);
+                if capability.history == History::All {
+                    event = event.into_versioned();
+                }
                 (event.log_key(), event)
             })
             .collect::<HashMap<_, _>>(),
@@ -83,18 +111,13 @@ pub(crate) async fn create_and_start_storage(
     let mut replication_log = None;
     let mut latest_updates = HashMap::default();
     if let Some(replica_config) = &config.replication {
-        if capability.history != History::Latest {
-            bail!(
-                "Replication was enabled for storage {name} but its history capability is not \
-                 supported: found < {:?} >, expected < {:?} >",
-                capability.history,
-                History::Latest
-            );
-        }
+        // NOTE: For a Storage with the capability `History::All`, only the latest version of each
+        //       key expression is initially added to the Replication Log.
         let mut log_latest = LogLatest::new(
             config.key_expr.clone(),
             config.strip_prefix.clone(),
             replica_config.clone(),
+            capability.history.clone(),
         );
         log_latest.update(entries.drain().map(|(_, event)| event));
 
@@ -105,6 +128,7 @@ pub(crate) async fn create_and_start_storage(
 
     let latest_updates = Arc::new(RwLock::new(latest_updates));
 
+    let storage: Box<dyn Storage> = Box::new(storage);
     let storage = Arc::new(Mutex::new(storage));
 
     // NOTE The StorageService method `start_storage_queryable_subscriber` does not spawn its own
@@ -119,13 +143,24 @@ pub(crate) async fn create_and_start_storage(
                 zenoh_session.clone(),
                 config.clone(),
                 &name,
+                admin_key,
                 storage,
                 capability,
                 CacheLatest::new(latest_updates.clone(), replication_log.clone()),
+                align_tx,
             )
             .await,
         );
 
+        if let Some(snapshot) = snapshot {
+            match storage_service.import_snapshot(snapshot).await {
+                Ok(imported) => {
+                    tracing::debug!("Imported {imported} entries in storage '{name}'")
+                }
+                Err(e) => tracing::error!("Failed to import snapshot in storage '{name}': {e:?}"),
+            }
+        }
+
         // Testing if the `replication_log` is set is equivalent to testing if the `replication` is
         // set: the `replication_log` is only set when the latter is.
         if let Some(replication_log) = replication_log {
@@ -142,6 +177,7 @@ pub(crate) async fn create_and_start_storage(
                 replication_log,
                 latest_updates,
                 rx_replication,
+                align_rx,
             )
             .await;
         }
@@ -153,3 +189,7 @@ pub(crate) async fn create_and_start_storage(
 
     Ok(tx)
 }
+
+#[cfg(test)]
+#[path = "tests/replication.test.rs"]
+mod tests;
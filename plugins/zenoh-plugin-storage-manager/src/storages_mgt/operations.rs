@@ -0,0 +1,196 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use serde::Serialize;
+use zenoh::{
+    internal::{bail, zerror, Timed},
+    key_expr::OwnedKeyExpr,
+    sample::SampleBuilder,
+    session::ZenohId,
+    Result as ZResult,
+};
+
+use super::service::StorageService;
+
+/// The operation, triggered by querying `<admin_key>/purge`, that deletes all the entries of the
+/// Storage or, if the `key_expr` parameter is provided, the ones it includes.
+pub(crate) const PURGE_OPERATION: &str = "purge";
+/// The operation, triggered by querying `<admin_key>/gc`, that immediately performs the garbage
+/// collection of the Storage.
+pub(crate) const GC_OPERATION: &str = "gc";
+/// The operation, triggered by querying `<admin_key>/align?replica=<zid>`, that retrieves all the
+/// entries of the designated Replica.
+pub(crate) const ALIGN_OPERATION: &str = "align";
+/// The operation, triggered by querying `<admin_key>/progress`, that reports the progress of the
+/// last run of each of the above operations.
+pub(crate) const PROGRESS_OPERATION: &str = "progress";
+
+/// The parameter of the `purge` operation restricting the entries that are deleted.
+pub(crate) const KEY_EXPR_PARAMETER: &str = "key_expr";
+/// The parameter of the `align` operation designating the Replica to align with.
+pub(crate) const REPLICA_PARAMETER: &str = "replica";
+
+/// The progress of the last run of an administrative operation.
+///
+/// The number of entries `processed` and their `total` are only reported by the operations that
+/// can count them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct OperationProgress {
+    started_at: String,
+    status: OperationStatus,
+    processed: Option<usize>,
+    total: Option<usize>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OperationStatus {
+    Running,
+    Done,
+    Failed,
+}
+
+impl StorageService {
+    /// Records the start of the `operation` and returns its progress.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the same operation is still running.
+    pub(crate) async fn start_operation(
+        &self,
+        operation: &str,
+        total: Option<usize>,
+    ) -> ZResult<OperationProgress> {
+        let mut operations = self.operations.write().await;
+        if operations
+            .get(operation)
+            .is_some_and(|progress| progress.status == OperationStatus::Running)
+        {
+            bail!("Operation '{operation}' is already running");
+        }
+
+        let progress = OperationProgress {
+            started_at: self.session.new_timestamp().to_string(),
+            status: OperationStatus::Running,
+            processed: total.map(|_| 0),
+            total,
+            error: None,
+        };
+        operations.insert(operation.to_string(), progress.clone());
+
+        Ok(progress)
+    }
+
+    async fn update_operation(&self, operation: &str, processed: usize) {
+        if let Some(progress) = self.operations.write().await.get_mut(operation) {
+            progress.processed = Some(processed);
+        }
+    }
+
+    /// Records the end of the `operation`, which failed if an `error` is provided, and returns its
+    /// progress.
+    pub(crate) async fn finish_operation(
+        &self,
+        operation: &str,
+        error: Option<String>,
+    ) -> Option<OperationProgress> {
+        let mut operations = self.operations.write().await;
+        let progress = operations.get_mut(operation)?;
+        progress.status = match error {
+            Some(_) => OperationStatus::Failed,
+            None => OperationStatus::Done,
+        };
+        progress.error = error;
+
+        Some(progress.clone())
+    }
+
+    /// Returns the progress of the last run of each operation.
+    pub(crate) async fn operations_progress(&self) -> serde_json::Value {
+        serde_json::json!(*self.operations.read().await)
+    }
+
+    /// Deletes all the entries of the Storage included in the provided key expression, or all of
+    /// them if none is provided.
+    ///
+    /// Each deletion is processed as if a Delete was received, with a new Timestamp: it is
+    /// recorded in the cache or Replication Log so that the other Replicas do not re-introduce the
+    /// purged entries.
+    pub(crate) async fn purge(&self, key_expr: Option<OwnedKeyExpr>) -> ZResult<OperationProgress> {
+        let prefix = self.configuration.strip_prefix.as_ref();
+
+        let entries = {
+            let storage = self.storage.lock().await;
+            storage.get_all_entries().await?
+        };
+
+        let mut keys = Vec::with_capacity(entries.len());
+        for (stripped_key, _) in entries {
+            let key = crate::prefix(prefix, stripped_key.as_ref())?;
+            if key_expr.as_ref().map_or(true, |ke| ke.includes(&key)) {
+                keys.push(key);
+            }
+        }
+
+        self.start_operation(PURGE_OPERATION, Some(keys.len()))
+            .await?;
+
+        let mut error = None;
+        for (processed, key) in keys.into_iter().enumerate() {
+            let sample = SampleBuilder::delete(key)
+                .timestamp(self.session.new_timestamp())
+                .into();
+            if let Err(e) = self.process_sample(sample).await {
+                error = Some(e.to_string());
+                break;
+            }
+            self.update_operation(PURGE_OPERATION, processed + 1).await;
+        }
+
+        self.finish_operation(PURGE_OPERATION, error)
+            .await
+            .ok_or_else(|| zerror!("Missing progress of operation '{PURGE_OPERATION}'").into())
+    }
+
+    /// Immediately performs the garbage collection of the Wildcard Updates and of the cache.
+    pub(crate) async fn collect_garbage(&self) -> ZResult<OperationProgress> {
+        self.start_operation(GC_OPERATION, None).await?;
+        let mut garbage_collection_event = self.garbage_collection_event();
+        garbage_collection_event.run().await;
+
+        self.finish_operation(GC_OPERATION, None)
+            .await
+            .ok_or_else(|| zerror!("Missing progress of operation '{GC_OPERATION}'").into())
+    }
+
+    /// Requests the Replication to retrieve all the entries of the provided Replica.
+    ///
+    /// The alignment is performed in the background, its progress is reported by the `progress`
+    /// operation.
+    pub(crate) async fn request_alignment(&self, replica: ZenohId) -> ZResult<OperationProgress> {
+        if self.configuration.replication.is_none() {
+            bail!("Replication is not enabled for storage '{}'", self.name);
+        }
+
+        let progress = self.start_operation(ALIGN_OPERATION, None).await?;
+        if let Err(e) = self.align_tx.send(replica).await {
+            self.finish_operation(ALIGN_OPERATION, Some(e.to_string()))
+                .await;
+            bail!("Failed to request alignment with replica < {replica} >: {e}");
+        }
+
+        Ok(progress)
+    }
+}
@@ -13,17 +13,17 @@
 //
 
 use std::{
-    collections::HashSet,
-    str::{self},
+    collections::{HashMap, HashSet},
+    str::{self, FromStr},
     sync::Arc,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use async_trait::async_trait;
-use tokio::sync::{broadcast::Receiver, Mutex, RwLock, RwLockWriteGuard};
+use tokio::sync::{broadcast::Receiver, mpsc, Mutex, RwLock, RwLockWriteGuard};
 use zenoh::{
     bytes::{Encoding, ZBytes},
-    internal::{bail, Timed, TimedEvent, Timer},
+    internal::{bail, zerror, Timed, TimedEvent, Timer},
     key_expr::{
         keyexpr,
         keyexpr_tree::{
@@ -31,8 +31,9 @@ use zenoh::{
         },
         OwnedKeyExpr,
     },
+    query::{Parameters, Query},
     sample::{Sample, SampleBuilder, SampleFields, SampleKind},
-    session::Session,
+    session::{Session, ZenohId},
     time::{Timestamp, NTP64},
     Result as ZResult,
 };
@@ -41,12 +42,33 @@ use zenoh_backend_traits::{
     Capability, History, StorageInsertionResult, StoredData,
 };
 
-use super::LatestUpdates;
+use super::{
+    operations::{
+        OperationProgress, ALIGN_OPERATION, GC_OPERATION, KEY_EXPR_PARAMETER, PROGRESS_OPERATION,
+        PURGE_OPERATION, REPLICA_PARAMETER,
+    },
+    LatestUpdates,
+};
 use crate::{
     replication::{Action, Event},
-    storages_mgt::{CacheLatest, StorageMessage},
+    storages_mgt::{CacheLatest, StorageMessage, CHANGES_CHUNK},
 };
 
+/// The key, in the attachment of a Sample, of the time-to-live (in seconds) that should be applied
+/// to the stored value.
+pub(crate) const TTL_ATTACHMENT_KEY: &str = "_ttl";
+
+/// The operation, triggered by querying `<admin_key>/export`, that exports a snapshot of the
+/// Storage.
+const EXPORT_OPERATION: &str = "export";
+/// The operation, triggered by querying `<admin_key>/import`, that imports a snapshot in the
+/// Storage.
+const IMPORT_OPERATION: &str = "import";
+
+fn progress_payload(progress: OperationProgress) -> ZBytes {
+    serde_json::json!(progress).to_string().into()
+}
+
 #[derive(Clone)]
 pub(crate) struct Update {
     kind: SampleKind,
@@ -79,34 +101,45 @@ impl From<Update> for StoredData {
 
 #[derive(Clone)]
 pub struct StorageService {
-    session: Arc<Session>,
+    pub(crate) session: Arc<Session>,
     pub(crate) configuration: StorageConfig,
-    name: String,
+    pub(crate) name: String,
+    admin_key: OwnedKeyExpr,
     pub(crate) storage: Arc<Mutex<Box<dyn zenoh_backend_traits::Storage>>>,
     capability: Capability,
     pub(crate) wildcard_deletes: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
     pub(crate) wildcard_puts: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
     cache_latest: CacheLatest,
+    expirations: Arc<RwLock<HashMap<Option<OwnedKeyExpr>, Timestamp>>>,
+    pub(crate) operations: Arc<RwLock<HashMap<String, OperationProgress>>>,
+    pub(crate) align_tx: mpsc::Sender<ZenohId>,
 }
 
 impl StorageService {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         session: Arc<Session>,
         config: StorageConfig,
         name: &str,
+        admin_key: OwnedKeyExpr,
         storage: Arc<Mutex<Box<dyn zenoh_backend_traits::Storage>>>,
         capability: Capability,
         cache_latest: CacheLatest,
+        align_tx: mpsc::Sender<ZenohId>,
     ) -> Self {
         StorageService {
             session,
             configuration: config,
             name: name.to_string(),
+            admin_key,
             storage,
             capability,
             wildcard_deletes: Arc::new(RwLock::new(KeBoxTree::default())),
             wildcard_puts: Arc::new(RwLock::new(KeBoxTree::default())),
             cache_latest,
+            expirations: Arc::new(RwLock::new(HashMap::default())),
+            operations: Arc::new(RwLock::new(HashMap::default())),
+            align_tx,
         }
     }
 
@@ -117,22 +150,9 @@ impl StorageService {
         // start periodic GC event
         let t = Timer::default();
 
-        let gc_config = self.configuration.garbage_collection_config.clone();
-
-        let latest_updates = if self.cache_latest.replication_log.is_none() {
-            Some(self.cache_latest.latest_updates.clone())
-        } else {
-            None
-        };
-
         let gc = TimedEvent::periodic(
-            gc_config.period,
-            GarbageCollectionEvent {
-                config: gc_config,
-                wildcard_deletes: self.wildcard_deletes.clone(),
-                wildcard_puts: self.wildcard_puts.clone(),
-                latest_updates,
-            },
+            self.configuration.garbage_collection_config.period,
+            self.garbage_collection_event(),
         );
         t.add_async(gc).await;
 
@@ -161,12 +181,45 @@ impl StorageService {
             }
         };
 
+        // answer to administrative operations on the admin key of the storage
+        let admin_queryable = match self
+            .session
+            .declare_queryable(&self.admin_key / keyexpr::new("*").unwrap())
+            .await
+        {
+            Ok(admin_queryable) => admin_queryable,
+            Err(e) => {
+                tracing::error!("Error starting storage '{}': {}", self.name, e);
+                return;
+            }
+        };
+
+        // signal that the changes of the storage are published: a consumer of the change feed
+        // detects through this token that it may have missed changes
+        let changes_token = match self
+            .session
+            .liveliness()
+            .declare_token(&self.admin_key / keyexpr::new(CHANGES_CHUNK).unwrap())
+            .await
+        {
+            Ok(changes_token) => changes_token,
+            Err(e) => {
+                tracing::error!("Error starting storage '{}': {}", self.name, e);
+                return;
+            }
+        };
+
         tracing::debug!(
             "Starting storage '{}' on keyexpr '{}'",
             self.name,
             storage_key_expr
         );
 
+        self.restore_expirations().await;
+
+        let mut expiration_interval = tokio::time::interval(self.configuration.ttl.period);
+        expiration_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
         tokio::task::spawn(async move {
             loop {
                 tokio::select!(
@@ -189,11 +242,23 @@ impl StorageService {
                     query = storage_queryable.recv_async() => {
                         self.reply_query(query).await;
                     },
+                    // on administrative operation
+                    query = admin_queryable.recv_async() => {
+                        match query {
+                            Ok(query) => self.reply_admin_query(query).await,
+                            Err(e) => tracing::error!("Error in query: {}", e),
+                        }
+                    },
+                    // on expiration check
+                    _ = expiration_interval.tick() => {
+                        self.expire_entries().await;
+                    },
                     // on storage handle drop
                     Ok(message) = rx.recv() => {
                         match message {
                             StorageMessage::Stop => {
                                 tracing::trace!("Dropping storage '{}'", self.name);
+                                drop(changes_token);
                                 return
                             },
                             StorageMessage::GetStatus(tx) => {
@@ -208,6 +273,23 @@ impl StorageService {
         });
     }
 
+    /// Returns the [GarbageCollectionEvent] of the Wildcard Updates and, if the Replication is not
+    /// enabled, of the cache.
+    pub(crate) fn garbage_collection_event(&self) -> GarbageCollectionEvent {
+        let latest_updates = if self.cache_latest.replication_log.is_none() {
+            Some(self.cache_latest.latest_updates.clone())
+        } else {
+            None
+        };
+
+        GarbageCollectionEvent {
+            config: self.configuration.garbage_collection_config.clone(),
+            wildcard_deletes: self.wildcard_deletes.clone(),
+            wildcard_puts: self.wildcard_puts.clone(),
+            latest_updates,
+        }
+    }
+
     // The storage should only simply save the key, sample pair while put and retrieve the same
     // during get the trimming during PUT and GET should be handled by the plugin
     pub(crate) async fn process_sample(&self, sample: Sample) -> ZResult<()> {
@@ -245,13 +327,17 @@ impl StorageService {
                 SampleKind::Delete => Action::WildcardDelete(key_expr.clone().into()),
             };
 
-            let event = Event::new(Some(key_expr.clone().into()), timestamp, &action);
+            // The Wildcard Updates are not tracked for a Storage keeping all versions: only the
+            // versions they produce are.
+            if self.capability.history == History::Latest {
+                let event = Event::new(Some(key_expr.clone().into()), timestamp, &action);
 
-            self.cache_latest
-                .latest_updates
-                .write()
-                .await
-                .insert(event.log_key(), event);
+                self.cache_latest
+                    .latest_updates
+                    .write()
+                    .await
+                    .insert(event.log_key(), event);
+            }
         }
 
         let matching_keys = if key_expr.is_wild() {
@@ -314,7 +400,8 @@ impl StorageService {
 
             // If the Storage was declared as only keeping the Latest value, we ensure that, for
             // each received Sample, it is indeed the Latest value that is processed.
-            let new_event = Event::new(stripped_key.clone(), sample_to_store_timestamp, &action);
+            let mut new_event =
+                Event::new(stripped_key.clone(), sample_to_store_timestamp, &action);
             let mut cache_guard = None;
             if self.capability.history == History::Latest {
                 match self.guard_cache_if_latest(&new_event).await {
@@ -326,8 +413,15 @@ impl StorageService {
                         continue;
                     }
                 }
+            } else if self.cache_latest.replication_log.is_some() {
+                // Every version is tracked by the Replication.
+                new_event = new_event.into_versioned();
+                cache_guard = Some(self.cache_latest.latest_updates.write().await);
             }
 
+            let ttl = ttl_from_attachment(sample_to_store.attachment(), self.configuration.ttl.max)
+                .or(self.configuration.ttl.default);
+
             let mut storage = self.storage.lock().await;
             let storage_result = match sample.kind() {
                 SampleKind::Put => {
@@ -357,6 +451,13 @@ impl StorageService {
                     if let Some(mut cache_guard) = cache_guard {
                         cache_guard.insert(new_event.log_key(), new_event);
                     }
+                    self.register_expiration(
+                        stripped_key,
+                        sample.kind(),
+                        sample_to_store_timestamp,
+                        ttl,
+                    )
+                    .await;
                 }
                 Err(e) => {
                     // TODO In case of a wildcard update, multiple keys can be updated. What should
@@ -625,6 +726,249 @@ impl StorageService {
         }
     }
 
+    /// Schedules the expiration of the entries already present in the Storage when it starts.
+    ///
+    /// The expirations are only kept in memory: they are derived again from the Timestamp of the
+    /// stored entries and the default time-to-live. A time-to-live set on a sample through its
+    /// attachment is not stored, the default one applies to it after a restart.
+    async fn restore_expirations(&self) {
+        if self.configuration.ttl.default.is_none() {
+            return;
+        }
+
+        let entries = match self.storage.lock().await.get_all_entries().await {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::error!(
+                    "Storage '{}' failed to list its entries to restore their expiration: {e:?}",
+                    self.name
+                );
+                return;
+            }
+        };
+
+        for (stripped_key, timestamp) in entries {
+            self.register_expiration(
+                stripped_key,
+                SampleKind::Put,
+                timestamp,
+                self.configuration.ttl.default,
+            )
+            .await;
+        }
+    }
+
+    /// Keeps track of when an entry updated through the alignment of the Replication expires.
+    ///
+    /// The attachment of the original sample is not transmitted when aligning: the default
+    /// time-to-live applies.
+    pub(crate) async fn register_aligned_expiration(
+        &self,
+        stripped_key: Option<OwnedKeyExpr>,
+        kind: SampleKind,
+        timestamp: Timestamp,
+    ) {
+        self.register_expiration(
+            stripped_key,
+            kind,
+            timestamp,
+            self.configuration.ttl.default,
+        )
+        .await;
+    }
+
+    /// Keeps track of when the entry associated with the `stripped_key` expires.
+    ///
+    /// A Put with a time-to-live schedules the expiration of the entry while a Delete, or a Put
+    /// without time-to-live, cancels any previously scheduled expiration.
+    ///
+    /// The expiration Timestamp is derived from the Timestamp of the Put: its time is increased by
+    /// the time-to-live and its ID is kept. Hence, all the replicas that stored the same Put will
+    /// generate the same deletion event when the entry expires.
+    async fn register_expiration(
+        &self,
+        stripped_key: Option<OwnedKeyExpr>,
+        kind: SampleKind,
+        timestamp: Timestamp,
+        ttl: Option<Duration>,
+    ) {
+        let mut expirations = self.expirations.write().await;
+        match (kind, ttl) {
+            (SampleKind::Put, Some(ttl)) => {
+                let ttl = ttl.min(self.configuration.ttl.max);
+                let Some(time) = timestamp
+                    .get_time()
+                    .as_u64()
+                    .checked_add(NTP64::from(ttl).as_u64())
+                else {
+                    tracing::warn!(
+                        "Storage '{}' ignores the time-to-live of < {stripped_key:?} >: its \
+                         expiration is out of range",
+                        self.name
+                    );
+                    expirations.remove(&stripped_key);
+                    return;
+                };
+                let expiration = Timestamp::new(NTP64(time), *timestamp.get_id());
+                expirations.insert(stripped_key, expiration);
+            }
+            _ => {
+                expirations.remove(&stripped_key);
+            }
+        }
+    }
+
+    /// Deletes the entries whose time-to-live has elapsed.
+    ///
+    /// The deletion is processed as if a Delete was received, with the expiration Timestamp. This
+    /// ensures that (i) the deletion is recorded in the cache or Replication Log and (ii) it is
+    /// discarded if the entry was updated in the meantime.
+    pub(crate) async fn expire_entries(&self) {
+        let now = NTP64::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap());
+
+        let expired_entries = {
+            let mut expirations = self.expirations.write().await;
+            let expired_entries = expirations
+                .iter()
+                .filter(|(_, expiration)| expiration.get_time() <= &now)
+                .map(|(stripped_key, expiration)| (stripped_key.clone(), *expiration))
+                .collect::<Vec<_>>();
+            for (stripped_key, _) in &expired_entries {
+                expirations.remove(stripped_key);
+            }
+            expired_entries
+        };
+
+        let prefix = self.configuration.strip_prefix.as_ref();
+
+        for (stripped_key, expiration) in expired_entries {
+            let key_expr = match crate::prefix(prefix, stripped_key.as_ref()) {
+                Ok(key_expr) => key_expr,
+                Err(e) => {
+                    tracing::error!("Failed to expire entry < {stripped_key:?} >: {e:?}");
+                    continue;
+                }
+            };
+
+            tracing::trace!("Entry < {} > expired at {}", key_expr, expiration);
+            let sample = SampleBuilder::delete(key_expr).timestamp(expiration).into();
+            if let Err(e) = self.process_sample(sample).await {
+                tracing::error!("{e:?}");
+            }
+        }
+    }
+
+    /// Performs the administrative operation designated by the last chunk of the key expression of
+    /// the [Query] and replies with its outcome.
+    ///
+    /// The operation is only performed if it is explicitly named: a Query whose last chunk is a
+    /// wildcard (for instance, a Query listing the adminspace) is ignored.
+    async fn reply_admin_query(&self, query: Query) {
+        let Some(operation) = query.key_expr().as_str().rsplit('/').next() else {
+            return;
+        };
+        let reply_key = match keyexpr::new(operation) {
+            Ok(operation_ke) if !operation_ke.is_wild() => &self.admin_key / operation_ke,
+            _ => return,
+        };
+
+        tracing::debug!(
+            "Storage '{}' processing operation '{}'",
+            self.name,
+            operation
+        );
+
+        let result = match self.authorize_admin_operation(operation) {
+            Ok(()) => self.perform_admin_operation(operation, &query).await,
+            Err(e) => Err(e),
+        };
+
+        let reply = match result {
+            Ok(payload) => {
+                query
+                    .reply(reply_key, payload)
+                    .encoding(Encoding::APPLICATION_JSON)
+                    .await
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Storage '{}' failed to perform operation '{}': {e}",
+                    self.name,
+                    operation
+                );
+                query.reply_err(e.to_string()).await
+            }
+        };
+        if let Err(e) = reply {
+            tracing::warn!(
+                "Storage '{}' raised an error replying a query: {}",
+                self.name,
+                e
+            );
+        }
+    }
+
+    /// Checks that the administrative `operation` may be performed.
+    ///
+    /// The operations accessing the content of the Storage are only performed if (i) they are
+    /// enabled in the configuration of the Storage and (ii) the adminspace accepts writes.
+    /// Reporting the progress of the operations is always allowed.
+    fn authorize_admin_operation(&self, operation: &str) -> ZResult<()> {
+        if !matches!(
+            operation,
+            EXPORT_OPERATION | IMPORT_OPERATION | PURGE_OPERATION | GC_OPERATION | ALIGN_OPERATION
+        ) {
+            return Ok(());
+        }
+
+        if !self.configuration.admin_operations {
+            bail!(
+                "Operation '{operation}' is disabled: `admin_operations` is not enabled for \
+                 storage '{}'",
+                self.name
+            );
+        }
+        if !self.session.config().lock().adminspace.permissions().write {
+            bail!(
+                "Operation '{operation}' is denied: adminspace.permissions.write=false in \
+                 configuration"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Performs the administrative `operation` and returns the payload of the reply.
+    async fn perform_admin_operation(&self, operation: &str, query: &Query) -> ZResult<ZBytes> {
+        match operation {
+            EXPORT_OPERATION => self.export_operation(query).await,
+            IMPORT_OPERATION => self.import_operation(query).await,
+            PURGE_OPERATION => {
+                match query
+                    .parameters()
+                    .get(KEY_EXPR_PARAMETER)
+                    .map(OwnedKeyExpr::try_from)
+                    .transpose()
+                {
+                    Ok(key_expr) => self.purge(key_expr).await.map(progress_payload),
+                    Err(e) => Err(e),
+                }
+            }
+            GC_OPERATION => self.collect_garbage().await.map(progress_payload),
+            ALIGN_OPERATION => match query.parameters().get(REPLICA_PARAMETER) {
+                Some(replica) => match ZenohId::from_str(replica) {
+                    Ok(replica) => self.request_alignment(replica).await.map(progress_payload),
+                    Err(e) => Err(e),
+                },
+                None => Err(
+                    zerror!("Missing replica: expected a `{REPLICA_PARAMETER}` parameter").into(),
+                ),
+            },
+            PROGRESS_OPERATION => Ok(self.operations_progress().await.to_string().into()),
+            _ => Err(zerror!("Unknown operation '{operation}'").into()),
+        }
+    }
+
     async fn get_matching_keys(&self, key_expr: &keyexpr) -> Vec<OwnedKeyExpr> {
         let mut result = Vec::new();
         // @TODO: if cache exists, use that to get the list
@@ -658,8 +1002,29 @@ impl StorageService {
     }
 }
 
+/// Returns the time-to-live carried in the attachment of a Sample, if any.
+///
+/// The attachment is expected to be a UTF-8 string formatted as [Parameters] (`key=value` pairs
+/// separated by `;`), the time-to-live being expressed in seconds under the key `_ttl`. A
+/// time-to-live longer than `max` is reduced to `max`.
+pub(crate) fn ttl_from_attachment(attachment: Option<&ZBytes>, max: Duration) -> Option<Duration> {
+    let attachment = attachment?.try_to_string().ok()?;
+    let ttl = Parameters::from(attachment.as_ref())
+        .get(TTL_ATTACHMENT_KEY)?
+        .parse::<f64>()
+        .ok()?;
+
+    if ttl.is_finite() && ttl > 0.0 {
+        // The conversion only fails if the time-to-live is too long to be represented.
+        Some(Duration::try_from_secs_f64(ttl).map_or(max, |ttl| ttl.min(max)))
+    } else {
+        tracing::warn!("Ignoring invalid time-to-live < {ttl} >");
+        None
+    }
+}
+
 // Periodic event cleaning-up data info for old metadata
-struct GarbageCollectionEvent {
+pub(crate) struct GarbageCollectionEvent {
     config: GarbageCollectionConfig,
     wildcard_deletes: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
     wildcard_puts: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
@@ -0,0 +1,340 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use serde::{Deserialize, Serialize};
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    internal::{bail, zerror},
+    key_expr::OwnedKeyExpr,
+    query::Query,
+    sample::{Sample, SampleBuilder},
+    time::Timestamp,
+    Result as ZResult,
+};
+
+use super::StorageService;
+
+/// The version of the snapshot format, bumped whenever it changes in an incompatible way.
+const SNAPSHOT_FORMAT_VERSION: u8 = 1;
+
+/// The parameter of the `export` and `import` operations indicating the path of the snapshot file.
+const SNAPSHOT_PATH_PARAMETER: &str = "path";
+
+/// Resolves the `path` of a snapshot file, provided by an administrative operation, within the
+/// `snapshot_dir`.
+///
+/// Only relative paths made of normal components are accepted: an absolute path or a path
+/// containing `..` could designate a file outside of the `snapshot_dir`. The resolved path is
+/// canonicalized and must remain within the `snapshot_dir`, such that a symbolic link cannot be
+/// used to escape it.
+///
+/// This function accesses the file system and thus blocks.
+fn resolve_snapshot_path(snapshot_dir: &Path, path: &Path) -> ZResult<PathBuf> {
+    if path.as_os_str().is_empty()
+        || !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        bail!(
+            "Invalid snapshot path < {} >: expected a relative path without any `..`",
+            path.display()
+        );
+    }
+
+    let snapshot_dir = snapshot_dir.canonicalize().map_err(|e| {
+        zerror!(
+            "Invalid snapshot directory < {} >: {e}",
+            snapshot_dir.display()
+        )
+    })?;
+    let full_path = snapshot_dir.join(path);
+    let resolved = match full_path.canonicalize() {
+        Ok(resolved) => resolved,
+        // The file an export writes does not necessarily exist yet, its parent directory is
+        // resolved instead. A dangling symbolic link is not followed.
+        Err(e) if e.kind() == ErrorKind::NotFound && full_path.symlink_metadata().is_err() => {
+            match (full_path.parent(), full_path.file_name()) {
+                (Some(parent), Some(file_name)) => parent
+                    .canonicalize()
+                    .map_err(|e| zerror!("Invalid snapshot path < {} >: {e}", path.display()))?
+                    .join(file_name),
+                _ => bail!("Invalid snapshot path < {} >", path.display()),
+            }
+        }
+        Err(e) => bail!("Invalid snapshot path < {} >: {e}", path.display()),
+    };
+
+    if !resolved.starts_with(&snapshot_dir) {
+        bail!(
+            "Invalid snapshot path < {} >: it resolves outside of the snapshot directory",
+            path.display()
+        );
+    }
+
+    Ok(resolved)
+}
+
+/// Runs the blocking file system operation `f` without blocking the asynchronous task.
+async fn blocking_fs<T: Send + 'static>(
+    f: impl FnOnce() -> ZResult<T> + Send + 'static,
+) -> ZResult<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| zerror!("Snapshot file operation failed: {e}"))?
+}
+
+/// A `Snapshot` is a portable copy of the content of a Storage.
+///
+/// It does not depend on the technology of the backend the Storage relies on: its entries are
+/// identified by their full key expression (i.e. not stripped of any prefix), their payload is
+/// encoded in base64 and their [Encoding] and [Timestamp] are kept in their textual form. Hence, a
+/// `Snapshot` exported from one Storage can be imported in another Storage that is configured
+/// differently.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct Snapshot {
+    pub(crate) version: u8,
+    pub(crate) key_expr: OwnedKeyExpr,
+    pub(crate) entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct SnapshotEntry {
+    pub(crate) key_expr: OwnedKeyExpr,
+    pub(crate) payload: String,
+    pub(crate) encoding: String,
+    pub(crate) timestamp: String,
+}
+
+impl Snapshot {
+    /// Reads and parses the `Snapshot` contained in the file at the provided `path`.
+    pub(crate) async fn read_from(path: PathBuf) -> ZResult<Self> {
+        let bytes = blocking_fs(move || {
+            std::fs::read(&path).map_err(|e| {
+                zerror!("Failed to read snapshot file < {} >: {e}", path.display()).into()
+            })
+        })
+        .await?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Serialises and writes the `Snapshot` in the file at the provided `path`.
+    pub(crate) async fn write_to(&self, path: PathBuf) -> ZResult<()> {
+        let bytes = self.to_bytes()?;
+        blocking_fs(move || {
+            std::fs::write(&path, bytes).map_err(|e| {
+                zerror!("Failed to write snapshot file < {} >: {e}", path.display()).into()
+            })
+        })
+        .await
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> ZResult<Self> {
+        let snapshot = serde_json::from_slice::<Snapshot>(bytes)
+            .map_err(|e| zerror!("Failed to parse snapshot: {e}"))?;
+        if snapshot.version != SNAPSHOT_FORMAT_VERSION {
+            bail!(
+                "Unsupported snapshot format version: found < {} >, expected < {} >",
+                snapshot.version,
+                SNAPSHOT_FORMAT_VERSION
+            );
+        }
+        Ok(snapshot)
+    }
+
+    pub(crate) fn to_bytes(&self) -> ZResult<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| zerror!("Failed to serialize snapshot: {e}").into())
+    }
+}
+
+impl SnapshotEntry {
+    fn new(
+        key_expr: OwnedKeyExpr,
+        payload: &ZBytes,
+        encoding: &Encoding,
+        timestamp: &Timestamp,
+    ) -> Self {
+        Self {
+            key_expr,
+            payload: b64_std_engine.encode(payload.to_bytes()),
+            encoding: encoding.to_string(),
+            timestamp: timestamp.to_string(),
+        }
+    }
+}
+
+impl StorageService {
+    /// Returns a [Snapshot] of all the entries currently held by the Storage.
+    ///
+    /// If the Storage keeps the history of the values associated with a key expression, all of
+    /// them are exported.
+    pub(crate) async fn export_snapshot(&self) -> ZResult<Snapshot> {
+        let prefix = self.configuration.strip_prefix.as_ref();
+        let mut storage = self.storage.lock().await;
+
+        let mut entries = Vec::default();
+        for (stripped_key, _) in storage.get_all_entries().await? {
+            let key_expr = match crate::prefix(prefix, stripped_key.as_ref()) {
+                Ok(key_expr) => key_expr,
+                Err(e) => {
+                    tracing::error!("Skipping entry < {stripped_key:?} > in snapshot: {e:?}");
+                    continue;
+                }
+            };
+
+            match storage.get(stripped_key, "").await {
+                Ok(stored_data) => entries.extend(stored_data.iter().map(|data| {
+                    SnapshotEntry::new(
+                        key_expr.clone(),
+                        &data.payload,
+                        &data.encoding,
+                        &data.timestamp,
+                    )
+                })),
+                Err(e) => {
+                    tracing::error!("Skipping entry < {key_expr} > in snapshot: {e:?}");
+                }
+            }
+        }
+
+        Ok(Snapshot {
+            version: SNAPSHOT_FORMAT_VERSION,
+            key_expr: self.configuration.key_expr.clone(),
+            entries,
+        })
+    }
+
+    /// Returns the path of the snapshot file designated by the `path` parameter of the [Query], if
+    /// any, resolved within the snapshot directory of the Storage.
+    ///
+    /// The `export` and `import` operations are only enabled if a snapshot directory is configured:
+    /// this method fails otherwise.
+    async fn snapshot_path(&self, query: &Query) -> ZResult<Option<PathBuf>> {
+        let Some(snapshot_dir) = &self.configuration.snapshot_dir else {
+            bail!(
+                "Snapshot operations are disabled: no `snapshot_dir` is configured for storage '{}'",
+                self.name
+            );
+        };
+
+        let Some(path) = query.parameters().get(SNAPSHOT_PATH_PARAMETER) else {
+            return Ok(None);
+        };
+        let (snapshot_dir, path) = (snapshot_dir.clone(), PathBuf::from(path));
+        blocking_fs(move || resolve_snapshot_path(&snapshot_dir, &path))
+            .await
+            .map(Some)
+    }
+
+    /// Performs the `export` operation: the [Snapshot] of the Storage is either written in the file
+    /// designated by the `path` parameter of the [Query] or returned, serialised, to be sent in the
+    /// reply.
+    pub(crate) async fn export_operation(&self, query: &Query) -> ZResult<ZBytes> {
+        let path = self.snapshot_path(query).await?;
+        let snapshot = self.export_snapshot().await?;
+        match path {
+            Some(path) => {
+                snapshot.write_to(path.clone()).await?;
+                Ok(serde_json::json!({
+                    "path": path,
+                    "entries": snapshot.entries.len(),
+                })
+                .to_string()
+                .into())
+            }
+            None => snapshot.to_bytes().map(ZBytes::from),
+        }
+    }
+
+    /// Performs the `import` operation: the [Snapshot] is either read from the file designated by
+    /// the `path` parameter of the [Query] or from its payload.
+    pub(crate) async fn import_operation(&self, query: &Query) -> ZResult<ZBytes> {
+        let snapshot = match (self.snapshot_path(query).await?, query.payload()) {
+            (Some(path), _) => Snapshot::read_from(path).await?,
+            (None, Some(payload)) => Snapshot::from_bytes(&payload.to_bytes())?,
+            (None, None) => bail!(
+                "Missing snapshot: expected either a `{SNAPSHOT_PATH_PARAMETER}` parameter or a \
+                 payload"
+            ),
+        };
+
+        let imported = self.import_snapshot(snapshot).await?;
+        Ok(serde_json::json!({ "entries": imported })
+            .to_string()
+            .into())
+    }
+
+    /// Imports the entries of the provided [Snapshot] in the Storage and returns how many were
+    /// processed.
+    ///
+    /// Each entry is processed as if a publication with the same key expression, payload, encoding
+    /// and timestamp was received. This implies that (i) an entry that is older than what the
+    /// Storage holds is discarded and (ii) the entries are registered for the Replication, if it is
+    /// enabled.
+    ///
+    /// Entries whose key expression is not included in the key expression of the Storage are
+    /// skipped.
+    pub(crate) async fn import_snapshot(&self, snapshot: Snapshot) -> ZResult<usize> {
+        // All the entries are validated before any is processed: an invalid snapshot is rejected as
+        // a whole instead of being partially imported.
+        let mut samples: Vec<Sample> = Vec::with_capacity(snapshot.entries.len());
+        for entry in snapshot.entries {
+            if !self.configuration.key_expr.includes(&entry.key_expr) {
+                tracing::warn!(
+                    "Skipping entry < {} > of snapshot: not included in < {} >",
+                    entry.key_expr,
+                    self.configuration.key_expr
+                );
+                continue;
+            }
+
+            let payload = b64_std_engine.decode(&entry.payload).map_err(|e| {
+                zerror!(
+                    "Invalid payload for entry < {} > of snapshot: {e}",
+                    entry.key_expr
+                )
+            })?;
+            let timestamp = Timestamp::from_str(&entry.timestamp).map_err(|e| {
+                zerror!(
+                    "Invalid timestamp for entry < {} > of snapshot: {e:?}",
+                    entry.key_expr
+                )
+            })?;
+
+            samples.push(
+                SampleBuilder::put(entry.key_expr, payload)
+                    .encoding(Encoding::from(entry.encoding))
+                    .timestamp(timestamp)
+                    .into(),
+            );
+        }
+
+        let imported = samples.len();
+        for sample in samples {
+            self.process_sample(sample).await?;
+        }
+
+        Ok(imported)
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/snapshot.test.rs"]
+mod tests;
@@ -0,0 +1,326 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    key_expr::OwnedKeyExpr,
+    sample::Locality,
+    time::Timestamp,
+    Config, Result as ZResult, Session,
+};
+use zenoh_backend_traits::{
+    config::{GarbageCollectionConfig, ReplicaConfig, StorageConfig, TtlConfig},
+    Capability, History, Persistence, Storage, StorageInsertionResult, StoredData, Volume,
+    VolumeInstance,
+};
+
+use super::{create_and_start_storage, StorageMessage};
+
+type Versions = Arc<RwLock<HashMap<Option<OwnedKeyExpr>, Vec<StoredData>>>>;
+
+/// A Volume keeping, in memory, all the versions of the key expressions it stores.
+struct VersionedVolume {
+    versions: Versions,
+}
+
+#[async_trait]
+impl Volume for VersionedVolume {
+    fn get_admin_status(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    fn get_capability(&self) -> Capability {
+        Capability {
+            persistence: Persistence::Volatile,
+            history: History::All,
+        }
+    }
+
+    async fn create_storage(&self, _config: StorageConfig) -> ZResult<Box<dyn Storage>> {
+        Ok(Box::new(VersionedStorage {
+            versions: self.versions.clone(),
+        }))
+    }
+}
+
+struct VersionedStorage {
+    versions: Versions,
+}
+
+#[async_trait]
+impl Storage for VersionedStorage {
+    fn get_admin_status(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    async fn put(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        payload: ZBytes,
+        encoding: Encoding,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        let mut versions = self.versions.write().await;
+        let key_versions = versions.entry(key).or_default();
+        if key_versions.iter().any(|data| data.timestamp == timestamp) {
+            return Ok(StorageInsertionResult::Outdated);
+        }
+        key_versions.push(StoredData {
+            payload,
+            encoding,
+            timestamp,
+        });
+        Ok(StorageInsertionResult::Inserted)
+    }
+
+    async fn delete(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        _timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        self.versions.write().await.remove(&key);
+        Ok(StorageInsertionResult::Deleted)
+    }
+
+    // NOTE: The `_time` parameter is ignored, all the versions are returned: the Replication looks
+    //       up the one it is interested in.
+    async fn get(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        _parameters: &str,
+    ) -> ZResult<Vec<StoredData>> {
+        Ok(self
+            .versions
+            .read()
+            .await
+            .get(&key)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+        Ok(self
+            .versions
+            .read()
+            .await
+            .iter()
+            .filter_map(|(key, key_versions)| {
+                key_versions
+                    .iter()
+                    .map(|data| data.timestamp)
+                    .max()
+                    .map(|timestamp| (key.clone(), timestamp))
+            })
+            .collect())
+    }
+}
+
+async fn open_session(endpoint: &str, listen: bool) -> Arc<Session> {
+    let mut config = Config::default();
+    config.insert_json5("mode", r#""peer""#).unwrap();
+    config
+        .insert_json5("scouting/multicast/enabled", "false")
+        .unwrap();
+    config.insert_json5("timestamping/enabled", "true").unwrap();
+    config
+        .insert_json5("adminspace/permissions/write", "true")
+        .unwrap();
+    let endpoints = if listen {
+        "listen/endpoints"
+    } else {
+        "connect/endpoints"
+    };
+    config
+        .insert_json5(endpoints, &format!(r#"["{endpoint}"]"#))
+        .unwrap();
+
+    Arc::new(zenoh::open(config).await.unwrap())
+}
+
+// NOTE: The returned Sender must be kept alive, the Storage is stopped once it is dropped.
+async fn start_replica(
+    session: &Arc<Session>,
+    name: &str,
+) -> (Versions, tokio::sync::broadcast::Sender<StorageMessage>) {
+    let versions = Versions::default();
+    let volume: VolumeInstance = Box::new(VersionedVolume {
+        versions: versions.clone(),
+    });
+
+    let config = StorageConfig {
+        name: name.to_string(),
+        key_expr: OwnedKeyExpr::from_str("replication/history/**").unwrap(),
+        complete: false,
+        strip_prefix: None,
+        volume_id: "versioned".to_string(),
+        volume_cfg: serde_json::Value::Null,
+        garbage_collection_config: GarbageCollectionConfig::default(),
+        replication: Some(ReplicaConfig {
+            interval: Duration::from_secs(1),
+            sub_intervals: 5,
+            hot: 6,
+            warm: 30,
+            propagation_delay: Duration::from_millis(250),
+        }),
+        ttl: TtlConfig::default(),
+        import_snapshot: None,
+        snapshot_dir: None,
+        admin_operations: true,
+    };
+
+    let admin_key = format!(
+        "@/{}/router/status/plugins/storage_manager/storages/{name}",
+        session.zid()
+    );
+    let storage_tx = create_and_start_storage(admin_key, config, &volume, session.clone())
+        .await
+        .unwrap();
+
+    (versions, storage_tx)
+}
+
+async fn content(versions: &Versions) -> BTreeSet<(String, String)> {
+    versions
+        .read()
+        .await
+        .iter()
+        .flat_map(|(key, key_versions)| {
+            key_versions.iter().map(move |data| {
+                (
+                    key.as_ref().map(|key| key.to_string()).unwrap_or_default(),
+                    data.payload.try_to_string().unwrap().into_owned(),
+                )
+            })
+        })
+        .collect()
+}
+
+// Two Replicas of a Storage keeping all the versions of its key expressions, each receiving
+// publications the other does not, must converge to the same set of versions.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_history_all_replicas_converge() {
+    let endpoint = "tcp/127.0.0.1:47447";
+    let session_a = open_session(endpoint, true).await;
+    let session_b = open_session(endpoint, false).await;
+
+    let (versions_a, _storage_a) = start_replica(&session_a, "replica_a").await;
+    let (versions_b, _storage_b) = start_replica(&session_b, "replica_b").await;
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    // The publications are only delivered to the local Replica: the other has to align to
+    // retrieve them.
+    for (session, key_expr, payload) in [
+        (&session_a, "replication/history/a", "a1"),
+        (&session_a, "replication/history/a", "a2"),
+        (&session_b, "replication/history/a", "a3"),
+        (&session_b, "replication/history/b", "b1"),
+    ] {
+        session
+            .put(key_expr, payload)
+            .allowed_destination(Locality::SessionLocal)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let expected: BTreeSet<_> = [
+        ("replication/history/a", "a1"),
+        ("replication/history/a", "a2"),
+        ("replication/history/a", "a3"),
+        ("replication/history/b", "b1"),
+    ]
+    .into_iter()
+    .map(|(key_expr, payload)| (key_expr.to_string(), payload.to_string()))
+    .collect();
+
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while content(&versions_a).await != expected || content(&versions_b).await != expected {
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| {
+        panic!("Replicas did not converge");
+    });
+}
+
+async fn align_status(session: &Session, name: &str, replica: &str) -> String {
+    let admin_key = format!(
+        "@/{}/router/status/plugins/storage_manager/storages/{name}",
+        session.zid()
+    );
+    session
+        .get(format!("{admin_key}/align?replica={replica}"))
+        .await
+        .unwrap()
+        .recv_async()
+        .await
+        .unwrap()
+        .into_result()
+        .unwrap();
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let reply = session
+                .get(format!("{admin_key}/progress"))
+                .await
+                .unwrap()
+                .recv_async()
+                .await
+                .unwrap();
+            let progress: serde_json::Value =
+                serde_json::from_slice(&reply.result().unwrap().payload().to_bytes()).unwrap();
+            let status = progress["align"]["status"].as_str().unwrap().to_string();
+            if status != "running" {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Full alignment did not finish")
+}
+
+// A full alignment requested through the adminspace reports its actual outcome.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_full_alignment_outcome() {
+    let endpoint = "tcp/127.0.0.1:47448";
+    let session_a = open_session(endpoint, true).await;
+    let session_b = open_session(endpoint, false).await;
+
+    let (_, _storage_a) = start_replica(&session_a, "replica_a").await;
+    let (_, _storage_b) = start_replica(&session_b, "replica_b").await;
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    assert_eq!(
+        align_status(&session_b, "replica_b", &session_a.zid().to_string()).await,
+        "done"
+    );
+
+    assert_eq!(
+        align_status(&session_b, "replica_b", "1bc7ef3bf85b21f59ec4c3fd4fa2bb2c").await,
+        "failed"
+    );
+}
@@ -0,0 +1,101 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{path::Path, str::FromStr};
+
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    key_expr::OwnedKeyExpr,
+    time::Timestamp,
+};
+
+use super::{resolve_snapshot_path, Snapshot, SnapshotEntry, SNAPSHOT_FORMAT_VERSION};
+
+#[test]
+fn test_snapshot_serialization() {
+    let timestamp =
+        Timestamp::from_str("7054123566570568799/BC779A06D7E049BD88C3FF3DB0C17FCC").unwrap();
+    let snapshot = Snapshot {
+        version: SNAPSHOT_FORMAT_VERSION,
+        key_expr: OwnedKeyExpr::from_str("test/**").unwrap(),
+        entries: vec![
+            SnapshotEntry::new(
+                OwnedKeyExpr::from_str("test/a").unwrap(),
+                &ZBytes::from(vec![0u8, 159, 146, 150]),
+                &Encoding::ZENOH_BYTES,
+                &timestamp,
+            ),
+            SnapshotEntry::new(
+                OwnedKeyExpr::from_str("test/b").unwrap(),
+                &ZBytes::from("{\"value\": 42}"),
+                &Encoding::APPLICATION_JSON.with_schema("answer"),
+                &timestamp,
+            ),
+        ],
+    };
+
+    let bytes = snapshot.to_bytes().unwrap();
+    assert_eq!(Snapshot::from_bytes(&bytes).unwrap(), snapshot);
+
+    assert_eq!(snapshot.entries[0].payload, "AJ+Slg==");
+    assert_eq!(snapshot.entries[1].encoding, "application/json;answer");
+    assert_eq!(
+        Timestamp::from_str(&snapshot.entries[0].timestamp).unwrap(),
+        timestamp
+    );
+}
+
+#[test]
+fn test_snapshot_version() {
+    let snapshot = Snapshot {
+        version: SNAPSHOT_FORMAT_VERSION + 1,
+        key_expr: OwnedKeyExpr::from_str("test/**").unwrap(),
+        entries: Vec::default(),
+    };
+
+    let bytes = snapshot.to_bytes().unwrap();
+    assert!(Snapshot::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_resolve_snapshot_path() {
+    let snapshot_dir =
+        std::env::temp_dir().join(format!("zenoh-resolve-snapshot-{}", std::process::id()));
+    std::fs::create_dir_all(snapshot_dir.join("demo")).unwrap();
+    let snapshot_dir = snapshot_dir.canonicalize().unwrap();
+
+    assert_eq!(
+        resolve_snapshot_path(&snapshot_dir, Path::new("demo/export.json")).unwrap(),
+        snapshot_dir.join("demo/export.json")
+    );
+    assert!(resolve_snapshot_path(&snapshot_dir, Path::new("")).is_err());
+    assert!(resolve_snapshot_path(&snapshot_dir, Path::new("/etc/passwd")).is_err());
+    assert!(resolve_snapshot_path(&snapshot_dir, Path::new("../export.json")).is_err());
+    assert!(resolve_snapshot_path(&snapshot_dir, Path::new("demo/../../export.json")).is_err());
+    assert!(resolve_snapshot_path(&snapshot_dir, Path::new("./export.json")).is_err());
+    assert!(resolve_snapshot_path(&snapshot_dir, Path::new("missing/export.json")).is_err());
+
+    // symbolic links cannot be used to escape the snapshot directory
+    #[cfg(unix)]
+    {
+        let outside = snapshot_dir.parent().unwrap();
+        std::os::unix::fs::symlink(outside, snapshot_dir.join("escape")).unwrap();
+        std::os::unix::fs::symlink(outside.join("missing.json"), snapshot_dir.join("dangling"))
+            .unwrap();
+        assert!(resolve_snapshot_path(&snapshot_dir, Path::new("escape/export.json")).is_err());
+        assert!(resolve_snapshot_path(&snapshot_dir, Path::new("dangling")).is_err());
+    }
+
+    std::fs::remove_dir_all(&snapshot_dir).unwrap();
+}
@@ -0,0 +1,248 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test administrative operations on storages -
+// 1. a subset or all the entries of a storage can be purged
+// 2. the garbage collection can be triggered
+// 3. an alignment is rejected if the replication is not enabled
+// 4. the progress of the last run of each operation is reported
+// 5. the operations modifying a storage are rejected unless they are enabled for that storage and
+//    the adminspace accepts writes
+
+use std::thread::sleep;
+
+use tokio::runtime::Runtime;
+use zenoh::{internal::zasync_executor_init, query::Reply, sample::Sample, Config, Session};
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &Session, key_expr: &str) -> Vec<Sample> {
+    let replies: Vec<Reply> = session.get(key_expr).await.unwrap().into_iter().collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut samples = Vec::new();
+    for reply in replies {
+        if let Ok(sample) = reply.into_result() {
+            samples.push(sample);
+        }
+    }
+    println!("Getting Data on '{key_expr}': '{samples:?}'...");
+    samples
+}
+
+async fn run_operation(session: &Session, selector: &str) -> Result<serde_json::Value, String> {
+    let replies: Vec<Reply> = session.get(selector).await.unwrap().into_iter().collect();
+    println!("Getting replies on '{selector}': '{replies:?}'...");
+    assert_eq!(replies.len(), 1);
+    match replies[0].result() {
+        Ok(sample) => Ok(serde_json::from_slice(&sample.payload().to_bytes()).unwrap()),
+        Err(e) => Err(e.payload().try_to_string().unwrap().into_owned()),
+    }
+}
+
+async fn test_admin_operations() {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        admin_test: {
+                            key_expr: "admin/test/**",
+                            volume: {
+                                id: "memory"
+                            },
+                            admin_operations: true,
+                        },
+                        admin_disabled: {
+                            key_expr: "admin/disabled/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5("adminspace/permissions/write", "true")
+        .unwrap();
+    config
+        .insert_json5(
+            "timestamping",
+            r#"{
+                    enabled: {
+                        router: true,
+                        peer: true,
+                        client: true
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::session::init(runtime).await.unwrap();
+    let admin_key = format!(
+        "@/{}/*/status/plugins/storage-manager/storages/admin_test",
+        session.zid()
+    );
+
+    sleep(std::time::Duration::from_secs(1));
+
+    session.put("admin/test/a/1", "1").await.unwrap();
+    session.put("admin/test/a/2", "2").await.unwrap();
+    session.put("admin/test/b", "3").await.unwrap();
+
+    sleep(std::time::Duration::from_millis(10));
+
+    // purge a subset of the storage
+    let progress = run_operation(
+        &session,
+        &format!("{admin_key}/purge?key_expr=admin/test/a/**"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(progress["status"], "done");
+    assert_eq!(progress["total"], 2);
+    assert_eq!(progress["processed"], 2);
+
+    let data = get_data(&session, "admin/test/**").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].key_expr().as_str(), "admin/test/b");
+
+    // purge the remaining entries
+    let progress = run_operation(&session, &format!("{admin_key}/purge"))
+        .await
+        .unwrap();
+    assert_eq!(progress["total"], 1);
+
+    let data = get_data(&session, "admin/test/**").await;
+    assert_eq!(data.len(), 0);
+
+    let progress = run_operation(&session, &format!("{admin_key}/gc"))
+        .await
+        .unwrap();
+    assert_eq!(progress["status"], "done");
+
+    let error = run_operation(
+        &session,
+        &format!("{admin_key}/align?replica=1bc7ef3bf85b21f59ec4c3fd4fa2bb2c"),
+    )
+    .await
+    .unwrap_err();
+    assert!(error.contains("not enabled"));
+
+    let progress = run_operation(&session, &format!("{admin_key}/progress"))
+        .await
+        .unwrap();
+    assert_eq!(progress["purge"]["status"], "done");
+    assert_eq!(progress["gc"]["status"], "done");
+    assert!(progress.get("align").is_none());
+
+    // the operations are disabled by default
+    session.put("admin/disabled/a", "1").await.unwrap();
+    sleep(std::time::Duration::from_millis(10));
+    let error = run_operation(
+        &session,
+        &format!(
+            "@/{}/*/status/plugins/storage-manager/storages/admin_disabled/purge",
+            session.zid()
+        ),
+    )
+    .await
+    .unwrap_err();
+    assert!(error.contains("disabled"));
+    let data = get_data(&session, "admin/disabled/**").await;
+    assert_eq!(data.len(), 1);
+
+    drop(storage);
+}
+
+async fn test_admin_operations_denied() {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        admin_denied: {
+                            key_expr: "admin/denied/**",
+                            volume: {
+                                id: "memory"
+                            },
+                            admin_operations: true,
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+    config.insert_json5("timestamping/enabled", "true").unwrap();
+    config
+        .insert_json5("scouting/multicast/enabled", "false")
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::session::init(runtime).await.unwrap();
+    let admin_key = format!(
+        "@/{}/*/status/plugins/storage-manager/storages/admin_denied",
+        session.zid()
+    );
+
+    sleep(std::time::Duration::from_secs(1));
+
+    // the adminspace does not accept writes by default
+    for operation in ["purge", "gc"] {
+        let error = run_operation(&session, &format!("{admin_key}/{operation}"))
+            .await
+            .unwrap_err();
+        assert!(error.contains("denied"));
+    }
+
+    // reporting the progress is still allowed
+    run_operation(&session, &format!("{admin_key}/progress"))
+        .await
+        .unwrap();
+
+    drop(storage);
+}
+
+#[test]
+fn admin_operations_test() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_admin_operations().await });
+}
+
+#[test]
+fn admin_operations_denied_test() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_admin_operations_denied().await });
+}
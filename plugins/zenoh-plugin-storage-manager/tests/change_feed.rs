@@ -0,0 +1,122 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the change feed of storages -
+// 1. a liveliness token is declared on the change feed of a running storage
+// 2. the outcome of each operation performed on the storage is published on its change feed
+
+use std::{thread::sleep, time::Duration};
+
+use tokio::runtime::Runtime;
+use zenoh::{internal::zasync_executor_init, Config};
+use zenoh_plugin_trait::Plugin;
+
+async fn test_change_feed() {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        feed_test: {
+                            key_expr: "feed/test/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5(
+            "timestamping",
+            r#"{
+                    enabled: {
+                        router: true,
+                        peer: true,
+                        client: true
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::session::init(runtime).await.unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    let changes_key = format!(
+        "@/{}/*/status/plugins/storage-manager/storages/feed_test/changes",
+        session.zid()
+    );
+
+    let tokens: Vec<_> = session
+        .liveliness()
+        .get(&changes_key)
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(tokens.len(), 1);
+
+    let subscriber = session.declare_subscriber(&changes_key).await.unwrap();
+
+    session.put("feed/test/a", "1").await.unwrap();
+    session.put("feed/test/a", "2").await.unwrap();
+    session.delete("feed/test/a").await.unwrap();
+
+    let mut results = Vec::new();
+    for _ in 0..3 {
+        let sample = tokio::time::timeout(Duration::from_secs(5), subscriber.recv_async())
+            .await
+            .unwrap()
+            .unwrap();
+        let change: serde_json::Value =
+            serde_json::from_slice(&sample.payload().to_bytes()).unwrap();
+        println!("Received change: {change}");
+        assert_eq!(change["key_expr"], "feed/test/a");
+        results.push((
+            change["kind"].as_str().unwrap().to_string(),
+            change["result"].as_str().unwrap().to_string(),
+        ));
+    }
+
+    assert_eq!(
+        results,
+        vec![
+            ("put".to_string(), "inserted".to_string()),
+            ("put".to_string(), "replaced".to_string()),
+            ("delete".to_string(), "deleted".to_string()),
+        ]
+    );
+
+    drop(storage);
+}
+
+#[test]
+fn change_feed_test() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_change_feed().await });
+}
@@ -0,0 +1,300 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test snapshots of storages -
+// 1. the content of a storage can be exported through the adminspace, to a file or as a reply
+// 2. a snapshot can be imported through the adminspace or when the storage starts
+// 3. the files accessed through the adminspace are confined to the snapshot directory, and these
+//    operations are disabled if no such directory is configured or if the administrative
+//    operations are not enabled
+
+use std::thread::sleep;
+
+use tokio::runtime::Runtime;
+use zenoh::{internal::zasync_executor_init, query::Reply, sample::Sample, Config, Session};
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &Session, key_expr: &str) -> Vec<Sample> {
+    let replies: Vec<Reply> = session.get(key_expr).await.unwrap().into_iter().collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut samples = Vec::new();
+    for reply in replies {
+        if let Ok(sample) = reply.into_result() {
+            samples.push(sample);
+        }
+    }
+    println!("Getting Data on '{key_expr}': '{samples:?}'...");
+    samples
+}
+
+async fn test_snapshot() {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+
+    let seed_path = std::env::temp_dir().join(format!("zenoh-seed-{}.json", std::process::id()));
+    std::fs::write(
+        &seed_path,
+        r#"{
+            "version": 1,
+            "key_expr": "snapshot/seeded/**",
+            "entries": [
+                {
+                    "key_expr": "snapshot/seeded/a",
+                    "payload": "MQ==",
+                    "encoding": "text/plain",
+                    "timestamp": "7054123566570568799/BC779A06D7E049BD88C3FF3DB0C17FCC"
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let snapshot_dir = std::env::temp_dir().join(format!("zenoh-snapshots-{}", std::process::id()));
+    std::fs::create_dir_all(&snapshot_dir).unwrap();
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            &format!(
+                r#"{{
+                    storages: {{
+                        snapshot_test: {{
+                            key_expr: "snapshot/test/**",
+                            volume: {{
+                                id: "memory"
+                            }},
+                            snapshot_dir: {snapshot_dir:?},
+                            admin_operations: true
+                        }},
+                        seeded_test: {{
+                            key_expr: "snapshot/seeded/**",
+                            volume: {{
+                                id: "memory"
+                            }},
+                            import_snapshot: {seed_path:?},
+                            snapshot_dir: {snapshot_dir:?},
+                            admin_operations: true
+                        }},
+                        locked_test: {{
+                            key_expr: "snapshot/locked/**",
+                            volume: {{
+                                id: "memory"
+                            }},
+                            snapshot_dir: {snapshot_dir:?}
+                        }},
+                        disabled_test: {{
+                            key_expr: "snapshot/disabled/**",
+                            volume: {{
+                                id: "memory"
+                            }}
+                        }}
+                    }}
+                }}"#
+            ),
+        )
+        .unwrap();
+    config
+        .insert_json5("adminspace/permissions/write", "true")
+        .unwrap();
+    config
+        .insert_json5(
+            "timestamping",
+            r#"{
+                    enabled: {
+                        router: true,
+                        peer: true,
+                        client: true
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::session::init(runtime).await.unwrap();
+    let admin_key = format!(
+        "@/{}/*/status/plugins/storage-manager/storages",
+        session.zid()
+    );
+
+    sleep(std::time::Duration::from_secs(1));
+
+    // expects the seeded storage to contain the entry of the snapshot
+    let data = get_data(&session, "snapshot/seeded/a").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].payload().try_to_string().unwrap(), "1");
+
+    session.put("snapshot/test/a", "1").await.unwrap();
+    session.put("snapshot/test/b", "2").await.unwrap();
+
+    sleep(std::time::Duration::from_millis(10));
+
+    // export as a reply
+    let replies: Vec<Reply> = session
+        .get(format!("{admin_key}/snapshot_test/export"))
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(replies.len(), 1);
+    let snapshot = replies[0]
+        .result()
+        .unwrap()
+        .payload()
+        .to_bytes()
+        .into_owned();
+    let snapshot_json: serde_json::Value = serde_json::from_slice(&snapshot).unwrap();
+    assert_eq!(snapshot_json["entries"].as_array().unwrap().len(), 2);
+
+    // export to a file, relative to the snapshot directory
+    let replies: Vec<Reply> = session
+        .get(format!("{admin_key}/snapshot_test/export?path=export.json"))
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(replies.len(), 1);
+    assert!(replies[0].result().is_ok());
+    assert_eq!(
+        std::fs::read(snapshot_dir.join("export.json")).unwrap(),
+        snapshot
+    );
+
+    // paths that could escape the snapshot directory are rejected
+    for path in [
+        "../export.json".to_string(),
+        seed_path.display().to_string(),
+    ] {
+        let replies: Vec<Reply> = session
+            .get(format!("{admin_key}/snapshot_test/export?path={path}"))
+            .await
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(replies.len(), 1);
+        assert!(replies[0].result().is_err());
+    }
+
+    // without a snapshot directory or the administrative operations, the operations are disabled
+    for (storage, operation) in [
+        ("disabled_test", "export"),
+        ("disabled_test", "import"),
+        ("locked_test", "export"),
+        ("locked_test", "import"),
+    ] {
+        let replies: Vec<Reply> = session
+            .get(format!("{admin_key}/{storage}/{operation}"))
+            .await
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(replies.len(), 1);
+        assert!(replies[0].result().is_err());
+    }
+
+    // import, as a payload, in the other storage a snapshot with newer data
+    let newer_snapshot = r#"{
+        "version": 1,
+        "key_expr": "snapshot/seeded/**",
+        "entries": [
+            {
+                "key_expr": "snapshot/seeded/b",
+                "payload": "Mg==",
+                "encoding": "text/plain",
+                "timestamp": "7054123824268606559/BC779A06D7E049BD88C3FF3DB0C17FCC"
+            },
+            {
+                "key_expr": "snapshot/test/c",
+                "payload": "Mw==",
+                "encoding": "text/plain",
+                "timestamp": "7054123824268606559/BC779A06D7E049BD88C3FF3DB0C17FCC"
+            }
+        ]
+    }"#;
+    let replies: Vec<Reply> = session
+        .get(format!("{admin_key}/seeded_test/import"))
+        .payload(newer_snapshot)
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(replies.len(), 1);
+    let outcome: serde_json::Value =
+        serde_json::from_slice(&replies[0].result().unwrap().payload().to_bytes()).unwrap();
+    // the entry that is not included in the key expression of the storage is skipped
+    assert_eq!(outcome["entries"], 1);
+
+    let data = get_data(&session, "snapshot/seeded/**").await;
+    assert_eq!(data.len(), 2);
+
+    // a snapshot with an invalid entry is rejected as a whole
+    let invalid_snapshot = r#"{
+        "version": 1,
+        "key_expr": "snapshot/seeded/**",
+        "entries": [
+            {
+                "key_expr": "snapshot/seeded/c",
+                "payload": "Mw==",
+                "encoding": "text/plain",
+                "timestamp": "7054123824268606559/BC779A06D7E049BD88C3FF3DB0C17FCC"
+            },
+            {
+                "key_expr": "snapshot/seeded/d",
+                "payload": "not base64!",
+                "encoding": "text/plain",
+                "timestamp": "7054123824268606559/BC779A06D7E049BD88C3FF3DB0C17FCC"
+            }
+        ]
+    }"#;
+    let replies: Vec<Reply> = session
+        .get(format!("{admin_key}/seeded_test/import"))
+        .payload(invalid_snapshot)
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(replies.len(), 1);
+    assert!(replies[0].result().is_err());
+
+    let data = get_data(&session, "snapshot/seeded/**").await;
+    assert_eq!(data.len(), 2);
+
+    // unknown operations are rejected
+    let replies: Vec<Reply> = session
+        .get(format!("{admin_key}/snapshot_test/unknown"))
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(replies.len(), 1);
+    assert!(replies[0].result().is_err());
+
+    std::fs::remove_file(seed_path).unwrap();
+    std::fs::remove_dir_all(snapshot_dir).unwrap();
+    drop(storage);
+}
+
+#[test]
+fn snapshot_test() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_snapshot().await });
+}
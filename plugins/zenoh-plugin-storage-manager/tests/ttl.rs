@@ -0,0 +1,148 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test time-to-live of stored data -
+// 1. samples stored with the storage default time-to-live expire
+// 2. the time-to-live of a sample can be overridden through its attachment
+// 3. an update cancels the expiration of the previous value
+// 4. a time-to-live too long to be represented is reduced to the configured maximum
+
+use std::thread::sleep;
+
+use tokio::runtime::Runtime;
+use zenoh::{internal::zasync_executor_init, query::Reply, sample::Sample, Config, Session};
+use zenoh_plugin_trait::Plugin;
+
+async fn put_data(session: &Session, key_expr: &str, value: &str, ttl: Option<&str>) {
+    println!("Putting Data ('{key_expr}': '{value}', ttl: {ttl:?})...");
+    match ttl {
+        Some(ttl) => session
+            .put(key_expr, value)
+            .attachment(format!("_ttl={ttl}"))
+            .await
+            .unwrap(),
+        None => session.put(key_expr, value).await.unwrap(),
+    }
+}
+
+async fn get_data(session: &Session, key_expr: &str) -> Vec<Sample> {
+    let replies: Vec<Reply> = session.get(key_expr).await.unwrap().into_iter().collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut samples = Vec::new();
+    for reply in replies {
+        if let Ok(sample) = reply.into_result() {
+            samples.push(sample);
+        }
+    }
+    println!("Getting Data on '{key_expr}': '{samples:?}'...");
+    samples
+}
+
+async fn test_ttl() {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        ttl_test: {
+                            key_expr: "ttl/test/**",
+                            volume: {
+                                id: "memory"
+                            },
+                            ttl: {
+                                default: 2,
+                                period: 0.1,
+                                max: 3600,
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5(
+            "timestamping",
+            r#"{
+                    enabled: {
+                        router: true,
+                        peer: true,
+                        client: true
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::session::init(runtime).await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    put_data(&session, "ttl/test/default", "1", None).await;
+    put_data(&session, "ttl/test/short", "2", Some("0.5")).await;
+    put_data(&session, "ttl/test/updated", "3", Some("0.5")).await;
+    put_data(&session, "ttl/test/huge", "5", Some("1e30")).await;
+
+    sleep(std::time::Duration::from_millis(10));
+
+    let data = get_data(&session, "ttl/test/**").await;
+    assert_eq!(data.len(), 4);
+
+    // the new value overrides the time-to-live of the previous one
+    put_data(&session, "ttl/test/updated", "4", Some("60")).await;
+
+    sleep(std::time::Duration::from_secs(1));
+
+    // expects "short" to have expired
+    let data = get_data(&session, "ttl/test/short").await;
+    assert_eq!(data.len(), 0);
+
+    let data = get_data(&session, "ttl/test/default").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].payload().try_to_string().unwrap(), "1");
+
+    sleep(std::time::Duration::from_secs(2));
+
+    // expects "default" to have expired
+    let data = get_data(&session, "ttl/test/default").await;
+    assert_eq!(data.len(), 0);
+
+    // expects "updated" to still be present
+    let data = get_data(&session, "ttl/test/updated").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].payload().try_to_string().unwrap(), "4");
+
+    // expects "huge" to still be present, the storage having survived its time-to-live
+    let data = get_data(&session, "ttl/test/huge").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].payload().try_to_string().unwrap(), "5");
+
+    drop(storage);
+}
+
+#[test]
+fn ttl_test() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_ttl().await });
+}
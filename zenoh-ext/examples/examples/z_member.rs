@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
 
 use futures::StreamExt;
 use zenoh::Config;
@@ -21,9 +21,7 @@ use zenoh_ext::group::*;
 async fn main() {
     zenoh::init_log_from_env_or("error");
     let z = Arc::new(zenoh::open(Config::default()).await.unwrap());
-    let member = Member::new(z.zid().to_string())
-        .unwrap()
-        .lease(Duration::from_secs(3));
+    let member = Member::new(z.zid().to_string()).unwrap();
 
     let group = Group::join(z.clone(), "zgroup", member).await.unwrap();
     let rx = group.subscribe().await;
@@ -37,9 +35,9 @@ async fn main() {
             v.iter()
                 .fold(String::from("\n"), |a, b| format!("\t{a} \n\t{b:?}")),
         );
-        println!(">>>>>>> Eventual Leader <<<<<<<<<");
-        let m = group.leader().await;
-        println!("Leader mid = {m:?}");
+        println!(">>>>>>> Leader <<<<<<<<<");
+        let l = group.elected_leader().await;
+        println!("Leader = {l:?}");
         println!(">>>>>>><<<<<<<<<");
     }
 }
@@ -12,10 +12,11 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use std::{
-    collections::VecDeque,
     future::{IntoFuture, Ready},
     ops::{Bound, RangeBounds},
+    path::PathBuf,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
 use zenoh::{
@@ -27,11 +28,18 @@ use zenoh::{
     liveliness::LivelinessToken,
     qos::{CongestionControl, Priority},
     query::{Queryable, ZenohParameters},
-    sample::{Locality, Sample, SampleBuilder},
+    sample::{Locality, Sample, SampleBuilder, SourceSn},
+    session::EntityGlobalId,
     Resolvable, Result as ZResult, Session, Wait, KE_ADV_PREFIX, KE_AT, KE_STARSTAR,
 };
 
+mod store;
+pub(crate) use store::Retention;
+use store::{Limits, Store};
+
 pub(crate) static KE_UHLC: &keyexpr = ke!("uhlc");
+pub(crate) static KE_ACK: &keyexpr = ke!("ack");
+pub(crate) static KE_DURABLE: &keyexpr = ke!("durable");
 #[zenoh_macros::unstable]
 kedefine!(
     pub(crate) ke_liveliness: "@adv/${entity:*}/${zid:*}/${eid:*}/${meta:**}/@/${remaining:**}",
@@ -82,33 +90,78 @@ impl QoSBuilderTrait for RepliesConfig {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 /// Configure an [`AdvancedPublisher`](crate::AdvancedPublisher) cache.
+///
+/// The oldest samples are evicted once one of the limits is exceeded,
+/// the cache keeping only the last sample if no limit is specified.
 #[zenoh_macros::unstable]
 pub struct CacheConfig {
-    max_samples: usize,
+    max_samples: Option<usize>,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    directory: Option<PathBuf>,
     replies_config: RepliesConfig,
 }
 
-#[zenoh_macros::unstable]
-impl Default for CacheConfig {
-    fn default() -> Self {
-        Self {
-            max_samples: 1,
-            replies_config: RepliesConfig::default(),
-        }
-    }
-}
-
 #[zenoh_macros::unstable]
 impl CacheConfig {
     /// Specify how many samples to keep for each resource.
     #[zenoh_macros::unstable]
     pub fn max_samples(mut self, depth: usize) -> Self {
-        self.max_samples = depth;
+        self.max_samples = Some(depth);
+        self
+    }
+
+    /// Specify the maximum size of the kept samples, in bytes.
+    ///
+    /// In memory, the size of a sample is the size of its payload and attachment. In a
+    /// [`directory`](CacheConfig::directory), it is the size of the stored sample, and the
+    /// files of the evicted samples are deleted by segments of an eighth of this size.
+    #[zenoh_macros::unstable]
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Specify the maximum age of the kept samples.
+    #[zenoh_macros::unstable]
+    pub fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// Store the samples in files of the given directory instead of memory.
+    ///
+    /// This allows to keep more samples than what fits in memory, and to keep them across
+    /// restarts: the samples stored in the directory are served again by the next cache using
+    /// it, and an [`AdvancedPublisher`](crate::AdvancedPublisher) with the same
+    /// [`EntityGlobalId`] resumes its sequence numbers after them.
+    /// A directory can't be used by several caches at the same time: the creation of a cache on a
+    /// directory used by another one fails.
+    ///
+    /// The samples are written to the files by a background thread. While the disk is slower
+    /// than the publisher, the samples waiting to be written are kept in memory, up to the
+    /// [`max_bytes`](CacheConfig::max_bytes) of the cache: the next samples are not cached, and
+    /// are detected as missed by the subscribers that query them.
+    #[zenoh_macros::unstable]
+    pub fn directory<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.directory = Some(directory.into());
         self
     }
 
+    fn limits(&self) -> Limits {
+        let mut limits = Limits {
+            max_samples: self.max_samples,
+            max_bytes: self.max_bytes,
+            max_age: self.max_age,
+        };
+        if self.max_samples.is_none() && self.max_bytes.is_none() && self.max_age.is_none() {
+            limits.max_samples = Some(1);
+        }
+        limits
+    }
+
     /// The QoS to apply to replies.
     #[zenoh_macros::unstable]
     pub fn replies_config(mut self, qos: RepliesConfig) -> Self {
@@ -209,8 +262,7 @@ fn decode_range(range: &str) -> (Bound<u32>, Bound<u32>) {
 /// [`AdvancedCache`].
 #[zenoh_macros::unstable]
 pub struct AdvancedCache {
-    cache: Arc<RwLock<VecDeque<Sample>>>,
-    max_samples: usize,
+    cache: Arc<RwLock<Store>>,
     _queryable: Queryable<()>,
     _token: Option<LivelinessToken>,
 }
@@ -227,11 +279,15 @@ impl AdvancedCache {
             Some(Err(e)) => bail!("Invalid key expression for queryable_prefix: {}", e),
         };
         tracing::debug!(
-            "Create AdvancedCache on {} with max_samples={:?}",
+            "Create AdvancedCache on {} with history={:?}",
             &key_expr,
             conf.history,
         );
-        let cache = Arc::new(RwLock::new(VecDeque::<Sample>::new()));
+        let store = match conf.history.directory.as_ref() {
+            Some(directory) => Store::directory(directory, conf.history.limits())?,
+            None => Store::memory(conf.history.limits()),
+        };
+        let cache = Arc::new(RwLock::new(store));
 
         // declare the queryable that will answer to queries on cache
         let queryable = conf
@@ -250,81 +306,46 @@ impl AdvancedCache {
                         .parameters()
                         .get("_max")
                         .and_then(|s| s.parse::<u32>().ok());
-                    if let Ok(queue) = cache.read() {
-                        if let Some(max) = max {
-                            let mut samples = VecDeque::new();
-                            for sample in queue.iter() {
-                                if range == (Bound::Unbounded, Bound::Unbounded)
-                                    || sample
-                                        .source_info()
-                                        .source_sn()
-                                        .is_some_and(|sn| range.contains(&sn))
+                    let time_range = query.parameters().time_range();
+                    // The samples stored in a directory are read while replying, once the store
+                    // is released
+                    let samples = if let Ok(store) = cache.read() {
+                        store.select(
+                            |source_sn, timestamp| {
+                                if range != (Bound::Unbounded, Bound::Unbounded)
+                                    && !source_sn.is_some_and(|sn| range.contains(&sn))
                                 {
-                                    if let (Some(Ok(time_range)), Some(timestamp)) =
-                                        (query.parameters().time_range(), sample.timestamp())
-                                    {
-                                        if !time_range
-                                            .contains(timestamp.get_time().to_system_time())
-                                        {
-                                            continue;
-                                        }
-                                    }
-                                    samples.push_front(sample);
-                                    samples.truncate(max as usize);
+                                    return false;
                                 }
-                            }
-                            for sample in samples.drain(..).rev() {
-                                if let Err(e) = query
-                                    .reply_sample(
-                                        SampleBuilder::from(sample.clone())
-                                            .congestion_control(
-                                                conf.history.replies_config.congestion_control,
-                                            )
-                                            .priority(conf.history.replies_config.priority)
-                                            .express(conf.history.replies_config.is_express)
-                                            .into(),
-                                    )
-                                    .wait()
+                                if let (Some(Ok(time_range)), Some(timestamp)) =
+                                    (&time_range, timestamp)
                                 {
-                                    tracing::warn!("Error replying to query: {}", e);
+                                    return time_range
+                                        .contains(timestamp.get_time().to_system_time());
                                 }
-                            }
-                        } else {
-                            for sample in queue.iter() {
-                                if range == (Bound::Unbounded, Bound::Unbounded)
-                                    || sample
-                                        .source_info()
-                                        .source_sn()
-                                        .is_some_and(|sn| range.contains(&sn))
-                                {
-                                    if let (Some(Ok(time_range)), Some(timestamp)) =
-                                        (query.parameters().time_range(), sample.timestamp())
-                                    {
-                                        if !time_range
-                                            .contains(timestamp.get_time().to_system_time())
-                                        {
-                                            continue;
-                                        }
-                                    }
-                                    if let Err(e) = query
-                                        .reply_sample(
-                                            SampleBuilder::from(sample.clone())
-                                                .congestion_control(
-                                                    conf.history.replies_config.congestion_control,
-                                                )
-                                                .priority(conf.history.replies_config.priority)
-                                                .express(conf.history.replies_config.is_express)
-                                                .into(),
-                                        )
-                                        .wait()
-                                    {
-                                        tracing::warn!("Error replying to query: {}", e);
-                                    }
-                                }
-                            }
-                        }
+                                true
+                            },
+                            max.map(|max| max as usize),
+                        )
                     } else {
                         tracing::error!("Unable to take AdvancedPublisher cache read lock");
+                        return;
+                    };
+                    for sample in samples {
+                        if let Err(e) = query
+                            .reply_sample(
+                                SampleBuilder::from(sample)
+                                    .congestion_control(
+                                        conf.history.replies_config.congestion_control,
+                                    )
+                                    .priority(conf.history.replies_config.priority)
+                                    .express(conf.history.replies_config.is_express)
+                                    .into(),
+                            )
+                            .wait()
+                        {
+                            tracing::warn!("Error replying to query: {}", e);
+                        }
                     }
                 }
             })
@@ -343,7 +364,6 @@ impl AdvancedCache {
 
         Ok(AdvancedCache {
             cache,
-            max_samples: conf.history.max_samples,
             _queryable: queryable,
             _token: token,
         })
@@ -351,13 +371,31 @@ impl AdvancedCache {
 
     #[zenoh_macros::unstable]
     pub(crate) fn cache_sample(&self, sample: Sample) {
-        if let Ok(mut queue) = self.cache.write() {
-            if queue.len() >= self.max_samples {
-                queue.pop_front();
+        if let Ok(mut store) = self.cache.write() {
+            if let Err(e) = store.push(sample) {
+                tracing::error!("Unable to store sample in AdvancedPublisher cache: {}", e);
             }
-            queue.push_back(sample);
         } else {
             tracing::error!("Unable to take AdvancedPublisher cache write lock");
         }
     }
+
+    /// Retains the samples of a source regardless of the limits of the cache.
+    #[zenoh_macros::unstable]
+    pub(crate) fn retainer(&self) -> impl Fn(Option<Retention>) + Send + Sync + 'static {
+        let cache = self.cache.clone();
+        move |retention| match cache.write() {
+            Ok(mut store) => store.set_retention(retention),
+            Err(_) => tracing::error!("Unable to take AdvancedPublisher cache write lock"),
+        }
+    }
+
+    /// Returns the sequence number of the last cached sample of `source_id`.
+    #[zenoh_macros::unstable]
+    pub(crate) fn last_sn(&self, source_id: &EntityGlobalId) -> Option<SourceSn> {
+        self.cache
+            .read()
+            .ok()
+            .and_then(|store| store.last_sn(source_id))
+    }
 }
@@ -0,0 +1,785 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The samples of an [`AdvancedCache`](super::AdvancedCache), kept in memory or in a directory.
+//!
+//! In a directory, the samples are appended to segment files named by their number, each sample
+//! being a record prefixed by its length. The segments are deleted once all their samples are
+//! evicted, and are read again when the cache is created. The segments are written by a
+//! dedicated thread, and read when replying to queries without holding the store. The directory
+//! is locked by the cache using it.
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use advisory_lock::{AdvisoryFileLock, FileLockMode};
+use flume::{Receiver, Sender, TrySendError};
+use uhlc::ID;
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    config::ZenohId,
+    internal::bail,
+    key_expr::KeyExpr,
+    sample::{Sample, SampleBuilder, SampleKind, SourceInfo, SourceSn},
+    session::EntityGlobalId,
+    time::{Timestamp, NTP64},
+    Result as ZResult,
+};
+
+use crate::{ZDeserializeError, ZDeserializer, ZSerializer};
+
+const SEGMENT_EXTENSION: &str = "seg";
+const LOCK_FILE: &str = "lock";
+const MAX_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+const MAX_PENDING_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_PENDING_OPERATIONS: usize = 64 * 1024;
+const RECORD_HEADER_SIZE: u64 = 4;
+
+/// The limits of a cache, the oldest samples being evicted once one of them is exceeded.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Limits {
+    pub(crate) max_samples: Option<usize>,
+    pub(crate) max_bytes: Option<u64>,
+    pub(crate) max_age: Option<Duration>,
+}
+
+/// Where a sample is stored.
+enum Location {
+    Memory(Box<Sample>),
+    /// A sample queued for writing in a segment, kept in memory until it is written.
+    Writing {
+        sample: Box<Sample>,
+        seq: u64,
+        segment: u64,
+        offset: u64,
+        len: u32,
+    },
+    Disk {
+        segment: u64,
+        offset: u64,
+        len: u32,
+    },
+}
+
+impl Location {
+    fn segment(&self) -> Option<u64> {
+        match self {
+            Location::Memory(_) => None,
+            Location::Writing { segment, .. } | Location::Disk { segment, .. } => Some(*segment),
+        }
+    }
+}
+
+/// A cached sample, with what is needed to select it without reading it.
+struct Entry {
+    time: SystemTime,
+    size: u64,
+    source_id: Option<EntityGlobalId>,
+    source_sn: Option<SourceSn>,
+    timestamp: Option<Timestamp>,
+    location: Location,
+}
+
+/// An operation on the segment files, performed by the writer thread.
+enum Operation {
+    Append { segment: u64, record: Vec<u8> },
+    Delete { first: u64, until: u64 },
+}
+
+/// The segment files of a cache stored in a directory.
+///
+/// The records are placed in the segments when they are added, but written by a dedicated
+/// thread, so that publishing never waits for the disk. The records waiting to be written are
+/// bounded: once `max_pending` bytes are waiting, the next records are rejected until the writer
+/// catches up.
+struct Segments {
+    directory: PathBuf,
+    segment_size: u64,
+    first: u64,
+    current: u64,
+    started: bool,
+    offset: u64,
+    queued: u64,
+    written: Arc<AtomicU64>,
+    pending: Arc<AtomicU64>,
+    max_pending: u64,
+    writer: Option<(Sender<Operation>, JoinHandle<()>)>,
+    _lock: File,
+}
+
+impl Segments {
+    fn new(
+        directory: &Path,
+        lock: File,
+        segment_size: u64,
+        max_pending: u64,
+        first: u64,
+        current: u64,
+    ) -> ZResult<Self> {
+        let (sender, receiver) = flume::bounded(MAX_PENDING_OPERATIONS);
+        let written = Arc::new(AtomicU64::new(0));
+        let pending = Arc::new(AtomicU64::new(0));
+        let handle = thread::Builder::new()
+            .name("cache-writer".to_string())
+            .spawn({
+                let directory = directory.to_path_buf();
+                let written = written.clone();
+                let pending = pending.clone();
+                move || write_segments(&directory, &receiver, &written, &pending)
+            })?;
+        Ok(Segments {
+            directory: directory.to_path_buf(),
+            segment_size,
+            first,
+            current,
+            started: false,
+            offset: 0,
+            queued: 0,
+            written,
+            pending,
+            max_pending,
+            writer: Some((sender, handle)),
+            _lock: lock,
+        })
+    }
+
+    fn send(&self, operation: Operation) -> ZResult<()> {
+        let Some((sender, _)) = self.writer.as_ref() else {
+            bail!("Cache writer of {} stopped", self.directory.display());
+        };
+        match sender.try_send(operation) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                bail!("Cache writer of {} is late", self.directory.display())
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                bail!("Cache writer of {} stopped", self.directory.display())
+            }
+        }
+    }
+
+    /// Queues a record for writing at the end of the current segment, starting a new one when
+    /// it is full, and returns its sequence number, segment and offset.
+    ///
+    /// The record is rejected if the records waiting to be written exceed `max_pending` bytes,
+    /// unless it is the only one.
+    fn append(&mut self, record: Vec<u8>) -> ZResult<(u64, u64, u64)> {
+        let size = RECORD_HEADER_SIZE + u64::from(u32::try_from(record.len())?);
+        let pending = self.pending.load(Ordering::Acquire);
+        if pending > 0 && pending + size > self.max_pending {
+            bail!(
+                "Cache writer of {} is late ({} bytes waiting to be written)",
+                self.directory.display(),
+                pending
+            );
+        }
+        let (segment, offset) = if !self.started || self.offset >= self.segment_size {
+            (self.current + u64::from(self.started), 0)
+        } else {
+            (self.current, self.offset)
+        };
+        self.send(Operation::Append { segment, record })?;
+        self.pending.fetch_add(size, Ordering::AcqRel);
+        self.current = segment;
+        self.started = true;
+        self.offset = offset + size;
+        let seq = self.queued;
+        self.queued += 1;
+        Ok((seq, segment, offset + RECORD_HEADER_SIZE))
+    }
+
+    /// Deletes the segments preceding `segment`, or retries on the next call if the writer is
+    /// late.
+    fn delete_until(&mut self, segment: u64) {
+        if self.first < segment {
+            match self.send(Operation::Delete {
+                first: self.first,
+                until: segment,
+            }) {
+                Ok(()) => self.first = segment,
+                Err(e) => tracing::debug!("Unable to delete cache segments: {}", e),
+            }
+        }
+    }
+}
+
+impl Drop for Segments {
+    fn drop(&mut self) {
+        // Wait for the queued records to be written, so that they are read by the next cache
+        if let Some((sender, handle)) = self.writer.take() {
+            drop(sender);
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Performs the operations on the segment files of `directory` until the cache is dropped.
+fn write_segments(
+    directory: &Path,
+    receiver: &Receiver<Operation>,
+    written: &AtomicU64,
+    pending: &AtomicU64,
+) {
+    let mut file: Option<(u64, File)> = None;
+    for operation in receiver.iter() {
+        match operation {
+            Operation::Append { segment, record } => {
+                let size = RECORD_HEADER_SIZE + record.len() as u64;
+                if let Err(e) = append(directory, &mut file, segment, &record) {
+                    tracing::error!(
+                        "Unable to write cache segment {}: {}",
+                        segment_path(directory, segment).display(),
+                        e
+                    );
+                }
+                written.fetch_add(1, Ordering::Release);
+                pending.fetch_sub(size, Ordering::AcqRel);
+            }
+            Operation::Delete { first, until } => {
+                if file.as_ref().is_some_and(|(current, _)| *current < until) {
+                    file = None;
+                }
+                for segment in first..until {
+                    let path = segment_path(directory, segment);
+                    if let Err(e) = fs::remove_file(&path) {
+                        if e.kind() != ErrorKind::NotFound {
+                            tracing::warn!(
+                                "Unable to delete cache segment {}: {}",
+                                path.display(),
+                                e
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Appends a record to `segment`, reusing the file of the previous append if it is the same.
+fn append(
+    directory: &Path,
+    file: &mut Option<(u64, File)>,
+    segment: u64,
+    record: &[u8],
+) -> std::io::Result<()> {
+    if file
+        .as_ref()
+        .map_or(true, |(current, _)| *current != segment)
+    {
+        let f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment_path(directory, segment))?;
+        *file = Some((segment, f));
+    }
+    let (_, f) = file.as_mut().unwrap();
+    f.write_all(&(record.len() as u32).to_le_bytes())?;
+    f.write_all(record)
+}
+
+fn segment_path(directory: &Path, segment: u64) -> PathBuf {
+    directory.join(format!("{segment:020}.{SEGMENT_EXTENSION}"))
+}
+
+fn encode(sample: &Sample, time: SystemTime) -> ZBytes {
+    let mut serializer = ZSerializer::new();
+    let millis = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    serializer.serialize(millis);
+    serializer.serialize(sample.kind() == SampleKind::Delete);
+    serializer.serialize(sample.key_expr().as_str());
+    serializer.serialize(sample.payload());
+    serializer.serialize(sample.encoding().to_string());
+    serializer.serialize(sample.timestamp().is_some());
+    if let Some(timestamp) = sample.timestamp() {
+        let id = timestamp.get_id();
+        serializer.serialize(timestamp.get_time().as_u64());
+        serializer.serialize(&id.to_le_bytes()[..id.size()]);
+    }
+    serializer.serialize(sample.source_info().source_id().is_some());
+    if let Some(source_id) = sample.source_info().source_id() {
+        serializer.serialize(source_id.zid().to_string());
+        serializer.serialize(source_id.eid());
+    }
+    serializer.serialize(sample.source_info().source_sn().is_some());
+    if let Some(source_sn) = sample.source_info().source_sn() {
+        serializer.serialize(source_sn);
+    }
+    serializer.serialize(sample.attachment().is_some());
+    if let Some(attachment) = sample.attachment() {
+        serializer.serialize(attachment);
+    }
+    serializer.finish()
+}
+
+fn decode(record: &ZBytes) -> Result<(SystemTime, Sample), ZDeserializeError> {
+    let mut deserializer = ZDeserializer::new(record);
+    let time = UNIX_EPOCH + Duration::from_millis(deserializer.deserialize::<u64>()?);
+    let delete: bool = deserializer.deserialize()?;
+    let key_expr =
+        KeyExpr::try_from(deserializer.deserialize::<String>()?).map_err(|_| ZDeserializeError)?;
+    let payload = ZBytes::from(deserializer.deserialize::<Vec<u8>>()?);
+    let encoding = Encoding::from(deserializer.deserialize::<String>()?);
+    let timestamp = if deserializer.deserialize()? {
+        let time = NTP64(deserializer.deserialize()?);
+        let id = ID::try_from(&deserializer.deserialize::<Vec<u8>>()?[..])
+            .map_err(|_| ZDeserializeError)?;
+        Some(Timestamp::new(time, id))
+    } else {
+        None
+    };
+    let source_id = if deserializer.deserialize()? {
+        let zid = ZenohId::from_str(&deserializer.deserialize::<String>()?)
+            .map_err(|_| ZDeserializeError)?;
+        Some(EntityGlobalId::new(zid, deserializer.deserialize()?))
+    } else {
+        None
+    };
+    let source_sn = if deserializer.deserialize()? {
+        Some(deserializer.deserialize()?)
+    } else {
+        None
+    };
+    let attachment = if deserializer.deserialize()? {
+        Some(ZBytes::from(deserializer.deserialize::<Vec<u8>>()?))
+    } else {
+        None
+    };
+    if !deserializer.done() {
+        return Err(ZDeserializeError);
+    }
+    let sample = if delete {
+        SampleBuilder::delete(key_expr)
+            .timestamp(timestamp)
+            .source_info(SourceInfo::new(source_id, source_sn))
+            .attachment(attachment)
+            .into()
+    } else {
+        SampleBuilder::put(key_expr, payload)
+            .encoding(encoding)
+            .timestamp(timestamp)
+            .source_info(SourceInfo::new(source_id, source_sn))
+            .attachment(attachment)
+            .into()
+    };
+    Ok((time, sample))
+}
+
+/// Reads the records of a segment, truncating it after the last valid one.
+fn read_segment(path: &Path, segment: u64, entries: &mut VecDeque<Entry>) -> ZResult<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
+    let mut offset = 0;
+    while let Some(header) = content.get(offset..offset + RECORD_HEADER_SIZE as usize) {
+        let len = u32::from_le_bytes(header.try_into().unwrap());
+        let start = offset + RECORD_HEADER_SIZE as usize;
+        let Some(record) = content.get(start..start + len as usize) else {
+            break;
+        };
+        let Ok((time, sample)) = decode(&ZBytes::from(record)) else {
+            break;
+        };
+        entries.push_back(Entry {
+            time,
+            size: RECORD_HEADER_SIZE + u64::from(len),
+            source_id: sample.source_info().source_id().copied(),
+            source_sn: sample.source_info().source_sn(),
+            timestamp: sample.timestamp().copied(),
+            location: Location::Disk {
+                segment,
+                offset: start as u64,
+                len,
+            },
+        });
+        offset = start + len as usize;
+    }
+    if offset < content.len() {
+        tracing::warn!(
+            "Truncating cache segment {} after its last valid sample",
+            path.display()
+        );
+        file.set_len(offset as u64)?;
+    }
+    Ok(())
+}
+
+/// The samples of a source retained regardless of their age, within the other limits: the ones
+/// following `acked`, or all of them if `acked` is `None`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Retention {
+    pub(crate) source_id: EntityGlobalId,
+    pub(crate) acked: Option<SourceSn>,
+}
+
+impl Retention {
+    fn retains(&self, entry: &Entry) -> bool {
+        entry.source_id == Some(self.source_id)
+            && match (self.acked, entry.source_sn) {
+                (Some(acked), Some(sn)) => sn > acked,
+                _ => true,
+            }
+    }
+}
+
+/// The samples of a cache, from the oldest to the newest.
+pub(crate) struct Store {
+    limits: Limits,
+    retention: Option<Retention>,
+    entries: VecDeque<Entry>,
+    size: u64,
+    segments: Option<Segments>,
+}
+
+impl Store {
+    /// Creates a store keeping the samples in memory.
+    pub(crate) fn memory(limits: Limits) -> Self {
+        Store {
+            limits,
+            retention: None,
+            entries: VecDeque::new(),
+            size: 0,
+            segments: None,
+        }
+    }
+
+    /// Creates a store keeping the samples in `directory`, reading the samples it contains.
+    ///
+    /// Fails if the directory is used by another store.
+    pub(crate) fn directory(directory: &Path, limits: Limits) -> ZResult<Self> {
+        if let Err(e) = fs::create_dir_all(directory) {
+            bail!(
+                "Unable to create cache directory {}: {}",
+                directory.display(),
+                e
+            );
+        }
+        let lock = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(directory.join(LOCK_FILE))?;
+        if let Err(e) = AdvisoryFileLock::try_lock(&lock, FileLockMode::Exclusive) {
+            bail!(
+                "Unable to lock cache directory {}, used by another cache: {}",
+                directory.display(),
+                e
+            );
+        }
+        let mut numbers = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION) {
+                if let Some(number) = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| stem.parse::<u64>().ok())
+                {
+                    numbers.push(number);
+                }
+            }
+        }
+        numbers.sort_unstable();
+        let mut entries = VecDeque::new();
+        for number in &numbers {
+            read_segment(&segment_path(directory, *number), *number, &mut entries)?;
+        }
+        let size = entries.iter().map(|entry| entry.size).sum();
+        let segment_size = limits.max_bytes.map_or(MAX_SEGMENT_SIZE, |max_bytes| {
+            (max_bytes / 8).clamp(1, MAX_SEGMENT_SIZE)
+        });
+        // The records waiting to be written are kept in memory within the size of the cache
+        let max_pending = limits.max_bytes.map_or(MAX_PENDING_BYTES, |max_bytes| {
+            max_bytes.min(MAX_PENDING_BYTES)
+        });
+        let first = numbers.first().copied().unwrap_or_default();
+        // The samples are appended to a new segment
+        let current = numbers.last().map_or(0, |last| last + 1);
+        let mut store = Store {
+            limits,
+            retention: None,
+            entries,
+            size,
+            segments: Some(Segments::new(
+                directory,
+                lock,
+                segment_size,
+                max_pending,
+                first,
+                current,
+            )?),
+        };
+        tracing::debug!(
+            "Read {} cached samples from {}",
+            store.entries.len(),
+            directory.display()
+        );
+        store.evict(None);
+        Ok(store)
+    }
+
+    /// Adds a sample, evicting the oldest ones exceeding the limits.
+    pub(crate) fn push(&mut self, sample: Sample) -> ZResult<()> {
+        let time = SystemTime::now();
+        let source_id = sample.source_info().source_id().copied();
+        let source_sn = sample.source_info().source_sn();
+        let timestamp = sample.timestamp().copied();
+        let (size, location) = match self.segments.as_mut() {
+            Some(segments) => {
+                let record = encode(&sample, time).to_bytes().into_owned();
+                let len = record.len() as u32;
+                let (seq, segment, offset) = segments.append(record)?;
+                let location = Location::Writing {
+                    sample: Box::new(sample),
+                    seq,
+                    segment,
+                    offset,
+                    len,
+                };
+                (RECORD_HEADER_SIZE + u64::from(len), location)
+            }
+            None => {
+                let size = sample.payload().len()
+                    + sample.attachment().map_or(0, |attachment| attachment.len());
+                (size as u64, Location::Memory(Box::new(sample)))
+            }
+        };
+        self.size += size;
+        self.entries.push_back(Entry {
+            time,
+            size,
+            source_id,
+            source_sn,
+            timestamp,
+            location,
+        });
+        self.evict(Some(time));
+        Ok(())
+    }
+
+    /// Sets the samples retained regardless of their age, evicting the ones no more retained.
+    pub(crate) fn set_retention(&mut self, retention: Option<Retention>) {
+        if self.retention != retention {
+            self.retention = retention;
+            self.evict(None);
+        }
+    }
+
+    /// Drops the in-memory copy of the samples written by the writer thread.
+    fn release_written(&mut self) {
+        let Some(segments) = self.segments.as_ref() else {
+            return;
+        };
+        let written = segments.written.load(Ordering::Acquire);
+        // The samples being written are the newest ones
+        for entry in self.entries.iter_mut().rev() {
+            match entry.location {
+                Location::Writing {
+                    seq,
+                    segment,
+                    offset,
+                    len,
+                    ..
+                } => {
+                    if seq < written {
+                        entry.location = Location::Disk {
+                            segment,
+                            offset,
+                            len,
+                        };
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Returns whether `entry` is retained regardless of its age.
+    fn retains(&self, entry: &Entry) -> bool {
+        self.retention
+            .is_some_and(|retention| retention.retains(entry))
+    }
+
+    /// Evicts the oldest samples exceeding the limits, the newest one being always kept.
+    fn evict(&mut self, now: Option<SystemTime>) {
+        self.release_written();
+        let now = now.unwrap_or_else(SystemTime::now);
+        while self.entries.len() > 1 {
+            let oldest = self.entries.front().unwrap();
+            let full = self
+                .limits
+                .max_samples
+                .is_some_and(|max| self.entries.len() > max)
+                || self.limits.max_bytes.is_some_and(|max| self.size > max);
+            let expired = self
+                .limits
+                .max_age
+                .is_some_and(|max| now.duration_since(oldest.time).unwrap_or_default() > max);
+            let retained = self.retains(oldest);
+            if !full && (!expired || retained) {
+                break;
+            }
+            if retained {
+                tracing::warn!(
+                    "Cache full: evicting sample {:?} of {:?} before it is acknowledged",
+                    oldest.source_sn,
+                    oldest.source_id
+                );
+            }
+            self.size -= oldest.size;
+            self.entries.pop_front();
+        }
+        if let Some(segments) = self.segments.as_mut() {
+            let first_used = self
+                .entries
+                .front()
+                .and_then(|entry| entry.location.segment())
+                .unwrap_or(segments.current);
+            segments.delete_until(first_used);
+        }
+    }
+
+    /// Returns the last `max` samples matching `selected`, or all of them, from the oldest to
+    /// the newest.
+    ///
+    /// The samples stored in the directory are only read while iterating over the returned
+    /// [`Selection`], so that it can be done without holding the store.
+    pub(crate) fn select<F>(&self, selected: F, max: Option<usize>) -> Selection
+    where
+        F: Fn(Option<SourceSn>, Option<&Timestamp>) -> bool,
+    {
+        let now = SystemTime::now();
+        let mut entries: Vec<Selected> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| {
+                self.limits.max_age.map_or(true, |max| {
+                    now.duration_since(entry.time).unwrap_or_default() <= max
+                }) || self.retains(entry)
+            })
+            .filter(|entry| selected(entry.source_sn, entry.timestamp.as_ref()))
+            .take(max.unwrap_or(usize::MAX))
+            .map(|entry| match &entry.location {
+                Location::Memory(sample) | Location::Writing { sample, .. } => {
+                    Selected::Sample(sample.as_ref().clone())
+                }
+                Location::Disk {
+                    segment,
+                    offset,
+                    len,
+                } => Selected::Disk {
+                    segment: *segment,
+                    offset: *offset,
+                    len: *len,
+                },
+            })
+            .collect();
+        entries.reverse();
+        Selection {
+            directory: self
+                .segments
+                .as_ref()
+                .map(|segments| segments.directory.clone()),
+            entries: entries.into_iter(),
+            file: None,
+        }
+    }
+
+    /// Returns the sequence number of the last sample of `source_id`.
+    pub(crate) fn last_sn(&self, source_id: &EntityGlobalId) -> Option<SourceSn> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.source_id.as_ref() == Some(source_id))
+            .and_then(|entry| entry.source_sn)
+    }
+}
+
+/// A sample selected in a [`Store`].
+enum Selected {
+    Sample(Sample),
+    Disk { segment: u64, offset: u64, len: u32 },
+}
+
+/// The samples selected in a [`Store`], the ones stored in a directory being read one at a
+/// time. The samples evicted in the meantime are skipped.
+pub(crate) struct Selection {
+    directory: Option<PathBuf>,
+    entries: std::vec::IntoIter<Selected>,
+    file: Option<(u64, File)>,
+}
+
+impl Selection {
+    /// Reads a sample from a segment, reusing the file of the previous read if it is the same.
+    fn read(&mut self, segment: u64, offset: u64, len: u32) -> ZResult<Option<Sample>> {
+        let Some(directory) = self.directory.as_ref() else {
+            bail!("Cache is not stored in a directory");
+        };
+        let path = segment_path(directory, segment);
+        if self
+            .file
+            .as_ref()
+            .map_or(true, |(current, _)| *current != segment)
+        {
+            match File::open(&path) {
+                Ok(file) => self.file = Some((segment, file)),
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let (_, file) = self.file.as_mut().unwrap();
+        file.seek(SeekFrom::Start(offset))?;
+        let mut record = vec![0; len as usize];
+        file.read_exact(&mut record)?;
+        match decode(&ZBytes::from(record)) {
+            Ok((_, sample)) => Ok(Some(sample)),
+            Err(_) => bail!("Invalid sample in cache segment {}", path.display()),
+        }
+    }
+}
+
+impl Iterator for Selection {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        loop {
+            match self.entries.next()? {
+                Selected::Sample(sample) => return Some(sample),
+                Selected::Disk {
+                    segment,
+                    offset,
+                    len,
+                } => match self.read(segment, offset, len) {
+                    Ok(Some(sample)) => return Some(sample),
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Unable to read cached sample: {}", e),
+                },
+            }
+        }
+    }
+}
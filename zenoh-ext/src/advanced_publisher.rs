@@ -12,14 +12,17 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use std::{
+    collections::{hash_map::Entry, HashMap},
     future::{IntoFuture, Ready},
     sync::{
         atomic::{AtomicU32, Ordering},
-        Arc,
+        Arc, Mutex,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use async_trait::async_trait;
+
 use zenoh::{
     bytes::{Encoding, OptionZBytes, ZBytes},
     internal::{
@@ -30,22 +33,26 @@ use zenoh::{
         },
         TerminatableTask,
     },
-    key_expr::{keyexpr, KeyExpr},
+    key_expr::{keyexpr, KeyExpr, OwnedKeyExpr},
     liveliness::LivelinessToken,
     pubsub::{
         PublicationBuilder, PublicationBuilderDelete, PublicationBuilderPut, Publisher,
-        PublisherBuilder,
+        PublisherBuilder, Subscriber,
     },
     qos::{CongestionControl, Priority, Reliability},
-    sample::{Locality, SourceInfo},
+    sample::{Locality, Sample, SampleKind, SourceInfo, SourceSn},
     session::EntityGlobalId,
     Resolvable, Resolve, Result as ZResult, Session, Wait, KE_ADV_PREFIX, KE_AT, KE_EMPTY,
+    KE_STARSTAR,
 };
 use zenoh_macros::ke;
+use zenoh_util::{Timed, TimedEvent, Timer};
 
 use crate::{
-    advanced_cache::{AdvancedCache, AdvancedCacheBuilder, CacheConfig, KE_UHLC},
-    z_serialize,
+    advanced_cache::{
+        AdvancedCache, AdvancedCacheBuilder, CacheConfig, Retention, KE_ACK, KE_DURABLE, KE_UHLC,
+    },
+    z_deserialize, z_serialize,
 };
 
 pub(crate) static KE_PUB: &keyexpr = ke!("pub");
@@ -73,6 +80,35 @@ impl MissDetectionConfig {
     }
 }
 
+#[zenoh_macros::unstable]
+const DEFAULT_DURABLE_EXPIRATION: Duration = Duration::from_secs(60 * 60);
+
+/// Configure the acknowledgement of the samples by durable subscribers.
+#[zenoh_macros::unstable]
+pub struct AcknowledgementConfig {
+    pub(crate) expiration: Duration,
+}
+
+#[zenoh_macros::unstable]
+impl Default for AcknowledgementConfig {
+    fn default() -> Self {
+        Self {
+            expiration: DEFAULT_DURABLE_EXPIRATION,
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+impl AcknowledgementConfig {
+    /// Forget the durable subscribers that are not running for longer than the given duration,
+    /// and stop retaining the samples for them (default: 1 hour).
+    #[zenoh_macros::unstable]
+    pub fn expiration(mut self, expiration: Duration) -> Self {
+        self.expiration = expiration;
+        self
+    }
+}
+
 /// The builder of PublicationCache, allowing to configure it.
 #[must_use = "Resolvables do nothing unless you resolve them using the `res` method from either `SyncResolve` or `AsyncResolve`"]
 #[zenoh_macros::unstable]
@@ -85,12 +121,14 @@ pub struct AdvancedPublisherBuilder<'a, 'b, 'c> {
     congestion_control: CongestionControl,
     priority: Priority,
     is_express: bool,
+    announce: bool,
     meta_key_expr: Option<ZResult<KeyExpr<'c>>>,
     sequencing: Sequencing,
     miss_config: Option<MissDetectionConfig>,
     liveliness: bool,
     cache: bool,
     history: CacheConfig,
+    acknowledgement: Option<AcknowledgementConfig>,
 }
 
 #[zenoh_macros::unstable]
@@ -106,12 +144,14 @@ impl<'a, 'b, 'c> AdvancedPublisherBuilder<'a, 'b, 'c> {
             congestion_control: builder.congestion_control,
             priority: builder.priority,
             is_express: builder.is_express,
+            announce: builder.announce,
             meta_key_expr: None,
             sequencing: Sequencing::None,
             miss_config: None,
             liveliness: false,
             cache: false,
             history: CacheConfig::default(),
+            acknowledgement: None,
         }
     }
 
@@ -163,6 +203,29 @@ impl<'a, 'b, 'c> AdvancedPublisherBuilder<'a, 'b, 'c> {
         self
     }
 
+    /// Retain the samples in the cache until they are acknowledged by all the
+    /// [`durable`](crate::AdvancedSubscriberBuilder::durable) [`AdvancedSubscribers`](crate::AdvancedSubscriber).
+    ///
+    /// The durable subscribers are known from their first detection, and the samples are retained
+    /// for them while they are not running, so that they can recover them once restarted, until
+    /// they [expire](AcknowledgementConfig::expiration) or are
+    /// [deregistered](crate::AdvancedSubscriber::deregister). The samples are retained beyond the
+    /// [`max_age`](crate::CacheConfig::max_age) of the cache, but within its
+    /// [`max_samples`](crate::CacheConfig::max_samples) and
+    /// [`max_bytes`](crate::CacheConfig::max_bytes): once exceeded, the oldest samples are evicted
+    /// even if they are not acknowledged, and are reported as missed to the durable subscribers.
+    ///
+    /// The known durable subscribers and their acknowledgements are only kept in memory: after a
+    /// restart of the publisher, the samples are only retained for the durable subscribers detected
+    /// again, from their detection.
+    /// Acknowledgement can only be achieved if [`cache`](crate::AdvancedPublisherBuilder::cache)
+    /// and [`sample_miss_detection`](crate::AdvancedPublisherBuilder::sample_miss_detection) are enabled.
+    #[zenoh_macros::unstable]
+    pub fn acknowledgement(mut self, config: AcknowledgementConfig) -> Self {
+        self.acknowledgement = Some(config);
+        self
+    }
+
     /// Allow this [`AdvancedPublisher`] to be detected by [`AdvancedSubscribers`](crate::AdvancedSubscriber).
     ///
     /// This allows [`AdvancedSubscribers`](crate::AdvancedSubscriber) to retrieve the local history.
@@ -261,6 +324,7 @@ pub struct AdvancedPublisher<'a> {
     cache: Option<AdvancedCache>,
     _token: Option<LivelinessToken>,
     _state_publisher: Option<TerminatableTask>,
+    _acknowledgement: Option<Acknowledgement>,
 }
 
 #[zenoh_macros::unstable]
@@ -282,6 +346,7 @@ impl<'a> AdvancedPublisher<'a> {
             .congestion_control(conf.congestion_control)
             .priority(conf.priority)
             .express(conf.is_express)
+            .announce(conf.announce)
             .wait()?;
         let id = publisher.id();
         let prefix = KE_ADV_PREFIX / KE_PUB / &id.zid().into_keyexpr();
@@ -322,6 +387,33 @@ impl<'a> AdvancedPublisher<'a> {
         } else {
             None
         };
+        // Resume after the samples kept in the cache across restarts
+        if let (Some(seqnum), Some(last_sn)) = (
+            seqnum.as_ref(),
+            cache.as_ref().and_then(|cache| cache.last_sn(&id)),
+        ) {
+            seqnum.store(last_sn.wrapping_add(1), Ordering::Relaxed);
+        }
+
+        let acknowledgement = if let Some(ack_config) = conf.acknowledgement {
+            let (Some(seqnum), Some(cache)) = (seqnum.as_ref(), cache.as_ref()) else {
+                bail!(
+                    "Cannot create AdvancedPublisher {} with acknowledgement: \
+                        cache and sample_miss_detection must be enabled.",
+                    key_expr,
+                )
+            };
+            Some(Acknowledgement::declare(
+                conf.session,
+                &key_expr,
+                id,
+                seqnum.clone(),
+                cache.retainer(),
+                ack_config.expiration,
+            )?)
+        } else {
+            None
+        };
 
         let token = if conf.liveliness {
             Some(
@@ -365,6 +457,7 @@ impl<'a> AdvancedPublisher<'a> {
             cache,
             _token: token,
             _state_publisher: state_publisher,
+            _acknowledgement: acknowledgement,
         })
     }
 
@@ -542,6 +635,207 @@ impl<'a> AdvancedPublisher<'a> {
     }
 }
 
+/// The last sequence number acknowledged by a durable subscriber, if any, and since when it is
+/// not running.
+#[zenoh_macros::unstable]
+struct DurableSubscriber {
+    acked: Option<SourceSn>,
+    lost: Option<Instant>,
+}
+
+#[zenoh_macros::unstable]
+type DurableSubscribers = HashMap<OwnedKeyExpr, DurableSubscriber>;
+
+#[zenoh_macros::unstable]
+type Retain = Arc<dyn Fn(&DurableSubscribers) + Send + Sync>;
+
+/// Returns the name of the durable subscriber declaring `key_expr`.
+#[zenoh_macros::unstable]
+fn durable_name(prefix: &str, key_expr: &KeyExpr<'_>) -> Option<OwnedKeyExpr> {
+    key_expr
+        .as_str()
+        .strip_prefix(prefix)
+        .and_then(|suffix| suffix.split_once("/@/"))
+        .and_then(|(name, _)| OwnedKeyExpr::try_from(name).ok())
+}
+
+/// Forgets a durable subscriber which is still not running once its expiration is reached.
+#[zenoh_macros::unstable]
+struct Expiration {
+    durables: Arc<Mutex<DurableSubscribers>>,
+    retain: Retain,
+    name: OwnedKeyExpr,
+    lost: Instant,
+}
+
+#[zenoh_macros::unstable]
+#[async_trait]
+impl Timed for Expiration {
+    async fn run(&mut self) {
+        let mut durables = self.durables.lock().unwrap();
+        if durables
+            .get(&self.name)
+            .is_some_and(|durable| durable.lost == Some(self.lost))
+        {
+            tracing::debug!("Forget expired durable subscriber {}", self.name);
+            durables.remove(&self.name);
+            (self.retain)(&durables);
+        }
+    }
+}
+
+/// The acknowledgements of the durable subscribers of an [`AdvancedPublisher`].
+#[zenoh_macros::unstable]
+struct Acknowledgement {
+    _durable_subscriber: Subscriber<()>,
+    _deregistration_subscriber: Subscriber<()>,
+    _ack_subscriber: Subscriber<()>,
+    _timer: Timer,
+}
+
+#[zenoh_macros::unstable]
+impl Acknowledgement {
+    fn declare(
+        session: &Session,
+        key_expr: &KeyExpr<'_>,
+        id: EntityGlobalId,
+        seqnum: Arc<AtomicU32>,
+        retain: impl Fn(Option<Retention>) + Send + Sync + 'static,
+        expiration: Duration,
+    ) -> ZResult<Self> {
+        let durables = Arc::new(Mutex::new(DurableSubscribers::new()));
+        let retain: Retain = Arc::new(move |durables: &DurableSubscribers| {
+            retain(
+                durables
+                    .values()
+                    .map(|durable| durable.acked)
+                    .min()
+                    .map(|acked| Retention {
+                        source_id: id,
+                        acked,
+                    }),
+            )
+        });
+        let timer = {
+            let _rt = ZRuntime::Application.enter();
+            Timer::new(false)
+        };
+
+        let durable_prefix = format!("{}/{}/", KE_ADV_PREFIX, KE_DURABLE);
+        let durable_key_expr = KE_ADV_PREFIX / KE_DURABLE / KE_STARSTAR / KE_AT / key_expr;
+        let durable_subscriber = session
+            .liveliness()
+            .declare_subscriber(&durable_key_expr)
+            .history(true)
+            .callback({
+                let durables = durables.clone();
+                let retain = retain.clone();
+                let timer = timer.clone();
+                let durable_prefix = durable_prefix.clone();
+                move |sample: Sample| {
+                    let Some(name) = durable_name(&durable_prefix, sample.key_expr()) else {
+                        return;
+                    };
+                    let mut guard = durables.lock().unwrap();
+                    match (sample.kind(), guard.entry(name)) {
+                        (SampleKind::Put, Entry::Vacant(entry)) => {
+                            tracing::debug!("Detected durable subscriber {}", entry.key());
+                            // The samples published before the detection are not retained for it
+                            entry.insert(DurableSubscriber {
+                                acked: seqnum.load(Ordering::Relaxed).checked_sub(1),
+                                lost: None,
+                            });
+                            retain(&guard);
+                        }
+                        (SampleKind::Put, Entry::Occupied(mut entry)) => {
+                            entry.get_mut().lost = None;
+                        }
+                        (SampleKind::Delete, Entry::Occupied(mut entry)) => {
+                            let lost = Instant::now();
+                            entry.get_mut().lost = Some(lost);
+                            timer.add(TimedEvent::once(
+                                lost + expiration,
+                                Expiration {
+                                    durables: durables.clone(),
+                                    retain: retain.clone(),
+                                    name: entry.key().clone(),
+                                    lost,
+                                },
+                            ));
+                        }
+                        (SampleKind::Delete, Entry::Vacant(_)) => {}
+                    }
+                }
+            })
+            .wait()?;
+
+        let deregistration_subscriber = session
+            .declare_subscriber(&durable_key_expr)
+            .callback({
+                let durables = durables.clone();
+                let retain = retain.clone();
+                move |sample: Sample| {
+                    if sample.kind() != SampleKind::Delete {
+                        return;
+                    }
+                    let Some(name) = durable_name(&durable_prefix, sample.key_expr()) else {
+                        return;
+                    };
+                    let mut durables = durables.lock().unwrap();
+                    if durables.remove(&name).is_some() {
+                        tracing::debug!("Deregistered durable subscriber {}", name);
+                        retain(&durables);
+                    }
+                }
+            })
+            .wait()?;
+
+        let ack_prefix = KE_ADV_PREFIX
+            / KE_ACK
+            / &id.zid().into_keyexpr()
+            / &KeyExpr::try_from(id.eid().to_string())?;
+        let ack_subscriber = session
+            .declare_subscriber(&ack_prefix / KE_STARSTAR)
+            .callback({
+                let ack_prefix = format!("{ack_prefix}/");
+                move |sample: Sample| {
+                    let Some(name) = sample
+                        .key_expr()
+                        .as_str()
+                        .strip_prefix(&ack_prefix)
+                        .and_then(|name| OwnedKeyExpr::try_from(name).ok())
+                    else {
+                        return;
+                    };
+                    let Ok(sn) = z_deserialize::<SourceSn>(sample.payload()) else {
+                        tracing::debug!(
+                            "Skipping invalid acknowledgement on '{}'",
+                            sample.key_expr()
+                        );
+                        return;
+                    };
+                    let mut durables = durables.lock().unwrap();
+                    // The acknowledgements of deregistered subscribers are ignored
+                    let Some(durable) = durables.get_mut(&name) else {
+                        return;
+                    };
+                    if durable.acked.map_or(true, |acked| sn > acked) {
+                        durable.acked = Some(sn);
+                        retain(&durables);
+                    }
+                }
+            })
+            .wait()?;
+
+        Ok(Acknowledgement {
+            _durable_subscriber: durable_subscriber,
+            _deregistration_subscriber: deregistration_subscriber,
+            _ack_subscriber: ack_subscriber,
+            _timer: timer,
+        })
+    }
+}
+
 #[zenoh_macros::unstable]
 pub type AdvancedPublisherPutBuilder<'a> = AdvancedPublicationBuilder<'a, PublicationBuilderPut>;
 #[zenoh_macros::unstable]
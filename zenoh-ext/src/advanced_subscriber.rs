@@ -16,7 +16,7 @@ use std::{collections::BTreeMap, future::IntoFuture, marker::PhantomData, str::F
 use zenoh::{
     config::ZenohId,
     handlers::{Callback, IntoHandler},
-    key_expr::KeyExpr,
+    key_expr::{KeyExpr, OwnedKeyExpr},
     liveliness::{LivelinessSubscriberBuilder, LivelinessToken},
     pubsub::SubscriberBuilder,
     query::{
@@ -27,19 +27,23 @@ use zenoh::{
     Resolvable, Resolve, Session, Wait, KE_ADV_PREFIX, KE_AT, KE_EMPTY, KE_PUB, KE_STAR,
     KE_STARSTAR, KE_SUB,
 };
-use zenoh_util::{Timed, TimedEvent, Timer};
+use zenoh_util::{Timed, TimedEvent, TimedHandle, Timer};
 #[zenoh_macros::unstable]
 use {
     async_trait::async_trait,
     std::collections::hash_map::Entry,
     std::collections::HashMap,
     std::convert::TryFrom,
+    std::fmt::Write as _,
+    std::fs::File,
     std::future::Ready,
-    std::sync::{Arc, Mutex},
+    std::io::Write as _,
+    std::path::{Path, PathBuf},
+    std::sync::{Arc, Mutex, MutexGuard},
     std::time::Duration,
     uhlc::ID,
     zenoh::handlers::{locked, DefaultHandler},
-    zenoh::internal::{runtime::ZRuntime, zlock},
+    zenoh::internal::{bail, runtime::ZRuntime, zlock, ResolveFuture},
     zenoh::pubsub::Subscriber,
     zenoh::query::{QueryTarget, Reply, ReplyKeyExpr},
     zenoh::time::Timestamp,
@@ -47,8 +51,8 @@ use {
 };
 
 use crate::{
-    advanced_cache::{ke_liveliness, KE_UHLC},
-    z_deserialize,
+    advanced_cache::{ke_liveliness, KE_ACK, KE_DURABLE, KE_UHLC},
+    z_deserialize, z_serialize,
 };
 
 #[derive(Debug, Default, Clone)]
@@ -160,6 +164,10 @@ pub struct AdvancedSubscriberBuilder<'a, 'b, 'c, Handler, const BACKGROUND: bool
     pub(crate) history: Option<HistoryConfig>,
     pub(crate) liveliness: bool,
     pub(crate) meta_key_expr: Option<ZResult<KeyExpr<'c>>>,
+    pub(crate) recovery_state: Option<PathBuf>,
+    pub(crate) durable: Option<ZResult<OwnedKeyExpr>>,
+    /// Whether the Samples are committed once the callback handler returns.
+    pub(crate) commit_on_return: bool,
     pub(crate) handler: Handler,
 }
 
@@ -178,6 +186,9 @@ impl<'a, 'b, Handler> AdvancedSubscriberBuilder<'a, 'b, '_, Handler> {
             history: None,
             liveliness: false,
             meta_key_expr: None,
+            recovery_state: None,
+            durable: None,
+            commit_on_return: false,
         }
     }
 }
@@ -191,7 +202,9 @@ impl<'a, 'b, 'c> AdvancedSubscriberBuilder<'a, 'b, 'c, DefaultHandler> {
     where
         F: Fn(Sample) + Send + Sync + 'static,
     {
-        self.with(Callback::new(Arc::new(callback)))
+        let mut builder = self.with(Callback::new(Arc::new(callback)));
+        builder.commit_on_return = true;
+        builder
     }
 
     /// Add callback to `AdvancedSubscriber`.
@@ -227,6 +240,9 @@ impl<'a, 'b, 'c> AdvancedSubscriberBuilder<'a, 'b, 'c, DefaultHandler> {
             history: self.history,
             liveliness: self.liveliness,
             meta_key_expr: self.meta_key_expr,
+            recovery_state: self.recovery_state,
+            durable: self.durable,
+            commit_on_return: false,
             handler,
         }
     }
@@ -248,6 +264,9 @@ impl<'a, 'b, 'c> AdvancedSubscriberBuilder<'a, 'b, 'c, Callback<Sample>> {
             history: self.history,
             liveliness: self.liveliness,
             meta_key_expr: self.meta_key_expr,
+            recovery_state: self.recovery_state,
+            durable: self.durable,
+            commit_on_return: self.commit_on_return,
             handler: self.handler,
         }
     }
@@ -323,6 +342,58 @@ impl<'a, 'c, Handler, const BACKGROUND: bool>
         self
     }
 
+    /// Persist the sequence number of the last Sample [committed](AdvancedSubscriber::commit) by
+    /// the application from each publisher to the given file, and resume from it when the file
+    /// exists.
+    ///
+    /// The Samples published after the committed ones are then queried from the publishers known
+    /// from the file, and the committed ones are not delivered again, so that a restarted
+    /// subscriber recovers the Samples it did not consume, within the
+    /// [`cache`](crate::AdvancedPublisherBuilder::cache) of the publishers: the Samples delivered
+    /// but not committed when the process stops, e.g. still queued in a channel handler, are
+    /// delivered again after a restart.
+    ///
+    /// With a [`callback`](AdvancedSubscriberBuilder::callback), each Sample is committed once
+    /// the callback returns. The other handlers have to commit the Samples they consumed.
+    /// Only the Samples of [`AdvancedPublishers`](crate::AdvancedPublisher) that enable
+    /// [`sample_miss_detection`](crate::AdvancedPublisherBuilder::sample_miss_detection) are tracked.
+    #[zenoh_macros::unstable]
+    pub fn recovery_state_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.recovery_state = Some(path.into());
+        self
+    }
+
+    /// Make this subscriber durable under the given name, acknowledging the delivered Samples
+    /// to the [`AdvancedPublishers`](crate::AdvancedPublisher) that enable
+    /// [`acknowledgement`](crate::AdvancedPublisherBuilder::acknowledgement).
+    ///
+    /// Those publishers retain the Samples until they are acknowledged by all the durable
+    /// subscribers, including the ones which are not running, which are identified by their name
+    /// across restarts, until they [expire](crate::AcknowledgementConfig::expiration) or are
+    /// [deregistered](AdvancedSubscriber::deregister).
+    ///
+    /// A durable subscriber requires [`recovery`](AdvancedSubscriberBuilder::recovery) and a
+    /// [`recovery_state_file`](AdvancedSubscriberBuilder::recovery_state_file), and passes each
+    /// Sample to its handler exactly once: the sequence number of a Sample is
+    /// [committed](AdvancedSubscriber::commit) before the Sample is passed to the handler, the
+    /// Samples whose sequence number is committed are dropped, and the missed ones are recovered,
+    /// after a restart too. The Samples are acknowledged once committed.
+    ///
+    /// A Sample is not passed to the handler if its sequence number can't be committed, and is
+    /// recovered after a restart. A Sample passed to the handler is never passed again, even if
+    /// the process stops before the application consumed it, e.g. while it is queued in a channel
+    /// handler. The Samples evicted from the cache of a publisher before they are recovered are
+    /// reported as missed.
+    #[zenoh_macros::unstable]
+    pub fn durable<TryIntoKeyExpr>(mut self, name: TryIntoKeyExpr) -> Self
+    where
+        TryIntoKeyExpr: TryInto<OwnedKeyExpr>,
+        <TryIntoKeyExpr as TryInto<OwnedKeyExpr>>::Error: Into<zenoh::Error>,
+    {
+        self.durable = Some(name.try_into().map_err(Into::into));
+        self
+    }
+
     #[zenoh_macros::unstable]
     fn with_static_keys(self) -> AdvancedSubscriberBuilder<'a, 'static, 'static, Handler> {
         AdvancedSubscriberBuilder {
@@ -335,6 +406,9 @@ impl<'a, 'c, Handler, const BACKGROUND: bool>
             history: self.history,
             liveliness: self.liveliness,
             meta_key_expr: self.meta_key_expr.map(|s| s.map(|s| s.into_owned())),
+            recovery_state: self.recovery_state,
+            durable: self.durable,
+            commit_on_return: self.commit_on_return,
             handler: self.handler,
         }
     }
@@ -390,6 +464,10 @@ impl Wait for AdvancedSubscriberBuilder<'_, '_, '_, Callback<Sample>, true> {
         if let Some(mut liveliness_sub) = sub.liveliness_subscriber.take() {
             liveliness_sub.set_background(true);
         }
+        // Keep acknowledging until the session is closed
+        if let Some(checkpoint) = sub.checkpoint.as_mut() {
+            checkpoint.handle = None;
+        }
         Ok(())
     }
 }
@@ -427,6 +505,9 @@ struct State {
     callback: Callback<Sample>,
     miss_handlers: HashMap<usize, Callback<Miss>>,
     token: Option<LivelinessToken>,
+    recovery_state: Option<Arc<RecoveryState>>,
+    durable: Option<Durable>,
+    checkpoint: Option<Timer>,
 }
 
 #[zenoh_macros::unstable]
@@ -458,11 +539,183 @@ macro_rules! spawn_periodoic_queries {
     }};
 }
 
+#[zenoh_macros::unstable]
+const CHECKPOINT_PERIOD: Duration = Duration::from_secs(1);
+
+/// The file where the last committed sequence numbers are persisted.
+#[zenoh_macros::unstable]
+struct RecoveryState {
+    path: PathBuf,
+    /// The persisted sequence numbers, whose lock serializes the writes of the file.
+    committed: Mutex<HashMap<EntityGlobalId, SourceSn>>,
+}
+
+#[zenoh_macros::unstable]
+impl RecoveryState {
+    /// Persists the sequence number `sn` as the last committed one of `source_id`, unless a
+    /// greater one is already persisted.
+    fn commit(&self, source_id: EntityGlobalId, sn: SourceSn) -> ZResult<()> {
+        let mut committed = zlock!(self.committed);
+        if committed.get(&source_id).is_some_and(|last| *last >= sn) {
+            return Ok(());
+        }
+        let mut content = String::new();
+        for (id, sn) in committed.iter().filter(|(id, _)| **id != source_id) {
+            let _ = writeln!(content, "{} {} {}", id.zid(), id.eid(), sn);
+        }
+        let _ = writeln!(content, "{} {} {}", source_id.zid(), source_id.eid(), sn);
+        if let Err(e) = write_recovery_state(&self.path, &content) {
+            bail!(
+                "Unable to write recovery state {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+        committed.insert(source_id, sn);
+        Ok(())
+    }
+}
+
+/// The name of a durable subscriber and the sequence numbers it acknowledged.
+#[zenoh_macros::unstable]
+struct Durable {
+    name: OwnedKeyExpr,
+    acked: HashMap<EntityGlobalId, SourceSn>,
+    _token: LivelinessToken,
+}
+
+/// Reads the last committed sequence numbers persisted in `path`, if it exists.
+///
+/// The file contains a `<zid> <eid> <sn>` line per publisher.
+#[zenoh_macros::unstable]
+fn load_recovery_state(path: &Path) -> ZResult<HashMap<EntityGlobalId, SourceSn>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => bail!("Unable to read recovery state {}: {}", path.display(), e),
+    };
+    let mut sources = HashMap::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let mut fields = line.split_whitespace();
+        let source = match (fields.next(), fields.next(), fields.next(), fields.next()) {
+            (Some(zid), Some(eid), Some(sn), None) => ZenohId::from_str(zid)
+                .ok()
+                .zip(EntityId::from_str(eid).ok())
+                .zip(SourceSn::from_str(sn).ok()),
+            _ => None,
+        };
+        match source {
+            Some(((zid, eid), sn)) => {
+                sources.insert(EntityGlobalId::new(zid, eid), sn);
+            }
+            None => tracing::warn!(
+                "Ignoring malformed line of recovery state {}: {}",
+                path.display(),
+                line
+            ),
+        }
+    }
+    Ok(sources)
+}
+
+/// Replaces the file at `path` by `content`, synchronized to the disk.
+#[zenoh_macros::unstable]
+fn write_recovery_state(path: &Path, content: &str) -> std::io::Result<()> {
+    // Written to a temporary file first, not to leave a truncated file if interrupted
+    let mut tmp = path.to_path_buf().into_os_string();
+    tmp.push(".tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)?;
+    // The rename itself is only durable once the directory is synchronized
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Acknowledges the committed sequence numbers, or the delivered ones if the recovery state is
+/// not persisted.
+#[zenoh_macros::unstable]
+fn checkpoint(statesref: &Arc<Mutex<State>>) {
+    let mut lock = zlock!(statesref);
+    let states = &mut *lock;
+    let Some(durable) = states.durable.as_mut() else {
+        return;
+    };
+    let delivered: HashMap<EntityGlobalId, SourceSn> = match states.recovery_state.as_ref() {
+        Some(recovery_state) => zlock!(recovery_state.committed).clone(),
+        None => states
+            .sequenced_states
+            .iter()
+            .filter_map(|(source_id, state)| state.last_delivered.map(|sn| (*source_id, sn)))
+            .collect(),
+    };
+    let acks: Vec<(EntityGlobalId, SourceSn)> = delivered
+        .into_iter()
+        .filter(|(source_id, sn)| durable.acked.get(source_id) != Some(sn))
+        .collect();
+    durable.acked.extend(acks.iter().copied());
+    let session = states.session.clone();
+    let name = durable.name.clone();
+    drop(lock);
+    for (source_id, sn) in acks {
+        let ack_expr = KE_ADV_PREFIX
+            / KE_ACK
+            / &source_id.zid().into_keyexpr()
+            / &KeyExpr::try_from(source_id.eid().to_string()).unwrap()
+            / &name;
+        if let Err(e) = session.put(ack_expr, z_serialize(&sn)).wait() {
+            tracing::warn!("Unable to acknowledge samples of {:?}: {}", source_id, e);
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+#[derive(Clone)]
+struct Checkpoint {
+    statesref: Arc<Mutex<State>>,
+}
+
+#[zenoh_macros::unstable]
+#[async_trait]
+impl Timed for Checkpoint {
+    async fn run(&mut self) {
+        checkpoint(&self.statesref);
+    }
+}
+
+/// Checkpoints a last time when the [`AdvancedSubscriber`] is dropped.
+#[zenoh_macros::unstable]
+struct CheckpointGuard {
+    statesref: Arc<Mutex<State>>,
+    handle: Option<TimedHandle>,
+}
+
+#[zenoh_macros::unstable]
+impl Drop for CheckpointGuard {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.defuse();
+        }
+        checkpoint(&self.statesref);
+    }
+}
+
 #[zenoh_macros::unstable]
 struct SourceState<T> {
     last_delivered: Option<T>,
     pending_queries: u64,
     pending_samples: BTreeMap<T, Sample>,
+    /// Whether the samples missed while the subscriber was not running are being recovered,
+    /// for a source known from the recovery state file.
+    recovering: bool,
 }
 
 /// [`AdvancedSubscriber`].
@@ -473,6 +726,8 @@ pub struct AdvancedSubscriber<Receiver> {
     receiver: Receiver,
     liveliness_subscriber: Option<Subscriber<()>>,
     _heartbeat_subscriber: Option<Subscriber<()>>,
+    checkpoint: Option<CheckpointGuard>,
+    recovery_state: Option<Arc<RecoveryState>>,
 }
 
 #[zenoh_macros::unstable]
@@ -518,6 +773,7 @@ fn handle_sample(states: &mut State, sample: Sample) -> bool {
             last_delivered: None,
             pending_queries: 0,
             pending_samples: BTreeMap::new(),
+            recovering: false,
         });
         if state.last_delivered.is_none() && states.global_pending_queries != 0 {
             // Avoid going through the Map if history_depth == 1
@@ -534,7 +790,7 @@ fn handle_sample(states: &mut State, sample: Sample) -> bool {
             }
         } else if state.last_delivered.is_some() && source_sn != state.last_delivered.unwrap() + 1 {
             if source_sn > state.last_delivered.unwrap() {
-                if states.retransmission {
+                if states.retransmission || state.recovering {
                     state.pending_samples.insert(source_sn, sample);
                 } else {
                     tracing::info!(
@@ -562,6 +818,7 @@ fn handle_sample(states: &mut State, sample: Sample) -> bool {
             last_delivered: None,
             pending_queries: 0,
             pending_samples: BTreeMap::new(),
+            recovering: false,
         });
         if state.last_delivered.map(|t| t < *timestamp).unwrap_or(true) {
             if (states.global_pending_queries == 0 && state.pending_queries == 0)
@@ -605,48 +862,62 @@ struct PeriodicQuery {
 impl Timed for PeriodicQuery {
     async fn run(&mut self) {
         let mut lock = zlock!(self.statesref);
-        let states = &mut *lock;
-        if let Some(state) = states.sequenced_states.get_mut(&self.source_id) {
+        if let Some(state) = lock.sequenced_states.get_mut(&self.source_id) {
             state.pending_queries += 1;
-            let query_expr = KE_ADV_PREFIX
-                / KE_STAR
-                / &self.source_id.zid().into_keyexpr()
-                / &KeyExpr::try_from(self.source_id.eid().to_string()).unwrap()
-                / KE_STARSTAR
-                / KE_AT
-                / &states.key_expr;
-            let seq_num_range = seq_num_range(state.last_delivered.map(|s| s + 1), None);
-
-            let session = states.session.clone();
-            let key_expr = states.key_expr.clone().into_owned();
-            let query_target = states.query_target;
-            let query_timeout = states.query_timeout;
-            drop(lock);
-            let handler = SequencedRepliesHandler {
-                source_id: self.source_id,
-                statesref: self.statesref.clone(),
-            };
-            let _ = session
-                .get(Selector::from((query_expr, seq_num_range)))
-                .callback({
-                    move |r: Reply| {
-                        if let Ok(s) = r.into_result() {
-                            if key_expr.intersects(s.key_expr()) {
-                                let states = &mut *zlock!(handler.statesref);
-                                handle_sample(states, s);
-                            }
-                        }
-                    }
-                })
-                .consolidation(ConsolidationMode::None)
-                .accept_replies(ReplyKeyExpr::Any)
-                .target(query_target)
-                .timeout(query_timeout)
-                .wait();
+            query_source(lock, &self.statesref, self.source_id);
         }
     }
 }
 
+/// Queries the samples of a source following the last delivered one.
+///
+/// The query must already be counted in the pending queries of the source.
+#[zenoh_macros::unstable]
+fn query_source(
+    lock: MutexGuard<'_, State>,
+    statesref: &Arc<Mutex<State>>,
+    source_id: EntityGlobalId,
+) {
+    let Some(state) = lock.sequenced_states.get(&source_id) else {
+        return;
+    };
+    let query_expr = KE_ADV_PREFIX
+        / KE_STAR
+        / &source_id.zid().into_keyexpr()
+        / &KeyExpr::try_from(source_id.eid().to_string()).unwrap()
+        / KE_STARSTAR
+        / KE_AT
+        / &lock.key_expr;
+    let seq_num_range = seq_num_range(state.last_delivered.map(|s| s + 1), None);
+
+    let session = lock.session.clone();
+    let key_expr = lock.key_expr.clone().into_owned();
+    let query_target = lock.query_target;
+    let query_timeout = lock.query_timeout;
+    drop(lock);
+    let handler = SequencedRepliesHandler {
+        source_id,
+        statesref: statesref.clone(),
+    };
+    let _ = session
+        .get(Selector::from((query_expr, seq_num_range)))
+        .callback({
+            move |r: Reply| {
+                if let Ok(s) = r.into_result() {
+                    if key_expr.intersects(s.key_expr()) {
+                        let states = &mut *zlock!(handler.statesref);
+                        handle_sample(states, s);
+                    }
+                }
+            }
+        })
+        .consolidation(ConsolidationMode::None)
+        .accept_replies(ReplyKeyExpr::Any)
+        .target(query_target)
+        .timeout(query_timeout)
+        .wait();
+}
+
 #[zenoh_macros::unstable]
 impl<Handler> AdvancedSubscriber<Handler> {
     fn new<H>(conf: AdvancedSubscriberBuilder<'_, '_, '_, H>) -> ZResult<Self>
@@ -663,9 +934,85 @@ impl<Handler> AdvancedSubscriber<Handler> {
         let query_target = conf.query_target;
         let query_timeout = conf.query_timeout;
         let session = conf.session.clone();
+        let durable = match conf.durable {
+            Some(name) => {
+                let name = name?;
+                if name.is_wild() || name.split('/').any(|chunk| chunk.starts_with('@')) {
+                    bail!("Invalid durable subscriber name: {}", name);
+                }
+                if retransmission.is_none() || conf.recovery_state.is_none() {
+                    bail!(
+                        "Durable subscriber {} requires recovery and a recovery state file",
+                        name
+                    );
+                }
+                Some(name)
+            }
+            None => None,
+        };
+        let recovered = match conf.recovery_state.as_ref() {
+            Some(path) => load_recovery_state(path)?,
+            None => HashMap::new(),
+        };
+        let recovery_state = conf.recovery_state.map(|path| {
+            Arc::new(RecoveryState {
+                path,
+                committed: Mutex::new(recovered.clone()),
+            })
+        });
+        let callback = match recovery_state.as_ref() {
+            // Committed before being passed to the handler, for the Sample to be passed once
+            Some(recovery_state) if durable.is_some() => {
+                let recovery_state = recovery_state.clone();
+                Callback::new(Arc::new(move |sample: Sample| {
+                    if let (Some(source_id), Some(sn)) = (
+                        sample.source_info().source_id(),
+                        sample.source_info().source_sn(),
+                    ) {
+                        if let Err(e) = recovery_state.commit(*source_id, sn) {
+                            tracing::error!(
+                                "Sample {} of {:?} not delivered: {}",
+                                sn,
+                                source_id,
+                                e
+                            );
+                            return;
+                        }
+                    }
+                    callback.call(sample);
+                }))
+            }
+            Some(recovery_state) if conf.commit_on_return => {
+                let recovery_state = recovery_state.clone();
+                Callback::new(Arc::new(move |sample: Sample| {
+                    let source_id = sample.source_info().source_id().copied();
+                    let source_sn = sample.source_info().source_sn();
+                    callback.call(sample);
+                    if let (Some(source_id), Some(sn)) = (source_id, source_sn) {
+                        if let Err(e) = recovery_state.commit(source_id, sn) {
+                            tracing::warn!("{}", e);
+                        }
+                    }
+                }))
+            }
+            _ => callback,
+        };
+        // The samples missed by the known sources are queried once subscribed
+        let sequenced_states = recovered
+            .iter()
+            .map(|(source_id, sn)| {
+                let state = SourceState::<u32> {
+                    last_delivered: Some(*sn),
+                    pending_queries: 1,
+                    pending_samples: BTreeMap::new(),
+                    recovering: true,
+                };
+                (*source_id, state)
+            })
+            .collect();
         let statesref = Arc::new(Mutex::new(State {
             next_id: 0,
-            sequenced_states: HashMap::new(),
+            sequenced_states,
             timestamped_states: HashMap::new(),
             global_pending_queries: if conf.history.is_some() { 1 } else { 0 },
             session,
@@ -688,6 +1035,12 @@ impl<Handler> AdvancedSubscriber<Handler> {
             callback: callback.clone(),
             miss_handlers: HashMap::new(),
             token: None,
+            checkpoint: durable.is_some().then(|| {
+                let _rt = ZRuntime::Application.enter();
+                Timer::new(false)
+            }),
+            recovery_state: recovery_state.clone(),
+            durable: None,
         }));
 
         let sub_callback = {
@@ -757,6 +1110,37 @@ impl<Handler> AdvancedSubscriber<Handler> {
             .allowed_origin(conf.origin)
             .wait()?;
 
+        for source_id in recovered.keys() {
+            query_source(zlock!(statesref), &statesref, *source_id);
+            spawn_periodoic_queries!(zlock!(statesref), *source_id, statesref.clone());
+        }
+        if let Some(name) = durable {
+            let token = conf
+                .session
+                .liveliness()
+                .declare_token(KE_ADV_PREFIX / KE_DURABLE / &name / KE_AT / &key_expr)
+                .wait()?;
+            zlock!(statesref).durable = Some(Durable {
+                name,
+                acked: HashMap::new(),
+                _token: token,
+            });
+        }
+        let checkpoint = zlock!(statesref).checkpoint.as_ref().map(|timer| {
+            let event = TimedEvent::periodic(
+                CHECKPOINT_PERIOD,
+                Checkpoint {
+                    statesref: statesref.clone(),
+                },
+            );
+            let handle = event.get_handle();
+            timer.add(event);
+            CheckpointGuard {
+                statesref: statesref.clone(),
+                handle: Some(handle),
+            }
+        });
+
         if let Some(historyconf) = conf.history.as_ref() {
             let handler = InitialRepliesHandler {
                 statesref: statesref.clone(),
@@ -816,6 +1200,7 @@ impl<Handler> AdvancedSubscriber<Handler> {
                                             last_delivered: None,
                                             pending_queries: 0,
                                             pending_samples: BTreeMap::new(),
+                                            recovering: false,
                                         });
                                         state.pending_queries += 1;
                                         drop(lock);
@@ -868,6 +1253,7 @@ impl<Handler> AdvancedSubscriber<Handler> {
                                             last_delivered: None,
                                             pending_queries: 0,
                                             pending_samples: BTreeMap::new(),
+                                            recovering: false,
                                         });
                                         state.pending_queries += 1;
                                         drop(lock);
@@ -1033,6 +1419,7 @@ impl<Handler> AdvancedSubscriber<Handler> {
                         last_delivered: None,
                         pending_queries: 0,
                         pending_samples: BTreeMap::new(),
+                        recovering: false,
                     });
 
                     // check that it's not an old sn, and that there are no pending queries
@@ -1106,6 +1493,8 @@ impl<Handler> AdvancedSubscriber<Handler> {
             receiver,
             liveliness_subscriber,
             _heartbeat_subscriber: heartbeat_subscriber,
+            checkpoint,
+            recovery_state,
         };
 
         Ok(reliable_subscriber)
@@ -1139,6 +1528,31 @@ impl<Handler> AdvancedSubscriber<Handler> {
         &mut self.receiver
     }
 
+    /// Commits the given Sample, and the Samples delivered before it from the same publisher, to
+    /// the [`recovery_state_file`](AdvancedSubscriberBuilder::recovery_state_file): they are not
+    /// delivered again after a restart.
+    ///
+    /// The file is written before this function returns: the Samples may be committed in
+    /// batches, e.g. by committing the last one of the batch. Committing a Sample that is not
+    /// tracked (see [`recovery_state_file`](AdvancedSubscriberBuilder::recovery_state_file)) has
+    /// no effect.
+    #[zenoh_macros::unstable]
+    pub fn commit(&self, sample: &Sample) -> ZResult<()> {
+        let Some(recovery_state) = self.recovery_state.as_ref() else {
+            bail!(
+                "AdvancedSubscriber {} has no recovery state file",
+                self.subscriber.key_expr()
+            );
+        };
+        match (
+            sample.source_info().source_id(),
+            sample.source_info().source_sn(),
+        ) {
+            (Some(source_id), Some(sn)) => recovery_state.commit(*source_id, sn),
+            _ => Ok(()),
+        }
+    }
+
     /// Declares a listener to detect missed samples.
     ///
     /// Missed samples can only be detected from [`AdvancedPublisher`](crate::AdvancedPublisher) that
@@ -1168,6 +1582,32 @@ impl<Handler> AdvancedSubscriber<Handler> {
     pub fn undeclare(self) -> impl Resolve<ZResult<()>> {
         self.subscriber.undeclare()
     }
+
+    /// Undeclares this [`durable`](AdvancedSubscriberBuilder::durable) AdvancedSubscriber and
+    /// deregisters it from the [`AdvancedPublishers`](crate::AdvancedPublisher), which stop
+    /// retaining the Samples for it.
+    #[zenoh_macros::unstable]
+    pub fn deregister(mut self) -> impl Resolve<ZResult<()>> {
+        ResolveFuture::new(async move {
+            let (session, durable_key_expr) = {
+                let states = zlock!(self.statesref);
+                let Some(durable) = states.durable.as_ref() else {
+                    bail!(
+                        "AdvancedSubscriber {} is not durable",
+                        self.subscriber.key_expr()
+                    );
+                };
+                (
+                    states.session.clone(),
+                    KE_ADV_PREFIX / KE_DURABLE / &durable.name / KE_AT / self.subscriber.key_expr(),
+                )
+            };
+            // The last acknowledgements are sent before the deregistration
+            drop(self.checkpoint.take());
+            self.subscriber.undeclare().await?;
+            session.delete(durable_key_expr).await
+        })
+    }
 }
 
 #[zenoh_macros::unstable]
@@ -1270,6 +1710,9 @@ impl Drop for SequencedRepliesHandler {
         let states = &mut *zlock!(self.statesref);
         if let Some(state) = states.sequenced_states.get_mut(&self.source_id) {
             state.pending_queries = state.pending_queries.saturating_sub(1);
+            if state.pending_queries == 0 {
+                state.recovering = false;
+            }
             if states.global_pending_queries == 0 {
                 flush_sequenced_source(
                     state,
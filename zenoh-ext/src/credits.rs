@@ -0,0 +1,380 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Flow control of the replies of a query.
+//!
+//! The querier grants credits to the queryable, which sends a reply per credit: the querier
+//! grants `window` credits with the query, in its `_window` parameter, then grants the credits of
+//! the replies it has consumed on `zenoh/ext/net/credits/<stream>`, `<stream>` being given by the
+//! `_stream` parameter of the query.
+//!
+//! The credits are granted to all the queryables answering the query: each of them may send
+//! `window` replies ahead of the ones consumed by the querier, and is granted the credits of the
+//! replies of the others.
+use std::{
+    future::{IntoFuture, Ready},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    handlers::FifoChannelHandler,
+    internal::bail,
+    key_expr::KeyExpr,
+    pubsub::{Publisher, Subscriber},
+    qos::CongestionControl,
+    query::{Parameters, Querier, Query, Reply},
+    sample::Sample,
+    Error, Resolvable, Result as ZResult, Session, Wait,
+};
+
+use crate::{z_deserialize, z_serialize};
+
+const CREDITS_PREFIX: &str = "zenoh/ext/net/credits";
+const STREAM_PARAMETER: &str = "_stream";
+const WINDOW_PARAMETER: &str = "_window";
+const DEFAULT_CREDIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Distinguishes the streams of a same session.
+static STREAM_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The credits granted by the querier to a [`CreditedQuery`].
+struct Credits {
+    available: u64,
+    subscriber: Subscriber<FifoChannelHandler<Sample>>,
+}
+
+/// A [`Query`] whose replies are sent as the querier grants credits, if it was sent as
+/// [`CreditedReplies`].
+///
+/// The queries sent without flow control are replied without waiting.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use zenoh_ext::CreditedQuery;
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let queryable = session.declare_queryable("storage/export").await.unwrap();
+/// while let Ok(query) = queryable.recv_async().await {
+///     let mut query = CreditedQuery::new(&session, query).await.unwrap();
+///     for i in 0..1_000_000u64 {
+///         // Waits for the querier to consume the previous replies
+///         query.reply("storage/export", i.to_string()).await.unwrap();
+///     }
+/// }
+/// # }
+/// ```
+#[zenoh_macros::unstable]
+pub struct CreditedQuery {
+    query: Query,
+    credits: Option<Credits>,
+    credit_timeout: Duration,
+}
+
+impl CreditedQuery {
+    /// Wraps a [`Query`], subscribing to the credits granted by the querier.
+    pub async fn new(session: &Session, query: Query) -> ZResult<Self> {
+        let parameters = query.parameters();
+        let credits = match (
+            parameters.get(STREAM_PARAMETER),
+            parameters.get(WINDOW_PARAMETER),
+        ) {
+            (Some(stream), Some(window)) => {
+                let Ok(available) = window.parse() else {
+                    bail!("Invalid {} parameter: {}", WINDOW_PARAMETER, window);
+                };
+                let subscriber = session
+                    .declare_subscriber(format!("{CREDITS_PREFIX}/{stream}"))
+                    .await?;
+                Some(Credits {
+                    available,
+                    subscriber,
+                })
+            }
+            _ => None,
+        };
+        Ok(Self {
+            query,
+            credits,
+            credit_timeout: DEFAULT_CREDIT_TIMEOUT,
+        })
+    }
+
+    /// Sets the maximum duration to wait for the querier to grant a credit, 10 seconds by
+    /// default, after which the reply fails.
+    pub fn credit_timeout(mut self, timeout: Duration) -> Self {
+        self.credit_timeout = timeout;
+        self
+    }
+
+    /// Returns the wrapped [`Query`].
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// Returns the number of replies that can be sent without waiting, or `None` if the query
+    /// is not flow controlled.
+    pub fn available(&self) -> Option<u64> {
+        self.credits.as_ref().map(|credits| credits.available)
+    }
+
+    /// Waits for a credit and consumes it, failing if none is granted within the credit timeout.
+    async fn acquire(&mut self) -> ZResult<()> {
+        let Some(credits) = &mut self.credits else {
+            return Ok(());
+        };
+        while credits.available == 0 {
+            let Ok(sample) =
+                tokio::time::timeout(self.credit_timeout, credits.subscriber.recv_async()).await
+            else {
+                bail!(
+                    "No credit granted by the querier within {:?}",
+                    self.credit_timeout
+                );
+            };
+            let sample = sample?;
+            match z_deserialize::<u64>(sample.payload()) {
+                Ok(granted) => credits.available += granted,
+                Err(_) => tracing::warn!("Invalid credits received on {}", sample.key_expr()),
+            }
+        }
+        credits.available -= 1;
+        Ok(())
+    }
+
+    /// Sends a reply, waiting for a credit.
+    pub async fn reply<'b, TryIntoKeyExpr, IntoZBytes>(
+        &mut self,
+        key_expr: TryIntoKeyExpr,
+        payload: IntoZBytes,
+    ) -> ZResult<()>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<Error>,
+        IntoZBytes: Into<ZBytes>,
+    {
+        self.reply_with_encoding(key_expr, payload, Encoding::default())
+            .await
+    }
+
+    pub(crate) async fn reply_with_encoding<'b, TryIntoKeyExpr, IntoZBytes>(
+        &mut self,
+        key_expr: TryIntoKeyExpr,
+        payload: IntoZBytes,
+        encoding: Encoding,
+    ) -> ZResult<()>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<Error>,
+        IntoZBytes: Into<ZBytes>,
+    {
+        self.acquire().await?;
+        self.query.reply(key_expr, payload).encoding(encoding).await
+    }
+
+    /// Sends a sample as reply, waiting for a credit.
+    pub async fn reply_sample(&mut self, sample: Sample) -> ZResult<()> {
+        self.acquire().await?;
+        self.query.reply_sample(sample).await
+    }
+
+    /// Sends an error reply, without waiting for a credit.
+    pub async fn reply_err<IntoZBytes>(&self, payload: IntoZBytes) -> ZResult<()>
+    where
+        IntoZBytes: Into<ZBytes>,
+    {
+        self.query.reply_err(payload).await
+    }
+
+    pub(crate) async fn reply_err_with_encoding<IntoZBytes>(
+        &self,
+        payload: IntoZBytes,
+        encoding: Encoding,
+    ) -> ZResult<()>
+    where
+        IntoZBytes: Into<ZBytes>,
+    {
+        self.query.reply_err(payload).encoding(encoding).await
+    }
+}
+
+/// A builder for initializing [`CreditedReplies`], returned by [`CreditedReplies::get`].
+#[zenoh_macros::unstable]
+#[must_use = "Resolvables do nothing unless you resolve them using `.await` or `zenoh::Wait::wait`"]
+pub struct CreditedGetBuilder<'a, 'b> {
+    session: &'a Session,
+    querier: &'a Querier<'b>,
+    window: u64,
+    payload: Option<ZBytes>,
+    encoding: Option<Encoding>,
+    parameters: Parameters<'static>,
+}
+
+impl CreditedGetBuilder<'_, '_> {
+    /// Sets the payload of the query.
+    pub fn payload<IntoZBytes: Into<ZBytes>>(mut self, payload: IntoZBytes) -> Self {
+        self.payload = Some(payload.into());
+        self
+    }
+
+    /// Sets the encoding of the payload of the query.
+    pub fn encoding<T: Into<Encoding>>(mut self, encoding: T) -> Self {
+        self.encoding = Some(encoding.into());
+        self
+    }
+
+    /// Sets the parameters of the query, to which the flow control parameters are added.
+    pub fn parameters<P: Into<Parameters<'static>>>(mut self, parameters: P) -> Self {
+        self.parameters = parameters.into();
+        self
+    }
+}
+
+impl Resolvable for CreditedGetBuilder<'_, '_> {
+    type To = ZResult<CreditedReplies>;
+}
+
+impl Wait for CreditedGetBuilder<'_, '_> {
+    fn wait(self) -> <Self as Resolvable>::To {
+        if self.window == 0 {
+            bail!("The window of a flow controlled query must be at least 1");
+        }
+        let stream = format!(
+            "{}/{}",
+            self.session.zid(),
+            STREAM_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let publisher = self
+            .session
+            .declare_publisher(format!("{CREDITS_PREFIX}/{stream}"))
+            .congestion_control(CongestionControl::Block)
+            .wait()?;
+        let mut parameters = self.parameters;
+        parameters.insert(STREAM_PARAMETER, stream);
+        parameters.insert(WINDOW_PARAMETER, self.window.to_string());
+        // The replies are bounded by the window
+        let mut get = self
+            .querier
+            .get()
+            .parameters(parameters)
+            .with(flume::unbounded());
+        if let Some(payload) = self.payload {
+            get = get.payload(payload);
+        }
+        if let Some(encoding) = self.encoding {
+            get = get.encoding(encoding);
+        }
+        Ok(CreditedReplies {
+            replies: get.wait()?,
+            publisher,
+            window: self.window,
+            consumed: 0,
+        })
+    }
+}
+
+impl IntoFuture for CreditedGetBuilder<'_, '_> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
+
+/// The replies of a query sent by a [`Querier`] with flow control: each queryable, replying with
+/// a [`CreditedQuery`], sends at most `window` replies ahead of the ones received with
+/// [`CreditedReplies::recv_async`].
+///
+/// The credits being granted to all the queryables, the replies received ahead are only bounded
+/// by `window` if a single queryable answers the query, and by `window` times the number of
+/// queryables otherwise.
+///
+/// The querier must be declared with [`ConsolidationMode::None`](zenoh::query::ConsolidationMode::None),
+/// as the consolidated replies are only received once the queryables have sent all their replies.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use zenoh::query::ConsolidationMode;
+/// use zenoh_ext::CreditedReplies;
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let querier = session
+///     .declare_querier("storage/export")
+///     .consolidation(ConsolidationMode::None)
+///     .await
+///     .unwrap();
+/// let mut replies = CreditedReplies::get(&session, &querier, 64).await.unwrap();
+/// while let Ok(reply) = replies.recv_async().await {
+///     println!("{:?}", reply.result());
+/// }
+/// # }
+/// ```
+#[zenoh_macros::unstable]
+pub struct CreditedReplies {
+    replies: flume::Receiver<Reply>,
+    publisher: Publisher<'static>,
+    window: u64,
+    consumed: u64,
+}
+
+impl CreditedReplies {
+    /// Sends a query with `querier`, granting `window` credits to the queryables, and the credits
+    /// of the replies as they are received.
+    pub fn get<'a, 'b>(
+        session: &'a Session,
+        querier: &'a Querier<'b>,
+        window: u64,
+    ) -> CreditedGetBuilder<'a, 'b> {
+        CreditedGetBuilder {
+            session,
+            querier,
+            window,
+            payload: None,
+            encoding: None,
+            parameters: Parameters::empty(),
+        }
+    }
+
+    /// Receives the next reply, waiting for it, and grants its credit.
+    ///
+    /// Returns an error when all the replies have been received.
+    pub async fn recv_async(&mut self) -> ZResult<Reply> {
+        let reply = self.replies.recv_async().await?;
+        self.consumed += 1;
+        // The credits are granted by half windows, not to publish a grant per reply
+        if self.consumed >= (self.window / 2).max(1) {
+            self.publisher.put(z_serialize(&self.consumed)).await?;
+            self.consumed = 0;
+        }
+        Ok(reply)
+    }
+
+    /// Returns the number of replies received but not yet consumed.
+    pub fn len(&self) -> usize {
+        self.replies.len()
+    }
+
+    /// Returns whether there are no replies received but not yet consumed.
+    pub fn is_empty(&self) -> bool {
+        self.replies.is_empty()
+    }
+}
@@ -13,12 +13,28 @@
 //
 
 //! To manage groups and group memberships
+//!
+//! The members of a group are tracked with liveliness tokens, whose lifecycle is managed by the
+//! routers: a member leaves the group when it leaves it explicitly, or when its session is closed
+//! or disconnected.
+//!
+//! The members may elect a leader with [`Group::campaign`]. A leader is elected by a quorum of the
+//! members for an epoch, greater than the epochs of the previous leaders, that serves as fencing
+//! token: as a member votes at most once per epoch, and only for increasing epochs, two leaders
+//! can't be elected for the same epoch, and the resources written by the leaders can reject the
+//! writes of a leader whose epoch is lower than the epoch of the last write, e.g. of a leader
+//! isolated by a partition.
+//!
+//! The votes of a member are only kept in memory: a member that restarts forgets them, and may
+//! vote a second time for an epoch until it sees the leader elected for it. The single leader per
+//! epoch is only guaranteed if the members that leave the group, e.g. on a crash, do not join it
+//! again before the leader of the epochs they voted for is announced, or before the election of
+//! a candidate that did not get their vote times out.
 use std::{
     collections::HashMap,
     convert::TryInto,
-    ops::Add,
-    sync::Arc,
-    time::{Duration, Instant},
+    sync::{Arc, Mutex as SyncMutex},
+    time::Duration,
 };
 
 use flume::{Receiver, Sender};
@@ -28,84 +44,110 @@ use tokio::sync::Mutex;
 use zenoh::{
     bytes::ZBytesReader,
     internal::{bail, Condition, TaskController},
-    key_expr::{keyexpr, KeyExpr, OwnedKeyExpr},
-    pubsub::Publisher,
+    key_expr::{keyexpr, OwnedKeyExpr},
+    liveliness::LivelinessToken,
     qos::Priority,
+    query::{ConsolidationMode, Query, QueryTarget},
+    sample::{Sample, SampleKind},
     Error as ZError, Result as ZResult, Session,
 };
 
 const GROUP_PREFIX: &str = "zenoh/ext/net/group";
-const EVENT_POSTFIX: &str = "evt";
-const VIEW_REFRESH_LEASE_RATIO: f32 = 0.75f32;
-const DEFAULT_LEASE: Duration = Duration::from_secs(18);
-const DEFAULT_PRIORITY: Priority = Priority::DataHigh;
+const MEMBER_INFIX: &str = "member";
+const INFO_INFIX: &str = "info";
+const LEADER_INFIX: &str = "leader";
+const VOTE_INFIX: &str = "vote";
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+const CAMPAIGN_BACKOFF: Duration = Duration::from_millis(200);
 
 #[zenoh_macros::unstable]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct JoinEvent {
     pub member: Member,
-}
-
-#[zenoh_macros::unstable]
-#[derive(Serialize, Deserialize, Debug)]
-pub struct LeaseExpiredEvent {
-    pub mid: OwnedKeyExpr,
+    /// The identifier of the view resulting from the join.
+    pub view_id: u64,
 }
 
 #[zenoh_macros::unstable]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LeaveEvent {
     pub mid: OwnedKeyExpr,
+    /// The identifier of the view resulting from the leave.
+    pub view_id: u64,
 }
 
 #[zenoh_macros::unstable]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NewLeaderEvent {
+    #[deprecated = "Use `leader` instead."]
     pub mid: OwnedKeyExpr,
+    pub leader: Leader,
 }
 
-#[zenoh_macros::unstable]
-#[derive(Serialize, Deserialize, Debug)]
-struct KeepAliveEvent {
-    pub mid: OwnedKeyExpr,
+#[allow(deprecated)]
+impl NewLeaderEvent {
+    fn new(leader: Leader) -> Self {
+        NewLeaderEvent {
+            mid: leader.mid.clone(),
+            leader,
+        }
+    }
 }
 
 #[zenoh_macros::unstable]
-#[derive(Serialize, Deserialize, Debug)]
-enum GroupNetEvent {
-    Join(JoinEvent),
-    Leave(LeaveEvent),
-    KeepAlive(KeepAliveEvent),
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LeaderLostEvent {
+    pub leader: Leader,
 }
 
-/// Events exposed to the user to be informed for relevant
-/// changes in the group.
-#[zenoh_macros::unstable]
-#[derive(Serialize, Deserialize, Debug)]
-pub enum GroupEvent {
-    Join(JoinEvent),
-    Leave(LeaveEvent),
-    LeaseExpired(LeaseExpiredEvent),
-    NewLeader(NewLeaderEvent),
-}
+// NOTE: The events and the deprecated items are declared in their own module, whose lint level
+//       also applies to the code derived from them.
+#[allow(deprecated)]
+mod events {
+    use serde::{Deserialize, Serialize};
+    use zenoh::key_expr::OwnedKeyExpr;
+
+    use super::{JoinEvent, LeaderLostEvent, LeaveEvent, NewLeaderEvent};
+
+    /// Events exposed to the user to be informed for relevant
+    /// changes in the group.
+    ///
+    /// The events are delivered in the order of the changes of the group, and
+    /// the views of the group returned after an event include its change.
+    #[zenoh_macros::unstable]
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub enum GroupEvent {
+        Join(JoinEvent),
+        Leave(LeaveEvent),
+        #[deprecated = "Group members no longer have leases: they leave with their session."]
+        LeaseExpired(LeaseExpiredEvent),
+        NewLeader(NewLeaderEvent),
+        LeaderLost(LeaderLostEvent),
+    }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[zenoh_macros::unstable]
-pub enum MemberLiveliness {
-    Auto,
-    Manual,
+    #[deprecated = "Group members no longer have leases: they leave with their session."]
+    #[zenoh_macros::unstable]
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct LeaseExpiredEvent {
+        pub mid: OwnedKeyExpr,
+    }
+
+    #[deprecated = "The liveliness of the members of a group is the one of their session."]
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[zenoh_macros::unstable]
+    pub enum MemberLiveliness {
+        Auto,
+        Manual,
+    }
 }
+#[allow(deprecated)]
+pub use events::{GroupEvent, LeaseExpiredEvent, MemberLiveliness};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[zenoh_macros::unstable]
 pub struct Member {
     mid: OwnedKeyExpr,
     info: Option<String>,
-    liveliness: MemberLiveliness,
-    lease: Duration,
-    refresh_ratio: f32,
-    #[serde(skip)]
-    priority: Priority,
 }
 
 impl Member {
@@ -118,14 +160,7 @@ impl Member {
         if mid.is_wild() {
             bail!("Member ID is not allowed to contain wildcards: {}", mid);
         }
-        Ok(Member {
-            mid,
-            info: None,
-            liveliness: MemberLiveliness::Auto,
-            lease: DEFAULT_LEASE,
-            refresh_ratio: VIEW_REFRESH_LEASE_RATIO,
-            priority: DEFAULT_PRIORITY,
-        })
+        Ok(Member { mid, info: None })
     }
 
     pub fn id(&self) -> &keyexpr {
@@ -140,40 +175,136 @@ impl Member {
         self
     }
 
-    pub fn lease(mut self, d: Duration) -> Self {
-        self.lease = d;
+    #[deprecated = "Group members no longer have leases: they leave with their session."]
+    pub fn lease(self, _d: Duration) -> Self {
         self
     }
 
-    pub fn liveliness(mut self, l: MemberLiveliness) -> Self {
-        self.liveliness = l;
+    #[deprecated = "The liveliness of the members of a group is the one of their session."]
+    #[allow(deprecated)]
+    pub fn liveliness(self, _l: MemberLiveliness) -> Self {
         self
     }
 
-    pub fn refresh_ratio(mut self, r: f32) -> Self {
-        self.refresh_ratio = r;
+    #[deprecated = "Group members no longer have leases: they leave with their session."]
+    pub fn refresh_ratio(self, _r: f32) -> Self {
         self
     }
 
-    pub fn priority(mut self, p: Priority) -> Self {
-        self.priority = p;
+    #[deprecated = "The members of a group no longer publish keep-alive messages."]
+    pub fn priority(self, _p: Priority) -> Self {
         self
     }
 }
 
+/// The leader of a group, elected for an epoch.
+#[zenoh_macros::unstable]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Leader {
+    pub mid: OwnedKeyExpr,
+    /// The epoch of the election, to be used as fencing token.
+    pub epoch: u64,
+}
+
+/// The request of a candidate for the votes of the members.
+#[derive(Serialize, Deserialize, Debug)]
+struct Ballot {
+    candidate: OwnedKeyExpr,
+    epoch: u64,
+}
+
+/// The answer of a member to a [`Ballot`], with the greatest epoch it knows.
+#[derive(Serialize, Deserialize, Debug)]
+struct Vote {
+    granted: bool,
+    epoch: u64,
+}
+
+/// The state of the group as seen by the local member.
+#[derive(Default)]
+struct View {
+    id: u64,
+    /// The other members.
+    members: HashMap<OwnedKeyExpr, Member>,
+    /// The leaders whose token is alive, by token key expression.
+    leaders: HashMap<OwnedKeyExpr, Leader>,
+    /// The greatest epoch for which the local member voted.
+    promised: u64,
+    /// The greatest epoch of the known leaders and votes.
+    max_epoch: u64,
+}
+
+impl View {
+    /// The leader with the greatest epoch.
+    fn leader(&self) -> Option<&Leader> {
+        self.leaders
+            .values()
+            .max_by_key(|l| (l.epoch, l.mid.as_str()))
+    }
+}
+
+/// The leadership of the local member, whose token is undeclared when it is lost.
+struct LocalLeadership {
+    epoch: u64,
+    quorum: usize,
+    _token: LivelinessToken,
+}
+
 struct GroupState {
-    gid: String,
+    gid: OwnedKeyExpr,
     local_member: Member,
-    members: Mutex<HashMap<OwnedKeyExpr, (Member, Instant)>>,
-    group_publisher: Publisher<'static>,
-    user_events_tx: Mutex<Option<Sender<GroupEvent>>>,
+    view: Mutex<View>,
+    leadership: SyncMutex<Option<LocalLeadership>>,
+    user_events_tx: Mutex<Vec<Sender<GroupEvent>>>,
     cond: Condition,
 }
 
+impl GroupState {
+    fn key_expr(&self, infix: &str) -> String {
+        format!("{GROUP_PREFIX}/{}/{infix}", self.gid)
+    }
+
+    /// Returns the suffix of `key_expr` after the key expression of `infix`.
+    fn suffix<'a>(&self, infix: &str, key_expr: &'a keyexpr) -> Option<&'a str> {
+        key_expr
+            .as_str()
+            .strip_prefix(self.key_expr(infix).as_str())?
+            .strip_prefix('/')
+    }
+
+    async fn notify(&self, evt: GroupEvent) {
+        self.user_events_tx
+            .lock()
+            .await
+            .retain(|tx| tx.send(evt.clone()).is_ok());
+    }
+
+    /// Gives up the local leadership if it was superseded or if the quorum is lost.
+    fn check_leadership(&self, view: &View) {
+        let mut leadership = self.leadership.lock().unwrap();
+        if let Some(l) = leadership.as_ref() {
+            let superseded = view.max_epoch > l.epoch;
+            let below_quorum = view.members.len() + 1 < l.quorum;
+            if superseded || below_quorum {
+                tracing::debug!(
+                    "Member {} gives up the leadership of epoch {} (superseded: {}, below quorum: {})",
+                    self.local_member.mid,
+                    l.epoch,
+                    superseded,
+                    below_quorum
+                );
+                *leadership = None;
+            }
+        }
+    }
+}
+
 #[zenoh_macros::unstable]
 pub struct Group {
     state: Arc<GroupState>,
+    session: Arc<Session>,
     task_controller: TaskController,
+    _token: LivelinessToken,
 }
 
 impl Drop for Group {
@@ -183,180 +314,157 @@ impl Drop for Group {
     }
 }
 
-async fn keep_alive_task(state: Arc<GroupState>) {
-    let mid = state.local_member.mid.clone();
-    let evt = GroupNetEvent::KeepAlive(KeepAliveEvent { mid });
-    let buf = bincode::serialize(&evt).unwrap();
-    let period = state
-        .local_member
-        .lease
-        .mul_f32(state.local_member.refresh_ratio);
-    loop {
-        tokio::time::sleep(period).await;
-        tracing::trace!("Sending Keep Alive for: {}", &state.local_member.mid);
-        let _ = state.group_publisher.put(buf.clone()).await;
+async fn fetch_member(z: &Session, state: &GroupState, mid: OwnedKeyExpr) -> Member {
+    let qres = format!("{}/{}", state.key_expr(INFO_INFIX), mid);
+    tracing::trace!("Issuing Query for {}", &qres);
+    if let Ok(receiver) = z.get(&qres).timeout(QUERY_TIMEOUT).await {
+        while let Ok(reply) = receiver.recv_async().await {
+            match reply.result() {
+                Ok(sample) => match bincode::deserialize_from::<ZBytesReader, Member>(
+                    sample.payload().reader(),
+                ) {
+                    Ok(m) => return m,
+                    Err(e) => {
+                        tracing::warn!("Unable to deserialize the Member info received: {}", e)
+                    }
+                },
+                Err(e) => tracing::warn!("Error received: {:?}", e),
+            }
+        }
     }
+    tracing::debug!("No information received for member {}", mid);
+    Member { mid, info: None }
 }
 
-async fn watchdog_task(s: Arc<GroupState>, period: Duration) {
-    loop {
-        tokio::time::sleep(period).await;
-        let now = Instant::now();
-        let mut ms = s.members.lock().await;
-        let expired_members: Vec<OwnedKeyExpr> = ms
-            .iter()
-            .filter(|e| e.1 .1 < now)
-            .map(|e| e.0.clone())
-            .collect();
-
-        for e in &expired_members {
-            tracing::debug!("Member with lease expired: {}", e);
-            ms.remove(e);
+async fn member_handler(z: Arc<Session>, state: Arc<GroupState>, sample: Sample) {
+    let Some(mid) = state.suffix(MEMBER_INFIX, sample.key_expr()) else {
+        return;
+    };
+    let Ok(mid) = OwnedKeyExpr::try_from(mid) else {
+        return;
+    };
+    if mid == state.local_member.mid {
+        return;
+    }
+    let (view, evt) = match sample.kind() {
+        SampleKind::Put => {
+            let member = fetch_member(&z, &state, mid).await;
+            tracing::debug!("Member join: {:?}", &member);
+            let mut view = state.view.lock().await;
+            // The token of a member may be received both from the history and from the
+            // subscription
+            if view.members.contains_key(&member.mid) {
+                return;
+            }
+            view.id += 1;
+            view.members.insert(member.mid.clone(), member.clone());
+            tracing::debug!("Other members list: {:?}", view.members.keys());
+            let view_id = view.id;
+            (view, GroupEvent::Join(JoinEvent { member, view_id }))
         }
-        if !expired_members.is_empty() {
-            tracing::debug!("Other members list: {:?}", ms.keys());
-            drop(ms);
-            let u_evt = &*s.user_events_tx.lock().await;
-            for e in expired_members {
-                if let Some(tx) = u_evt {
-                    tx.send(GroupEvent::LeaseExpired(LeaseExpiredEvent { mid: e }))
-                        .unwrap()
-                }
+        SampleKind::Delete => {
+            tracing::debug!("Member leave: {:?}", &mid);
+            let mut view = state.view.lock().await;
+            if view.members.remove(&mid).is_none() {
+                return;
             }
+            view.id += 1;
+            tracing::debug!("Other members list: {:?}", view.members.keys());
+            state.check_leadership(&view);
+            let view_id = view.id;
+            (view, GroupEvent::Leave(LeaveEvent { mid, view_id }))
+        }
+    };
+    // Notified before the view lock is released for the events to be ordered as the views
+    state.notify(evt).await;
+    state.cond.notify_all();
+    drop(view);
+}
+
+async fn leader_handler(state: Arc<GroupState>, sample: Sample) {
+    let Some(leader) = state
+        .suffix(LEADER_INFIX, sample.key_expr())
+        .and_then(|suffix| suffix.split_once('/'))
+        .and_then(|(epoch, mid)| {
+            Some(Leader {
+                mid: OwnedKeyExpr::try_from(mid).ok()?,
+                epoch: epoch.parse().ok()?,
+            })
+        })
+    else {
+        return;
+    };
+    let mut view = state.view.lock().await;
+    let previous = view.leader().cloned();
+    match sample.kind() {
+        SampleKind::Put => {
+            view.max_epoch = view.max_epoch.max(leader.epoch);
+            view.leaders
+                .insert(sample.key_expr().clone().into(), leader);
+        }
+        SampleKind::Delete => {
+            view.leaders.remove(sample.key_expr().as_keyexpr());
         }
     }
+    state.check_leadership(&view);
+    let current = view.leader().cloned();
+    if previous != current {
+        tracing::debug!("Leader of group {}: {:?}", state.gid, current);
+        if let Some(leader) = previous {
+            state
+                .notify(GroupEvent::LeaderLost(LeaderLostEvent { leader }))
+                .await;
+        }
+        if let Some(leader) = current {
+            state
+                .notify(GroupEvent::NewLeader(NewLeaderEvent::new(leader)))
+                .await;
+        }
+    }
+    state.cond.notify_all();
 }
 
-async fn query_handler(z: Arc<Session>, state: Arc<GroupState>) {
-    let qres: KeyExpr = format!(
-        "{}/{}/{}",
-        GROUP_PREFIX, &state.gid, &state.local_member.mid
-    )
-    .try_into()
-    .unwrap();
-    tracing::debug!("Started query handler for: {}", &qres);
-    let buf = bincode::serialize(&state.local_member).unwrap();
-    let queryable = z.declare_queryable(&qres).await.unwrap();
-
-    while let Ok(query) = queryable.recv_async().await {
-        tracing::trace!("Serving query for: {}", &qres);
-        query.reply(qres.clone(), buf.clone()).await.unwrap();
+async fn reply(query: Query, buf: Vec<u8>) {
+    let key_expr = query.key_expr().clone();
+    if let Err(e) = query.reply(key_expr, buf).await {
+        tracing::warn!("Unable to reply to query: {}", e);
     }
 }
 
-async fn net_event_handler(z: Arc<Session>, state: Arc<GroupState>) {
-    let sub = z
-        .declare_subscriber(state.group_publisher.key_expr())
-        .await
-        .unwrap();
-    while let Ok(s) = sub.recv_async().await {
-        match bincode::deserialize_from::<ZBytesReader, GroupNetEvent>(s.payload().reader()) {
-            Ok(evt) => match evt {
-                GroupNetEvent::Join(je) => {
-                    tracing::debug!("Member join: {:?}", &je.member);
-                    let alive_till = Instant::now().add(je.member.lease);
-                    let mut ms = state.members.lock().await;
-                    ms.insert(je.member.mid.clone(), (je.member.clone(), alive_till));
-                    tracing::debug!("Other members list: {:?}", ms.keys());
-                    state.cond.notify_all();
-                    drop(ms);
-                    let u_evt = &*state.user_events_tx.lock().await;
-                    if let Some(tx) = u_evt {
-                        tx.send(GroupEvent::Join(je)).unwrap()
-                    }
-                }
-                GroupNetEvent::Leave(le) => {
-                    tracing::debug!("Member leave: {:?}", &le.mid);
-                    let mut ms = state.members.lock().await;
-                    ms.remove(&le.mid);
-                    tracing::debug!("Other members list: {:?}", ms.keys());
-                    drop(ms);
-                    let u_evt = &*state.user_events_tx.lock().await;
-                    if let Some(tx) = u_evt {
-                        tx.send(GroupEvent::Leave(le)).unwrap()
-                    }
-                }
-                GroupNetEvent::KeepAlive(kae) => {
-                    tracing::debug!(
-                        "KeepAlive from {} ({})",
-                        &kae.mid,
-                        if kae.mid.ne(&state.local_member.mid) {
-                            "other"
-                        } else {
-                            "myself"
-                        }
-                    );
-                    if kae.mid.ne(&state.local_member.mid) {
-                        let mut mm = state.members.lock().await;
-                        let v = mm.remove(&kae.mid);
-                        match v {
-                            Some((m, _)) => {
-                                tracing::trace!("Updating leasefor: {:?}", &kae.mid);
-                                let alive_till = Instant::now().add(m.lease);
-                                mm.insert(m.mid.clone(), (m, alive_till));
-                            }
-                            None => {
-                                tracing::debug!(
-                                    "Received Keep Alive from unknown member: {}",
-                                    &kae.mid
-                                );
-                                let qres = format!("{}/{}/{}", GROUP_PREFIX, &state.gid, kae.mid);
-                                // @TODO: we could also send this member info
-                                let qc = zenoh::query::ConsolidationMode::None;
-                                tracing::trace!("Issuing Query for {}", &qres);
-                                let receiver = z.get(&qres).consolidation(qc).await.unwrap();
-
-                                while let Ok(reply) = receiver.recv_async().await {
-                                    match reply.result() {
-                                        Ok(sample) => {
-                                            match bincode::deserialize_from::<ZBytesReader, Member>(
-                                                sample.payload().reader(),
-                                            ) {
-                                                Ok(m) => {
-                                                    let mut expiry = Instant::now();
-                                                    expiry = expiry.add(m.lease);
-                                                    tracing::debug!(
-                                                        "Received member information: {:?}",
-                                                        &m
-                                                    );
-                                                    mm.insert(kae.mid.clone(), (m.clone(), expiry));
-                                                    tracing::debug!(
-                                                        "Other members list: {:?}",
-                                                        mm.keys()
-                                                    );
-                                                    // Advertise a JoinEvent
-                                                    let u_evt = &*state.user_events_tx.lock().await;
-                                                    if let Some(tx) = u_evt {
-                                                        let je = JoinEvent { member: m };
-                                                        tx.send_async(GroupEvent::Join(je))
-                                                            .await
-                                                            .unwrap()
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    tracing::warn!(
-                                                        "Unable to deserialize the Member info received: {}", e);
-                                                }
-                                            }
-                                        }
-                                        Err(e) => {
-                                            tracing::warn!("Error received: {:?}", e);
-                                        }
-                                    }
-                                }
-                                state.cond.notify_all();
-                            }
-                        }
-                    } else {
-                        tracing::trace!("KeepAlive from Local Participant -- Ignoring");
-                    }
-                }
-            },
-            Err(e) => {
-                tracing::warn!("Failed decoding net-event due to: {:?}", e);
-            }
-        }
+async fn query_handler(state: Arc<GroupState>, query: Query) {
+    let key_expr = query.key_expr().as_keyexpr();
+    if state.suffix(INFO_INFIX, key_expr).is_some() {
+        tracing::trace!("Serving query for: {}", key_expr);
+        let buf = bincode::serialize(&state.local_member).unwrap();
+        reply(query, buf).await;
+        return;
+    }
+    let Some(ballot) = query.payload().and_then(|payload| {
+        bincode::deserialize_from::<ZBytesReader, Ballot>(payload.reader()).ok()
+    }) else {
+        tracing::warn!("Invalid ballot received on {}", key_expr);
+        return;
+    };
+    let mut view = state.view.lock().await;
+    // A member doesn't vote against a living leader, so that it is not deposed by a
+    // candidate that can't see it
+    let granted = ballot.epoch > view.promised.max(view.max_epoch) && view.leader().is_none();
+    if granted {
+        view.promised = ballot.epoch;
     }
+    tracing::debug!(
+        "Member {} votes {} for {} at epoch {}",
+        state.local_member.mid,
+        granted,
+        ballot.candidate,
+        ballot.epoch
+    );
+    let vote = Vote {
+        granted,
+        epoch: view.promised.max(view.max_epoch),
+    };
+    drop(view);
+    reply(query, bincode::serialize(&vote).unwrap()).await;
 }
 
 impl Group {
@@ -370,48 +478,79 @@ impl Group {
             bail!("Group ID is not allowed to contain wildcards: {}", group);
         }
 
-        let event_expr = format!("{GROUP_PREFIX}/{group}/{EVENT_POSTFIX}");
-        let publisher = z
-            .declare_publisher(event_expr)
-            .priority(with.priority)
-            .await
-            .unwrap();
         let state = Arc::new(GroupState {
-            gid: String::from(group),
-            local_member: with.clone(),
-            members: Mutex::new(Default::default()),
-            group_publisher: publisher,
+            gid: group,
+            local_member: with,
+            view: Mutex::new(Default::default()),
+            leadership: SyncMutex::new(None),
             user_events_tx: Mutex::new(Default::default()),
             cond: Condition::new(),
         });
-        let is_auto_liveliness = matches!(with.liveliness, MemberLiveliness::Auto);
+        let mid = &state.local_member.mid;
+        let task_controller = TaskController::default();
+
+        // The information and the votes of the member are served before it is announced.
+        let info_queryable = z
+            .declare_queryable(format!("{}/{}", state.key_expr(INFO_INFIX), mid))
+            .await?;
+        let vote_queryable = z
+            .declare_queryable(format!("{}/{}", state.key_expr(VOTE_INFIX), mid))
+            .await?;
+        let member_subscriber = z
+            .liveliness()
+            .declare_subscriber(format!("{}/**", state.key_expr(MEMBER_INFIX)))
+            .history(true)
+            .await?;
+        let leader_subscriber = z
+            .liveliness()
+            .declare_subscriber(format!("{}/**", state.key_expr(LEADER_INFIX)))
+            .history(true)
+            .await?;
+
+        for queryable in [info_queryable, vote_queryable] {
+            let s = state.clone();
+            task_controller.spawn_abortable(async move {
+                while let Ok(query) = queryable.recv_async().await {
+                    query_handler(s.clone(), query).await;
+                }
+            });
+        }
+        let (s, session) = (state.clone(), z.clone());
+        task_controller.spawn_abortable(async move {
+            while let Ok(sample) = member_subscriber.recv_async().await {
+                member_handler(session.clone(), s.clone(), sample).await;
+            }
+        });
+        let s = state.clone();
+        task_controller.spawn_abortable(async move {
+            while let Ok(sample) = leader_subscriber.recv_async().await {
+                leader_handler(s.clone(), sample).await;
+            }
+        });
 
         // announce the member:
-        tracing::debug!("Sending Join Message for local member: {:?}", &with);
-        let join_evt = GroupNetEvent::Join(JoinEvent { member: with });
-        let buf = bincode::serialize(&join_evt).unwrap();
-        let _ = state.group_publisher.put(buf).await;
+        tracing::debug!(
+            "Declaring token for local member: {:?}",
+            &state.local_member
+        );
+        let token = z
+            .liveliness()
+            .declare_token(format!("{}/{}", state.key_expr(MEMBER_INFIX), mid))
+            .await?;
 
-        let task_controller = TaskController::default();
-        // If the liveliness is manual it is the user who has to assert it.
-        if is_auto_liveliness {
-            task_controller.spawn_abortable(keep_alive_task(state.clone()));
-        }
-        task_controller.spawn_abortable(net_event_handler(z.clone(), state.clone()));
-        task_controller.spawn_abortable(query_handler(z.clone(), state.clone()));
-        task_controller.spawn_abortable(watchdog_task(state.clone(), Duration::from_secs(1)));
         Ok(Group {
             state,
+            session: z,
             task_controller,
+            _token: token,
         })
     }
 
-    /// Returns a receivers that will allow to receive notifications for group events.
-    /// Notice that there can be a single subscription at the time, each call to subscribe
-    /// will cancel the previous subscription.
+    /// Returns a receivers that will allow to receive notifications for group events,
+    /// from the current view of the group.
     pub async fn subscribe(&self) -> Receiver<GroupEvent> {
         let (tx, rx) = flume::unbounded();
-        *self.state.user_events_tx.lock().await = Some(tx);
+        self.state.user_events_tx.lock().await.push(tx);
         rx
     }
 
@@ -430,58 +569,214 @@ impl Group {
     pub async fn view(&self) -> Vec<Member> {
         let mut ms: Vec<Member> = self
             .state
-            .members
+            .view
             .lock()
             .await
-            .iter()
-            .map(|e| e.1 .0.clone())
+            .members
+            .values()
+            .cloned()
             .collect();
         ms.push(self.state.local_member.clone());
         ms
     }
 
+    /// Returns the identifier of the current group view, incremented on each join or leave.
+    pub async fn view_id(&self) -> u64 {
+        self.state.view.lock().await.id
+    }
+
     /// Wait for a view size to be established or times out. The resulting selector parameters
     /// indicates whether the desired view size has been established.
     pub async fn wait_for_view_size(&self, size: usize, timeout: Duration) -> bool {
-        if self.state.members.lock().await.len() + 1 >= size {
-            true
-        } else {
-            // let s = self.state.clone();
-            let f = async {
-                loop {
-                    let ms = self.state.members.lock().await;
-                    if ms.len() + 1 >= size {
-                        return true;
-                    } else {
-                        self.state.cond.wait(ms).await;
-                    }
+        let f = async {
+            loop {
+                let view = self.state.view.lock().await;
+                if view.members.len() + 1 >= size {
+                    return true;
+                } else {
+                    self.state.cond.wait(view).await;
                 }
-            };
-            let r: bool = select! {
-                p = f.fuse() => p,
-                _ = tokio::time::sleep(timeout).fuse() => false,
-            };
-            r
+            }
+        };
+        select! {
+            p = f.fuse() => p,
+            _ = tokio::time::sleep(timeout).fuse() => false,
         }
     }
 
     /// Returns the current group size.
     pub async fn size(&self) -> usize {
-        let ms = self.state.members.lock().await;
-        ms.len() + 1 // with +1 being the local member
+        let view = self.state.view.lock().await;
+        view.members.len() + 1 // with +1 being the local member
     }
 
     /// Returns the evental leader for this group. Notice that a view change may cause
     /// a change on leader. Thus it is wise to always get the leader after a view change.
+    #[deprecated = "Use `elected_leader` instead, the leaders being elected with `campaign`."]
     pub async fn leader(&self) -> Member {
-        use std::cmp::Ordering;
-        let group = self.view().await;
-        let mut leader = self.state.local_member.clone();
-        for m in group {
-            if leader.id().as_str().cmp(m.id().as_str()) == Ordering::Less {
-                leader = m
+        let view = self.state.view.lock().await;
+        view.members
+            .values()
+            .chain(std::iter::once(&self.state.local_member))
+            .max_by(|a, b| a.mid.as_str().cmp(b.mid.as_str()))
+            .cloned()
+            .unwrap_or_else(|| self.state.local_member.clone())
+    }
+
+    /// Returns the leader of the group with the greatest epoch, if any. Notice that
+    /// a leader isolated by a partition may still see itself as leader, until its
+    /// view drops below its quorum: the epoch must be used to fence its writes.
+    pub async fn elected_leader(&self) -> Option<Leader> {
+        self.state.view.lock().await.leader().cloned()
+    }
+
+    /// Campaigns for the leadership of the group, until this member is elected
+    /// by `quorum` members, itself included.
+    ///
+    /// To guarantee that there is a single leader per epoch, the quorum must be a
+    /// majority of the members that may join the group. The leadership is given up
+    /// when the view drops below the quorum, when a leader of a greater epoch is
+    /// elected, or when the returned [`Leadership`] is dropped.
+    pub async fn campaign(&self, quorum: usize) -> ZResult<Leadership> {
+        if quorum == 0 {
+            bail!("The quorum of an election must be at least 1");
+        }
+        let state = &self.state;
+        let mid = &state.local_member.mid;
+        loop {
+            // Wait for an election to be possible
+            let (epoch, rank) = loop {
+                let view = state.view.lock().await;
+                if view.leader().is_none() && view.members.len() + 1 >= quorum {
+                    // The members with the lowest ids campaign first, to avoid split votes
+                    let rank = view
+                        .members
+                        .keys()
+                        .filter(|m| m.as_str() < mid.as_str())
+                        .count();
+                    break (view.promised.max(view.max_epoch) + 1, rank as u32);
+                }
+                state.cond.wait(view).await;
+            };
+            tokio::time::sleep(CAMPAIGN_BACKOFF * rank).await;
+
+            let ballot = Ballot {
+                candidate: mid.clone(),
+                epoch,
+            };
+            tracing::debug!("Member {} campaigns for epoch {}", mid, epoch);
+            let replies = self
+                .session
+                .get(format!("{}/**", state.key_expr(VOTE_INFIX)))
+                .payload(bincode::serialize(&ballot).unwrap())
+                .target(QueryTarget::All)
+                .consolidation(ConsolidationMode::None)
+                .timeout(QUERY_TIMEOUT)
+                .await?;
+            let mut votes = 0;
+            let mut max_epoch = 0;
+            while let Ok(reply) = replies.recv_async().await {
+                let Ok(sample) = reply.result() else {
+                    continue;
+                };
+                match bincode::deserialize_from::<ZBytesReader, Vote>(sample.payload().reader()) {
+                    Ok(vote) => {
+                        votes += vote.granted as usize;
+                        max_epoch = max_epoch.max(vote.epoch);
+                    }
+                    Err(e) => tracing::warn!("Unable to deserialize the vote received: {}", e),
+                }
+            }
+
+            let mut view = state.view.lock().await;
+            view.max_epoch = view.max_epoch.max(max_epoch);
+            if votes >= quorum && view.max_epoch <= epoch && view.members.len() + 1 >= quorum {
+                let key_expr: OwnedKeyExpr =
+                    format!("{}/{}/{}", state.key_expr(LEADER_INFIX), epoch, mid).try_into()?;
+                let token = self.session.liveliness().declare_token(&key_expr).await?;
+                tracing::debug!("Member {} elected for epoch {}", mid, epoch);
+                *state.leadership.lock().unwrap() = Some(LocalLeadership {
+                    epoch,
+                    quorum,
+                    _token: token,
+                });
+                let previous = view.leader().cloned();
+                let leader = Leader {
+                    mid: mid.clone(),
+                    epoch,
+                };
+                view.max_epoch = epoch;
+                view.leaders.insert(key_expr, leader.clone());
+                if let Some(leader) = previous {
+                    state
+                        .notify(GroupEvent::LeaderLost(LeaderLostEvent { leader }))
+                        .await;
+                }
+                state
+                    .notify(GroupEvent::NewLeader(NewLeaderEvent::new(leader)))
+                    .await;
+                state.cond.notify_all();
+                return Ok(Leadership {
+                    state: state.clone(),
+                    epoch,
+                });
             }
+            tracing::debug!(
+                "Member {} not elected for epoch {} ({} votes)",
+                mid,
+                epoch,
+                votes
+            );
+            drop(view);
+            tokio::time::sleep(CAMPAIGN_BACKOFF).await;
+        }
+    }
+}
+
+/// The leadership of the local member, returned by [`Group::campaign`].
+///
+/// The leadership is given up when it is dropped.
+#[zenoh_macros::unstable]
+pub struct Leadership {
+    state: Arc<GroupState>,
+    epoch: u64,
+}
+
+impl Leadership {
+    /// Returns the epoch of the election, strictly greater than the ones of the
+    /// previous leaders, to be used as fencing token.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns whether the local member is still the leader.
+    pub fn is_leader(&self) -> bool {
+        matches!(&*self.state.leadership.lock().unwrap(), Some(l) if l.epoch == self.epoch)
+    }
+
+    /// Waits until the leadership is lost.
+    pub async fn lost(&self) {
+        loop {
+            let view = self.state.view.lock().await;
+            if !self.is_leader() {
+                return;
+            }
+            self.state.cond.wait(view).await;
+        }
+    }
+
+    /// Gives up the leadership.
+    pub fn resign(self) {
+        drop(self)
+    }
+}
+
+impl Drop for Leadership {
+    fn drop(&mut self) {
+        let mut leadership = self.state.leadership.lock().unwrap();
+        if matches!(&*leadership, Some(l) if l.epoch == self.epoch) {
+            *leadership = None;
+            self.state.cond.notify_all();
         }
-        leader
     }
 }
@@ -18,21 +18,33 @@ mod advanced_publisher;
 #[cfg(feature = "unstable")]
 mod advanced_subscriber;
 #[cfg(feature = "unstable")]
+mod credits;
+#[cfg(feature = "unstable")]
 pub mod group;
 #[cfg(feature = "unstable")]
+mod lock;
+#[cfg(feature = "unstable")]
 mod publication_cache;
 #[cfg(feature = "unstable")]
 mod publisher_ext;
 #[cfg(feature = "unstable")]
 mod querying_subscriber;
+#[cfg(feature = "unstable")]
+mod rpc;
+mod serde_serialization;
 mod serialization;
 #[cfg(feature = "unstable")]
 mod session_ext;
 #[cfg(feature = "unstable")]
 mod subscriber_ext;
+#[cfg(feature = "unstable")]
+mod typed;
 
 #[cfg(feature = "internal")]
 pub use crate::serialization::VarInt;
+pub use zenoh_macros::{Deserialize, Serialize};
+
+pub use crate::serde_serialization::{z_deserialize_serde, z_serialize_serde, ZSerdeError};
 pub use crate::serialization::{
     z_deserialize, z_serialize, Deserialize, Serialize, ZDeserializeError, ZDeserializer,
     ZReadIter, ZSerializer,
@@ -42,19 +54,27 @@ pub use crate::serialization::{
 pub use crate::{
     advanced_cache::{CacheConfig, RepliesConfig},
     advanced_publisher::{
-        AdvancedPublicationBuilder, AdvancedPublisher, AdvancedPublisherBuilder,
-        AdvancedPublisherDeleteBuilder, AdvancedPublisherPutBuilder, MissDetectionConfig,
+        AcknowledgementConfig, AdvancedPublicationBuilder, AdvancedPublisher,
+        AdvancedPublisherBuilder, AdvancedPublisherDeleteBuilder, AdvancedPublisherPutBuilder,
+        MissDetectionConfig,
     },
     advanced_subscriber::{
         AdvancedSubscriber, AdvancedSubscriberBuilder, HistoryConfig, Miss, RecoveryConfig,
         SampleMissHandlerUndeclaration, SampleMissListener, SampleMissListenerBuilder,
     },
+    credits::{CreditedGetBuilder, CreditedQuery, CreditedReplies},
+    lock::{Lease, Lock},
     publication_cache::{PublicationCache, PublicationCacheBuilder},
     publisher_ext::AdvancedPublisherBuilderExt,
     querying_subscriber::{
         ExtractSample, FetchingSubscriber, FetchingSubscriberBuilder, KeySpace, LivelinessSpace,
         QueryingSubscriberBuilder, UserSpace,
     },
-    session_ext::SessionExt,
+    rpc::{
+        RpcClient, RpcClientBuilder, RpcError, RpcErrorCode, RpcMethod, RpcSender, RpcService,
+        RpcServiceBuilder, RpcStream,
+    },
+    session_ext::{RpcSessionExt, SessionExt},
     subscriber_ext::{AdvancedSubscriberBuilderExt, SubscriberBuilderExt, SubscriberForward},
+    typed::{TypedError, TypedPublisher, TypedSample, TypedSubscriber, ZSchema},
 };
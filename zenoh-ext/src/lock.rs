@@ -0,0 +1,475 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{sync::watch, time::Instant};
+use zenoh::{
+    bytes::ZBytesReader,
+    internal::{bail, runtime::ZRuntime, zerror, TerminatableTask},
+    key_expr::{keyexpr, OwnedKeyExpr},
+    liveliness::LivelinessToken,
+    pubsub::Subscriber,
+    query::{ConsolidationMode, Query, QueryTarget, Queryable},
+    Error as ZError, Result as ZResult, Session, Wait,
+};
+
+const LOCK_PREFIX: &str = "zenoh/ext/net/lock";
+const CHOOSING_INFIX: &str = "choosing";
+const TICKET_INFIX: &str = "ticket";
+const VOTE_INFIX: &str = "vote";
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+const ELECTION_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Distinguishes the locks of a same session.
+static LOCK_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The tickets and the contenders choosing their ticket, in a snapshot of a lock.
+#[derive(Default)]
+struct Queue {
+    choosing: Vec<OwnedKeyExpr>,
+    tickets: Vec<(u64, OwnedKeyExpr)>,
+}
+
+/// The request of a holder for the votes of the locks on a fencing counter.
+#[derive(Serialize, Deserialize, Debug)]
+struct Ballot {
+    epoch: u64,
+}
+
+/// The answer of a lock to a [`Ballot`], with the greatest fencing counter it knows.
+#[derive(Serialize, Deserialize, Debug)]
+struct Vote {
+    granted: bool,
+    epoch: u64,
+}
+
+/// A distributed lock on a key expression.
+///
+/// The contenders of the lock are queued by ticket, as in the bakery algorithm: each contender
+/// declares a liveliness token for a ticket greater than the tickets of the other contenders it
+/// sees, and acquires the lock when its ticket is the lowest one it sees. The tokens being
+/// undeclared when the sessions of their contenders are closed or disconnected, the lock is
+/// released when its holder dies.
+///
+/// The lock is best effort, it does not guarantee mutual exclusion: the contenders only see each
+/// other through their liveliness tokens, whose propagation is asynchronous, so that two
+/// contenders may hold the lock at the same time, e.g. if one enqueues before the token of the
+/// other reached it, or while they are isolated by a partition.
+///
+/// A resource that must not be written by two holders at once is protected with the fencing
+/// counters of the leases, enabled with [`Lock::fencing`]: it rejects the writes whose counter is
+/// lower than the counter of the last write. As for the epochs of
+/// [`Group::campaign`](crate::group::Group::campaign), the counter of a lease is elected by a
+/// quorum of the locks of the same name, each lock voting only for counters greater than the ones
+/// it voted for or saw: the quorum being a majority, the counter of a lease is greater than the
+/// counters of the leases elected before it.
+///
+/// The votes of a lock are only kept in memory: a lock that is created again, e.g. after a crash,
+/// forgets them, and may vote a second time for a counter, so that the counters only increase if
+/// the locks that are created again don't make up a majority with the locks that did not see the
+/// last counter.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use std::time::Duration;
+///
+/// use zenoh_ext::Lock;
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// // The lock is created by 3 processes, whose majority is 2
+/// let lock = Lock::new(&session, "scheduler/lock")
+///     .await
+///     .unwrap()
+///     .ttl(Duration::from_secs(30))
+///     .fencing(2);
+/// let lease = lock.acquire().await.unwrap();
+/// println!("Acquired with fencing counter {:?}", lease.fence());
+/// lease.release().await.unwrap();
+/// # }
+/// ```
+#[zenoh_macros::unstable]
+pub struct Lock {
+    session: Session,
+    name: OwnedKeyExpr,
+    id: OwnedKeyExpr,
+    ttl: Option<Duration>,
+    quorum: Option<usize>,
+    /// The greatest fencing counter for which the lock voted, or that it saw.
+    promised: Arc<Mutex<u64>>,
+    /// Notified when a contender enqueues or leaves the queue.
+    changes: Arc<watch::Sender<()>>,
+    _subscriber: Subscriber<()>,
+    _queryable: Queryable<()>,
+}
+
+impl Lock {
+    /// Creates a lock named by the key expression `name`, which must not contain wildcards.
+    pub async fn new<T>(session: &Session, name: T) -> ZResult<Lock>
+    where
+        T: TryInto<OwnedKeyExpr>,
+        <T as TryInto<OwnedKeyExpr>>::Error: Into<ZError>,
+    {
+        let name: OwnedKeyExpr = name.try_into().map_err(|e| e.into())?;
+        if name.is_wild() {
+            bail!("Lock name is not allowed to contain wildcards: {}", name);
+        }
+        let id = OwnedKeyExpr::try_from(format!(
+            "{}-{}",
+            session.zid(),
+            LOCK_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))?;
+        let changes = Arc::new(watch::Sender::new(()));
+        let subscriber = session
+            .liveliness()
+            .declare_subscriber(format!("{LOCK_PREFIX}/{name}/**"))
+            .callback({
+                let changes = changes.clone();
+                move |_| {
+                    changes.send_replace(());
+                }
+            })
+            .await?;
+        let promised = Arc::new(Mutex::new(0));
+        let queryable = session
+            .declare_queryable(format!("{LOCK_PREFIX}/{name}/{VOTE_INFIX}/{id}"))
+            .callback({
+                let promised = promised.clone();
+                move |query| vote(&promised, query)
+            })
+            .await?;
+        Ok(Lock {
+            session: session.clone(),
+            name,
+            id,
+            ttl: None,
+            quorum: None,
+            promised,
+            changes,
+            _subscriber: subscriber,
+            _queryable: queryable,
+        })
+    }
+
+    /// Sets the time to live of the leases, after which they expire unless renewed.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Elects the fencing counters of the leases by `quorum` locks of the same name, this one
+    /// included, which must be a majority of them.
+    ///
+    /// The lock is acquired once the counter of its lease is elected, which requires `quorum`
+    /// locks to be reachable.
+    pub fn fencing(mut self, quorum: usize) -> Self {
+        self.quorum = Some(quorum);
+        self
+    }
+
+    /// Returns the name of the lock.
+    pub fn name(&self) -> &keyexpr {
+        &self.name
+    }
+
+    /// Acquires the lock, waiting for the previous contenders to release it.
+    pub async fn acquire(&self) -> ZResult<Lease> {
+        let (ticket, token) = self.enqueue().await?;
+        loop {
+            let changes = self.changes.subscribe();
+            if self.is_first(ticket).await? == Some(true) {
+                return self.lease(ticket, token).await;
+            }
+            Self::wait_change(changes).await?;
+        }
+    }
+
+    /// Acquires the lock if it is not held by another contender.
+    pub async fn try_acquire(&self) -> ZResult<Option<Lease>> {
+        let (ticket, token) = self.enqueue().await?;
+        loop {
+            let changes = self.changes.subscribe();
+            match self.is_first(ticket).await? {
+                Some(true) => return self.lease(ticket, token).await.map(Some),
+                Some(false) => {
+                    token.undeclare().await?;
+                    return Ok(None);
+                }
+                None => Self::wait_change(changes).await?,
+            }
+        }
+    }
+
+    fn key_expr(&self, infix: &str) -> String {
+        format!("{LOCK_PREFIX}/{}/{infix}", self.name)
+    }
+
+    /// Declares a ticket greater than the tickets of the other contenders.
+    async fn enqueue(&self) -> ZResult<(u64, LivelinessToken)> {
+        let choosing = self
+            .session
+            .liveliness()
+            .declare_token(format!("{}/{}", self.key_expr(CHOOSING_INFIX), self.id))
+            .await?;
+        let queue = self.snapshot().await?;
+        let max_ticket = queue.tickets.iter().map(|(ticket, _)| *ticket).max();
+        let now = self.session.new_timestamp().get_time().as_u64();
+        let ticket = max_ticket.map_or(now, |max_ticket| now.max(max_ticket + 1));
+        let token = self
+            .session
+            .liveliness()
+            .declare_token(format!(
+                "{}/{}/{}",
+                self.key_expr(TICKET_INFIX),
+                ticket,
+                self.id
+            ))
+            .await?;
+        choosing.undeclare().await?;
+        tracing::debug!(
+            "Lock {}: {} enqueued with ticket {}",
+            self.name,
+            self.id,
+            ticket
+        );
+        Ok((ticket, token))
+    }
+
+    /// Returns the current contenders of the lock.
+    async fn snapshot(&self) -> ZResult<Queue> {
+        let replies = self
+            .session
+            .liveliness()
+            .get(format!("{LOCK_PREFIX}/{}/**", self.name))
+            .await?;
+        let choosing_prefix = format!("{}/", self.key_expr(CHOOSING_INFIX));
+        let ticket_prefix = format!("{}/", self.key_expr(TICKET_INFIX));
+        let mut queue = Queue::default();
+        while let Ok(reply) = replies.recv_async().await {
+            let Ok(sample) = reply.result() else {
+                continue;
+            };
+            let key_expr = sample.key_expr().as_str();
+            if let Some(id) = key_expr.strip_prefix(&choosing_prefix) {
+                if let Ok(id) = OwnedKeyExpr::try_from(id) {
+                    queue.choosing.push(id);
+                }
+            } else if let Some((ticket, id)) = key_expr
+                .strip_prefix(&ticket_prefix)
+                .and_then(|suffix| suffix.split_once('/'))
+            {
+                if let (Ok(ticket), Ok(id)) = (ticket.parse(), OwnedKeyExpr::try_from(id)) {
+                    queue.tickets.push((ticket, id));
+                }
+            }
+        }
+        Ok(queue)
+    }
+
+    /// Returns whether the ticket is the lowest one, or `None` while other contenders are
+    /// choosing their ticket, which may be lower.
+    async fn is_first(&self, ticket: u64) -> ZResult<Option<bool>> {
+        let queue = self.snapshot().await?;
+        if queue.choosing.iter().any(|id| *id != self.id) {
+            return Ok(None);
+        }
+        let first = queue
+            .tickets
+            .iter()
+            .filter(|(_, id)| *id != self.id)
+            .all(|(other, id)| (ticket, self.id.as_str()) < (*other, id.as_str()));
+        Ok(Some(first))
+    }
+
+    /// Waits for a contender to enqueue or to leave the queue since `changes` was subscribed.
+    async fn wait_change(mut changes: watch::Receiver<()>) -> ZResult<()> {
+        changes.changed().await.map_err(|e| zerror!(e).into())
+    }
+
+    /// Elects a fencing counter greater than the ones the quorum voted for or saw.
+    async fn elect_fence(&self, quorum: usize) -> ZResult<u64> {
+        if quorum == 0 {
+            bail!("The quorum of the fencing counters must be at least 1");
+        }
+        loop {
+            let epoch = *self.promised.lock().unwrap() + 1;
+            let replies = self
+                .session
+                .get(format!("{}/**", self.key_expr(VOTE_INFIX)))
+                .payload(bincode::serialize(&Ballot { epoch }).unwrap())
+                .target(QueryTarget::All)
+                .consolidation(ConsolidationMode::None)
+                .timeout(QUERY_TIMEOUT)
+                .await?;
+            let mut votes = 0;
+            let mut max_epoch = 0;
+            while let Ok(reply) = replies.recv_async().await {
+                let Ok(sample) = reply.result() else {
+                    continue;
+                };
+                match bincode::deserialize_from::<ZBytesReader, Vote>(sample.payload().reader()) {
+                    Ok(vote) => {
+                        votes += vote.granted as usize;
+                        max_epoch = max_epoch.max(vote.epoch);
+                    }
+                    Err(e) => tracing::warn!("Unable to deserialize the vote received: {}", e),
+                }
+            }
+            {
+                let mut promised = self.promised.lock().unwrap();
+                *promised = (*promised).max(max_epoch);
+            }
+            if votes >= quorum && max_epoch <= epoch {
+                return Ok(epoch);
+            }
+            tracing::debug!(
+                "Lock {}: fencing counter {} not elected for {} ({} votes)",
+                self.name,
+                epoch,
+                self.id,
+                votes
+            );
+            tokio::time::sleep(ELECTION_BACKOFF).await;
+        }
+    }
+
+    async fn lease(&self, ticket: u64, token: LivelinessToken) -> ZResult<Lease> {
+        let fence = match self.quorum {
+            Some(quorum) => Some(self.elect_fence(quorum).await?),
+            None => None,
+        };
+        tracing::debug!(
+            "Lock {}: acquired by {} with ticket {} and fencing counter {:?}",
+            self.name,
+            self.id,
+            ticket,
+            fence
+        );
+        let token = Arc::new(Mutex::new(Some(token)));
+        let deadline = self
+            .ttl
+            .map(|ttl| Arc::new(Mutex::new(Instant::now() + ttl)));
+        let expiration = deadline.clone().map(|deadline| {
+            let token = token.clone();
+            let name = self.name.clone();
+            TerminatableTask::spawn_abortable(ZRuntime::Net, async move {
+                loop {
+                    let expiry = *deadline.lock().unwrap();
+                    tokio::time::sleep_until(expiry).await;
+                    if *deadline.lock().unwrap() <= Instant::now() {
+                        tracing::debug!("Lock {}: lease of ticket {} expired", name, ticket);
+                        token.lock().unwrap().take();
+                        break;
+                    }
+                }
+            })
+        });
+        Ok(Lease {
+            ticket,
+            fence,
+            ttl: self.ttl,
+            token,
+            deadline,
+            _expiration: expiration,
+        })
+    }
+}
+
+/// Grants the fencing counter of `query` if it is greater than the ones the lock voted for or saw.
+fn vote(promised: &Mutex<u64>, query: Query) {
+    let Some(ballot) = query.payload().and_then(|payload| {
+        bincode::deserialize_from::<ZBytesReader, Ballot>(payload.reader()).ok()
+    }) else {
+        tracing::warn!("Invalid ballot received on {}", query.key_expr());
+        return;
+    };
+    let mut promised = promised.lock().unwrap();
+    let granted = ballot.epoch > *promised;
+    if granted {
+        *promised = ballot.epoch;
+    }
+    let vote = Vote {
+        granted,
+        epoch: *promised,
+    };
+    drop(promised);
+    let key_expr = query.key_expr().clone();
+    if let Err(e) = query
+        .reply(key_expr, bincode::serialize(&vote).unwrap())
+        .wait()
+    {
+        tracing::warn!("Unable to reply to query: {}", e);
+    }
+}
+
+/// The holding of a [`Lock`], released when it is dropped or when its time to live expires.
+#[zenoh_macros::unstable]
+pub struct Lease {
+    ticket: u64,
+    fence: Option<u64>,
+    ttl: Option<Duration>,
+    token: Arc<Mutex<Option<LivelinessToken>>>,
+    deadline: Option<Arc<Mutex<Instant>>>,
+    _expiration: Option<TerminatableTask>,
+}
+
+impl Lease {
+    /// Returns the fencing counter of the lease, greater than the ones of the previous leases of
+    /// the lock, or `None` if the lock has no [fencing](Lock::fencing).
+    pub fn fence(&self) -> Option<u64> {
+        self.fence
+    }
+
+    /// Returns whether the lease is still held, i.e. has not expired.
+    pub fn is_valid(&self) -> bool {
+        self.token.lock().unwrap().is_some()
+    }
+
+    /// Returns the instant at which the lease expires, if it has a time to live.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+            .as_ref()
+            .map(|deadline| *deadline.lock().unwrap())
+    }
+
+    /// Extends the lease by its time to live, failing if it has already expired.
+    pub fn renew(&self) -> ZResult<()> {
+        let token = self.token.lock().unwrap();
+        if token.is_none() {
+            bail!("The lease of ticket {} has expired", self.ticket);
+        }
+        if let (Some(deadline), Some(ttl)) = (&self.deadline, self.ttl) {
+            *deadline.lock().unwrap() = Instant::now() + ttl;
+        }
+        Ok(())
+    }
+
+    /// Releases the lock.
+    pub async fn release(self) -> ZResult<()> {
+        let token = self.token.lock().unwrap().take();
+        match token {
+            Some(token) => token.undeclare().await,
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,713 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    collections::HashMap,
+    fmt,
+    future::{IntoFuture, Ready},
+    marker::PhantomData,
+    sync::Arc,
+    time::Duration,
+};
+
+use futures::{future::BoxFuture, Future, FutureExt};
+use tokio::{sync::Mutex, time::Instant};
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    internal::{bail, runtime::ZRuntime, TaskController},
+    key_expr::{keyexpr, KeyExpr, OwnedKeyExpr},
+    query::{ConsolidationMode, Querier, Query, Queryable, ReplyError},
+    Error, Resolvable, Result as ZResult, Session, Wait,
+};
+
+use crate::{
+    credits::{CreditedQuery, CreditedReplies},
+    z_deserialize, z_serialize, Deserialize, Serialize, ZDeserializeError, ZDeserializer,
+    ZSerializer,
+};
+
+/// The parameter of the requests carrying the timeout of the client, in milliseconds.
+const TIMEOUT_PARAMETER: &str = "_timeout";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_STREAM_TIMEOUT: Duration = Duration::from_secs(300);
+/// The delay added to the timeout of the queries of a client, for the calls to time out with an
+/// [`RpcErrorCode::Timeout`] before the queries.
+const QUERY_TIMEOUT_MARGIN: Duration = Duration::from_secs(1);
+const ERROR_SCHEMA: &str = "zenoh/rpc/error";
+
+fn error_encoding() -> Encoding {
+    Encoding::ZENOH_SERIALIZED.with_schema(ERROR_SCHEMA)
+}
+
+/// The code of an [`RpcError`].
+#[zenoh_macros::unstable]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpcErrorCode {
+    /// The error was not raised by an RPC service.
+    Unknown,
+    /// The request can't be deserialized into the request type of the method.
+    InvalidRequest,
+    /// The response can't be deserialized into the response type of the method.
+    InvalidResponse,
+    /// The service has no method of this name.
+    MethodNotFound,
+    /// The method didn't respond in time, and was canceled.
+    Timeout,
+    /// No service responded.
+    Unavailable,
+    /// The method failed.
+    Internal,
+    /// An error code defined by the application.
+    Application(u32),
+}
+
+impl Serialize for RpcErrorCode {
+    fn serialize(&self, serializer: &mut ZSerializer) {
+        let (tag, application_code) = match self {
+            Self::Unknown => (0u32, None),
+            Self::InvalidRequest => (1, None),
+            Self::InvalidResponse => (2, None),
+            Self::MethodNotFound => (3, None),
+            Self::Timeout => (4, None),
+            Self::Unavailable => (5, None),
+            Self::Internal => (6, None),
+            Self::Application(code) => (7, Some(*code)),
+        };
+        serializer.serialize(tag);
+        if let Some(code) = application_code {
+            serializer.serialize(code);
+        }
+    }
+}
+
+impl Deserialize for RpcErrorCode {
+    fn deserialize(deserializer: &mut ZDeserializer) -> Result<Self, ZDeserializeError> {
+        Ok(match deserializer.deserialize::<u32>()? {
+            0 => Self::Unknown,
+            1 => Self::InvalidRequest,
+            2 => Self::InvalidResponse,
+            3 => Self::MethodNotFound,
+            4 => Self::Timeout,
+            5 => Self::Unavailable,
+            6 => Self::Internal,
+            7 => Self::Application(deserializer.deserialize()?),
+            _ => return Err(ZDeserializeError),
+        })
+    }
+}
+
+/// The error of an RPC, returned by the methods of an [`RpcService`] and by the calls of an
+/// [`RpcMethod`].
+///
+/// The errors returned by the methods are sent in the [`ReplyError`] of the query, serialized
+/// with the encoding `zenoh/serialized;zenoh/rpc/error`.
+#[zenoh_macros::unstable]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    code: RpcErrorCode,
+    message: String,
+}
+
+impl RpcError {
+    pub fn new(code: RpcErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn code(&self) -> RpcErrorCode {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl Serialize for RpcError {
+    fn serialize(&self, serializer: &mut ZSerializer) {
+        serializer.serialize(self.code);
+        serializer.serialize(&self.message);
+    }
+}
+
+impl Deserialize for RpcError {
+    fn deserialize(deserializer: &mut ZDeserializer) -> Result<Self, ZDeserializeError> {
+        Ok(Self {
+            code: deserializer.deserialize()?,
+            message: deserializer.deserialize()?,
+        })
+    }
+}
+
+impl From<&ReplyError> for RpcError {
+    fn from(error: &ReplyError) -> Self {
+        if *error.encoding() == error_encoding() {
+            if let Ok(error) = z_deserialize(error.payload()) {
+                return error;
+            }
+        }
+        let message = error
+            .payload()
+            .try_to_string()
+            .map(|message| message.into_owned())
+            .unwrap_or_default();
+        Self::new(RpcErrorCode::Unknown, message)
+    }
+}
+
+type UnaryMethod =
+    Arc<dyn Fn(&ZBytes) -> BoxFuture<'static, Result<ZBytes, RpcError>> + Send + Sync>;
+type StreamingMethod = Arc<
+    dyn Fn(&ZBytes, Arc<Mutex<CreditedQuery>>) -> BoxFuture<'static, Result<(), RpcError>>
+        + Send
+        + Sync,
+>;
+
+#[derive(Clone)]
+enum Method {
+    Unary(UnaryMethod),
+    Streaming(StreamingMethod),
+}
+
+fn invalid_request<T: Send + 'static>(
+    error: ZDeserializeError,
+) -> BoxFuture<'static, Result<T, RpcError>> {
+    futures::future::ready(Err(RpcError::new(
+        RpcErrorCode::InvalidRequest,
+        error.to_string(),
+    )))
+    .boxed()
+}
+
+/// A builder for initializing an [`RpcService`], returned by
+/// [`RpcSessionExt::declare_rpc_service`](crate::RpcSessionExt::declare_rpc_service).
+#[zenoh_macros::unstable]
+#[must_use = "Resolvables do nothing unless you resolve them using `.await` or `zenoh::Wait::wait`"]
+pub struct RpcServiceBuilder<'a, 'b> {
+    session: &'a Session,
+    prefix: ZResult<KeyExpr<'b>>,
+    methods: HashMap<String, Method>,
+    timeout: Duration,
+    stream_timeout: Duration,
+}
+
+impl<'a, 'b> RpcServiceBuilder<'a, 'b> {
+    pub(crate) fn new(session: &'a Session, prefix: ZResult<KeyExpr<'b>>) -> Self {
+        Self {
+            session,
+            prefix,
+            methods: HashMap::new(),
+            timeout: DEFAULT_TIMEOUT,
+            stream_timeout: DEFAULT_STREAM_TIMEOUT,
+        }
+    }
+
+    /// Adds a method, answering the requests on `<prefix>/<name>`.
+    ///
+    /// The requests are deserialized into `Req`, and the responses serialized from `Resp`, with
+    /// the zenoh-ext serialization.
+    pub fn method<Req, Resp, F, Fut>(mut self, name: &str, handler: F) -> Self
+    where
+        Req: Deserialize + Send + 'static,
+        Resp: Serialize + 'static,
+        F: Fn(Req) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Resp, RpcError>> + Send + 'static,
+    {
+        let method: UnaryMethod = Arc::new(move |payload| match z_deserialize::<Req>(payload) {
+            Ok(request) => handler(request)
+                .map(|response| response.map(|response| z_serialize(&response)))
+                .boxed(),
+            Err(e) => invalid_request(e),
+        });
+        self.methods.insert(name.to_string(), Method::Unary(method));
+        self
+    }
+
+    /// Adds a streaming method, answering the requests on `<prefix>/<name>` with the responses
+    /// sent by the handler with an [`RpcSender`].
+    ///
+    /// The responses are sent as the client grants credits when it calls the method with
+    /// [`RpcMethod::call_streaming`].
+    pub fn streaming_method<Req, Resp, F, Fut>(mut self, name: &str, handler: F) -> Self
+    where
+        Req: Deserialize + Send + 'static,
+        Resp: Serialize + 'static,
+        F: Fn(Req, RpcSender<Resp>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), RpcError>> + Send + 'static,
+    {
+        let method: StreamingMethod =
+            Arc::new(move |payload, query| match z_deserialize::<Req>(payload) {
+                Ok(request) => handler(
+                    request,
+                    RpcSender {
+                        query,
+                        _phantom: PhantomData,
+                    },
+                )
+                .boxed(),
+                Err(e) => invalid_request(e),
+            });
+        self.methods
+            .insert(name.to_string(), Method::Streaming(method));
+        self
+    }
+
+    /// Sets the maximum duration of the methods, after which they are canceled, 10 seconds by
+    /// default. The methods are also canceled after the timeout of the client.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the maximum duration of the streaming methods, after which they are canceled,
+    /// 5 minutes by default. The methods are also canceled after the stream timeout of the client.
+    pub fn stream_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_timeout = timeout;
+        self
+    }
+}
+
+impl Resolvable for RpcServiceBuilder<'_, '_> {
+    type To = ZResult<RpcService>;
+}
+
+impl Wait for RpcServiceBuilder<'_, '_> {
+    fn wait(self) -> <Self as Resolvable>::To {
+        RpcService::new(self)
+    }
+}
+
+impl IntoFuture for RpcServiceBuilder<'_, '_> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
+
+/// A service answering requests with named methods, under a key expression prefix.
+///
+/// Each request is answered by a task, which is canceled when the service is undeclared.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use zenoh_ext::{RpcError, RpcErrorCode, RpcSessionExt};
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let service = session
+///     .declare_rpc_service("calculator")
+///     .method("add", |(a, b): (i64, i64)| async move { Ok(a + b) })
+///     .method("div", |(a, b): (i64, i64)| async move {
+///         a.checked_div(b)
+///             .ok_or_else(|| RpcError::new(RpcErrorCode::Application(1), "division by zero"))
+///     })
+///     .await
+///     .unwrap();
+///
+/// let client = session.declare_rpc_client("calculator").await.unwrap();
+/// let add = client.method::<(i64, i64), i64>("add").unwrap();
+/// assert_eq!(add.call(&(1, 2)).await.unwrap(), 3);
+/// # }
+/// ```
+#[zenoh_macros::unstable]
+pub struct RpcService {
+    _queryable: Queryable<()>,
+    task_controller: TaskController,
+}
+
+impl RpcService {
+    fn new(conf: RpcServiceBuilder<'_, '_>) -> ZResult<Self> {
+        let prefix = conf.prefix?.into_owned();
+        for name in conf.methods.keys() {
+            match keyexpr::new(name.as_str()) {
+                Ok(ke) if !ke.is_wild() && !name.contains('/') => {}
+                _ => bail!(
+                    "Invalid name for a method of RPC service {}: {}",
+                    prefix,
+                    name
+                ),
+            }
+        }
+        tracing::debug!(
+            "Declare RPC service {} with methods {:?}",
+            prefix,
+            conf.methods.keys()
+        );
+        let methods = conf.methods;
+        let (timeout, stream_timeout) = (conf.timeout, conf.stream_timeout);
+        let task_controller = TaskController::default();
+        let tasks = task_controller.clone();
+        let service_prefix = prefix.clone();
+        let session = conf.session.clone();
+        let queryable = conf
+            .session
+            .declare_queryable(&prefix / keyexpr::new("*")?)
+            .callback(move |query| {
+                let method = query
+                    .key_expr()
+                    .as_str()
+                    .strip_prefix(service_prefix.as_str())
+                    .and_then(|name| name.strip_prefix('/'))
+                    .and_then(|name| methods.get(name))
+                    .cloned();
+                tasks.spawn_abortable_with_rt(
+                    ZRuntime::Application,
+                    handle(session.clone(), query, method, timeout, stream_timeout),
+                );
+            })
+            .wait()?;
+        Ok(RpcService {
+            _queryable: queryable,
+            task_controller,
+        })
+    }
+
+    /// Undeclares the service, canceling the methods being executed.
+    pub fn undeclare(self) {
+        drop(self)
+    }
+}
+
+impl Drop for RpcService {
+    fn drop(&mut self) {
+        self.task_controller.terminate_all(Duration::from_secs(10));
+    }
+}
+
+async fn handle(
+    session: Session,
+    query: Query,
+    method: Option<Method>,
+    timeout: Duration,
+    stream_timeout: Duration,
+) {
+    let key_expr = query.key_expr().clone();
+    let streaming = matches!(method, Some(Method::Streaming(_)));
+    let timeout = if streaming { stream_timeout } else { timeout };
+    let timeout = query
+        .parameters()
+        .get(TIMEOUT_PARAMETER)
+        .and_then(|timeout| timeout.parse().ok())
+        .map_or(timeout, |client_timeout| {
+            timeout.min(Duration::from_millis(client_timeout))
+        });
+    let payload = query.payload().cloned().unwrap_or_default();
+    let query = match CreditedQuery::new(&session, query).await {
+        Ok(query) => Arc::new(Mutex::new(query)),
+        Err(e) => {
+            tracing::warn!("Invalid RPC {}: {}", key_expr, e);
+            return;
+        }
+    };
+    let result = match method {
+        None => Err(RpcError::new(
+            RpcErrorCode::MethodNotFound,
+            format!("No method for {key_expr}"),
+        )),
+        Some(method) => {
+            let result = match method {
+                Method::Unary(method) => tokio::time::timeout(timeout, method(&payload)).await,
+                Method::Streaming(method) => {
+                    tokio::time::timeout(
+                        timeout,
+                        method(&payload, query.clone()).map(|result| result.map(|_| ZBytes::new())),
+                    )
+                    .await
+                }
+            };
+            result.unwrap_or_else(|_| {
+                Err(RpcError::new(
+                    RpcErrorCode::Timeout,
+                    format!("{key_expr} canceled after {timeout:?}"),
+                ))
+            })
+        }
+    };
+    let query = query.lock().await;
+    let reply = match result {
+        // The responses of a streaming method were already sent
+        Ok(_) if streaming => Ok(()),
+        Ok(response) => {
+            query
+                .query()
+                .reply(key_expr.clone(), response)
+                .encoding(Encoding::ZENOH_SERIALIZED)
+                .await
+        }
+        Err(error) => {
+            tracing::debug!("RPC {} failed: {}", key_expr, error);
+            query
+                .reply_err_with_encoding(z_serialize(&error), error_encoding())
+                .await
+        }
+    };
+    if let Err(e) = reply {
+        tracing::warn!("Error replying to RPC {}: {}", key_expr, e);
+    }
+}
+
+/// The sender of the responses of a streaming method of an [`RpcService`].
+///
+/// The call ends when the handler of the method returns and the sender is dropped.
+#[zenoh_macros::unstable]
+pub struct RpcSender<Resp> {
+    query: Arc<Mutex<CreditedQuery>>,
+    _phantom: PhantomData<fn(&Resp)>,
+}
+
+impl<Resp: Serialize> RpcSender<Resp> {
+    /// Sends a response, waiting for the client to grant a credit.
+    pub async fn send(&self, response: &Resp) -> Result<(), RpcError> {
+        let mut query = self.query.lock().await;
+        let key_expr = query.query().key_expr().clone();
+        query
+            .reply_with_encoding(key_expr, z_serialize(response), Encoding::ZENOH_SERIALIZED)
+            .await
+            .map_err(|e| RpcError::new(RpcErrorCode::Unavailable, e.to_string()))
+    }
+}
+
+/// A builder for initializing an [`RpcClient`], returned by
+/// [`RpcSessionExt::declare_rpc_client`](crate::RpcSessionExt::declare_rpc_client).
+#[zenoh_macros::unstable]
+#[must_use = "Resolvables do nothing unless you resolve them using `.await` or `zenoh::Wait::wait`"]
+pub struct RpcClientBuilder<'a, 'b> {
+    session: &'a Session,
+    prefix: ZResult<KeyExpr<'b>>,
+    timeout: Duration,
+    stream_timeout: Duration,
+}
+
+impl<'a, 'b> RpcClientBuilder<'a, 'b> {
+    pub(crate) fn new(session: &'a Session, prefix: ZResult<KeyExpr<'b>>) -> Self {
+        Self {
+            session,
+            prefix,
+            timeout: DEFAULT_TIMEOUT,
+            stream_timeout: DEFAULT_STREAM_TIMEOUT,
+        }
+    }
+
+    /// Sets the timeout of the calls, 10 seconds by default, after which the services cancel
+    /// the methods.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the timeout of the calls of the streaming methods, 5 minutes by default, after which
+    /// the services cancel the methods.
+    pub fn stream_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_timeout = timeout;
+        self
+    }
+}
+
+impl Resolvable for RpcClientBuilder<'_, '_> {
+    type To = ZResult<RpcClient>;
+}
+
+impl Wait for RpcClientBuilder<'_, '_> {
+    fn wait(self) -> <Self as Resolvable>::To {
+        Ok(RpcClient {
+            session: self.session.clone(),
+            prefix: self.prefix?.into_owned().into(),
+            timeout: self.timeout,
+            stream_timeout: self.stream_timeout,
+        })
+    }
+}
+
+impl IntoFuture for RpcClientBuilder<'_, '_> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
+
+/// A client of an [`RpcService`], creating the stubs of its methods.
+#[zenoh_macros::unstable]
+#[derive(Debug, Clone)]
+pub struct RpcClient {
+    session: Session,
+    prefix: OwnedKeyExpr,
+    timeout: Duration,
+    stream_timeout: Duration,
+}
+
+impl RpcClient {
+    /// Returns the stub of a method of the service.
+    pub fn method<Req: Serialize, Resp: Deserialize>(
+        &self,
+        name: &str,
+    ) -> ZResult<RpcMethod<Req, Resp>> {
+        let name = keyexpr::new(name)?;
+        if name.is_wild() || name.as_str().contains('/') {
+            bail!(
+                "Invalid name for a method of RPC service {}: {}",
+                self.prefix,
+                name
+            );
+        }
+        let key_expr = &self.prefix / name;
+        Ok(RpcMethod {
+            session: self.session.clone(),
+            unary: Caller::new(&self.session, &key_expr, self.timeout)?,
+            streaming: Caller::new(&self.session, &key_expr, self.stream_timeout)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// The querier of the calls of a method, with their timeout.
+#[derive(Debug)]
+struct Caller {
+    querier: Querier<'static>,
+    timeout: Duration,
+    parameters: String,
+}
+
+impl Caller {
+    fn new(session: &Session, key_expr: &keyexpr, timeout: Duration) -> ZResult<Self> {
+        let querier = session
+            .declare_querier(key_expr.to_owned())
+            .consolidation(ConsolidationMode::None)
+            .timeout(timeout + QUERY_TIMEOUT_MARGIN)
+            .wait()?;
+        Ok(Caller {
+            querier,
+            timeout,
+            parameters: format!("{TIMEOUT_PARAMETER}={}", timeout.as_millis()),
+        })
+    }
+
+    fn timeout_error(&self) -> RpcError {
+        RpcError::new(
+            RpcErrorCode::Timeout,
+            format!(
+                "{} timed out after {:?}",
+                self.querier.key_expr(),
+                self.timeout
+            ),
+        )
+    }
+}
+
+/// The stub of a method of an [`RpcService`], returned by [`RpcClient::method`].
+#[zenoh_macros::unstable]
+#[derive(Debug)]
+pub struct RpcMethod<Req, Resp> {
+    session: Session,
+    unary: Caller,
+    streaming: Caller,
+    _phantom: PhantomData<fn(&Req) -> Resp>,
+}
+
+impl<Req: Serialize, Resp: Deserialize> RpcMethod<Req, Resp> {
+    /// Calls the method, returning the first response.
+    pub async fn call(&self, request: &Req) -> Result<Resp, RpcError> {
+        let replies = self
+            .unary
+            .querier
+            .get()
+            .payload(z_serialize(request))
+            .parameters(self.unary.parameters.as_str())
+            .await
+            .map_err(|e: Error| RpcError::new(RpcErrorCode::Unknown, e.to_string()))?;
+        let reply = tokio::time::timeout(self.unary.timeout, replies.recv_async())
+            .await
+            .map_err(|_| self.unary.timeout_error())?
+            .map_err(|_| {
+                RpcError::new(
+                    RpcErrorCode::Unavailable,
+                    format!("No service replied on {}", self.key_expr()),
+                )
+            })?;
+        match reply.result() {
+            Ok(sample) => z_deserialize(sample.payload())
+                .map_err(|e| RpcError::new(RpcErrorCode::InvalidResponse, e.to_string())),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Calls a streaming method, granting `window` credits to the service, and the credits of
+    /// the responses as they are received.
+    ///
+    /// The call is subject to the stream timeout of the client, not to its timeout.
+    pub async fn call_streaming(
+        &self,
+        request: &Req,
+        window: u64,
+    ) -> Result<RpcStream<Resp>, RpcError> {
+        let replies = CreditedReplies::get(&self.session, &self.streaming.querier, window)
+            .payload(z_serialize(request))
+            .parameters(self.streaming.parameters.clone())
+            .await
+            .map_err(|e: Error| RpcError::new(RpcErrorCode::Unknown, e.to_string()))?;
+        Ok(RpcStream {
+            replies,
+            deadline: Instant::now() + self.streaming.timeout,
+            timeout_error: Some(self.streaming.timeout_error()),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the key expression of the method.
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        self.unary.querier.key_expr()
+    }
+}
+
+/// The responses of a streaming method, returned by [`RpcMethod::call_streaming`].
+///
+/// The responses end when the handler of the method returns, when no service answers the call,
+/// or with an [`RpcErrorCode::Timeout`] error once the stream timeout of the client has elapsed.
+#[zenoh_macros::unstable]
+pub struct RpcStream<Resp> {
+    replies: CreditedReplies,
+    deadline: Instant,
+    /// The error returned once the call times out, before the end of the responses.
+    timeout_error: Option<RpcError>,
+    _phantom: PhantomData<fn() -> Resp>,
+}
+
+impl<Resp: Deserialize> RpcStream<Resp> {
+    /// Receives the next response, or `None` at the end of the responses.
+    pub async fn next(&mut self) -> Option<Result<Resp, RpcError>> {
+        let reply = match tokio::time::timeout_at(self.deadline, self.replies.recv_async()).await {
+            Ok(reply) => reply.ok()?,
+            Err(_) => return self.timeout_error.take().map(Err),
+        };
+        Some(match reply.result() {
+            Ok(sample) => z_deserialize(sample.payload())
+                .map_err(|e| RpcError::new(RpcErrorCode::InvalidResponse, e.to_string())),
+            Err(error) => Err(error.into()),
+        })
+    }
+}
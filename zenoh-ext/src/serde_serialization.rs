@@ -0,0 +1,732 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The [Zenoh serialization format][1] of the `serde` data model.
+//!
+//! [`ZSerializer`] and [`ZDeserializer`] are a `serde` serializer and deserializer, so that the
+//! types implementing the `serde` traits are serialized as the equivalent types implementing the
+//! `zenoh-ext` traits:
+//!
+//! - the primitives, strings, bytes, sequences and maps as the corresponding Rust types;
+//! - the tuples, structs and arrays as the tuple of their fields or elements, the arrays being
+//!   then serialized without their length;
+//! - the newtype structs as their field, the unit and unit structs as nothing;
+//! - the variants of the enums as their index, serialized as an `u32`, followed by their fields,
+//!   as the derived `zenoh-ext` traits with `#[repr(u32)]` and the index as discriminant;
+//! - the chars as their `u32` value;
+//! - the options as a `bool`, followed by their value if they have one.
+//!
+//! The format not being self-describing, the `serde` attributes requiring it (e.g. `untagged` or
+//! `flatten`) are not supported, and the sequences must have a known length.
+//!
+//! [1]: https://github.com/eclipse-zenoh/roadmap/blob/main/rfcs/ALL/Serialization.md
+use std::fmt;
+
+use serde::{
+    de::{self, IntoDeserializer},
+    ser,
+};
+use zenoh::bytes::ZBytes;
+
+use crate::serialization::{VarInt, ZDeserializeError, ZDeserializer, ZSerializer};
+
+/// Error occurring in the serialization or deserialization of a `serde` type.
+#[derive(Debug)]
+pub struct ZSerdeError(String);
+
+impl fmt::Display for ZSerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ZSerdeError {}
+
+impl ser::Error for ZSerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for ZSerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl From<ZDeserializeError> for ZSerdeError {
+    fn from(value: ZDeserializeError) -> Self {
+        Self(value.to_string())
+    }
+}
+
+/// Serialize a `serde` type according to the [Zenoh serialization format][1].
+///
+/// The type is serialized as the equivalent `zenoh-ext` type, e.g. a struct as the tuple of its
+/// fields.
+///
+/// # Examples
+///
+/// ```rust
+/// use zenoh_ext::*;
+///
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+/// struct Position {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// let zbytes = z_serialize_serde(&Position { x: 1.0, y: 2.0 }).unwrap();
+/// assert_eq!(z_deserialize::<(f64, f64)>(&zbytes).unwrap(), (1.0, 2.0));
+/// assert_eq!(
+///     z_deserialize_serde::<Position>(&zbytes).unwrap(),
+///     Position { x: 1.0, y: 2.0 }
+/// );
+/// ```
+///
+/// [1]: https://github.com/eclipse-zenoh/roadmap/blob/main/rfcs/ALL/Serialization.md
+pub fn z_serialize_serde<T: ser::Serialize + ?Sized>(t: &T) -> Result<ZBytes, ZSerdeError> {
+    let mut serializer = ZSerializer::new();
+    t.serialize(&mut serializer)?;
+    Ok(serializer.finish())
+}
+
+/// Deserialize a `serde` type according to the [Zenoh serialization format][1].
+///
+/// See [`z_serialize_serde`].
+///
+/// [1]: https://github.com/eclipse-zenoh/roadmap/blob/main/rfcs/ALL/Serialization.md
+pub fn z_deserialize_serde<T: de::DeserializeOwned>(zbytes: &ZBytes) -> Result<T, ZSerdeError> {
+    let mut deserializer = ZDeserializer::new(zbytes);
+    let t = T::deserialize(&mut deserializer)?;
+    if !deserializer.done() {
+        return Err(ZSerdeError("trailing bytes".into()));
+    }
+    Ok(t)
+}
+
+macro_rules! serialize_primitives {
+    ($($method:ident: $ty:ty),* $(,)?) => {$(
+        fn $method(self, v: $ty) -> Result<(), ZSerdeError> {
+            self.serialize(v);
+            Ok(())
+        }
+    )*};
+}
+
+impl<'a> ser::Serializer for &'a mut ZSerializer {
+    type Ok = ();
+    type Error = ZSerdeError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    serialize_primitives!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_str: &str,
+        serialize_bytes: &[u8],
+    );
+
+    fn serialize_char(self, v: char) -> Result<(), ZSerdeError> {
+        self.serialize(v as u32);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), ZSerdeError> {
+        self.serialize(false);
+        Ok(())
+    }
+
+    fn serialize_some<T: ser::Serialize + ?Sized>(self, value: &T) -> Result<(), ZSerdeError> {
+        self.serialize(true);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), ZSerdeError> {
+        self.serialize(variant_index);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ser::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), ZSerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ser::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), ZSerdeError> {
+        self.serialize(variant_index);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, ZSerdeError> {
+        let len = len.ok_or_else(|| ZSerdeError("sequences must have a known length".into()))?;
+        self.serialize(VarInt(len));
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, ZSerdeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, ZSerdeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, ZSerdeError> {
+        self.serialize(variant_index);
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, ZSerdeError> {
+        self.serialize_seq(len)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, ZSerdeError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, ZSerdeError> {
+        self.serialize(variant_index);
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeSeq for &mut ZSerializer {
+    type Ok = ();
+    type Error = ZSerdeError;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ZSerdeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut ZSerializer {
+    type Ok = ();
+    type Error = ZSerdeError;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ZSerdeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut ZSerializer {
+    type Ok = ();
+    type Error = ZSerdeError;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ZSerdeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut ZSerializer {
+    type Ok = ();
+    type Error = ZSerdeError;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ZSerdeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut ZSerializer {
+    type Ok = ();
+    type Error = ZSerdeError;
+
+    fn serialize_key<T: ser::Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ZSerdeError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ZSerdeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut ZSerializer {
+    type Ok = ();
+    type Error = ZSerdeError;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), ZSerdeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut ZSerializer {
+    type Ok = ();
+    type Error = ZSerdeError;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), ZSerdeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+}
+
+/// The elements of a sequence, a tuple or a map.
+struct Elements<'a, 'b> {
+    deserializer: &'b mut ZDeserializer<'a>,
+    len: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for Elements<'_, '_> {
+    type Error = ZSerdeError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, ZSerdeError> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Elements<'_, '_> {
+    type Error = ZSerdeError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ZSerdeError> {
+        de::SeqAccess::next_element_seed(self, seed)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+macro_rules! deserialize_primitives {
+    ($($method:ident: $ty:ty => $visit:ident),* $(,)?) => {$(
+        fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ZSerdeError> {
+            visitor.$visit(self.deserialize::<$ty>()?)
+        }
+    )*};
+}
+
+impl<'de, 'a> de::Deserializer<'de> for &mut ZDeserializer<'a> {
+    type Error = ZSerdeError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, ZSerdeError> {
+        Err(ZSerdeError(
+            "the Zenoh serialization format is not self-describing".into(),
+        ))
+    }
+
+    deserialize_primitives!(
+        deserialize_bool: bool => visit_bool,
+        deserialize_i8: i8 => visit_i8,
+        deserialize_i16: i16 => visit_i16,
+        deserialize_i32: i32 => visit_i32,
+        deserialize_i64: i64 => visit_i64,
+        deserialize_i128: i128 => visit_i128,
+        deserialize_u8: u8 => visit_u8,
+        deserialize_u16: u16 => visit_u16,
+        deserialize_u32: u32 => visit_u32,
+        deserialize_u64: u64 => visit_u64,
+        deserialize_u128: u128 => visit_u128,
+        deserialize_f32: f32 => visit_f32,
+        deserialize_f64: f64 => visit_f64,
+        deserialize_str: String => visit_string,
+        deserialize_string: String => visit_string,
+        deserialize_bytes: Vec<u8> => visit_byte_buf,
+        deserialize_byte_buf: Vec<u8> => visit_byte_buf,
+    );
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ZSerdeError> {
+        let value = self.deserialize::<u32>()?;
+        let char = char::from_u32(value)
+            .ok_or_else(|| ZSerdeError(format!("invalid char value {value}")))?;
+        visitor.visit_char(char)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ZSerdeError> {
+        if self.deserialize::<bool>()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ZSerdeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ZSerdeError> {
+        let len = self.deserialize::<VarInt<usize>>()?.0;
+        visitor.visit_seq(Elements {
+            deserializer: self,
+            len,
+        })
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        visitor.visit_seq(Elements {
+            deserializer: self,
+            len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ZSerdeError> {
+        let len = self.deserialize::<VarInt<usize>>()?.0;
+        visitor.visit_map(Elements {
+            deserializer: self,
+            len,
+        })
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        Err(ZSerdeError(
+            "the Zenoh serialization format does not serialize identifiers".into(),
+        ))
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        self.deserialize_any(visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut ZDeserializer<'_> {
+    type Error = ZSerdeError;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), ZSerdeError> {
+        let index = self.deserialize::<u32>()?;
+        let variant =
+            seed.deserialize(IntoDeserializer::<ZSerdeError>::into_deserializer(index))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut ZDeserializer<'_> {
+    type Error = ZSerdeError;
+
+    fn unit_variant(self) -> Result<(), ZSerdeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, ZSerdeError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ZSerdeError> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::serialization::{z_deserialize, z_serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        name: String,
+        position: Position,
+        tags: Vec<String>,
+        counters: BTreeMap<String, u64>,
+        id: (u16, i8),
+        data: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Meters(f32);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Command {
+        Stop,
+        Move(Position),
+        Turn(f32, f32),
+        Rename { name: String },
+    }
+
+    macro_rules! serialize_deserialize {
+        ($ty:ty, $expr:expr) => {
+            let expr: &$ty = &$expr;
+            let payload = z_serialize_serde(expr).unwrap();
+            let output = z_deserialize_serde::<$ty>(&payload).unwrap();
+            assert_eq!(*expr, output);
+        };
+    }
+
+    #[test]
+    fn serde_serialization() {
+        serialize_deserialize!(
+            Record,
+            Record {
+                name: "robot".into(),
+                position: Position { x: 1.5, y: -2.0 },
+                tags: vec!["a".into(), "b".into()],
+                counters: BTreeMap::from([("x".into(), 1), ("y".into(), 2)]),
+                id: (42, -1),
+                data: vec![1, 2, 3],
+            }
+        );
+        serialize_deserialize!(Meters, Meters(2.5));
+        serialize_deserialize!(Command, Command::Stop);
+        serialize_deserialize!(Command, Command::Move(Position { x: 0.0, y: 1.0 }));
+        serialize_deserialize!(Command, Command::Turn(0.5, -0.5));
+        serialize_deserialize!(
+            Command,
+            Command::Rename {
+                name: "rover".into()
+            }
+        );
+        serialize_deserialize!(Option<char>, Some('z'));
+        serialize_deserialize!(Option<char>, None);
+        serialize_deserialize!((), ());
+    }
+
+    #[test]
+    fn serde_binary_format() {
+        // Serialized as the equivalent `zenoh-ext` types
+        let position = Position { x: 1.5, y: -2.0 };
+        assert_eq!(
+            z_serialize_serde(&position).unwrap().to_bytes(),
+            z_serialize(&(1.5f64, -2.0f64)).to_bytes()
+        );
+        let payload = z_serialize_serde(&position).unwrap();
+        assert_eq!(
+            z_deserialize::<(f64, f64)>(&payload).unwrap(),
+            (1.5f64, -2.0f64)
+        );
+
+        let vp: Vec<(&str, i16)> = vec![("s1", 10), ("s2", -10000)];
+        assert_eq!(
+            z_serialize_serde(&vp).unwrap().to_bytes(),
+            vec![2, 2, 115, 49, 10, 0, 2, 115, 50, 240, 216]
+        );
+        let map = HashMap::from([("hello".to_string(), 42u32)]);
+        assert_eq!(
+            z_serialize_serde(&map).unwrap().to_bytes(),
+            z_serialize(&map).to_bytes()
+        );
+        assert_eq!(
+            z_serialize_serde("test").unwrap().to_bytes(),
+            z_serialize("test").to_bytes()
+        );
+        assert_eq!(
+            z_serialize_serde(&Command::Turn(1.0, 2.0))
+                .unwrap()
+                .to_bytes(),
+            z_serialize(&(2u32, 1.0f32, 2.0f32)).to_bytes()
+        );
+        assert_eq!(
+            z_serialize_serde(&Some(1u8)).unwrap().to_bytes(),
+            vec![1, 1]
+        );
+
+        // Errors
+        assert!(z_deserialize_serde::<Command>(&z_serialize(&4u32)).is_err());
+        assert!(z_deserialize_serde::<u8>(&z_serialize(&(1u8, 2u8))).is_err());
+    }
+}
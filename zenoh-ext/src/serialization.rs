@@ -41,6 +41,8 @@ fn default_serialize_n<T: Serialize>(slice: &[T], serializer: &mut ZSerializer)
 
 /// Serialization implementation.
 ///
+/// It can be derived for structs and enums with `#[derive(zenoh_ext::Serialize)]`.
+///
 /// See [Zenoh serialization format RFC][1].
 ///
 /// [1]: https://github.com/eclipse-zenoh/roadmap/blob/main/rfcs/ALL/Serialization.md
@@ -86,6 +88,8 @@ fn default_deserialize_n_uninit<'a, T: Deserialize>(
 
 /// Deserialization implementation.
 ///
+/// It can be derived for structs and enums with `#[derive(zenoh_ext::Deserialize)]`.
+///
 /// See [Zenoh serialization format RFC][1].
 ///
 /// [1]: https://github.com/eclipse-zenoh/roadmap/blob/main/rfcs/ALL/Serialization.md
@@ -153,6 +157,8 @@ pub fn z_deserialize<T: Deserialize>(zbytes: &ZBytes) -> Result<T, ZDeserializeE
 ///
 /// Serializing objects one after the other is equivalent to serialize a tuple of these objects.
 ///
+/// `&mut ZSerializer` is also a `serde` serializer, see [`z_serialize_serde`](crate::z_serialize_serde).
+///
 /// # Examples
 ///
 /// ```rust
@@ -220,6 +226,9 @@ impl From<ZSerializer> for ZBytes {
 ///
 /// Deserializing objects one after the other is equivalent to serialize a tuple of these objects.
 ///
+/// `&mut ZDeserializer` is also a `serde` deserializer, see
+/// [`z_deserialize_serde`](crate::z_deserialize_serde).
+///
 /// # Examples
 ///
 /// ```rust
@@ -16,6 +16,7 @@ use zenoh::{key_expr::KeyExpr, session::Session, Error};
 
 #[allow(deprecated)]
 use super::PublicationCacheBuilder;
+use crate::{RpcClientBuilder, RpcServiceBuilder};
 
 /// Some extensions to the [`zenoh::Session`](zenoh::Session)
 #[zenoh_macros::unstable]
@@ -66,3 +67,53 @@ impl SessionExt for Session {
         PublicationCacheBuilder::new(self, pub_key_expr.try_into().map_err(Into::into))
     }
 }
+
+/// The extensions of the [`zenoh::Session`](zenoh::Session) declaring RPC services and clients.
+#[zenoh_macros::unstable]
+pub trait RpcSessionExt {
+    /// Declares an [`RpcService`](crate::RpcService) answering the requests on `<prefix>/<method>`.
+    #[zenoh_macros::unstable]
+    fn declare_rpc_service<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        prefix: TryIntoKeyExpr,
+    ) -> RpcServiceBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<Error>;
+
+    /// Declares an [`RpcClient`](crate::RpcClient) of the service declared on `prefix`.
+    #[zenoh_macros::unstable]
+    fn declare_rpc_client<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        prefix: TryIntoKeyExpr,
+    ) -> RpcClientBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<Error>;
+}
+
+impl RpcSessionExt for Session {
+    #[zenoh_macros::unstable]
+    fn declare_rpc_service<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        prefix: TryIntoKeyExpr,
+    ) -> RpcServiceBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<Error>,
+    {
+        RpcServiceBuilder::new(self, prefix.try_into().map_err(Into::into))
+    }
+
+    #[zenoh_macros::unstable]
+    fn declare_rpc_client<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        prefix: TryIntoKeyExpr,
+    ) -> RpcClientBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<Error>,
+    {
+        RpcClientBuilder::new(self, prefix.try_into().map_err(Into::into))
+    }
+}
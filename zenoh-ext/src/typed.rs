@@ -0,0 +1,314 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{fmt, marker::PhantomData};
+
+use zenoh::{
+    bytes::Encoding,
+    handlers::FifoChannelHandler,
+    pubsub::{Publisher, PublisherDeleteBuilder, PublisherPutBuilder, Subscriber},
+    sample::{Sample, SampleKind},
+    Resolve, Result as ZResult,
+};
+
+use crate::{z_deserialize, z_serialize, Deserialize, Serialize, ZDeserializeError};
+
+/// The schema of a type serialized by a [`TypedPublisher`], identifying the layout of its payloads.
+///
+/// The schema is stamped in the encoding of the payloads, `zenoh/serialized;<name>` or
+/// `zenoh/serialized;<name>@<version>` if the version is not 0, so that a [`TypedSubscriber`]
+/// rejects the payloads of another type or of another version of its type instead of
+/// deserializing them wrongly.
+///
+/// # Examples
+///
+/// ```rust
+/// use zenoh_ext::ZSchema;
+///
+/// #[derive(zenoh_ext::Serialize, zenoh_ext::Deserialize)]
+/// struct Position {
+///     x: f64,
+///     y: f64,
+///     z: f64,
+/// }
+///
+/// impl ZSchema for Position {
+///     const NAME: &'static str = "robot/Position";
+///     // Incremented when the `z` field was added
+///     const VERSION: u32 = 2;
+/// }
+///
+/// assert_eq!(Position::encoding().to_string(), "zenoh/serialized;robot/Position@2");
+/// ```
+#[zenoh_macros::unstable]
+pub trait ZSchema {
+    /// The name of the schema, e.g. the name of the type.
+    const NAME: &'static str;
+    /// The version of the schema, to be incremented on each change of the layout of the type.
+    const VERSION: u32 = 0;
+
+    /// Returns the schema stamped in the encoding of the payloads.
+    fn schema() -> String {
+        match Self::VERSION {
+            0 => Self::NAME.to_string(),
+            version => format!("{}@{version}", Self::NAME),
+        }
+    }
+
+    /// Returns the encoding of the payloads.
+    fn encoding() -> Encoding {
+        Encoding::ZENOH_SERIALIZED.with_schema(Self::schema())
+    }
+}
+
+macro_rules! impl_schema {
+    ($($ty:ty),* $(,)?) => {$(
+        impl ZSchema for $ty {
+            const NAME: &'static str = stringify!($ty);
+        }
+    )*};
+}
+impl_schema!(bool, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64, String);
+
+/// Error occurring when a [`TypedSubscriber`] receives a sample.
+#[zenoh_macros::unstable]
+#[derive(Debug)]
+pub enum TypedError {
+    /// The encoding of the sample is not the one of the schema of the type.
+    SchemaMismatch {
+        /// The encoding of the schema of the type.
+        expected: Encoding,
+        sample: Box<Sample>,
+    },
+    /// The payload of the sample, whose encoding is the one of the schema of the type, can't be
+    /// deserialized.
+    Deserialize {
+        error: ZDeserializeError,
+        sample: Box<Sample>,
+    },
+    /// The subscriber can't receive samples anymore.
+    Disconnected(zenoh::Error),
+}
+
+impl fmt::Display for TypedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SchemaMismatch { expected, sample } => write!(
+                f,
+                "sample on {} encoded as {} instead of {expected}",
+                sample.key_expr(),
+                sample.encoding()
+            ),
+            Self::Deserialize { error, sample } => write!(
+                f,
+                "sample on {} encoded as {}: {error}",
+                sample.key_expr(),
+                sample.encoding()
+            ),
+            Self::Disconnected(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for TypedError {}
+
+/// A sample received by a [`TypedSubscriber`], with its deserialized value.
+#[zenoh_macros::unstable]
+#[derive(Debug)]
+pub struct TypedSample<T> {
+    sample: Sample,
+    value: Option<T>,
+}
+
+impl<T> TypedSample<T> {
+    /// Returns the received sample.
+    pub fn sample(&self) -> &Sample {
+        &self.sample
+    }
+
+    /// Returns the deserialized value, or `None` if the sample is a deletion.
+    pub fn value(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Returns the deserialized value, or `None` if the sample is a deletion.
+    pub fn into_value(self) -> Option<T> {
+        self.value
+    }
+
+    /// Returns the received sample and the deserialized value.
+    pub fn into_parts(self) -> (Sample, Option<T>) {
+        (self.sample, self.value)
+    }
+}
+
+impl<T: Deserialize + ZSchema> TryFrom<Sample> for TypedSample<T> {
+    type Error = TypedError;
+
+    fn try_from(sample: Sample) -> Result<Self, TypedError> {
+        if sample.kind() == SampleKind::Delete {
+            return Ok(Self {
+                sample,
+                value: None,
+            });
+        }
+        let expected = T::encoding();
+        if *sample.encoding() != expected {
+            return Err(TypedError::SchemaMismatch {
+                expected,
+                sample: Box::new(sample),
+            });
+        }
+        match z_deserialize(sample.payload()) {
+            Ok(value) => Ok(Self {
+                sample,
+                value: Some(value),
+            }),
+            Err(error) => Err(TypedError::Deserialize {
+                error,
+                sample: Box::new(sample),
+            }),
+        }
+    }
+}
+
+/// A [`Publisher`] of the values of a type, serialized with the encoding of its [`ZSchema`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use zenoh_ext::{TypedPublisher, TypedSubscriber};
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let subscriber = TypedSubscriber::<f64>::new(
+///     session.declare_subscriber("robot/speed").await.unwrap(),
+/// );
+/// let publisher = TypedPublisher::<f64>::new(
+///     session.declare_publisher("robot/speed").await.unwrap(),
+/// );
+/// publisher.put(&1.5).await.unwrap();
+/// let sample = subscriber.recv_async().await.unwrap();
+/// assert_eq!(sample.value(), Some(&1.5));
+/// # }
+/// ```
+#[zenoh_macros::unstable]
+#[derive(Debug)]
+pub struct TypedPublisher<'a, T> {
+    publisher: Publisher<'a>,
+    encoding: Encoding,
+    _phantom: PhantomData<fn(&T)>,
+}
+
+impl<'a, T: Serialize + ZSchema> TypedPublisher<'a, T> {
+    /// Wraps a [`Publisher`], whose encoding is replaced by the one of the schema of `T`.
+    pub fn new(publisher: Publisher<'a>) -> Self {
+        Self {
+            publisher,
+            encoding: T::encoding(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Publishes a value.
+    pub fn put(&self, value: &T) -> PublisherPutBuilder<'_> {
+        self.publisher
+            .put(z_serialize(value))
+            .encoding(self.encoding.clone())
+    }
+
+    /// Publishes a deletion.
+    pub fn delete(&self) -> PublisherDeleteBuilder<'_> {
+        self.publisher.delete()
+    }
+
+    /// Returns the wrapped [`Publisher`].
+    pub fn publisher(&self) -> &Publisher<'a> {
+        &self.publisher
+    }
+
+    /// Returns the wrapped [`Publisher`].
+    pub fn into_inner(self) -> Publisher<'a> {
+        self.publisher
+    }
+
+    /// Undeclares the wrapped [`Publisher`].
+    pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
+        self.publisher.undeclare()
+    }
+}
+
+/// A [`Subscriber`] deserializing the received samples into the values of a type, checking that
+/// they are encoded with its [`ZSchema`].
+///
+/// See [`TypedPublisher`] for an example.
+#[zenoh_macros::unstable]
+#[derive(Debug)]
+pub struct TypedSubscriber<T> {
+    subscriber: Subscriber<FifoChannelHandler<Sample>>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Deserialize + ZSchema> TypedSubscriber<T> {
+    /// Wraps a [`Subscriber`] with the default handler.
+    pub fn new(subscriber: Subscriber<FifoChannelHandler<Sample>>) -> Self {
+        Self {
+            subscriber,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Receives the next sample, waiting for it.
+    pub fn recv(&self) -> Result<TypedSample<T>, TypedError> {
+        let sample = self.subscriber.recv().map_err(TypedError::Disconnected)?;
+        TypedSample::try_from(sample)
+    }
+
+    /// Receives the next sample, waiting for it asynchronously.
+    pub async fn recv_async(&self) -> Result<TypedSample<T>, TypedError> {
+        let sample = self
+            .subscriber
+            .recv_async()
+            .await
+            .map_err(TypedError::Disconnected)?;
+        TypedSample::try_from(sample)
+    }
+
+    /// Receives the next sample if one is available.
+    pub fn try_recv(&self) -> Result<Option<TypedSample<T>>, TypedError> {
+        match self
+            .subscriber
+            .try_recv()
+            .map_err(TypedError::Disconnected)?
+        {
+            Some(sample) => TypedSample::try_from(sample).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the wrapped [`Subscriber`].
+    pub fn subscriber(&self) -> &Subscriber<FifoChannelHandler<Sample>> {
+        &self.subscriber
+    }
+
+    /// Returns the wrapped [`Subscriber`].
+    pub fn into_inner(self) -> Subscriber<FifoChannelHandler<Sample>> {
+        self.subscriber
+    }
+
+    /// Undeclares the wrapped [`Subscriber`].
+    pub fn undeclare(self) -> impl Resolve<ZResult<()>> {
+        self.subscriber.undeclare()
+    }
+}
@@ -15,8 +15,8 @@
 use zenoh::sample::SampleKind;
 use zenoh_config::{EndPoint, ModeDependentValue, WhatAmI};
 use zenoh_ext::{
-    AdvancedPublisherBuilderExt, AdvancedSubscriberBuilderExt, CacheConfig, HistoryConfig,
-    MissDetectionConfig, RecoveryConfig,
+    AcknowledgementConfig, AdvancedPublisherBuilderExt, AdvancedSubscriberBuilderExt, CacheConfig,
+    HistoryConfig, MissDetectionConfig, RecoveryConfig,
 };
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
@@ -836,3 +836,344 @@ async fn test_advanced_retransmission_heartbeat() {
 
     router.close().await.unwrap();
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_advanced_recovery_state() {
+    use std::time::Duration;
+
+    use zenoh::internal::ztimeout;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+    const PEER1_ENDPOINT: &str = "tcp/localhost:27057";
+
+    const ADVANCED_RECOVERY_STATE_KEYEXPR: &str = "test/advanced/recovery/state";
+
+    zenoh_util::init_log_from_env_or("error");
+
+    let recovery_state = std::env::temp_dir().join(format!(
+        "zenoh-ext-test-recovery-state-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&recovery_state);
+
+    let peer1 = {
+        let mut c = zenoh::Config::default();
+        c.listen
+            .endpoints
+            .set(vec![PEER1_ENDPOINT.parse::<EndPoint>().unwrap()])
+            .unwrap();
+        c.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let _ = c.set_mode(Some(WhatAmI::Peer));
+        let s = ztimeout!(zenoh::open(c)).unwrap();
+        tracing::info!("Peer (1) ZID: {}", s.zid());
+        s
+    };
+
+    let open_peer2 = || async {
+        let mut c = zenoh::Config::default();
+        c.connect
+            .endpoints
+            .set(vec![PEER1_ENDPOINT.parse::<EndPoint>().unwrap()])
+            .unwrap();
+        c.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let _ = c.set_mode(Some(WhatAmI::Peer));
+        let s = ztimeout!(zenoh::open(c)).unwrap();
+        tracing::info!("Peer (2) ZID: {}", s.zid());
+        s
+    };
+
+    let publ = ztimeout!(peer1
+        .declare_publisher(ADVANCED_RECOVERY_STATE_KEYEXPR)
+        .cache(CacheConfig::default().max_samples(10))
+        .sample_miss_detection(MissDetectionConfig::default()))
+    .unwrap();
+
+    let peer2 = open_peer2().await;
+    let sub = ztimeout!(peer2
+        .declare_subscriber(ADVANCED_RECOVERY_STATE_KEYEXPR)
+        .recovery(RecoveryConfig::default().heartbeat())
+        .recovery_state_file(&recovery_state))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    ztimeout!(publ.put("1")).unwrap();
+    ztimeout!(publ.put("2")).unwrap();
+
+    for expected in ["1", "2"] {
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.payload().try_to_string().unwrap().as_ref(), expected);
+        // The last sample is consumed but not committed
+        if expected == "1" {
+            sub.commit(&sample).unwrap();
+        }
+    }
+    assert!(recovery_state.exists());
+
+    // Restart the subscriber
+    drop(sub);
+    peer2.close().await.unwrap();
+
+    ztimeout!(publ.put("3")).unwrap();
+    ztimeout!(publ.put("4")).unwrap();
+
+    let peer2 = open_peer2().await;
+    let sub = ztimeout!(peer2
+        .declare_subscriber(ADVANCED_RECOVERY_STATE_KEYEXPR)
+        .recovery(RecoveryConfig::default().heartbeat())
+        .recovery_state_file(&recovery_state))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    ztimeout!(publ.put("5")).unwrap();
+
+    for expected in ["2", "3", "4", "5"] {
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.payload().try_to_string().unwrap().as_ref(), expected);
+    }
+    tokio::time::sleep(SLEEP).await;
+    assert!(sub.try_recv().unwrap().is_none());
+
+    publ.undeclare().await.unwrap();
+    drop(sub);
+
+    peer1.close().await.unwrap();
+    peer2.close().await.unwrap();
+    let _ = std::fs::remove_file(&recovery_state);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_advanced_cache_directory() {
+    use std::time::Duration;
+
+    use zenoh::internal::ztimeout;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+    const PEER1_ENDPOINT: &str = "tcp/localhost:27058";
+
+    const ADVANCED_CACHE_DIRECTORY_KEYEXPR: &str = "test/advanced/cache/directory";
+
+    zenoh_util::init_log_from_env_or("error");
+
+    let directory = std::env::temp_dir().join(format!(
+        "zenoh-ext-test-cache-directory-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&directory);
+
+    let peer1 = {
+        let mut c = zenoh::Config::default();
+        c.listen
+            .endpoints
+            .set(vec![PEER1_ENDPOINT.parse::<EndPoint>().unwrap()])
+            .unwrap();
+        c.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let _ = c.set_mode(Some(WhatAmI::Peer));
+        let s = ztimeout!(zenoh::open(c)).unwrap();
+        tracing::info!("Peer (1) ZID: {}", s.zid());
+        s
+    };
+
+    let cache = CacheConfig::default().max_samples(3).directory(&directory);
+    let publ = ztimeout!(peer1
+        .declare_publisher(ADVANCED_CACHE_DIRECTORY_KEYEXPR)
+        .cache(cache.clone())
+        .sample_miss_detection(MissDetectionConfig::default()))
+    .unwrap();
+    for value in ["1", "2", "3", "4"] {
+        ztimeout!(publ.put(value).attachment(value)).unwrap();
+    }
+    ztimeout!(publ.undeclare()).unwrap();
+    assert!(std::fs::read_dir(&directory).unwrap().next().is_some());
+
+    // The samples are served again after a restart
+    let publ = ztimeout!(peer1
+        .declare_publisher(ADVANCED_CACHE_DIRECTORY_KEYEXPR)
+        .cache(cache.clone())
+        .sample_miss_detection(MissDetectionConfig::default()))
+    .unwrap();
+    ztimeout!(publ.put("5").attachment("5")).unwrap();
+
+    // The directory can't be used by another cache at the same time
+    assert!(ztimeout!(peer1
+        .declare_publisher(ADVANCED_CACHE_DIRECTORY_KEYEXPR)
+        .cache(cache))
+    .is_err());
+
+    let peer2 = {
+        let mut c = zenoh::Config::default();
+        c.connect
+            .endpoints
+            .set(vec![PEER1_ENDPOINT.parse::<EndPoint>().unwrap()])
+            .unwrap();
+        c.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let _ = c.set_mode(Some(WhatAmI::Peer));
+        let s = ztimeout!(zenoh::open(c)).unwrap();
+        tracing::info!("Peer (2) ZID: {}", s.zid());
+        s
+    };
+
+    let sub = ztimeout!(peer2
+        .declare_subscriber(ADVANCED_CACHE_DIRECTORY_KEYEXPR)
+        .history(HistoryConfig::default()))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    let mut values = vec![];
+    for _ in 0..3 {
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.kind(), SampleKind::Put);
+        let value = sample.payload().try_to_string().unwrap().into_owned();
+        assert_eq!(
+            sample.attachment().unwrap().try_to_string().unwrap(),
+            value.as_str()
+        );
+        values.push(value);
+    }
+    values.sort();
+    assert_eq!(values, ["3", "4", "5"]);
+    assert!(sub.try_recv().unwrap().is_none());
+
+    publ.undeclare().await.unwrap();
+
+    peer1.close().await.unwrap();
+    peer2.close().await.unwrap();
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_advanced_acknowledgement() {
+    use std::time::Duration;
+
+    use zenoh::internal::ztimeout;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+    const HEARTBEAT_PERIOD: Duration = Duration::from_millis(500);
+    const PEER1_ENDPOINT: &str = "tcp/localhost:27059";
+
+    const ADVANCED_ACKNOWLEDGEMENT_KEYEXPR: &str = "test/advanced/acknowledgement";
+
+    zenoh_util::init_log_from_env_or("error");
+
+    let recovery_state = std::env::temp_dir().join(format!(
+        "zenoh-ext-test-acknowledgement-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&recovery_state);
+
+    let peer1 = {
+        let mut c = zenoh::Config::default();
+        c.listen
+            .endpoints
+            .set(vec![PEER1_ENDPOINT.parse::<EndPoint>().unwrap()])
+            .unwrap();
+        c.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let _ = c.set_mode(Some(WhatAmI::Peer));
+        let s = ztimeout!(zenoh::open(c)).unwrap();
+        tracing::info!("Peer (1) ZID: {}", s.zid());
+        s
+    };
+
+    let open_peer2 = || async {
+        let mut c = zenoh::Config::default();
+        c.connect
+            .endpoints
+            .set(vec![PEER1_ENDPOINT.parse::<EndPoint>().unwrap()])
+            .unwrap();
+        c.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let _ = c.set_mode(Some(WhatAmI::Peer));
+        let s = ztimeout!(zenoh::open(c)).unwrap();
+        tracing::info!("Peer (2) ZID: {}", s.zid());
+        s
+    };
+
+    let publ = ztimeout!(peer1
+        .declare_publisher(ADVANCED_ACKNOWLEDGEMENT_KEYEXPR)
+        .cache(CacheConfig::default().max_samples(4).max_age(2 * SLEEP))
+        .sample_miss_detection(MissDetectionConfig::default().heartbeat(HEARTBEAT_PERIOD))
+        .acknowledgement(AcknowledgementConfig::default()))
+    .unwrap();
+
+    let peer2 = open_peer2().await;
+    // A durable subscriber requires a recovery state file
+    assert!(ztimeout!(peer2
+        .declare_subscriber(ADVANCED_ACKNOWLEDGEMENT_KEYEXPR)
+        .recovery(RecoveryConfig::default().heartbeat())
+        .durable("billing"))
+    .is_err());
+    let sub = ztimeout!(peer2
+        .declare_subscriber(ADVANCED_ACKNOWLEDGEMENT_KEYEXPR)
+        .recovery(RecoveryConfig::default().heartbeat())
+        .recovery_state_file(&recovery_state)
+        .durable("billing"))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    for value in ["1", "2", "3"] {
+        ztimeout!(publ.put(value)).unwrap();
+    }
+    for expected in ["1", "2", "3"] {
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.payload().try_to_string().unwrap().as_ref(), expected);
+    }
+    tokio::time::sleep(2 * SLEEP).await;
+
+    // The samples are retained beyond their age while the subscriber is not running, but not
+    // beyond the maximum number of samples of the cache
+    drop(sub);
+    peer2.close().await.unwrap();
+    for value in ["4", "5", "6", "7", "8"] {
+        ztimeout!(publ.put(value)).unwrap();
+    }
+    tokio::time::sleep(3 * SLEEP).await;
+
+    let peer2 = open_peer2().await;
+    let sub = ztimeout!(peer2
+        .declare_subscriber(ADVANCED_ACKNOWLEDGEMENT_KEYEXPR)
+        .recovery(RecoveryConfig::default().heartbeat())
+        .recovery_state_file(&recovery_state)
+        .durable("billing"))
+    .unwrap();
+    // The samples delivered before the restart are not delivered again, and the sample evicted
+    // from the cache is missed
+    for expected in ["5", "6", "7", "8"] {
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.payload().try_to_string().unwrap().as_ref(), expected);
+    }
+    tokio::time::sleep(2 * SLEEP).await;
+    assert!(sub.try_recv().unwrap().is_none());
+
+    // Once acknowledged, the samples are evicted
+    let history = ztimeout!(peer2
+        .declare_subscriber(ADVANCED_ACKNOWLEDGEMENT_KEYEXPR)
+        .history(HistoryConfig::default()))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert!(history.try_recv().unwrap().is_none());
+    drop(history);
+
+    // Once deregistered, the samples are no more retained for the subscriber
+    ztimeout!(sub.deregister()).unwrap();
+    tokio::time::sleep(SLEEP).await;
+    for value in ["9", "10", "11"] {
+        ztimeout!(publ.put(value)).unwrap();
+    }
+    tokio::time::sleep(3 * SLEEP).await;
+    ztimeout!(publ.put("12")).unwrap();
+    let history = ztimeout!(peer2
+        .declare_subscriber(ADVANCED_ACKNOWLEDGEMENT_KEYEXPR)
+        .history(HistoryConfig::default()))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+    let sample = ztimeout!(history.recv_async()).unwrap();
+    assert_eq!(sample.payload().try_to_string().unwrap().as_ref(), "12");
+    assert!(history.try_recv().unwrap().is_none());
+
+    publ.undeclare().await.unwrap();
+
+    peer1.close().await.unwrap();
+    peer2.close().await.unwrap();
+    let _ = std::fs::remove_file(&recovery_state);
+}
@@ -0,0 +1,129 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "unstable")]
+use std::{sync::Arc, time::Duration};
+
+use zenoh::{
+    config::{EndPoint, WhatAmI},
+    internal::ztimeout,
+    Session,
+};
+use zenoh_ext::group::{Group, GroupEvent, Member};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const HUB_ENDPOINT: &str = "tcp/127.0.0.1:47460";
+
+async fn open_peer(listen: bool) -> Arc<Session> {
+    let mut c = zenoh::Config::default();
+    let endpoints = vec![HUB_ENDPOINT.parse::<EndPoint>().unwrap()];
+    if listen {
+        c.listen.endpoints.set(endpoints).unwrap();
+    } else {
+        c.connect.endpoints.set(endpoints).unwrap();
+    }
+    c.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let _ = c.set_mode(Some(WhatAmI::Peer));
+    Arc::new(ztimeout!(zenoh::open(c)).unwrap())
+}
+
+async fn wait_for_leader(group: &Group, mid: &str, epoch: u64) {
+    ztimeout!(async {
+        while !group
+            .elected_leader()
+            .await
+            .is_some_and(|l| l.mid.as_str() == mid && l.epoch == epoch)
+        {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+}
+
+async fn join(session: &Arc<Session>, mid: &str) -> Group {
+    let member = Member::new(mid).unwrap().info(format!("info of {mid}"));
+    ztimeout!(Group::join(session.clone(), "test/group", member)).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_group_election() {
+    zenoh_util::init_log_from_env_or("error");
+
+    let hub = open_peer(true).await;
+    let group_a = join(&hub, "a").await;
+    let events = group_a.subscribe().await;
+
+    let peer_b = open_peer(false).await;
+    let group_b = join(&peer_b, "b").await;
+    let peer_c = open_peer(false).await;
+    let group_c = join(&peer_c, "c").await;
+
+    for group in [&group_a, &group_b, &group_c] {
+        assert!(group.wait_for_view_size(3, TIMEOUT).await);
+    }
+    let view = group_a.view().await;
+    let b = view.iter().find(|m| m.id().as_str() == "b").unwrap();
+    assert!(format!("{b:?}").contains("info of b"));
+
+    let mut joined = vec![];
+    for _ in 0..2 {
+        match ztimeout!(events.recv_async()).unwrap() {
+            GroupEvent::Join(evt) => joined.push((evt.member.id().to_string(), evt.view_id)),
+            evt => panic!("Unexpected event: {evt:?}"),
+        }
+    }
+    joined.sort();
+    assert_eq!(joined[0].0, "b");
+    assert_eq!(joined[1].0, "c");
+    assert_eq!(group_a.view_id().await, 2);
+
+    // Election
+    let leadership_b = ztimeout!(group_b.campaign(2)).unwrap();
+    assert_eq!(leadership_b.epoch(), 1);
+    assert!(leadership_b.is_leader());
+    match ztimeout!(events.recv_async()).unwrap() {
+        GroupEvent::NewLeader(evt) => {
+            assert_eq!(evt.leader.mid.as_str(), "b");
+            assert_eq!(evt.leader.epoch, 1);
+        }
+        evt => panic!("Unexpected event: {evt:?}"),
+    }
+    for group in [&group_a, &group_c] {
+        wait_for_leader(group, "b", 1).await;
+    }
+
+    // No election while the leader is alive
+    assert!(tokio::time::timeout(3 * SLEEP, group_c.campaign(2))
+        .await
+        .is_err());
+
+    // Resignation
+    leadership_b.resign();
+    match ztimeout!(events.recv_async()).unwrap() {
+        GroupEvent::LeaderLost(evt) => assert_eq!(evt.leader.mid.as_str(), "b"),
+        evt => panic!("Unexpected event: {evt:?}"),
+    }
+    let leadership_c = ztimeout!(group_c.campaign(2)).unwrap();
+    assert_eq!(leadership_c.epoch(), 2);
+    wait_for_leader(&group_b, "c", 2).await;
+
+    // The leader gives up the leadership when its view drops below the quorum
+    drop(group_b);
+    ztimeout!(peer_b.close()).unwrap();
+    drop(group_a);
+    ztimeout!(leadership_c.lost());
+    assert!(!leadership_c.is_leader());
+    assert_eq!(group_c.size().await, 1);
+    let leadership_c = ztimeout!(group_c.campaign(1)).unwrap();
+    assert_eq!(leadership_c.epoch(), 3);
+}
@@ -0,0 +1,96 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "unstable")]
+use std::time::Duration;
+
+use zenoh::{
+    config::{EndPoint, WhatAmI},
+    internal::ztimeout,
+    Session,
+};
+use zenoh_ext::Lock;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const HUB_ENDPOINT: &str = "tcp/127.0.0.1:47461";
+const LOCK_NAME: &str = "test/lock";
+
+async fn open_peer(listen: bool) -> Session {
+    let mut c = zenoh::Config::default();
+    let endpoints = vec![HUB_ENDPOINT.parse::<EndPoint>().unwrap()];
+    if listen {
+        c.listen.endpoints.set(endpoints).unwrap();
+    } else {
+        c.connect.endpoints.set(endpoints).unwrap();
+    }
+    c.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let _ = c.set_mode(Some(WhatAmI::Peer));
+    ztimeout!(zenoh::open(c)).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_lock() {
+    zenoh_util::init_log_from_env_or("error");
+
+    let hub = open_peer(true).await;
+    let peer1 = open_peer(false).await;
+    let peer2 = open_peer(false).await;
+    tokio::time::sleep(SLEEP).await;
+
+    let lock0 = ztimeout!(Lock::new(&hub, LOCK_NAME)).unwrap().fencing(2);
+    let lock1 = ztimeout!(Lock::new(&peer1, LOCK_NAME)).unwrap().fencing(2);
+    let lock2 = ztimeout!(Lock::new(&peer2, LOCK_NAME))
+        .unwrap()
+        .ttl(2 * SLEEP)
+        .fencing(2);
+
+    // Mutual exclusion
+    let lease1 = ztimeout!(lock1.acquire()).unwrap();
+    assert!(lease1.is_valid());
+    tokio::time::sleep(SLEEP).await;
+    assert!(ztimeout!(lock0.try_acquire()).unwrap().is_none());
+    assert!(tokio::time::timeout(SLEEP, lock2.acquire()).await.is_err());
+
+    // Released on drop
+    let waiting = tokio::spawn(async move {
+        let lease = lock2.acquire().await.unwrap();
+        (lock2, lease)
+    });
+    tokio::time::sleep(SLEEP).await;
+    let fence1 = lease1.fence().unwrap();
+    drop(lease1);
+    let (lock2, lease2) = ztimeout!(waiting).unwrap();
+    assert!(lease2.fence().unwrap() > fence1);
+
+    // Expired after its time to live unless renewed
+    tokio::time::sleep(SLEEP).await;
+    lease2.renew().unwrap();
+    tokio::time::sleep(SLEEP + SLEEP / 2).await;
+    assert!(lease2.is_valid());
+    tokio::time::sleep(SLEEP).await;
+    assert!(!lease2.is_valid());
+    assert!(lease2.renew().is_err());
+    let lease0 = ztimeout!(lock0.acquire()).unwrap();
+    assert!(lease0.fence().unwrap() > lease2.fence().unwrap());
+    ztimeout!(lease0.release()).unwrap();
+
+    // Released when the session of the holder is closed
+    let lease2 = ztimeout!(lock2.acquire()).unwrap();
+    let fence2 = lease2.fence().unwrap();
+    std::mem::forget(lease2);
+    drop(lock2);
+    ztimeout!(peer2.close()).unwrap();
+    let lease1 = ztimeout!(lock1.acquire()).unwrap();
+    assert!(lease1.fence().unwrap() > fence2);
+}
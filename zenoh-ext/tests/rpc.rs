@@ -0,0 +1,220 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "unstable")]
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use zenoh::{internal::ztimeout, query::ConsolidationMode};
+use zenoh_ext::{
+    z_deserialize, z_serialize, CreditedQuery, CreditedReplies, RpcError, RpcErrorCode,
+    RpcSessionExt,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_rpc() {
+    zenoh_util::init_log_from_env_or("error");
+
+    let session = ztimeout!(zenoh::open(zenoh::Config::default())).unwrap();
+    let completed = Arc::new(AtomicBool::new(false));
+    let c = completed.clone();
+    let _service = ztimeout!(session
+        .declare_rpc_service("test/rpc/calculator")
+        .method("add", |(a, b): (i64, i64)| async move { Ok(a + b) })
+        .method("div", |(a, b): (i64, i64)| async move {
+            a.checked_div(b)
+                .ok_or_else(|| RpcError::new(RpcErrorCode::Application(42), "division by zero"))
+        })
+        .method("sleep", move |ms: u64| {
+            let c = c.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+                c.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+        }))
+    .unwrap();
+
+    let client = ztimeout!(session
+        .declare_rpc_client("test/rpc/calculator")
+        .timeout(Duration::from_secs(1)))
+    .unwrap();
+
+    let add = client.method::<(i64, i64), i64>("add").unwrap();
+    assert_eq!(ztimeout!(add.call(&(1, 2))).unwrap(), 3);
+
+    let div = client.method::<(i64, i64), i64>("div").unwrap();
+    assert_eq!(ztimeout!(div.call(&(6, 3))).unwrap(), 2);
+    let error = ztimeout!(div.call(&(6, 0))).unwrap_err();
+    assert_eq!(error.code(), RpcErrorCode::Application(42));
+    assert_eq!(error.message(), "division by zero");
+
+    let invalid = client.method::<String, i64>("add").unwrap();
+    let error = ztimeout!(invalid.call(&"1 + 2".into())).unwrap_err();
+    assert_eq!(error.code(), RpcErrorCode::InvalidRequest);
+
+    let missing = client.method::<(), ()>("sub").unwrap();
+    let error = ztimeout!(missing.call(&())).unwrap_err();
+    assert_eq!(error.code(), RpcErrorCode::MethodNotFound);
+
+    // Canceled after the timeout of the client
+    let sleep = client.method::<u64, ()>("sleep").unwrap();
+    ztimeout!(sleep.call(&10)).unwrap();
+    completed.store(false, Ordering::Relaxed);
+    let error = ztimeout!(sleep.call(&3000)).unwrap_err();
+    assert_eq!(error.code(), RpcErrorCode::Timeout);
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert!(!completed.load(Ordering::Relaxed));
+
+    let other = ztimeout!(session.declare_rpc_client("test/rpc/other")).unwrap();
+    let error = ztimeout!(other.method::<(), ()>("add").unwrap().call(&())).unwrap_err();
+    assert_eq!(error.code(), RpcErrorCode::Unavailable);
+    assert!(client.method::<(), ()>("a/b").is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_rpc_streaming() {
+    zenoh_util::init_log_from_env_or("error");
+
+    let session = ztimeout!(zenoh::open(zenoh::Config::default())).unwrap();
+    let sent = Arc::new(AtomicUsize::new(0));
+    let s = sent.clone();
+    let _service = ztimeout!(session
+        .declare_rpc_service("test/rpc/storage")
+        .streaming_method("export", move |count: u64, sender| {
+            let s = s.clone();
+            async move {
+                for i in 0..count {
+                    sender.send(&i).await?;
+                    s.fetch_add(1, Ordering::Relaxed);
+                }
+                Ok(())
+            }
+        })
+        .streaming_method("fail", |_: (), sender| async move {
+            sender.send(&0u64).await?;
+            Err(RpcError::new(RpcErrorCode::Internal, "storage failure"))
+        })
+        .streaming_method("slow", |count: u64, sender| async move {
+            for i in 0..count {
+                tokio::time::sleep(Duration::from_millis(400)).await;
+                sender.send(&i).await?;
+            }
+            Ok(())
+        }))
+    .unwrap();
+    let client = ztimeout!(session.declare_rpc_client("test/rpc/storage")).unwrap();
+
+    // The service sends at most a window ahead of the consumed responses
+    let export = client.method::<u64, u64>("export").unwrap();
+    let mut stream = ztimeout!(export.call_streaming(&100, 4)).unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(sent.load(Ordering::Relaxed), 4);
+    for i in 0..100 {
+        assert_eq!(ztimeout!(stream.next()).unwrap().unwrap(), i);
+        assert!(sent.load(Ordering::Relaxed) <= i as usize + 1 + 4);
+    }
+    assert!(ztimeout!(stream.next()).is_none());
+
+    let fail = client.method::<(), u64>("fail").unwrap();
+    let mut stream = ztimeout!(fail.call_streaming(&(), 4)).unwrap();
+    assert_eq!(ztimeout!(stream.next()).unwrap().unwrap(), 0);
+    let error = ztimeout!(stream.next()).unwrap().unwrap_err();
+    assert_eq!(error.code(), RpcErrorCode::Internal);
+    assert!(ztimeout!(stream.next()).is_none());
+
+    // The streaming methods are subject to the stream timeout, not to the timeout of the calls
+    let client = ztimeout!(session
+        .declare_rpc_client("test/rpc/storage")
+        .timeout(Duration::from_millis(500))
+        .stream_timeout(Duration::from_secs(2)))
+    .unwrap();
+    let slow = client.method::<u64, u64>("slow").unwrap();
+    let mut stream = ztimeout!(slow.call_streaming(&3, 4)).unwrap();
+    for i in 0..3 {
+        assert_eq!(ztimeout!(stream.next()).unwrap().unwrap(), i);
+    }
+    assert!(ztimeout!(stream.next()).is_none());
+    let mut stream = ztimeout!(slow.call_streaming(&10, 4)).unwrap();
+    let error = loop {
+        if let Err(error) = ztimeout!(stream.next()).unwrap() {
+            break error;
+        }
+    };
+    assert_eq!(error.code(), RpcErrorCode::Timeout);
+    assert!(ztimeout!(stream.next()).is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_credited_query() {
+    zenoh_util::init_log_from_env_or("error");
+
+    let session = ztimeout!(zenoh::open(zenoh::Config::default())).unwrap();
+    let queryable = ztimeout!(session.declare_queryable("test/credits")).unwrap();
+    let sent = Arc::new(AtomicUsize::new(0));
+    let failed = Arc::new(AtomicBool::new(false));
+    let (s, f) = (sent.clone(), failed.clone());
+    let server = session.clone();
+    tokio::spawn(async move {
+        while let Ok(query) = queryable.recv_async().await {
+            let mut query = CreditedQuery::new(&server, query)
+                .await
+                .unwrap()
+                .credit_timeout(Duration::from_secs(3));
+            for i in 0..10u32 {
+                if query.reply("test/credits", z_serialize(&i)).await.is_err() {
+                    f.store(true, Ordering::Relaxed);
+                    break;
+                }
+                s.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    });
+
+    let querier = ztimeout!(session
+        .declare_querier("test/credits")
+        .consolidation(ConsolidationMode::None))
+    .unwrap();
+    let mut replies = ztimeout!(CreditedReplies::get(&session, &querier, 2)).unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(sent.load(Ordering::Relaxed), 2);
+    for i in 0..10u32 {
+        let reply = ztimeout!(replies.recv_async()).unwrap();
+        let value: u32 = z_deserialize(reply.result().unwrap().payload()).unwrap();
+        assert_eq!(value, i);
+    }
+    assert!(ztimeout!(replies.recv_async()).is_err());
+    assert!(!failed.load(Ordering::Relaxed));
+
+    // The replies fail when the querier does not grant credits in time
+    sent.store(0, Ordering::Relaxed);
+    let _replies = ztimeout!(CreditedReplies::get(&session, &querier, 2)).unwrap();
+    tokio::time::sleep(Duration::from_secs(4)).await;
+    assert_eq!(sent.load(Ordering::Relaxed), 2);
+    assert!(failed.load(Ordering::Relaxed));
+
+    // Without flow control
+    let replies = ztimeout!(querier.get()).unwrap();
+    let mut count = 0;
+    while ztimeout!(replies.recv_async()).is_ok() {
+        count += 1;
+    }
+    assert_eq!(count, 10);
+}
@@ -0,0 +1,117 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::HashMap;
+
+use zenoh_ext::{z_deserialize, z_serialize, Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Position {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Named(String, u16);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Unit;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Generic<T> {
+    values: Vec<T>,
+    labels: HashMap<String, T>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+enum Command {
+    Stop = 0,
+    Move(Position) = 1,
+    Rename { name: String, unit: Unit } = 7,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Level {
+    Low = -1,
+    High = 1,
+}
+
+macro_rules! serialize_deserialize {
+    ($ty:ty, $expr:expr) => {
+        let expr: &$ty = &$expr;
+        let payload = z_serialize(expr);
+        let output = z_deserialize::<$ty>(&payload).unwrap();
+        assert_eq!(*expr, output);
+    };
+}
+
+#[test]
+fn derive_serialization() {
+    serialize_deserialize!(Position, Position { x: 1.5, y: -2.0 });
+    serialize_deserialize!(Named, Named("test".into(), 42));
+    serialize_deserialize!(Unit, Unit);
+    serialize_deserialize!(
+        Generic<i32>,
+        Generic {
+            values: vec![1, 2, 3],
+            labels: HashMap::from([("one".into(), 1)]),
+        }
+    );
+    serialize_deserialize!(Command, Command::Stop);
+    serialize_deserialize!(Command, Command::Move(Position { x: 0.0, y: 1.0 }));
+    serialize_deserialize!(
+        Command,
+        Command::Rename {
+            name: "robot".into(),
+            unit: Unit
+        }
+    );
+    serialize_deserialize!(Level, Level::Low);
+}
+
+#[test]
+fn derive_binary_format() {
+    // The format of the tuple of the fields
+    assert_eq!(
+        z_serialize(&Position { x: 1.5, y: -2.0 }).to_bytes(),
+        z_serialize(&(1.5f64, -2.0f64)).to_bytes()
+    );
+    assert_eq!(
+        z_serialize(&Named("test".into(), 500)).to_bytes(),
+        vec![4, 116, 101, 115, 116, 244, 1]
+    );
+    assert!(z_serialize(&Unit).is_empty());
+
+    // The discriminant typed by the `repr` of the enum, then the fields
+    assert_eq!(z_serialize(&Command::Stop).to_bytes(), vec![0]);
+    assert_eq!(
+        z_serialize(&Command::Move(Position { x: 1.5, y: -2.0 })).to_bytes(),
+        z_serialize(&(1u8, 1.5f64, -2.0f64)).to_bytes()
+    );
+    assert_eq!(
+        z_serialize(&Command::Rename {
+            name: "a".into(),
+            unit: Unit
+        })
+        .to_bytes(),
+        vec![7, 1, 97]
+    );
+    assert_eq!(
+        z_serialize(&Level::Low).to_bytes(),
+        vec![255, 255, 255, 255]
+    );
+
+    // Unknown discriminant
+    assert!(z_deserialize::<Command>(&z_serialize(&2u8)).is_err());
+}
@@ -0,0 +1,115 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "unstable")]
+use std::time::Duration;
+
+use zenoh::{bytes::Encoding, internal::ztimeout, sample::SampleKind};
+use zenoh_ext::{
+    z_serialize, Deserialize, Serialize, TypedError, TypedPublisher, TypedSubscriber, ZSchema,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Position {
+    x: f64,
+    y: f64,
+}
+
+impl ZSchema for Position {
+    const NAME: &'static str = "test/Position";
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PositionV2 {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl ZSchema for PositionV2 {
+    const NAME: &'static str = "test/Position";
+    const VERSION: u32 = 2;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_typed_pub_sub() {
+    const KEY_EXPR: &str = "test/typed/position";
+
+    zenoh_util::init_log_from_env_or("error");
+
+    let mut config = zenoh::Config::default();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = ztimeout!(zenoh::open(config)).unwrap();
+
+    let subscriber =
+        TypedSubscriber::<Position>::new(ztimeout!(session.declare_subscriber(KEY_EXPR)).unwrap());
+    let subscriber_v2 = TypedSubscriber::<PositionV2>::new(
+        ztimeout!(session.declare_subscriber(KEY_EXPR)).unwrap(),
+    );
+    let publisher =
+        TypedPublisher::<Position>::new(ztimeout!(session.declare_publisher(KEY_EXPR)).unwrap());
+    assert_eq!(
+        Position::encoding().to_string(),
+        "zenoh/serialized;test/Position"
+    );
+    assert_eq!(
+        PositionV2::encoding().to_string(),
+        "zenoh/serialized;test/Position@2"
+    );
+
+    let position = Position { x: 1.0, y: 2.0 };
+    ztimeout!(publisher.put(&position)).unwrap();
+    let sample = ztimeout!(subscriber.recv_async()).unwrap();
+    assert_eq!(sample.value(), Some(&position));
+    assert_eq!(*sample.sample().encoding(), Position::encoding());
+
+    // Another version of the type
+    match ztimeout!(subscriber_v2.recv_async()) {
+        Err(TypedError::SchemaMismatch { expected, sample }) => {
+            assert_eq!(expected, PositionV2::encoding());
+            assert_eq!(*sample.encoding(), Position::encoding());
+        }
+        result => panic!("Unexpected result: {result:?}"),
+    }
+
+    // A payload not matching its schema
+    ztimeout!(session
+        .put(KEY_EXPR, z_serialize(&1.0f64))
+        .encoding(Position::encoding()))
+    .unwrap();
+    assert!(matches!(
+        ztimeout!(subscriber.recv_async()),
+        Err(TypedError::Deserialize { .. })
+    ));
+    assert!(subscriber_v2.try_recv().is_err());
+
+    // A payload without schema
+    ztimeout!(session
+        .put(KEY_EXPR, z_serialize(&(1.0f64, 2.0f64)))
+        .encoding(Encoding::ZENOH_SERIALIZED))
+    .unwrap();
+    assert!(matches!(
+        ztimeout!(subscriber.recv_async()),
+        Err(TypedError::SchemaMismatch { .. })
+    ));
+
+    ztimeout!(publisher.delete()).unwrap();
+    let sample = ztimeout!(subscriber.recv_async()).unwrap();
+    assert_eq!(sample.sample().kind(), SampleKind::Delete);
+    assert!(sample.value().is_none());
+
+    ztimeout!(publisher.undeclare()).unwrap();
+    ztimeout!(subscriber.undeclare()).unwrap();
+}
@@ -26,6 +26,8 @@ use zenoh_protocol::{core::WireExpr, network::NetworkMessage};
 use zenoh_transport::{
     TransportEventHandler, TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
 };
+#[cfg(feature = "unstable")]
+use {zenoh_config::wrappers::ZenohId, zenoh_protocol::core::EntityId};
 
 use crate as zenoh;
 use crate::{
@@ -60,6 +62,8 @@ pub static KE_EMPTY: &keyexpr = ke!("_");
 static KE_EMPTY: &keyexpr = ke!("_");
 #[cfg(feature = "internal")]
 pub static KE_STAR: &keyexpr = ke!("*");
+#[cfg(all(feature = "unstable", not(feature = "internal")))]
+static KE_STAR: &keyexpr = ke!("*");
 #[cfg(feature = "internal")]
 pub static KE_STARSTAR: &keyexpr = ke!("**");
 #[cfg(not(feature = "internal"))]
@@ -68,6 +72,45 @@ static KE_STARSTAR: &keyexpr = ke!("**");
 static KE_SESSION: &keyexpr = ke!("session");
 static KE_TRANSPORT_UNICAST: &keyexpr = ke!("transport/unicast");
 static KE_LINK: &keyexpr = ke!("link");
+#[cfg(feature = "unstable")]
+pub(crate) static KE_PUBLISHER: &keyexpr = ke!("publisher");
+#[cfg(feature = "unstable")]
+pub(crate) static KE_QUERIER: &keyexpr = ke!("querier");
+
+/// Key expression of the liveliness token announcing a publisher or querier of this session:
+/// `@/<zid>/session/<publisher|querier>/<eid>/<key_expr>`.
+#[cfg(feature = "unstable")]
+pub(crate) fn announcement_key_expr(
+    zid: &ZenohId,
+    kind: &keyexpr,
+    eid: EntityId,
+    key_expr: &keyexpr,
+) -> ZResult<KeyExpr<'static>> {
+    let zid = zid.to_string();
+    let eid = eid.to_string();
+    Ok((KE_AT / keyexpr::new(&zid)? / KE_SESSION / kind / keyexpr::new(&eid)? / key_expr).into())
+}
+
+/// Key expression matching the announcements of all the publishers or queriers
+/// whose key expression intersects with `key_expr`.
+#[cfg(feature = "unstable")]
+pub(crate) fn announcements_key_expr(kind: &keyexpr, key_expr: &keyexpr) -> KeyExpr<'static> {
+    (KE_AT / KE_STAR / KE_SESSION / kind / KE_STAR / key_expr).into()
+}
+
+/// Return true if the announcement `key_expr` comes from a session allowed by `origin`,
+/// `zid` being the id of the local session.
+#[cfg(feature = "unstable")]
+pub(crate) fn is_announced_from(key_expr: &keyexpr, zid: &str, origin: Locality) -> bool {
+    let Some(announcer) = key_expr.as_str().split('/').nth(1) else {
+        return false;
+    };
+    match origin {
+        Locality::Any => true,
+        Locality::SessionLocal => announcer == zid,
+        Locality::Remote => announcer != zid,
+    }
+}
 
 pub(crate) fn init(session: WeakSession) {
     if let Ok(own_zid) = keyexpr::new(&session.zid().to_string()) {
@@ -25,3 +25,5 @@ pub(crate) mod sample;
 pub(crate) mod scouting;
 pub(crate) mod session;
 pub(crate) mod subscriber;
+#[cfg(feature = "unstable")]
+pub(crate) mod transport_events_listener;
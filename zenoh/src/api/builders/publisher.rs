@@ -324,6 +324,12 @@ pub struct PublisherBuilder<'a, 'b> {
     pub destination: Locality,
     #[cfg(not(feature = "internal"))]
     pub(crate) destination: Locality,
+    #[cfg(feature = "internal")]
+    #[cfg(feature = "unstable")]
+    pub announce: bool,
+    #[cfg(not(feature = "internal"))]
+    #[cfg(feature = "unstable")]
+    pub(crate) announce: bool,
 }
 
 impl Clone for PublisherBuilder<'_, '_> {
@@ -341,6 +347,8 @@ impl Clone for PublisherBuilder<'_, '_> {
             #[cfg(feature = "unstable")]
             reliability: self.reliability,
             destination: self.destination,
+            #[cfg(feature = "unstable")]
+            announce: self.announce,
         }
     }
 }
@@ -449,6 +457,18 @@ impl PublisherBuilder<'_, '_> {
             ..self
         }
     }
+
+    /// Announces this publisher, so that the matching [`Subscribers`](crate::pubsub::Subscriber)
+    /// can detect it with their [`matching_status`](crate::pubsub::Subscriber::matching_status)
+    /// and [`matching_listener`](crate::pubsub::Subscriber::matching_listener).
+    ///
+    /// Publishers are not announced by default, as announcing them declares a liveliness token
+    /// for each of them.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn announce(self, announce: bool) -> Self {
+        Self { announce, ..self }
+    }
 }
 
 impl<'b> Resolvable for PublisherBuilder<'_, 'b> {
@@ -462,10 +482,12 @@ impl Wait for PublisherBuilder<'_, '_> {
         if !key_expr.is_fully_optimized(&self.session.0) {
             key_expr = self.session.declare_keyexpr(key_expr).wait()?;
         }
-        let id = self
-            .session
-            .0
-            .declare_publisher_inner(key_expr.clone(), self.destination)?;
+        let id = self.session.0.declare_publisher_inner(
+            key_expr.clone(),
+            self.destination,
+            #[cfg(feature = "unstable")]
+            self.announce,
+        )?;
         Ok(Publisher {
             session: self.session.downgrade(),
             id,
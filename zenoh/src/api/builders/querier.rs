@@ -26,7 +26,7 @@ use zenoh_result::ZResult;
 
 use super::sample::QoSBuilderTrait;
 #[cfg(feature = "unstable")]
-use crate::api::query::ReplyKeyExpr;
+use crate::api::query::{CancellationToken, ReplyKeyExpr};
 #[cfg(feature = "unstable")]
 use crate::api::sample::SourceInfo;
 #[cfg(feature = "unstable")]
@@ -83,6 +83,8 @@ pub struct QuerierBuilder<'a, 'b> {
     pub(crate) timeout: Duration,
     #[cfg(feature = "unstable")]
     pub(crate) accept_replies: ReplyKeyExpr,
+    #[cfg(feature = "unstable")]
+    pub(crate) announce: bool,
 }
 
 #[zenoh_macros::internal_trait]
@@ -148,6 +150,18 @@ impl QuerierBuilder<'_, '_> {
             ..self
         }
     }
+
+    /// Announces this querier, so that the matching [`Queryables`](crate::query::Queryable)
+    /// can detect it with their [`matching_status`](crate::query::Queryable::matching_status)
+    /// and [`matching_listener`](crate::query::Queryable::matching_listener).
+    ///
+    /// Queriers are not announced by default, as announcing them declares a liveliness token
+    /// for each of them.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn announce(self, announce: bool) -> Self {
+        Self { announce, ..self }
+    }
 }
 
 impl<'b> Resolvable for QuerierBuilder<'_, 'b> {
@@ -160,10 +174,11 @@ impl Wait for QuerierBuilder<'_, '_> {
         if !key_expr.is_fully_optimized(&self.session.0) {
             key_expr = self.session.declare_keyexpr(key_expr).wait()?;
         }
-        let id = self
-            .session
-            .0
-            .declare_querier_inner(key_expr.clone(), self.destination)?;
+        let id = self.session.0.declare_querier_inner(
+            key_expr.clone(),
+            self.destination,
+            self.announce,
+        )?;
         Ok(Querier {
             session: self.session.downgrade(),
             id,
@@ -226,6 +241,8 @@ pub struct QuerierGetBuilder<'a, 'b, Handler> {
     pub(crate) attachment: Option<ZBytes>,
     #[cfg(feature = "unstable")]
     pub(crate) source_info: SourceInfo,
+    #[cfg(feature = "unstable")]
+    pub(crate) cancellation_token: Option<CancellationToken>,
 }
 
 #[zenoh_macros::internal_trait]
@@ -361,6 +378,8 @@ impl<'a, 'b> QuerierGetBuilder<'a, 'b, DefaultHandler> {
             attachment,
             #[cfg(feature = "unstable")]
             source_info,
+            #[cfg(feature = "unstable")]
+            cancellation_token,
             handler: _,
         } = self;
         QuerierGetBuilder {
@@ -370,6 +389,8 @@ impl<'a, 'b> QuerierGetBuilder<'a, 'b, DefaultHandler> {
             attachment,
             #[cfg(feature = "unstable")]
             source_info,
+            #[cfg(feature = "unstable")]
+            cancellation_token,
             handler,
         }
     }
@@ -398,6 +419,14 @@ impl<'b, Handler> QuerierGetBuilder<'_, 'b, Handler> {
         self.parameters = parameters.into();
         self
     }
+
+    /// Set a [`CancellationToken`] to cancel the query before all the replies are received.
+    #[inline]
+    #[zenoh_macros::unstable]
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
 }
 
 impl<Handler> Resolvable for QuerierGetBuilder<'_, '_, Handler>
@@ -437,6 +466,8 @@ where
                 self.attachment,
                 #[cfg(feature = "unstable")]
                 self.source_info,
+                #[cfg(feature = "unstable")]
+                self.cancellation_token,
                 callback,
             )
             .map(|_| receiver)
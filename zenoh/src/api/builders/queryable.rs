@@ -208,14 +208,21 @@ where
 {
     fn wait(self) -> <Self as Resolvable>::To {
         let session = self.session;
+        let key_expr = self.key_expr?;
         let (callback, receiver) = self.handler.into_handler();
         session
             .0
-            .declare_queryable_inner(&self.key_expr?, self.complete, self.origin, callback)
+            .declare_queryable_inner(&key_expr, self.complete, self.origin, callback)
             .map(|qable_state| Queryable {
                 inner: QueryableInner {
                     session: self.session.downgrade(),
                     id: qable_state.id,
+                    #[cfg(feature = "unstable")]
+                    key_expr: key_expr.into_owned(),
+                    #[cfg(feature = "unstable")]
+                    origin: self.origin,
+                    #[cfg(feature = "unstable")]
+                    matching_listeners: Default::default(),
                     undeclare_on_drop: true,
                 },
                 handler: receiver,
@@ -225,6 +225,10 @@ where
                     id: sub_state.id,
                     key_expr: sub_state.key_expr.clone(),
                     kind: SubscriberKind::Subscriber,
+                    #[cfg(feature = "unstable")]
+                    origin: self.origin,
+                    #[cfg(feature = "unstable")]
+                    matching_listeners: Default::default(),
                     undeclare_on_drop: true,
                 },
                 handler: receiver,
@@ -0,0 +1,238 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    future::{IntoFuture, Ready},
+    sync::Arc,
+};
+
+use zenoh_core::{Resolvable, Wait};
+use zenoh_result::ZResult;
+
+use crate::api::{
+    connectivity::{TransportEvent, TransportEventsListener, TransportEventsListenerInner},
+    handlers::{Callback, DefaultHandler, IntoHandler},
+    session::WeakSession,
+};
+
+/// A builder for initializing a [`TransportEventsListener`], returned by
+/// [`SessionInfo::transport_events()`](crate::session::SessionInfo::transport_events).
+#[zenoh_macros::unstable]
+#[must_use = "Resolvables do nothing unless you resolve them using `.await` or `zenoh::Wait::wait`"]
+pub struct TransportEventsListenerBuilder<'a, Handler, const BACKGROUND: bool = false> {
+    pub(crate) session: &'a WeakSession,
+    pub(crate) history: bool,
+    pub(crate) handler: Handler,
+}
+
+#[zenoh_macros::unstable]
+impl<'a> TransportEventsListenerBuilder<'a, DefaultHandler> {
+    /// Receive the transport events with a callback.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session
+    ///     .info()
+    ///     .transport_events()
+    ///     .callback(|event| println!("{:?}", event))
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn callback<F>(
+        self,
+        callback: F,
+    ) -> TransportEventsListenerBuilder<'a, Callback<TransportEvent>>
+    where
+        F: Fn(TransportEvent) + Send + Sync + 'static,
+    {
+        self.with(Callback::new(Arc::new(callback)))
+    }
+
+    /// Receive the transport events with a mutable callback.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let mut n = 0;
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session
+    ///     .info()
+    ///     .transport_events()
+    ///     .callback_mut(move |_event| { n += 1; })
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn callback_mut<F>(
+        self,
+        callback: F,
+    ) -> TransportEventsListenerBuilder<'a, Callback<TransportEvent>>
+    where
+        F: FnMut(TransportEvent) + Send + Sync + 'static,
+    {
+        self.callback(crate::api::handlers::locked(callback))
+    }
+
+    /// Receive the transport events with a [`Handler`](IntoHandler).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session
+    ///     .info()
+    ///     .transport_events()
+    ///     .with(flume::bounded(32))
+    ///     .await
+    ///     .unwrap();
+    /// while let Ok(event) = listener.recv_async().await {
+    ///     println!("{:?}", event);
+    /// }
+    /// # }
+    /// ```
+    #[inline]
+    pub fn with<Handler>(self, handler: Handler) -> TransportEventsListenerBuilder<'a, Handler>
+    where
+        Handler: IntoHandler<TransportEvent>,
+    {
+        TransportEventsListenerBuilder {
+            session: self.session,
+            history: self.history,
+            handler,
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<'a> TransportEventsListenerBuilder<'a, Callback<TransportEvent>> {
+    /// Register the listener callback to be run in background until the session is closed.
+    ///
+    /// Background builder doesn't return a `TransportEventsListener` object anymore.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// // no need to assign and keep a variable with a background listener
+    /// session
+    ///     .info()
+    ///     .transport_events()
+    ///     .callback(|event| println!("{:?}", event))
+    ///     .background()
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn background(self) -> TransportEventsListenerBuilder<'a, Callback<TransportEvent>, true> {
+        TransportEventsListenerBuilder {
+            session: self.session,
+            history: self.history,
+            handler: self.handler,
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler, const BACKGROUND: bool> TransportEventsListenerBuilder<'_, Handler, BACKGROUND> {
+    /// Report the transports and links already opened when the listener is declared, as
+    /// [`Opened`](TransportEvent::Opened) and [`LinkAdded`](TransportEvent::LinkAdded) events.
+    /// The events occurring meanwhile are reported after them, each transport and link being
+    /// reported opened once.
+    #[inline]
+    pub fn history(mut self, history: bool) -> Self {
+        self.history = history;
+        self
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> Resolvable for TransportEventsListenerBuilder<'_, Handler>
+where
+    Handler: IntoHandler<TransportEvent> + Send,
+    Handler::Handler: Send,
+{
+    type To = ZResult<TransportEventsListener<Handler::Handler>>;
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> Wait for TransportEventsListenerBuilder<'_, Handler>
+where
+    Handler: IntoHandler<TransportEvent> + Send,
+    Handler::Handler: Send,
+{
+    fn wait(self) -> <Self as Resolvable>::To {
+        let (callback, handler) = self.handler.into_handler();
+        let id = self
+            .session
+            .declare_transport_events_listener_inner(callback, self.history)?;
+        Ok(TransportEventsListener {
+            inner: TransportEventsListenerInner {
+                session: self.session.clone(),
+                id,
+                undeclare_on_drop: true,
+            },
+            handler,
+        })
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> IntoFuture for TransportEventsListenerBuilder<'_, Handler>
+where
+    Handler: IntoHandler<TransportEvent> + Send,
+    Handler::Handler: Send,
+{
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
+
+#[zenoh_macros::unstable]
+impl Resolvable for TransportEventsListenerBuilder<'_, Callback<TransportEvent>, true> {
+    type To = ZResult<()>;
+}
+
+#[zenoh_macros::unstable]
+impl Wait for TransportEventsListenerBuilder<'_, Callback<TransportEvent>, true> {
+    fn wait(self) -> <Self as Resolvable>::To {
+        self.session
+            .declare_transport_events_listener_inner(self.handler, self.history)?;
+        Ok(())
+    }
+}
+
+#[zenoh_macros::unstable]
+impl IntoFuture for TransportEventsListenerBuilder<'_, Callback<TransportEvent>, true> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
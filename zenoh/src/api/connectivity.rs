@@ -0,0 +1,518 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{
+    fmt,
+    future::{IntoFuture, Ready},
+    sync::{Arc, Mutex},
+};
+
+use tracing::error;
+use zenoh_config::wrappers::ZenohId;
+use zenoh_core::{zcondfeat, zlock, Resolvable, Wait};
+use zenoh_protocol::{
+    core::{Locator, WhatAmI},
+    network::NetworkMessage,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    multicast::TransportMulticast, unicast::TransportUnicast, TransportEventHandler,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
+};
+
+use super::{
+    handlers::Callback,
+    session::{UndeclarableSealed, WeakSession},
+    Id,
+};
+
+/// A transport between the current zenoh [`Session`](crate::Session) and a remote zenoh node.
+#[zenoh_macros::unstable]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transport {
+    pub(crate) zid: ZenohId,
+    pub(crate) whatami: WhatAmI,
+    pub(crate) is_qos: bool,
+    pub(crate) is_multilink: bool,
+    pub(crate) is_lowlatency: bool,
+    pub(crate) is_shm: bool,
+    pub(crate) is_multicast: bool,
+}
+
+#[zenoh_macros::unstable]
+impl Transport {
+    pub(crate) fn new_unicast(peer: &TransportPeer, transport: &TransportUnicast) -> Self {
+        Transport {
+            zid: peer.zid.into(),
+            whatami: peer.whatami,
+            is_qos: peer.is_qos,
+            is_multilink: transport.is_multilink().unwrap_or(false),
+            is_lowlatency: transport.is_lowlatency().unwrap_or(false),
+            is_shm: zcondfeat!("shared-memory", peer.is_shm, false),
+            is_multicast: false,
+        }
+    }
+
+    pub(crate) fn new_multicast(peer: &TransportPeer) -> Self {
+        Transport {
+            zid: peer.zid.into(),
+            whatami: peer.whatami,
+            is_qos: peer.is_qos,
+            is_multilink: false,
+            is_lowlatency: false,
+            is_shm: zcondfeat!("shared-memory", peer.is_shm, false),
+            is_multicast: true,
+        }
+    }
+
+    /// Return the [`ZenohId`] of the remote zenoh node.
+    pub fn zid(&self) -> ZenohId {
+        self.zid
+    }
+
+    /// Return whether the remote zenoh node is a router, a peer or a client.
+    pub fn whatami(&self) -> WhatAmI {
+        self.whatami
+    }
+
+    /// Return true if QoS was negotiated on this transport.
+    pub fn is_qos(&self) -> bool {
+        self.is_qos
+    }
+
+    /// Return true if this transport may aggregate several links.
+    pub fn is_multilink(&self) -> bool {
+        self.is_multilink
+    }
+
+    /// Return true if the low latency transport was negotiated.
+    pub fn is_lowlatency(&self) -> bool {
+        self.is_lowlatency
+    }
+
+    /// Return true if shared memory was negotiated on this transport.
+    pub fn is_shm(&self) -> bool {
+        self.is_shm
+    }
+
+    /// Return true if the remote zenoh node is reached through a multicast group.
+    pub fn is_multicast(&self) -> bool {
+        self.is_multicast
+    }
+}
+
+/// A link of a [`Transport`].
+#[zenoh_macros::unstable]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Link {
+    pub(crate) src: Locator,
+    pub(crate) dst: Locator,
+    pub(crate) mtu: u16,
+    pub(crate) is_streamed: bool,
+    pub(crate) interfaces: Vec<String>,
+}
+
+#[zenoh_macros::unstable]
+impl Link {
+    /// Return the local [`Locator`] of the link.
+    pub fn src(&self) -> &Locator {
+        &self.src
+    }
+
+    /// Return the remote [`Locator`] of the link.
+    pub fn dst(&self) -> &Locator {
+        &self.dst
+    }
+
+    /// Return the protocol of the link, e.g. `tcp` or `udp`.
+    pub fn protocol(&self) -> &str {
+        self.dst.protocol().as_str()
+    }
+
+    /// Return the MTU of the link.
+    pub fn mtu(&self) -> u16 {
+        self.mtu
+    }
+
+    /// Return true if the link is stream oriented, e.g. over TCP.
+    pub fn is_streamed(&self) -> bool {
+        self.is_streamed
+    }
+
+    /// Return the network interfaces the link is bound to.
+    pub fn interfaces(&self) -> &[String] {
+        &self.interfaces
+    }
+}
+
+#[zenoh_macros::unstable]
+impl From<zenoh_link::Link> for Link {
+    fn from(link: zenoh_link::Link) -> Self {
+        Link {
+            src: link.src,
+            dst: link.dst,
+            mtu: link.mtu,
+            is_streamed: link.is_streamed,
+            interfaces: link.interfaces,
+        }
+    }
+}
+
+/// A change of the connectivity of the current zenoh [`Session`](crate::Session), received by
+/// a [`TransportEventsListener`].
+///
+/// A transport is reported [`Opened`](TransportEvent::Opened) before its links are reported
+/// [`LinkAdded`](TransportEvent::LinkAdded), and [`Closed`](TransportEvent::Closed) once it has
+/// no more links.
+#[zenoh_macros::unstable]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransportEvent {
+    Opened(Transport),
+    Closed(Transport),
+    LinkAdded(Transport, Link),
+    LinkRemoved(Transport, Link),
+}
+
+#[zenoh_macros::unstable]
+impl TransportEvent {
+    /// Return the [`Transport`] this event relates to.
+    pub fn transport(&self) -> &Transport {
+        match self {
+            TransportEvent::Opened(transport)
+            | TransportEvent::Closed(transport)
+            | TransportEvent::LinkAdded(transport, _)
+            | TransportEvent::LinkRemoved(transport, _) => transport,
+        }
+    }
+
+    /// Return the [`Link`] this event relates to, if any.
+    pub fn link(&self) -> Option<&Link> {
+        match self {
+            TransportEvent::Opened(_) | TransportEvent::Closed(_) => None,
+            TransportEvent::LinkAdded(_, link) | TransportEvent::LinkRemoved(_, link) => Some(link),
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+pub(crate) struct TransportEventsListenerState {
+    pub(crate) id: Id,
+    pub(crate) callback: Callback<TransportEvent>,
+    /// The events received while the history is reported, if it is being reported.
+    pub(crate) pending: Mutex<Option<Vec<TransportEvent>>>,
+}
+
+#[zenoh_macros::unstable]
+impl TransportEventsListenerState {
+    pub(crate) fn new(id: Id, callback: Callback<TransportEvent>, history: bool) -> Self {
+        TransportEventsListenerState {
+            id,
+            callback,
+            pending: Mutex::new(history.then(Vec::new)),
+        }
+    }
+
+    /// Reports an event, or queues it if the history is being reported.
+    pub(crate) fn call(&self, event: TransportEvent) {
+        let mut pending = zlock!(self.pending);
+        if let Some(pending) = pending.as_mut() {
+            pending.push(event);
+            return;
+        }
+        drop(pending);
+        self.callback.call(event);
+    }
+
+    /// Reports the `history` events, then the events queued meanwhile, dropping the ones
+    /// superseded by the history: the opening of the transports and links it already reports,
+    /// and the closing of the ones it does not report.
+    pub(crate) fn report_history(&self, history: Vec<TransportEvent>) {
+        let mut transports = vec![];
+        let mut links = vec![];
+        for event in history {
+            match &event {
+                TransportEvent::Opened(transport) => transports.push(transport.clone()),
+                TransportEvent::LinkAdded(transport, link) => {
+                    links.push((transport.clone(), link.clone()))
+                }
+                TransportEvent::Closed(_) | TransportEvent::LinkRemoved(_, _) => {}
+            }
+            self.callback.call(event);
+        }
+        loop {
+            let events = {
+                let mut pending = zlock!(self.pending);
+                match pending.as_mut() {
+                    Some(events) if !events.is_empty() => std::mem::take(events),
+                    _ => {
+                        *pending = None;
+                        return;
+                    }
+                }
+            };
+            for event in events {
+                let reported = match &event {
+                    TransportEvent::Opened(transport) => {
+                        let opened = !transports.contains(transport);
+                        if opened {
+                            transports.push(transport.clone());
+                        }
+                        opened
+                    }
+                    TransportEvent::Closed(transport) => {
+                        let position = transports.iter().position(|t| t == transport);
+                        position.map(|i| transports.swap_remove(i)).is_some()
+                    }
+                    TransportEvent::LinkAdded(transport, link) => {
+                        let link = (transport.clone(), link.clone());
+                        let added = !links.contains(&link);
+                        if added {
+                            links.push(link);
+                        }
+                        added
+                    }
+                    TransportEvent::LinkRemoved(transport, link) => {
+                        let position = links.iter().position(|(t, l)| t == transport && l == link);
+                        position.map(|i| links.swap_remove(i)).is_some()
+                    }
+                };
+                if reported {
+                    self.callback.call(event);
+                }
+            }
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+impl fmt::Debug for TransportEventsListenerState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TransportEventsListener")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// Forwards the transport events of the runtime to the [`TransportEventsListener`]s of a session.
+#[derive(Clone)]
+pub(crate) struct Handler {
+    pub(crate) session: WeakSession,
+}
+
+impl Handler {
+    pub(crate) fn new(session: WeakSession) -> Self {
+        Self { session }
+    }
+
+    fn new_transport(&self, transport: Transport) -> Arc<dyn TransportPeerEventHandler> {
+        self.session
+            .execute_transport_events_callbacks(TransportEvent::Opened(transport.clone()));
+        Arc::new(PeerHandler {
+            session: self.session.clone(),
+            transport,
+        })
+    }
+}
+
+impl TransportEventHandler for Handler {
+    fn new_unicast(
+        &self,
+        peer: TransportPeer,
+        transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(self.new_transport(Transport::new_unicast(&peer, &transport)))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        Ok(Arc::new(self.clone()))
+    }
+}
+
+impl TransportMulticastEventHandler for Handler {
+    fn new_peer(&self, peer: TransportPeer) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(self.new_transport(Transport::new_multicast(&peer)))
+    }
+
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+pub(crate) struct PeerHandler {
+    pub(crate) session: WeakSession,
+    pub(crate) transport: Transport,
+}
+
+impl TransportPeerEventHandler for PeerHandler {
+    fn handle_message(&self, _msg: NetworkMessage) -> ZResult<()> {
+        Ok(())
+    }
+
+    fn new_link(&self, link: zenoh_link::Link) {
+        self.session
+            .execute_transport_events_callbacks(TransportEvent::LinkAdded(
+                self.transport.clone(),
+                link.into(),
+            ));
+    }
+
+    fn del_link(&self, link: zenoh_link::Link) {
+        self.session
+            .execute_transport_events_callbacks(TransportEvent::LinkRemoved(
+                self.transport.clone(),
+                link.into(),
+            ));
+    }
+
+    fn closed(&self) {
+        self.session
+            .execute_transport_events_callbacks(TransportEvent::Closed(self.transport.clone()));
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[zenoh_macros::unstable]
+pub(crate) struct TransportEventsListenerInner {
+    pub(crate) session: WeakSession,
+    pub(crate) id: Id,
+    pub(crate) undeclare_on_drop: bool,
+}
+
+/// A listener that sends notifications when transports and links of the current zenoh
+/// [`Session`](crate::Session) are opened or closed.
+///
+/// Callback listeners run in background until the session is closed, while listeners with
+/// a handler are automatically undeclared when dropped.
+///
+/// # Examples
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use zenoh::session::TransportEvent;
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let listener = session.info().transport_events().history(true).await.unwrap();
+/// while let Ok(event) = listener.recv_async().await {
+///     match event {
+///         TransportEvent::Opened(transport) => println!("Connected to {}", transport.zid()),
+///         TransportEvent::Closed(transport) => println!("Disconnected from {}", transport.zid()),
+///         _ => {}
+///     }
+/// }
+/// # }
+/// ```
+#[zenoh_macros::unstable]
+pub struct TransportEventsListener<Handler> {
+    pub(crate) inner: TransportEventsListenerInner,
+    pub(crate) handler: Handler,
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> TransportEventsListener<Handler> {
+    /// Undeclare the [`TransportEventsListener`].
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session.info().transport_events().await.unwrap();
+    /// listener.undeclare().await.unwrap();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn undeclare(self) -> TransportEventsListenerUndeclaration<Handler>
+    where
+        Handler: Send,
+    {
+        self.undeclare_inner(())
+    }
+
+    fn undeclare_impl(&mut self) -> ZResult<()> {
+        // set the flag first to avoid double panic if this function panic
+        self.inner.undeclare_on_drop = false;
+        self.inner
+            .session
+            .undeclare_transport_events_listener_inner(self.inner.id)
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl<Handler> Drop for TransportEventsListener<Handler> {
+    fn drop(&mut self) {
+        if self.inner.undeclare_on_drop {
+            if let Err(error) = self.undeclare_impl() {
+                error!(error);
+            }
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler: Send> UndeclarableSealed<()> for TransportEventsListener<Handler> {
+    type Undeclaration = TransportEventsListenerUndeclaration<Handler>;
+
+    fn undeclare_inner(self, _: ()) -> Self::Undeclaration {
+        TransportEventsListenerUndeclaration(self)
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> std::ops::Deref for TransportEventsListener<Handler> {
+    type Target = Handler;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handler
+    }
+}
+#[zenoh_macros::unstable]
+impl<Handler> std::ops::DerefMut for TransportEventsListener<Handler> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.handler
+    }
+}
+
+#[zenoh_macros::unstable]
+pub struct TransportEventsListenerUndeclaration<Handler>(TransportEventsListener<Handler>);
+
+#[zenoh_macros::unstable]
+impl<Handler> Resolvable for TransportEventsListenerUndeclaration<Handler> {
+    type To = ZResult<()>;
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> Wait for TransportEventsListenerUndeclaration<Handler> {
+    fn wait(mut self) -> <Self as Resolvable>::To {
+        self.0.undeclare_impl()
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> IntoFuture for TransportEventsListenerUndeclaration<Handler> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
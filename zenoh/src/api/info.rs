@@ -13,6 +13,11 @@
 //
 
 //! Tools to access information about the current zenoh [`Session`](crate::Session).
+#[cfg(feature = "unstable")]
+use crate::api::{
+    builders::transport_events_listener::TransportEventsListenerBuilder, handlers::DefaultHandler,
+    session::WeakSession,
+};
 use crate::{
     api::builders::info::{PeersZenohIdBuilder, RoutersZenohIdBuilder, ZenohIdBuilder},
     net::runtime::Runtime,
@@ -32,6 +37,8 @@ use crate::{
 /// ```
 pub struct SessionInfo {
     pub(crate) runtime: Runtime,
+    #[cfg(feature = "unstable")]
+    pub(crate) session: WeakSession,
 }
 
 impl SessionInfo {
@@ -82,4 +89,37 @@ impl SessionInfo {
     pub fn peers_zid(&self) -> PeersZenohIdBuilder<'_> {
         PeersZenohIdBuilder::new(&self.runtime)
     }
+
+    /// Create a [`TransportEventsListener`](crate::session::TransportEventsListener) notified
+    /// when transports to remote zenoh nodes and their links are opened or closed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use zenoh::{config::WhatAmI, session::TransportEvent};
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session.info().transport_events().history(true).await.unwrap();
+    /// while let Ok(event) = listener.recv_async().await {
+    ///     match event {
+    ///         TransportEvent::Opened(t) if t.whatami() == WhatAmI::Router => {
+    ///             println!("Connected to router {}", t.zid())
+    ///         }
+    ///         TransportEvent::LinkRemoved(t, link) => {
+    ///             println!("Lost link {} to {}", link.dst(), t.zid())
+    ///         }
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn transport_events(&self) -> TransportEventsListenerBuilder<'_, DefaultHandler> {
+        TransportEventsListenerBuilder {
+            session: &self.session,
+            history: false,
+            handler: DefaultHandler::default(),
+        }
+    }
 }
@@ -572,6 +572,10 @@ where
                     id: sub_state.id,
                     key_expr: sub_state.key_expr.clone(),
                     kind: SubscriberKind::LivelinessSubscriber,
+                    #[cfg(feature = "unstable")]
+                    origin: Locality::default(),
+                    #[cfg(feature = "unstable")]
+                    matching_listeners: Default::default(),
                     undeclare_on_drop: true,
                 },
                 handler,
@@ -21,8 +21,12 @@ use std::{
 
 use tracing::error;
 use zenoh_core::{Resolvable, Wait};
+#[cfg(feature = "unstable")]
+use zenoh_keyexpr::keyexpr;
 use zenoh_result::ZResult;
 
+#[cfg(feature = "unstable")]
+use super::admin;
 use super::{
     handlers::Callback,
     key_expr::KeyExpr,
@@ -47,6 +51,7 @@ use super::{
 #[derive(Copy, Clone, Debug)]
 pub struct MatchingStatus {
     pub(crate) matching: bool,
+    pub(crate) count: Option<usize>,
 }
 
 #[cfg(feature = "unstable")]
@@ -54,11 +59,28 @@ pub struct MatchingStatus {
 pub(crate) enum MatchingStatusType {
     Subscribers,
     Queryables(bool),
+    Publishers,
+    Queriers,
+}
+
+#[cfg(feature = "unstable")]
+impl MatchingStatusType {
+    /// Matching publishers and queriers are tracked through the liveliness tokens
+    /// they announce themselves with, rather than through the routing tables.
+    pub(crate) fn announcement_kind(&self) -> Option<&'static keyexpr> {
+        match self {
+            MatchingStatusType::Publishers => Some(admin::KE_PUBLISHER),
+            MatchingStatusType::Queriers => Some(admin::KE_QUERIER),
+            MatchingStatusType::Subscribers | MatchingStatusType::Queryables(_) => None,
+        }
+    }
 }
 
 #[zenoh_macros::unstable]
 impl MatchingStatus {
-    /// Return true if there exist entities matching the target (i.e either Subscribers matching Publisher's key expression or Queryables matching Querier's key expression and target).
+    /// Return true if there exist entities matching the target (i.e either Subscribers matching Publisher's key expression,
+    /// Queryables matching Querier's key expression and target, Publishers matching Subscriber's key expression
+    /// or Queriers matching Queryable's key expression).
     ///
     /// # Examples
     /// ```
@@ -77,6 +99,30 @@ impl MatchingStatus {
     pub fn matching(&self) -> bool {
         self.matching
     }
+
+    /// Return the number of matching entities, if known.
+    ///
+    /// The count is only known for the matching Publishers of a Subscriber and the matching
+    /// Queriers of a Queryable. Publishers and Queriers only know whether there is a match,
+    /// so `None` is returned for them.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let subscriber = session.declare_subscriber("key/expression").await.unwrap();
+    /// let matching_publishers: Option<usize> = subscriber
+    ///     .matching_status()
+    ///     .await
+    ///     .unwrap()
+    ///     .count();
+    /// # }
+    /// ```
+    pub fn count(&self) -> Option<usize> {
+        self.count
+    }
 }
 #[zenoh_macros::unstable]
 pub(crate) struct MatchingListenerState {
@@ -86,6 +132,7 @@ pub(crate) struct MatchingListenerState {
     pub(crate) destination: Locality,
     pub(crate) match_type: MatchingStatusType,
     pub(crate) callback: Callback<MatchingStatus>,
+    pub(crate) announcements_subscriber: Option<Id>,
 }
 
 #[cfg(feature = "unstable")]
@@ -106,6 +153,7 @@ impl MatchingListenerState {
                     || (self.match_type == MatchingStatusType::Queryables(true)
                         && key_expr.includes(&self.key_expr))
             }
+            MatchingStatusType::Publishers | MatchingStatusType::Queriers => false,
         }
     }
 }
@@ -59,6 +59,8 @@ pub(crate) struct PublisherState {
     pub(crate) remote_id: Id,
     pub(crate) key_expr: KeyExpr<'static>,
     pub(crate) destination: Locality,
+    #[cfg(feature = "unstable")]
+    pub(crate) token: Option<Id>,
 }
 
 impl fmt::Debug for PublisherState {
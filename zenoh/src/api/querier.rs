@@ -52,6 +52,8 @@ pub(crate) struct QuerierState {
     pub(crate) remote_id: Id,
     pub(crate) key_expr: KeyExpr<'static>,
     pub(crate) destination: Locality,
+    #[cfg(feature = "unstable")]
+    pub(crate) token: Option<Id>,
 }
 
 /// A querier that allows to send queries to a queryable.
@@ -165,6 +167,8 @@ impl<'a> Querier<'a> {
             querier: self,
             #[cfg(feature = "unstable")]
             source_info: SourceInfo::empty(),
+            #[cfg(feature = "unstable")]
+            cancellation_token: None,
             value: None,
             attachment: None,
             parameters: Parameters::empty(),
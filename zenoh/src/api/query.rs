@@ -13,6 +13,11 @@
 //
 
 use std::{collections::HashMap, error::Error, fmt::Display};
+#[cfg(feature = "unstable")]
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
 
 #[cfg(feature = "unstable")]
 use serde::Deserialize;
@@ -165,6 +170,10 @@ pub(crate) struct QueryState {
     pub(crate) reception_mode: ConsolidationMode,
     pub(crate) replies: Option<HashMap<OwnedKeyExpr, Reply>>,
     pub(crate) callback: Callback<Reply>,
+    #[cfg(feature = "unstable")]
+    pub(crate) destination: crate::api::sample::Locality,
+    #[cfg(feature = "unstable")]
+    pub(crate) _cancellation: Option<CancellationRegistration>,
 }
 
 impl QueryState {
@@ -172,6 +181,108 @@ impl QueryState {
         Selector::borrowed(&self.key_expr, &self.parameters)
     }
 }
+
+/// A handler registered on a [`CancellationToken`], unregistered when dropped.
+#[cfg(feature = "unstable")]
+pub(crate) struct CancellationRegistration {
+    token: CancellationToken,
+    id: usize,
+}
+
+#[cfg(feature = "unstable")]
+impl Drop for CancellationRegistration {
+    fn drop(&mut self) {
+        zlock!(self.token.state).handlers.remove(&self.id);
+    }
+}
+
+#[cfg(feature = "unstable")]
+#[derive(Default)]
+struct CancellationTokenState {
+    cancelled: bool,
+    next_id: usize,
+    handlers: HashMap<usize, Box<dyn FnOnce() + Send + Sync>>,
+}
+
+/// A token to cancel in-flight queries.
+///
+/// The queries sent with a [`CancellationToken`] stop receiving replies when it is cancelled,
+/// and the queryables which received them see them [cancelled](crate::query::Query::is_cancelled).
+/// The queries sent with an already cancelled token are not sent.
+///
+/// # Examples
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use zenoh::query::CancellationToken;
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let token = CancellationToken::new();
+/// let replies = session
+///     .get("key/expression")
+///     .cancellation_token(token.clone())
+///     .await
+///     .unwrap();
+/// token.cancel();
+/// while let Ok(reply) = replies.recv_async().await {}
+/// # }
+/// ```
+#[zenoh_macros::unstable]
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<Mutex<CancellationTokenState>>,
+}
+
+#[zenoh_macros::unstable]
+impl CancellationToken {
+    /// Create a new [`CancellationToken`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the queries sent with this token, and the queries sent with it afterwards.
+    pub fn cancel(&self) {
+        let handlers = {
+            let mut state = zlock!(self.state);
+            state.cancelled = true;
+            std::mem::take(&mut state.handlers)
+        };
+        for (_, handler) in handlers {
+            handler();
+        }
+    }
+
+    /// Return true if this token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        zlock!(self.state).cancelled
+    }
+
+    /// Register a handler called on cancellation, or return `None` if the token is already
+    /// cancelled.
+    pub(crate) fn register<F>(self, handler: F) -> Option<CancellationRegistration>
+    where
+        F: FnOnce() + Send + Sync + 'static,
+    {
+        let mut state = zlock!(self.state);
+        if state.cancelled {
+            return None;
+        }
+        let id = state.next_id;
+        state.next_id += 1;
+        state.handlers.insert(id, Box::new(handler));
+        drop(state);
+        Some(CancellationRegistration { token: self, id })
+    }
+}
+
+#[zenoh_macros::unstable]
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
 /// The kind of accepted query replies.
 #[zenoh_macros::unstable]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
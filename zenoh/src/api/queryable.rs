@@ -28,7 +28,18 @@ use zenoh_protocol::{
 use zenoh_result::ZResult;
 #[zenoh_macros::unstable]
 use {
-    crate::api::query::ReplyKeyExpr, zenoh_config::wrappers::EntityGlobalId,
+    crate::api::{
+        builders::matching_listener::MatchingListenerBuilder,
+        handlers::DefaultHandler,
+        matching::{MatchingStatus, MatchingStatusType},
+        query::ReplyKeyExpr,
+    },
+    std::{
+        collections::HashSet,
+        sync::atomic::{AtomicBool, Ordering},
+        sync::Mutex,
+    },
+    zenoh_config::wrappers::EntityGlobalId,
     zenoh_protocol::core::EntityGlobalIdProto,
 };
 
@@ -55,6 +66,8 @@ pub(crate) struct QueryInner {
     pub(crate) qid: RequestId,
     pub(crate) zid: ZenohIdProto,
     pub(crate) primitives: Arc<dyn Primitives>,
+    #[cfg(feature = "unstable")]
+    pub(crate) cancellation: Option<QueryCancellation>,
 }
 
 impl Drop for QueryInner {
@@ -63,10 +76,25 @@ impl Drop for QueryInner {
             rid: self.qid,
             ext_qos: response::ext::QoSType::RESPONSE_FINAL,
             ext_tstamp: None,
+            ext_cancel: None,
         });
+        #[cfg(feature = "unstable")]
+        if let Some(cancellation) = &self.cancellation {
+            cancellation
+                .session
+                .unregister_incoming_query(cancellation.local, self.qid);
+        }
     }
 }
 
+/// The registration of a query received by a session, flagged when the querier cancels it.
+#[zenoh_macros::unstable]
+pub(crate) struct QueryCancellation {
+    pub(crate) session: WeakSession,
+    pub(crate) local: bool,
+    pub(crate) cancelled: Arc<AtomicBool>,
+}
+
 /// Structs received by a [`Queryable`].
 #[derive(Clone)]
 pub struct Query {
@@ -195,6 +223,38 @@ impl Query {
             }
         })
     }
+    /// Return true if the querier cancelled this query with a
+    /// [`CancellationToken`](crate::query::CancellationToken), in which case its replies are
+    /// dropped.
+    ///
+    /// The cancellation is only notified if all the nodes routing the query support it: through
+    /// nodes of older versions, this always returns false and the replies are dropped on the way.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let queryable = session.declare_queryable("key/expression").await.unwrap();
+    /// while let Ok(query) = queryable.recv_async().await {
+    ///     for i in 0..1_000_000 {
+    ///         if query.is_cancelled() {
+    ///             break;
+    ///         }
+    ///         query.reply("key/expression", i.to_string()).await.unwrap();
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner
+            .cancellation
+            .as_ref()
+            .is_some_and(|c| c.cancelled.load(Ordering::Relaxed))
+    }
+
     #[cfg(feature = "unstable")]
     fn _accepts_any_replies(&self) -> ZResult<bool> {
         Ok(self.parameters().reply_key_expr_any())
@@ -324,6 +384,12 @@ impl fmt::Debug for QueryableState {
 pub(crate) struct QueryableInner {
     pub(crate) session: WeakSession,
     pub(crate) id: Id,
+    #[cfg(feature = "unstable")]
+    pub(crate) key_expr: KeyExpr<'static>,
+    #[cfg(feature = "unstable")]
+    pub(crate) origin: Locality,
+    #[cfg(feature = "unstable")]
+    pub(crate) matching_listeners: Arc<Mutex<HashSet<Id>>>,
     pub(crate) undeclare_on_drop: bool,
 }
 
@@ -459,6 +525,82 @@ impl<Handler> Queryable<Handler> {
         &mut self.handler
     }
 
+    /// Return the [`MatchingStatus`] of the queryable.
+    ///
+    /// [`MatchingStatus::matching`] will return true if there exist Queriers
+    /// matching the Queryable's key expression and false otherwise.
+    /// [`MatchingStatus::count`] returns the number of those Queriers.
+    ///
+    /// Queriers are detected through the liveliness tokens they declare, so only the
+    /// Queriers declared with [`announce`](crate::query::QuerierBuilder::announce) and not
+    /// restricted to their own session are taken into account. Queriers of older zenoh versions
+    /// or of other bindings are not announced either: the status may be non matching although
+    /// some Queriers match the Queryable.
+    /// Queries issued with [`Session::get`](crate::Session::get) are not declared and are
+    /// therefore never matching.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let queryable = session.declare_queryable("key/expression").await.unwrap();
+    /// let matching_queriers: bool = queryable
+    ///     .matching_status()
+    ///     .await
+    ///     .unwrap()
+    ///     .matching();
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn matching_status(&self) -> impl Resolve<ZResult<MatchingStatus>> + '_ {
+        let session = self.inner.session.clone();
+        let key_expr = self.inner.key_expr.clone();
+        let origin = self.inner.origin;
+        zenoh_core::ResolveFuture::new(async move {
+            session
+                .announcements_matching_status(&key_expr, origin, MatchingStatusType::Queriers)
+                .await
+        })
+    }
+
+    /// Return a [`MatchingListener`](crate::api::matching::MatchingListener) for this Queryable.
+    ///
+    /// The [`MatchingListener`](crate::api::matching::MatchingListener) will send a notification each time
+    /// the number of Queriers matching the Queryable changes.
+    /// As for [`matching_status`](Queryable::matching_status), only the announced Queriers
+    /// are taken into account.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let queryable = session.declare_queryable("key/expression").await.unwrap();
+    /// let matching_listener = queryable.matching_listener().await.unwrap();
+    /// while let Ok(matching_status) = matching_listener.recv_async().await {
+    ///     if matching_status.matching() {
+    ///         println!("Queryable has {:?} matching queriers.", matching_status.count());
+    ///     } else {
+    ///         println!("Queryable has NO MORE matching queriers.");
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn matching_listener(&self) -> MatchingListenerBuilder<'_, DefaultHandler> {
+        MatchingListenerBuilder {
+            session: &self.inner.session,
+            key_expr: &self.inner.key_expr,
+            destination: self.inner.origin,
+            matching_listeners: &self.inner.matching_listeners,
+            matching_status_type: MatchingStatusType::Queriers,
+            handler: DefaultHandler::default(),
+        }
+    }
+
     /// Undeclare the [`Queryable`].
     ///
     /// # Examples
@@ -484,6 +626,13 @@ impl<Handler> Queryable<Handler> {
     fn undeclare_impl(&mut self) -> ZResult<()> {
         // set the flag first to avoid double panic if this function panic
         self.inner.undeclare_on_drop = false;
+        #[cfg(feature = "unstable")]
+        {
+            let ids: Vec<Id> = zlock!(self.inner.matching_listeners).drain().collect();
+            for id in ids {
+                self.inner.session.undeclare_matches_listener_inner(id)?
+            }
+        }
         self.inner.session.close_queryable(self.inner.id)
     }
 
@@ -37,7 +37,7 @@ use zenoh_config::{qos::PublisherQoSConfig, unwrap_or_default, wrappers::ZenohId
 use zenoh_core::{zconfigurable, zread, Resolve, ResolveClosure, ResolveFuture, Wait};
 use zenoh_keyexpr::keyexpr_tree::KeBoxTree;
 #[cfg(feature = "unstable")]
-use zenoh_protocol::network::declare::SubscriberId;
+use zenoh_protocol::network::{declare::SubscriberId, response};
 use zenoh_protocol::{
     core::{
         key_expr::{keyexpr, OwnedKeyExpr},
@@ -73,9 +73,11 @@ use crate::api::selector::ZenohParameters;
 #[cfg(feature = "unstable")]
 use crate::api::{
     builders::querier::QuerierBuilder,
+    connectivity::{self, Transport, TransportEvent, TransportEventsListenerState},
     matching::{MatchingListenerState, MatchingStatus, MatchingStatusType},
     querier::QuerierState,
-    query::ReplyKeyExpr,
+    query::{CancellationToken, ReplyKeyExpr},
+    queryable::QueryCancellation,
     sample::SourceInfo,
 };
 use crate::{
@@ -116,6 +118,8 @@ use crate::{
     query::ReplyError,
     Config,
 };
+#[cfg(feature = "unstable")]
+use std::sync::atomic::AtomicBool;
 
 zconfigurable! {
     pub(crate) static ref API_DATA_RECEPTION_CHANNEL_SIZE: usize = 256;
@@ -145,7 +149,13 @@ pub(crate) struct SessionState {
     pub(crate) remote_queryables: HashMap<Id, (KeyExpr<'static>, bool)>,
     #[cfg(feature = "unstable")]
     pub(crate) matching_listeners: HashMap<Id, Arc<MatchingListenerState>>,
+    #[cfg(feature = "unstable")]
+    pub(crate) transport_events_listeners: HashMap<Id, Arc<TransportEventsListenerState>>,
     pub(crate) queries: HashMap<RequestId, QueryState>,
+    // The cancellation flags of the queries received by the queryables, indexed by whether the
+    // query was sent by this session and by request id
+    #[cfg(feature = "unstable")]
+    pub(crate) incoming_queries: HashMap<(bool, RequestId), Arc<AtomicBool>>,
     pub(crate) liveliness_queries: HashMap<InterestId, LivelinessQueryState>,
     pub(crate) aggregated_subscribers: Vec<OwnedKeyExpr>,
     pub(crate) aggregated_publishers: Vec<OwnedKeyExpr>,
@@ -179,7 +189,11 @@ impl SessionState {
             remote_queryables: HashMap::new(),
             #[cfg(feature = "unstable")]
             matching_listeners: HashMap::new(),
+            #[cfg(feature = "unstable")]
+            transport_events_listeners: HashMap::new(),
             queries: HashMap::new(),
+            #[cfg(feature = "unstable")]
+            incoming_queries: HashMap::new(),
             liveliness_queries: HashMap::new(),
             aggregated_subscribers,
             aggregated_publishers,
@@ -313,12 +327,14 @@ impl SessionState {
         id: EntityId,
         key_expr: &'a KeyExpr,
         destination: Locality,
+        token: Option<Id>,
     ) -> Option<KeyExpr<'a>> {
         let mut querier_state = QuerierState {
             id,
             remote_id: id,
             key_expr: key_expr.clone().into_owned(),
             destination,
+            token,
         };
 
         let declared_querier =
@@ -692,6 +708,8 @@ impl Session {
             }));
 
             runtime.new_handler(Arc::new(admin::Handler::new(session.downgrade())));
+            #[cfg(feature = "unstable")]
+            runtime.new_handler(Arc::new(connectivity::Handler::new(session.downgrade())));
 
             let primitives = Some(router.new_primitives(Arc::new(session.downgrade())));
             zwrite!(session.0.state).primitives = primitives;
@@ -850,6 +868,8 @@ impl Session {
     pub fn info(&self) -> SessionInfo {
         SessionInfo {
             runtime: self.0.runtime.clone(),
+            #[cfg(feature = "unstable")]
+            session: self.downgrade(),
         }
     }
 
@@ -970,6 +990,8 @@ impl Session {
             #[cfg(feature = "unstable")]
             reliability: Reliability::DEFAULT,
             destination: Locality::default(),
+            #[cfg(feature = "unstable")]
+            announce: false,
         }
     }
 
@@ -1015,6 +1037,8 @@ impl Session {
             timeout,
             #[cfg(feature = "unstable")]
             accept_replies: ReplyKeyExpr::default(),
+            #[cfg(feature = "unstable")]
+            announce: false,
         }
     }
 
@@ -1217,6 +1241,8 @@ impl Session {
             handler: DefaultHandler::default(),
             #[cfg(feature = "unstable")]
             source_info: SourceInfo::empty(),
+            #[cfg(feature = "unstable")]
+            cancellation_token: None,
         }
     }
 }
@@ -1312,16 +1338,27 @@ impl SessionInner {
         &self,
         key_expr: KeyExpr,
         destination: Locality,
+        #[cfg(feature = "unstable")] announce: bool,
     ) -> ZResult<EntityId> {
-        let mut state = zwrite!(self.state);
         tracing::trace!("declare_publisher({:?})", key_expr);
         let id = self.runtime.next_id();
+        #[cfg(feature = "unstable")]
+        let token = self.declare_announcement_token(
+            admin::KE_PUBLISHER,
+            id,
+            &key_expr,
+            destination,
+            announce,
+        )?;
+        let mut state = zwrite!(self.state);
 
         let mut pub_state = PublisherState {
             id,
             remote_id: id,
             key_expr: key_expr.clone().into_owned(),
             destination,
+            #[cfg(feature = "unstable")]
+            token,
         };
 
         let declared_pub = (destination != Locality::SessionLocal)
@@ -1384,10 +1421,15 @@ impl SessionInner {
             if pub_state.destination != Locality::SessionLocal {
                 // Note: there might be several publishers on the same KeyExpr.
                 // Before calling forget_publishers(key_expr), check if this was the last one.
-                if !state.publishers.values().any(|p| {
+                let last = !state.publishers.values().any(|p| {
                     p.destination != Locality::SessionLocal && p.remote_id == pub_state.remote_id
-                }) {
-                    drop(state);
+                });
+                drop(state);
+                #[cfg(feature = "unstable")]
+                if let Some(tid) = pub_state.token {
+                    self.undeclare_liveliness(tid)?;
+                }
+                if last {
                     primitives.send_interest(Interest {
                         id: pub_state.remote_id,
                         mode: InterestMode::Final,
@@ -1412,11 +1454,19 @@ impl SessionInner {
         &self,
         key_expr: KeyExpr,
         destination: Locality,
+        announce: bool,
     ) -> ZResult<EntityId> {
         tracing::trace!("declare_querier({:?})", key_expr);
-        let mut state = zwrite!(self.state);
         let id = self.runtime.next_id();
-        let declared_querier = state.register_querier(id, &key_expr, destination);
+        let token = self.declare_announcement_token(
+            admin::KE_QUERIER,
+            id,
+            &key_expr,
+            destination,
+            announce,
+        )?;
+        let mut state = zwrite!(self.state);
+        let declared_querier = state.register_querier(id, &key_expr, destination, token);
         if let Some(res) = declared_querier {
             let primitives = state.primitives()?;
             drop(state);
@@ -1444,11 +1494,15 @@ impl SessionInner {
             if querier_state.destination != Locality::SessionLocal {
                 // Note: there might be several queriers on the same KeyExpr.
                 // Before calling forget_queriers(key_expr), check if this was the last one.
-                if !state.queriers.values().any(|p| {
+                let last = !state.queriers.values().any(|p| {
                     p.destination != Locality::SessionLocal
                         && p.remote_id == querier_state.remote_id
-                }) {
-                    drop(state);
+                });
+                drop(state);
+                if let Some(tid) = querier_state.token {
+                    self.undeclare_liveliness(tid)?;
+                }
+                if last {
                     primitives.send_interest(Interest {
                         id: querier_state.remote_id,
                         mode: InterestMode::Final,
@@ -1862,6 +1916,24 @@ impl SessionInner {
         Ok(())
     }
 
+    /// Announce a publisher or querier with a liveliness token, so that matching subscribers
+    /// and queryables can detect it. Entities restricted to the session are not announced.
+    #[cfg(feature = "unstable")]
+    fn declare_announcement_token(
+        &self,
+        kind: &keyexpr,
+        id: EntityId,
+        key_expr: &KeyExpr,
+        destination: Locality,
+        announce: bool,
+    ) -> ZResult<Option<Id>> {
+        if !announce || destination == Locality::SessionLocal {
+            return Ok(None);
+        }
+        let token_expr = admin::announcement_key_expr(&self.zid(), kind, id, key_expr)?;
+        self.declare_liveliness_inner(&token_expr).map(Some)
+    }
+
     #[zenoh_macros::unstable]
     pub(crate) fn declare_matches_listener_inner(
         &self,
@@ -1870,6 +1942,15 @@ impl SessionInner {
         match_type: MatchingStatusType,
         callback: Callback<MatchingStatus>,
     ) -> ZResult<Arc<MatchingListenerState>> {
+        let announcements_subscriber = match match_type.announcement_kind() {
+            Some(kind) => Some(self.declare_announcements_subscriber(
+                kind,
+                key_expr,
+                destination,
+                callback.clone(),
+            )?),
+            None => None,
+        };
         let mut state = zwrite!(self.state);
         let id = self.runtime.next_id();
         tracing::trace!("matches_listener({:?}: {:?}) => {id}", match_type, key_expr);
@@ -1880,9 +1961,14 @@ impl SessionInner {
             key_expr: key_expr.clone().into_owned(),
             match_type,
             callback,
+            announcements_subscriber,
         });
         state.matching_listeners.insert(id, listener_state.clone());
         drop(state);
+        if announcements_subscriber.is_some() {
+            // The announcements subscriber is declared with history and reports the current status itself.
+            return Ok(listener_state);
+        }
         match listener_state.current.lock() {
             Ok(mut current) => {
                 if self
@@ -1891,9 +1977,10 @@ impl SessionInner {
                     .unwrap_or(true)
                 {
                     *current = true;
-                    listener_state
-                        .callback
-                        .call(MatchingStatus { matching: true });
+                    listener_state.callback.call(MatchingStatus {
+                        matching: true,
+                        count: None,
+                    });
                 }
             }
             Err(e) => tracing::error!("Error trying to acquire MathginListener lock: {}", e),
@@ -1901,6 +1988,80 @@ impl SessionInner {
         Ok(listener_state)
     }
 
+    /// Track the announcements of the publishers or queriers matching `key_expr`,
+    /// notifying `callback` each time their number changes.
+    #[zenoh_macros::unstable]
+    fn declare_announcements_subscriber(
+        &self,
+        kind: &keyexpr,
+        key_expr: &KeyExpr,
+        origin: Locality,
+        callback: Callback<MatchingStatus>,
+    ) -> ZResult<Id> {
+        let zid = self.zid().to_string();
+        let announced = std::sync::Mutex::new(std::collections::HashSet::new());
+        let sub_state = self.declare_liveliness_subscriber_inner(
+            &admin::announcements_key_expr(kind, key_expr),
+            Locality::Any,
+            true,
+            Callback::new(Arc::new(move |sample: Sample| {
+                if !admin::is_announced_from(&sample.key_expr, &zid, origin) {
+                    return;
+                }
+                let mut announced = zlock!(announced);
+                let changed = match sample.kind {
+                    SampleKind::Put => announced.insert(sample.key_expr.as_str().to_owned()),
+                    SampleKind::Delete => announced.remove(sample.key_expr.as_str()),
+                };
+                if changed {
+                    callback.call(MatchingStatus {
+                        matching: !announced.is_empty(),
+                        count: Some(announced.len()),
+                    });
+                }
+            })),
+        )?;
+        Ok(sub_state.id)
+    }
+
+    /// Query the announcements of the publishers or queriers matching `key_expr`.
+    #[zenoh_macros::unstable]
+    pub(crate) async fn announcements_matching_status(
+        self: &Arc<Self>,
+        key_expr: &KeyExpr<'_>,
+        origin: Locality,
+        match_type: MatchingStatusType,
+    ) -> ZResult<MatchingStatus> {
+        let kind = match_type
+            .announcement_kind()
+            .ok_or_else(|| zerror!("Matching {:?} are not announced", match_type))?;
+        let timeout = {
+            let conf = &self.runtime.config().lock().0;
+            Duration::from_millis(unwrap_or_default!(conf.queries_default_timeout()))
+        };
+        let (sender, receiver) = flume::unbounded();
+        self.liveliness_query(
+            &admin::announcements_key_expr(kind, key_expr),
+            timeout,
+            Callback::new(Arc::new(move |reply: Reply| {
+                let _ = sender.send(reply);
+            })),
+        )?;
+        let zid = self.zid().to_string();
+        let mut announced = std::collections::HashSet::new();
+        while let Ok(reply) = receiver.recv_async().await {
+            if let Ok(sample) = reply.into_result() {
+                if admin::is_announced_from(&sample.key_expr, &zid, origin) {
+                    announced.insert(sample.key_expr.as_str().to_owned());
+                }
+            }
+        }
+        Ok(MatchingStatus {
+            matching: !announced.is_empty(),
+            count: Some(announced.len()),
+        })
+    }
+
     #[zenoh_macros::unstable]
     fn matching_status_local(
         &self,
@@ -1924,8 +2085,19 @@ impl SessionInner {
                         .local_wireexpr_to_expr(&q.key_expr)
                         .is_ok_and(|ke| ke.includes(key_expr))
             }),
+            MatchingStatusType::Publishers => state
+                .publishers
+                .values()
+                .any(|p| p.key_expr.intersects(key_expr)),
+            MatchingStatusType::Queriers => state
+                .queriers
+                .values()
+                .any(|q| q.key_expr.intersects(key_expr)),
         };
-        MatchingStatus { matching }
+        MatchingStatus {
+            matching,
+            count: None,
+        }
     }
 
     #[zenoh_macros::unstable]
@@ -1949,6 +2121,12 @@ impl SessionInner {
                     &tables, key_expr, complete,
                 )
             }
+            MatchingStatusType::Publishers | MatchingStatusType::Queriers => {
+                bail!(
+                    "Remote {:?} are not known from the routing tables",
+                    matching_type
+                )
+            }
         };
 
         drop(tables);
@@ -1969,7 +2147,10 @@ impl SessionInner {
                 }
             }
         };
-        Ok(MatchingStatus { matching })
+        Ok(MatchingStatus {
+            matching,
+            count: None,
+        })
     }
 
     #[zenoh_macros::unstable]
@@ -2040,19 +2221,104 @@ impl SessionInner {
     }
 
     #[zenoh_macros::unstable]
-    pub(crate) fn undeclare_matches_listener_inner(&self, sid: Id) -> ZResult<()> {
+    pub(crate) fn undeclare_matches_listener_inner(self: &Arc<Self>, sid: Id) -> ZResult<()> {
         let mut state = zwrite!(self.state);
         if state.primitives.is_none() {
             return Ok(());
         }
-        if let Some(state) = state.matching_listeners.remove(&sid) {
-            trace!("undeclare_matches_listener_inner({:?})", state);
+        if let Some(listener_state) = state.matching_listeners.remove(&sid) {
+            trace!("undeclare_matches_listener_inner({:?})", listener_state);
+            drop(state);
+            if let Some(id) = listener_state.announcements_subscriber {
+                self.undeclare_subscriber_inner(id, SubscriberKind::LivelinessSubscriber)?;
+            }
             Ok(())
         } else {
             Err(zerror!("Unable to find MatchingListener").into())
         }
     }
 
+    #[zenoh_macros::unstable]
+    pub(crate) fn declare_transport_events_listener_inner(
+        &self,
+        callback: Callback<TransportEvent>,
+        history: bool,
+    ) -> ZResult<Id> {
+        let mut state = zwrite!(self.state);
+        if state.primitives.is_none() {
+            return Err(SessionClosedError.into());
+        }
+        let id = self.runtime.next_id();
+        tracing::trace!("transport_events_listener() => {id}");
+        let listener_state = Arc::new(TransportEventsListenerState::new(id, callback, history));
+        state
+            .transport_events_listeners
+            .insert(id, listener_state.clone());
+        drop(state);
+        if history {
+            // The events received from the registration are queued until the history is reported
+            listener_state.report_history(self.transport_events_history());
+        }
+        Ok(id)
+    }
+
+    #[zenoh_macros::unstable]
+    fn transport_events_history(&self) -> Vec<TransportEvent> {
+        let manager = self.runtime.manager();
+        let mut events = vec![];
+        for transport in
+            zenoh_runtime::ZRuntime::Application.block_in_place(manager.get_transports_unicast())
+        {
+            let (Ok(peer), Ok(links)) = (transport.get_peer(), transport.get_links()) else {
+                continue;
+            };
+            let t = Transport::new_unicast(&peer, &transport);
+            events.push(TransportEvent::Opened(t.clone()));
+            for link in links {
+                events.push(TransportEvent::LinkAdded(t.clone(), link.into()));
+            }
+        }
+        for transport in
+            zenoh_runtime::ZRuntime::Application.block_in_place(manager.get_transports_multicast())
+        {
+            for peer in transport.get_peers().unwrap_or_default() {
+                let t = Transport::new_multicast(&peer);
+                events.push(TransportEvent::Opened(t.clone()));
+                for link in peer.links {
+                    events.push(TransportEvent::LinkAdded(t.clone(), link.into()));
+                }
+            }
+        }
+        events
+    }
+
+    #[zenoh_macros::unstable]
+    pub(crate) fn undeclare_transport_events_listener_inner(&self, id: Id) -> ZResult<()> {
+        let mut state = zwrite!(self.state);
+        if state.primitives.is_none() {
+            return Ok(());
+        }
+        if let Some(state) = state.transport_events_listeners.remove(&id) {
+            trace!("undeclare_transport_events_listener_inner({:?})", state);
+            Ok(())
+        } else {
+            Err(zerror!("Unable to find TransportEventsListener").into())
+        }
+    }
+
+    #[zenoh_macros::unstable]
+    pub(crate) fn execute_transport_events_callbacks(&self, event: TransportEvent) {
+        let state = zread!(self.state);
+        if state.primitives.is_none() {
+            return; // Session closing or closed
+        }
+        let listeners: Vec<_> = state.transport_events_listeners.values().cloned().collect();
+        drop(state);
+        for listener in listeners {
+            listener.call(event.clone());
+        }
+    }
+
     #[allow(clippy::too_many_arguments)] // TODO fixme
     pub(crate) fn execute_subscriber_callbacks(
         &self,
@@ -2233,6 +2499,7 @@ impl SessionInner {
         value: Option<(ZBytes, Encoding)>,
         attachment: Option<ZBytes>,
         #[cfg(feature = "unstable")] source: SourceInfo,
+        #[cfg(feature = "unstable")] cancellation_token: Option<CancellationToken>,
         callback: Callback<Reply>,
     ) -> ZResult<()> {
         tracing::trace!(
@@ -2253,6 +2520,19 @@ impl SessionInner {
             Locality::Any => 2,
             _ => 1,
         };
+        #[cfg(feature = "unstable")]
+        let cancellation = match cancellation_token {
+            Some(token) => {
+                let session = WeakSession::new(self);
+                let registration = token.register(move || session.cancel_query(qid));
+                if registration.is_none() {
+                    tracing::debug!("Query {} not sent: cancelled", qid);
+                    return Ok(());
+                }
+                registration
+            }
+            None => None,
+        };
 
         let token = self.task_controller.get_cancellation_token();
         self.task_controller
@@ -2293,6 +2573,10 @@ impl SessionInner {
                 reception_mode: consolidation,
                 replies: (consolidation != ConsolidationMode::None).then(HashMap::new),
                 callback,
+                #[cfg(feature = "unstable")]
+                destination,
+                #[cfg(feature = "unstable")]
+                _cancellation: cancellation,
             },
         );
 
@@ -2348,6 +2632,48 @@ impl SessionInner {
         Ok(())
     }
 
+    #[zenoh_macros::unstable]
+    pub(crate) fn cancel_query(&self, qid: RequestId) {
+        let mut state = zwrite!(self.state);
+        let Ok(primitives) = state.primitives() else {
+            return;
+        };
+        let Some(query) = state.queries.get_mut(&qid) else {
+            return;
+        };
+        trace!("Cancel query {}", qid);
+        // The query is kept until its final replies are received, but its replies are dropped
+        let callback = std::mem::replace(&mut query.callback, Callback::new(Arc::new(|_| {})));
+        query.replies = query.replies.as_ref().map(|_| HashMap::new());
+        let destination = query.destination;
+        drop(state);
+        drop(callback);
+        if destination != Locality::SessionLocal {
+            primitives.send_response_final(ResponseFinal {
+                rid: qid,
+                ext_qos: response::ext::QoSType::RESPONSE_FINAL,
+                ext_tstamp: None,
+                ext_cancel: Some(response::ext::Cancel::new()),
+            });
+        }
+        if destination != Locality::Remote {
+            self.cancel_incoming_query(true, qid);
+        }
+    }
+
+    #[zenoh_macros::unstable]
+    fn cancel_incoming_query(&self, local: bool, qid: RequestId) {
+        if let Some(cancelled) = zread!(self.state).incoming_queries.get(&(local, qid)) {
+            trace!("Query {} cancelled by the querier", qid);
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    #[zenoh_macros::unstable]
+    pub(crate) fn unregister_incoming_query(&self, local: bool, qid: RequestId) {
+        zwrite!(self.state).incoming_queries.remove(&(local, qid));
+    }
+
     pub(crate) fn liveliness_query(
         self: &Arc<Self>,
         key_expr: &KeyExpr<'_>,
@@ -2458,6 +2784,18 @@ impl SessionInner {
 
         let zid = self.zid();
 
+        #[cfg(feature = "unstable")]
+        let cancellation = (!queryables.is_empty()).then(|| {
+            let cancelled = Arc::new(AtomicBool::new(false));
+            zwrite!(self.state)
+                .incoming_queries
+                .insert((local, qid), cancelled.clone());
+            QueryCancellation {
+                session: WeakSession::new(self),
+                local,
+                cancelled,
+            }
+        });
         let query_inner = Arc::new(QueryInner {
             key_expr,
             parameters: parameters.to_owned().into(),
@@ -2468,6 +2806,8 @@ impl SessionInner {
             } else {
                 primitives
             },
+            #[cfg(feature = "unstable")]
+            cancellation,
         });
         let mut query = Query {
             inner: query_inner,
@@ -3015,6 +3355,11 @@ impl Primitives for WeakSession {
 
     fn send_response_final(&self, msg: ResponseFinal) {
         trace!("recv ResponseFinal {:?}", msg);
+        if msg.ext_cancel.is_some() {
+            #[cfg(feature = "unstable")]
+            self.cancel_incoming_query(false, msg.rid);
+            return;
+        }
         let mut state = zwrite!(self.state);
         if state.primitives.is_none() {
             return; // Session closing or closed
@@ -3152,6 +3497,7 @@ impl Closee for Arc<SessionInner> {
             // will be stabilized.
             let mut state = zwrite!(self.state);
             let _matching_listeners = std::mem::take(&mut state.matching_listeners);
+            let _transport_events_listeners = std::mem::take(&mut state.transport_events_listeners);
             drop(state);
         }
     }
@@ -21,7 +21,20 @@ use tracing::error;
 use zenoh_core::{Resolvable, Wait};
 use zenoh_result::ZResult;
 #[cfg(feature = "unstable")]
-use {zenoh_config::wrappers::EntityGlobalId, zenoh_protocol::core::EntityGlobalIdProto};
+use {
+    crate::api::{
+        builders::matching_listener::MatchingListenerBuilder,
+        handlers::DefaultHandler,
+        matching::{MatchingStatus, MatchingStatusType},
+    },
+    std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    },
+    zenoh_config::wrappers::EntityGlobalId,
+    zenoh_core::Resolve,
+    zenoh_protocol::core::EntityGlobalIdProto,
+};
 
 use crate::api::{
     handlers::Callback,
@@ -54,6 +67,10 @@ pub(crate) struct SubscriberInner {
     pub(crate) id: Id,
     pub(crate) key_expr: KeyExpr<'static>,
     pub(crate) kind: SubscriberKind,
+    #[cfg(feature = "unstable")]
+    pub(crate) origin: Locality,
+    #[cfg(feature = "unstable")]
+    pub(crate) matching_listeners: Arc<Mutex<HashSet<Id>>>,
     pub(crate) undeclare_on_drop: bool,
 }
 
@@ -187,6 +204,80 @@ impl<Handler> Subscriber<Handler> {
         &mut self.handler
     }
 
+    /// Return the [`MatchingStatus`] of the subscriber.
+    ///
+    /// [`MatchingStatus::matching`] will return true if there exist Publishers
+    /// matching the Subscriber's key expression and false otherwise.
+    /// [`MatchingStatus::count`] returns the number of those Publishers.
+    ///
+    /// Publishers are detected through the liveliness tokens they declare, so only the
+    /// Publishers declared with [`announce`](crate::pubsub::PublisherBuilder::announce) and not
+    /// restricted to their own session are taken into account. Publishers of older zenoh versions
+    /// or of other bindings are not announced either: the status may be non matching although
+    /// some Publishers match the Subscriber.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let subscriber = session.declare_subscriber("key/expression").await.unwrap();
+    /// let matching_publishers: bool = subscriber
+    ///     .matching_status()
+    ///     .await
+    ///     .unwrap()
+    ///     .matching();
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn matching_status(&self) -> impl Resolve<ZResult<MatchingStatus>> + '_ {
+        let session = self.inner.session.clone();
+        let key_expr = self.inner.key_expr.clone();
+        let origin = self.inner.origin;
+        zenoh_core::ResolveFuture::new(async move {
+            session
+                .announcements_matching_status(&key_expr, origin, MatchingStatusType::Publishers)
+                .await
+        })
+    }
+
+    /// Return a [`MatchingListener`](crate::api::matching::MatchingListener) for this Subscriber.
+    ///
+    /// The [`MatchingListener`](crate::api::matching::MatchingListener) will send a notification each time
+    /// the number of Publishers matching the Subscriber changes.
+    /// As for [`matching_status`](Subscriber::matching_status), only the announced Publishers
+    /// are taken into account.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let subscriber = session.declare_subscriber("key/expression").await.unwrap();
+    /// let matching_listener = subscriber.matching_listener().await.unwrap();
+    /// while let Ok(matching_status) = matching_listener.recv_async().await {
+    ///     if matching_status.matching() {
+    ///         println!("Subscriber has {:?} matching publishers.", matching_status.count());
+    ///     } else {
+    ///         println!("Subscriber has NO MORE matching publishers.");
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn matching_listener(&self) -> MatchingListenerBuilder<'_, DefaultHandler> {
+        MatchingListenerBuilder {
+            session: &self.inner.session,
+            key_expr: &self.inner.key_expr,
+            destination: self.inner.origin,
+            matching_listeners: &self.inner.matching_listeners,
+            matching_status_type: MatchingStatusType::Publishers,
+            handler: DefaultHandler::default(),
+        }
+    }
+
     /// Undeclare the [`Subscriber`].
     ///
     /// # Examples
@@ -212,6 +303,13 @@ impl<Handler> Subscriber<Handler> {
     fn undeclare_impl(&mut self) -> ZResult<()> {
         // set the flag first to avoid double panic if this function panic
         self.inner.undeclare_on_drop = false;
+        #[cfg(feature = "unstable")]
+        {
+            let ids: Vec<Id> = zlock!(self.inner.matching_listeners).drain().collect();
+            for id in ids {
+                self.inner.session.undeclare_matches_listener_inner(id)?
+            }
+        }
         self.inner
             .session
             .undeclare_subscriber_inner(self.inner.id, self.inner.kind)
@@ -206,6 +206,14 @@ pub mod session {
 
     #[zenoh_macros::internal]
     pub use crate::api::builders::session::{init, InitBuilder};
+    #[zenoh_macros::unstable]
+    pub use crate::api::{
+        builders::transport_events_listener::TransportEventsListenerBuilder,
+        connectivity::{
+            Link, Transport, TransportEvent, TransportEventsListener,
+            TransportEventsListenerUndeclaration,
+        },
+    };
     pub use crate::api::{
         builders::{
             close::CloseBuilder,
@@ -294,7 +302,7 @@ pub mod query {
     pub use crate::api::{
         builders::querier::{QuerierBuilder, QuerierGetBuilder},
         querier::Querier,
-        query::ReplyKeyExpr,
+        query::{CancellationToken, ReplyKeyExpr},
         selector::ZenohParameters,
     };
     pub use crate::api::{
@@ -519,6 +527,13 @@ pub mod internal {
         };
     }
 
+    /// Access control support for the users authenticated by the plugins.
+    pub mod access_control {
+        pub use zenoh_config::{AclMessage, Permission};
+
+        pub use crate::net::routing::interceptor::UserAclEnforcer;
+    }
+
     pub use zenoh_result::ErrNo;
 }
 
@@ -15,7 +15,7 @@ use std::{
     any::Any,
     collections::HashMap,
     fmt,
-    sync::{Arc, Weak},
+    sync::{Arc, Mutex, Weak},
     time::Duration,
 };
 
@@ -72,6 +72,10 @@ pub struct FaceState {
     pub(crate) remote_mappings: HashMap<ExprId, Arc<Resource>>,
     pub(crate) next_qid: RequestId,
     pub(crate) pending_queries: HashMap<RequestId, (Arc<Query>, CancellationToken)>,
+    // The queries received from this face that were routed, to cancel them
+    pub(crate) routed_queries: Mutex<HashMap<RequestId, RoutedQuery>>,
+    // Whether the queries routed to this face can be cancelled
+    pub(crate) query_cancellation: bool,
     pub(crate) mcast_group: Option<TransportMulticast>,
     pub(crate) in_interceptors: Option<Arc<InterceptorsChain>>,
     pub(crate) hat: Box<dyn Any + Send + Sync>,
@@ -86,6 +90,7 @@ impl FaceState {
         whatami: WhatAmI,
         #[cfg(feature = "stats")] stats: Option<Arc<TransportStats>>,
         primitives: Arc<dyn crate::net::primitives::EPrimitives + Send + Sync>,
+        query_cancellation: bool,
         mcast_group: Option<TransportMulticast>,
         in_interceptors: Option<Arc<InterceptorsChain>>,
         hat: Box<dyn Any + Send + Sync>,
@@ -104,6 +109,8 @@ impl FaceState {
             remote_mappings: HashMap::new(),
             next_qid: 0,
             pending_queries: HashMap::new(),
+            routed_queries: Mutex::new(HashMap::new()),
+            query_cancellation,
             mcast_group,
             in_interceptors,
             hat,
@@ -450,7 +457,11 @@ impl Primitives for Face {
     }
 
     fn send_response_final(&self, msg: ResponseFinal) {
-        route_send_response_final(&self.tables, &mut self.state.clone(), msg.rid);
+        if msg.ext_cancel.is_some() {
+            route_cancel_query(&self.state, msg.rid);
+        } else {
+            route_send_response_final(&self.tables, &mut self.state.clone(), msg.rid);
+        }
     }
 
     fn send_close(&self) {
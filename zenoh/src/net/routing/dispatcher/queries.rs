@@ -13,7 +13,10 @@
 //
 use std::{
     collections::HashMap,
-    sync::{Arc, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
     time::Duration,
 };
 
@@ -52,6 +55,13 @@ use crate::net::routing::{
 pub(crate) struct Query {
     src_face: Arc<FaceState>,
     src_qid: RequestId,
+    cancelled: AtomicBool,
+}
+
+/// A query routed to other faces, indexed by its id on the face it was received from.
+pub(crate) struct RoutedQuery {
+    query: Weak<Query>,
+    routes: Vec<(Weak<FaceState>, RequestId)>,
 }
 
 #[zenoh_macros::unstable]
@@ -490,11 +500,32 @@ pub fn route_query(
                 let query = Arc::new(Query {
                     src_face: face.clone(),
                     src_qid: qid,
+                    cancelled: AtomicBool::new(false),
                 });
 
                 let queries_lock = zwrite!(tables_ref.queries_lock);
-                let route =
-                    compute_final_route(&rtables, &route, face, &mut expr, &ext_target, query);
+                let route = compute_final_route(
+                    &rtables,
+                    &route,
+                    face,
+                    &mut expr,
+                    &ext_target,
+                    query.clone(),
+                );
+                // Only the queries that may be cancelled by the source face are recorded
+                if face.query_cancellation && !route.is_empty() {
+                    zlock!(face.routed_queries).insert(
+                        qid,
+                        RoutedQuery {
+                            query: Arc::downgrade(&query),
+                            routes: route
+                                .values()
+                                .map(|((outface, _, _), outqid)| (Arc::downgrade(outface), *outqid))
+                                .collect(),
+                        },
+                    );
+                }
+                drop(query);
                 let timeout = ext_timeout.unwrap_or(rtables.queries_default_timeout);
                 drop(queries_lock);
                 drop(rtables);
@@ -509,6 +540,7 @@ pub fn route_query(
                         rid: qid,
                         ext_qos: response::ext::QoSType::RESPONSE_FINAL,
                         ext_tstamp: None,
+                        ext_cancel: None,
                     });
                 } else {
                     for ((outface, key_expr, context), outqid) in route.values() {
@@ -549,6 +581,7 @@ pub fn route_query(
                     rid: qid,
                     ext_qos: response::ext::QoSType::RESPONSE_FINAL,
                     ext_tstamp: None,
+                    ext_cancel: None,
                 });
             }
         }
@@ -563,6 +596,7 @@ pub fn route_query(
                 rid: qid,
                 ext_qos: response::ext::QoSType::RESPONSE_FINAL,
                 ext_tstamp: None,
+                ext_cancel: None,
             });
         }
     }
@@ -590,6 +624,9 @@ pub(crate) fn route_send_response(
     }
 
     match face.pending_queries.get(&qid) {
+        Some((query, _)) if query.cancelled.load(Ordering::Relaxed) => {
+            tracing::trace!("Drop reply {}:{} to cancelled query", face, qid);
+        }
         Some((query, _)) => {
             drop(queries_lock);
 
@@ -640,6 +677,55 @@ pub(crate) fn route_send_response_final(
     }
 }
 
+pub(crate) fn route_cancel_query(face: &Arc<FaceState>, qid: RequestId) {
+    let routed = zlock!(face.routed_queries).remove(&qid);
+    let Some((query, routes)) =
+        routed.and_then(|routed| Some((routed.query.upgrade()?, routed.routes)))
+    else {
+        tracing::debug!("Cancel query {}:{}: Query not found!", face, qid);
+        return;
+    };
+    if query.cancelled.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    // The pending queries are removed when the final replies of the cancelled query are received
+    tracing::debug!("Cancel query {}:{}", face, qid);
+    face.primitives.clone().send_response_final(ResponseFinal {
+        rid: qid,
+        ext_qos: response::ext::QoSType::RESPONSE_FINAL,
+        ext_tstamp: None,
+        ext_cancel: None,
+    });
+    for (outface, outqid) in routes {
+        let Some(outface) = outface.upgrade() else {
+            continue;
+        };
+        if !outface.query_cancellation {
+            tracing::trace!(
+                "Cannot propagate query cancellation {}:{} to {}:{}",
+                face,
+                qid,
+                outface,
+                outqid
+            );
+            continue;
+        }
+        tracing::trace!(
+            "Propagate query cancellation {}:{} to {}:{}",
+            face,
+            qid,
+            outface,
+            outqid
+        );
+        outface.primitives.send_response_final(ResponseFinal {
+            rid: outqid,
+            ext_qos: response::ext::QoSType::RESPONSE_FINAL,
+            ext_tstamp: None,
+            ext_cancel: Some(response::ext::Cancel::new()),
+        });
+    }
+}
+
 pub(crate) fn finalize_pending_queries(tables_ref: &TablesLock, face: &mut Arc<FaceState>) {
     let queries_lock = zwrite!(tables_ref.queries_lock);
     for (_, query) in get_mut_unchecked(face).pending_queries.drain() {
@@ -651,7 +737,11 @@ pub(crate) fn finalize_pending_queries(tables_ref: &TablesLock, face: &mut Arc<F
 pub(crate) fn finalize_pending_query(query: (Arc<Query>, CancellationToken)) {
     let (query, cancellation_token) = query;
     cancellation_token.cancel();
-    if let Some(query) = Arc::into_inner(query) {
+    let Some(query) = Arc::into_inner(query) else {
+        return;
+    };
+    zlock!(query.src_face.routed_queries).remove(&query.src_qid);
+    if !query.cancelled.load(Ordering::Relaxed) {
         tracing::debug!("Propagate final reply {}:{}", query.src_face, query.src_qid);
         query
             .src_face
@@ -661,6 +751,7 @@ pub(crate) fn finalize_pending_query(query: (Arc<Query>, CancellationToken)) {
                 rid: query.src_qid,
                 ext_qos: response::ext::QoSType::RESPONSE_FINAL,
                 ext_tstamp: None,
+                ext_cancel: None,
             });
     }
 }
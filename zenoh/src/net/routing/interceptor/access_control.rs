@@ -715,3 +715,64 @@ impl AclActionMethods for IngressAclEnforcer {
         self.subject.clone()
     }
 }
+
+/// Enforces the access control policy of the router on behalf of the users authenticated by a
+/// plugin (e.g. the HTTP users of the REST plugin).
+///
+/// A user is matched against the ACL subjects by its username only, as if it had opened a
+/// transport with the same username: its messages are then checked as ingress messages.
+#[cfg(feature = "internal")]
+pub struct UserAclEnforcer {
+    enforcer: Option<Arc<PolicyEnforcer>>,
+}
+
+#[cfg(feature = "internal")]
+impl UserAclEnforcer {
+    pub fn new(acl_config: &AclConfig) -> ZResult<Self> {
+        if !acl_config.enabled {
+            return Ok(Self { enforcer: None });
+        }
+
+        let mut policy_enforcer = PolicyEnforcer::new();
+        if let Err(e) = policy_enforcer.init(acl_config) {
+            bail!("Access control not enabled due to: {}", e);
+        }
+        Ok(Self {
+            enforcer: Some(Arc::new(policy_enforcer)),
+        })
+    }
+
+    /// Returns the permission of the user identified by `username` (if any) to perform `action`
+    /// on `key_expr`.
+    pub fn action(&self, username: Option<&str>, action: AclMessage, key_expr: &str) -> Permission {
+        let Some(enforcer) = &self.enforcer else {
+            return Permission::Allow;
+        };
+
+        let query = SubjectQuery {
+            interface: None,
+            cert_common_name: None,
+            username: username.map(|username| Username(username.to_string())),
+        };
+        let Some(entry) = enforcer.subject_store.query(&query) else {
+            tracing::debug!(
+                "{query} did not match any configured ACL subject. Default permission `{:?}` is applied",
+                enforcer.default_permission
+            );
+            return enforcer.default_permission;
+        };
+
+        match enforcer.policy_decision_point(entry.id, InterceptorFlow::Ingress, action, key_expr) {
+            Ok(permission) => {
+                tracing::trace!("{query} is {permission:?} to {action:?} on {key_expr}");
+                permission
+            }
+            Err(e) => {
+                tracing::debug!(
+                    "{query} has an authorization error to {action:?} on {key_expr}: {e}"
+                );
+                Permission::Deny
+            }
+        }
+    }
+}
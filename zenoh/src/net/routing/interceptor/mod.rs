@@ -20,6 +20,8 @@
 //!
 mod access_control;
 use access_control::acl_interceptor_factories;
+#[cfg(feature = "internal")]
+pub use access_control::UserAclEnforcer;
 
 mod authorization;
 use std::any::Any;
@@ -89,6 +89,7 @@ impl Router {
                     #[cfg(feature = "stats")]
                     None,
                     primitives.clone(),
+                    true,
                     None,
                     None,
                     ctrl_lock.new_face(),
@@ -123,6 +124,7 @@ impl Router {
         let fid = tables.face_counter;
         tables.face_counter += 1;
         let zid = transport.get_zid()?;
+        let query_cancellation = transport.get_patch()?.has_query_cancellation();
         #[cfg(feature = "stats")]
         let stats = transport.get_stats()?;
         let (ingress, egress): (Vec<_>, Vec<_>) = tables
@@ -148,6 +150,7 @@ impl Router {
                     #[cfg(feature = "stats")]
                     Some(stats),
                     mux.clone(),
+                    query_cancellation,
                     None,
                     Some(ingress.clone()),
                     ctrl_lock.new_face(),
@@ -200,6 +203,7 @@ impl Router {
             #[cfg(feature = "stats")]
             None,
             mux.clone(),
+            false,
             Some(transport),
             None,
             ctrl_lock.new_face(),
@@ -237,6 +241,7 @@ impl Router {
             #[cfg(feature = "stats")]
             Some(transport.get_stats().unwrap()),
             Arc::new(DummyPrimitives),
+            false,
             Some(transport),
             Some(interceptor.clone()),
             ctrl_lock.new_face(),
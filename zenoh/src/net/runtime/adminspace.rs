@@ -449,6 +449,7 @@ impl Primitives for AdminSpace {
                             rid: msg.id,
                             ext_qos: ext::QoSType::RESPONSE_FINAL,
                             ext_tstamp: None,
+                            ext_cancel: None,
                         });
                         return;
                     }
@@ -462,6 +463,7 @@ impl Primitives for AdminSpace {
                             rid: msg.id,
                             ext_qos: ext::QoSType::RESPONSE_FINAL,
                             ext_tstamp: None,
+                            ext_cancel: None,
                         });
                         return;
                     }
@@ -475,6 +477,8 @@ impl Primitives for AdminSpace {
                         qid: msg.id,
                         zid: zid.into(),
                         primitives,
+                        #[cfg(feature = "unstable")]
+                        cancellation: None,
                     }),
                     eid: self.queryable_id,
                     value: query
@@ -25,9 +25,13 @@ use zenoh_protocol::{
         key_expr::keyexpr, Encoding, ExprId, Reliability, WhatAmI, WireExpr, ZenohIdProto,
         EMPTY_EXPR_ID,
     },
-    network::{ext, Declare, DeclareBody, DeclareKeyExpr},
-    zenoh::{PushBody, Put},
+    network::{
+        declare::queryable::ext::QueryableInfoType, ext, response, Declare, DeclareBody,
+        DeclareKeyExpr, Request, RequestId, ResponseFinal,
+    },
+    zenoh::{query::ConsolidationMode, PushBody, Put, Query, RequestBody},
 };
+use zenoh_sync::get_mut_unchecked;
 
 use crate::net::{
     primitives::{DummyPrimitives, EPrimitives, Primitives},
@@ -885,3 +889,113 @@ fn get_best_key_test() {
     assert_wire_expr!(get_best_key("a", "/d", &face2), { scope: 0, suffix: "a/d" });
     assert_wire_expr!(get_best_key("a/b", "", &face2), { scope: 2, suffix: "" });
 }
+
+/// Records the requests and the final responses sent to a face.
+#[derive(Default)]
+struct QueryPrimitives {
+    requests: std::sync::Mutex<Vec<RequestId>>,
+    /// The request ids of the final responses, and whether they cancel the requests.
+    finals: std::sync::Mutex<Vec<(RequestId, bool)>>,
+}
+
+impl EPrimitives for QueryPrimitives {
+    fn send_interest(&self, _ctx: RoutingContext<zenoh_protocol::network::Interest>) {}
+
+    fn send_declare(&self, _ctx: RoutingContext<zenoh_protocol::network::Declare>) {}
+
+    fn send_push(&self, _msg: zenoh_protocol::network::Push, _reliability: Reliability) {}
+
+    fn send_request(&self, msg: Request) {
+        zlock!(self.requests).push(msg.id);
+    }
+
+    fn send_response(&self, _msg: zenoh_protocol::network::Response) {}
+
+    fn send_response_final(&self, msg: ResponseFinal) {
+        zlock!(self.finals).push((msg.rid, msg.ext_cancel.is_some()));
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+fn query(id: RequestId) -> Request {
+    Request {
+        id,
+        wire_expr: "test/cancel".into(),
+        ext_qos: ext::QoSType::REQUEST,
+        ext_tstamp: None,
+        ext_nodeid: ext::NodeIdType::DEFAULT,
+        ext_target: zenoh_protocol::network::request::ext::QueryTarget::All,
+        ext_budget: None,
+        ext_timeout: None,
+        payload: RequestBody::Query(Query {
+            consolidation: ConsolidationMode::None,
+            parameters: String::new(),
+            ext_sinfo: None,
+            ext_body: None,
+            ext_attachment: None,
+            ext_unknown: vec![],
+        }),
+    }
+}
+
+#[test]
+fn query_cancellation_test() {
+    let config = Config::default();
+    let router = Router::new(
+        ZenohIdProto::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        Some(Arc::new(HLC::default())),
+        &config,
+    )
+    .unwrap();
+    let tables = router.tables.clone();
+
+    let querier_primitives = Arc::new(QueryPrimitives::default());
+    let querier = router.new_primitives(querier_primitives.clone());
+    let primitives1 = Arc::new(QueryPrimitives::default());
+    let face1 = router.new_primitives(primitives1.clone());
+    // A face that did not negotiate the query cancellation, e.g. with a node of patch 1
+    let primitives2 = Arc::new(QueryPrimitives::default());
+    let face2 = router.new_primitives(primitives2.clone());
+    get_mut_unchecked(&mut face2.state.clone()).query_cancellation = false;
+    for face in [&face1, &face2] {
+        declare_queryable(
+            zlock!(tables.ctrl_lock).as_ref(),
+            &tables,
+            &mut face.state.clone(),
+            0,
+            &"test/cancel".into(),
+            &QueryableInfoType::DEFAULT,
+            NodeId::default(),
+            &mut |p, m| p.send_declare(m),
+        );
+    }
+
+    Primitives::send_request(querier.as_ref(), query(1));
+    assert_eq!(zlock!(primitives1.requests).len(), 1);
+    assert_eq!(zlock!(primitives2.requests).len(), 1);
+
+    Primitives::send_response_final(
+        querier.as_ref(),
+        ResponseFinal {
+            rid: 1,
+            ext_qos: response::ext::QoSType::RESPONSE_FINAL,
+            ext_tstamp: None,
+            ext_cancel: Some(response::ext::Cancel::new()),
+        },
+    );
+    assert_eq!(*zlock!(querier_primitives.finals), [(1, false)]);
+    let outqid = zlock!(primitives1.requests)[0];
+    assert_eq!(*zlock!(primitives1.finals), [(outqid, true)]);
+    // The cancellation is not sent to the face that can't decode it
+    assert!(zlock!(primitives2.finals).is_empty());
+
+    // The queries of a face that can't cancel them are not recorded
+    get_mut_unchecked(&mut querier.state.clone()).query_cancellation = false;
+    Primitives::send_request(querier.as_ref(), query(2));
+    assert_eq!(zlock!(primitives1.requests).len(), 2);
+    assert!(zlock!(querier.state.routed_queries).is_empty());
+}
@@ -1529,3 +1529,69 @@ async fn test_liveliness_deny_allow_query(port: u16) {
     close_sessions(reader_session, writer_session).await;
     close_router_session(session).await;
 }
+
+#[cfg(feature = "internal")]
+#[test]
+fn test_acl_user_enforcer() {
+    use zenoh::internal::access_control::{AclMessage, Permission, UserAclEnforcer};
+
+    let mut config = zenoh_config::Config::default();
+    config
+        .insert_json5(
+            "access_control",
+            r#"{
+                    "enabled": true,
+                    "default_permission": "deny",
+                    "rules": [
+                        {
+                            "id": "r1",
+                            "permission": "allow",
+                            "flows": ["ingress"],
+                            "messages": ["put"],
+                            "key_exprs": ["test/demo/**"],
+                        },
+                    ],
+                    "subjects": [
+                        {
+                            "id": "s1",
+                            "usernames": ["user1"],
+                        }
+                    ],
+                    "policies": [
+                        {
+                            "rules": ["r1"],
+                            "subjects": ["s1"],
+                        }
+                    ]
+                }"#,
+        )
+        .unwrap();
+    let enforcer = UserAclEnforcer::new(config.access_control()).unwrap();
+
+    assert_eq!(
+        enforcer.action(Some("user1"), AclMessage::Put, "test/demo/a"),
+        Permission::Allow
+    );
+    assert_eq!(
+        enforcer.action(Some("user1"), AclMessage::Delete, "test/demo/a"),
+        Permission::Deny
+    );
+    assert_eq!(
+        enforcer.action(Some("user1"), AclMessage::Put, "test/other"),
+        Permission::Deny
+    );
+    assert_eq!(
+        enforcer.action(Some("user2"), AclMessage::Put, "test/demo/a"),
+        Permission::Deny
+    );
+    assert_eq!(
+        enforcer.action(None, AclMessage::Put, "test/demo/a"),
+        Permission::Deny
+    );
+
+    let enforcer = UserAclEnforcer::new(zenoh_config::Config::default().access_control()).unwrap();
+    assert_eq!(
+        enforcer.action(None, AclMessage::Put, "test/demo/a"),
+        Permission::Allow
+    );
+}
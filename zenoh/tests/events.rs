@@ -118,3 +118,58 @@ async fn zenoh_events() {
     ztimeout!(sub1.undeclare()).unwrap();
     close_session(session).await;
 }
+
+#[cfg(feature = "unstable")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn zenoh_transport_events() {
+    use zenoh::{config::WhatAmI, session::TransportEvent};
+
+    let session = open_session(&["tcp/127.0.0.1:18449"], &[]).await;
+    let events = ztimeout!(session.info().transport_events()).unwrap();
+
+    let session2 = open_session(&["tcp/127.0.0.1:18450"], &["tcp/127.0.0.1:18449"]).await;
+    let zid2 = session2.zid();
+
+    match ztimeout!(events.recv_async()).unwrap() {
+        TransportEvent::Opened(transport) => {
+            assert_eq!(transport.zid(), zid2);
+            assert_eq!(transport.whatami(), WhatAmI::Peer);
+            assert!(!transport.is_multicast());
+        }
+        event => panic!("Unexpected event: {event:?}"),
+    }
+    match ztimeout!(events.recv_async()).unwrap() {
+        TransportEvent::LinkAdded(transport, link) => {
+            assert_eq!(transport.zid(), zid2);
+            assert_eq!(link.protocol(), "tcp");
+            assert_eq!(link.src().to_string(), "tcp/127.0.0.1:18449");
+        }
+        event => panic!("Unexpected event: {event:?}"),
+    }
+
+    // The transports already opened are reported to the late listeners
+    let history = ztimeout!(session.info().transport_events().history(true)).unwrap();
+    let event = ztimeout!(history.recv_async()).unwrap();
+    assert!(matches!(event, TransportEvent::Opened(_)));
+    assert_eq!(event.transport().zid(), zid2);
+    let event = ztimeout!(history.recv_async()).unwrap();
+    assert!(matches!(event, TransportEvent::LinkAdded(_, _)));
+    assert_eq!(event.link().unwrap().protocol(), "tcp");
+    ztimeout!(history.undeclare()).unwrap();
+
+    close_session(session2).await;
+
+    loop {
+        match ztimeout!(events.recv_async()).unwrap() {
+            TransportEvent::LinkRemoved(transport, _) => assert_eq!(transport.zid(), zid2),
+            TransportEvent::Closed(transport) => {
+                assert_eq!(transport.zid(), zid2);
+                break;
+            }
+            event => panic!("Unexpected event: {event:?}"),
+        }
+    }
+
+    ztimeout!(events.undeclare()).unwrap();
+    close_session(session).await;
+}
@@ -58,6 +58,13 @@ fn get_matching_listener_status(
     received_status.ok().flatten().map(|s| s.matching())
 }
 
+fn get_matching_listener_count(
+    matching_listener: &MatchingListener<FifoChannelHandler<MatchingStatus>>,
+) -> Option<usize> {
+    let received_status = matching_listener.recv_timeout(RECV_TIMEOUT);
+    received_status.ok().flatten().and_then(|s| s.count())
+}
+
 fn is_locality_compatible(locality: Locality, same_session: bool) -> bool {
     match locality {
         Locality::SessionLocal => same_session,
@@ -224,6 +231,139 @@ async fn zenoh_publisher_matching_status_inner(publisher_locality: Locality, sam
     );
 }
 
+async fn zenoh_subscriber_matching_status_inner(subscriber_locality: Locality, same_session: bool) {
+    println!(
+        "Subscriber origin: {:?}, same session: {same_session}",
+        subscriber_locality
+    );
+    zenoh_util::init_log_from_env_or("error");
+    let key_expr = match subscriber_locality {
+        Locality::SessionLocal => "zenoh_subscriber_matching_status_local_test",
+        Locality::Remote => "zenoh_subscriber_matching_status_remote_test",
+        Locality::Any => "zenoh_subscriber_matching_status_any_test",
+    };
+
+    let (session1, session2) = match same_session {
+        false => create_session_pair("tcp/127.0.0.1:18003").await,
+        true => {
+            let s1 = ztimeout!(zenoh::open(zenoh::Config::default())).unwrap();
+            let s2 = s1.clone();
+            (s1, s2)
+        }
+    };
+    let locality_compatible = is_locality_compatible(subscriber_locality, same_session);
+    let expected_count = |count: usize| if locality_compatible { count } else { 0 };
+
+    let subscriber = ztimeout!(session2
+        .declare_subscriber(format!("{key_expr}/value"))
+        .allowed_origin(subscriber_locality))
+    .unwrap();
+
+    let matching_listener = ztimeout!(subscriber.matching_listener()).unwrap();
+
+    assert_eq!(get_matching_listener_count(&matching_listener), None);
+    assert_eq!(
+        ztimeout!(subscriber.matching_status()).unwrap().count(),
+        Some(0)
+    );
+
+    let _hidden = ztimeout!(session1.declare_publisher(format!("{key_expr}/value"))).unwrap();
+    assert_eq!(get_matching_listener_count(&matching_listener), None);
+
+    let publisher1 = ztimeout!(session1
+        .declare_publisher(format!("{key_expr}/*"))
+        .announce(true))
+    .unwrap();
+    assert_eq!(
+        get_matching_listener_count(&matching_listener),
+        locality_compatible.then_some(1)
+    );
+
+    let publisher2 = ztimeout!(session1
+        .declare_publisher(format!("{key_expr}/value"))
+        .announce(true))
+    .unwrap();
+    assert_eq!(
+        get_matching_listener_count(&matching_listener),
+        locality_compatible.then_some(2)
+    );
+
+    let _other = ztimeout!(session1
+        .declare_publisher(format!("{key_expr}/other"))
+        .announce(true))
+    .unwrap();
+    assert_eq!(get_matching_listener_count(&matching_listener), None);
+
+    let status = ztimeout!(subscriber.matching_status()).unwrap();
+    assert_eq!(status.matching(), locality_compatible);
+    assert_eq!(status.count(), Some(expected_count(2)));
+
+    ztimeout!(publisher1.undeclare()).unwrap();
+    assert_eq!(
+        get_matching_listener_count(&matching_listener),
+        locality_compatible.then_some(1)
+    );
+
+    ztimeout!(publisher2.undeclare()).unwrap();
+    assert_eq!(
+        get_matching_listener_count(&matching_listener),
+        locality_compatible.then_some(0)
+    );
+    assert!(!ztimeout!(subscriber.matching_status()).unwrap().matching());
+}
+
+async fn zenoh_queryable_matching_status_inner(queryable_locality: Locality, same_session: bool) {
+    println!(
+        "Queryable origin: {:?}, same session: {same_session}",
+        queryable_locality
+    );
+    zenoh_util::init_log_from_env_or("error");
+    let key_expr = match queryable_locality {
+        Locality::SessionLocal => "zenoh_queryable_matching_status_local_test",
+        Locality::Remote => "zenoh_queryable_matching_status_remote_test",
+        Locality::Any => "zenoh_queryable_matching_status_any_test",
+    };
+
+    let (session1, session2) = match same_session {
+        false => create_session_pair("tcp/127.0.0.1:18004").await,
+        true => {
+            let s1 = ztimeout!(zenoh::open(zenoh::Config::default())).unwrap();
+            let s2 = s1.clone();
+            (s1, s2)
+        }
+    };
+    let locality_compatible = is_locality_compatible(queryable_locality, same_session);
+
+    let queryable = ztimeout!(session2
+        .declare_queryable(format!("{key_expr}/value"))
+        .allowed_origin(queryable_locality))
+    .unwrap();
+
+    let matching_listener = ztimeout!(queryable.matching_listener()).unwrap();
+
+    assert_eq!(get_matching_listener_status(&matching_listener), None);
+    assert!(!ztimeout!(queryable.matching_status()).unwrap().matching());
+
+    let querier = ztimeout!(session1
+        .declare_querier(format!("{key_expr}/**"))
+        .announce(true))
+    .unwrap();
+    assert_eq!(
+        get_matching_listener_status(&matching_listener),
+        locality_compatible.then_some(true)
+    );
+    assert_eq!(
+        ztimeout!(queryable.matching_status()).unwrap().matching(),
+        locality_compatible
+    );
+
+    ztimeout!(querier.undeclare()).unwrap();
+    assert_eq!(
+        get_matching_listener_status(&matching_listener),
+        locality_compatible.then_some(false)
+    );
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn zenoh_querier_matching_status() -> ZResult<()> {
     zenoh_util::init_log_from_env_or("error");
@@ -247,3 +387,27 @@ async fn zenoh_publisher_matching_status() -> ZResult<()> {
     zenoh_publisher_matching_status_inner(Locality::SessionLocal, false).await;
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn zenoh_subscriber_matching_status() -> ZResult<()> {
+    zenoh_util::init_log_from_env_or("error");
+    zenoh_subscriber_matching_status_inner(Locality::Any, true).await;
+    zenoh_subscriber_matching_status_inner(Locality::Any, false).await;
+    zenoh_subscriber_matching_status_inner(Locality::Remote, true).await;
+    zenoh_subscriber_matching_status_inner(Locality::Remote, false).await;
+    zenoh_subscriber_matching_status_inner(Locality::SessionLocal, true).await;
+    zenoh_subscriber_matching_status_inner(Locality::SessionLocal, false).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn zenoh_queryable_matching_status() -> ZResult<()> {
+    zenoh_util::init_log_from_env_or("error");
+    zenoh_queryable_matching_status_inner(Locality::Any, true).await;
+    zenoh_queryable_matching_status_inner(Locality::Any, false).await;
+    zenoh_queryable_matching_status_inner(Locality::Remote, true).await;
+    zenoh_queryable_matching_status_inner(Locality::Remote, false).await;
+    zenoh_queryable_matching_status_inner(Locality::SessionLocal, true).await;
+    zenoh_queryable_matching_status_inner(Locality::SessionLocal, false).await;
+    Ok(())
+}
@@ -442,3 +442,98 @@ async fn test_undeclare_subscribers_same_keyexpr() {
     ztimeout!(sub1.undeclare()).unwrap();
     ztimeout!(sub2.undeclare()).unwrap();
 }
+
+#[cfg(feature = "unstable")]
+async fn test_session_query_cancellation(querier: &Session, queryable: &Session) {
+    use std::sync::atomic::AtomicBool;
+
+    use zenoh::query::CancellationToken;
+
+    let key_expr = "test/session/cancellation";
+    let qbl = ztimeout!(queryable.declare_queryable(key_expr)).unwrap();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let c_cancelled = cancelled.clone();
+    let task = tokio::spawn(async move {
+        let query = qbl.recv_async().await.unwrap();
+        for i in 0..1_000u32 {
+            if query.is_cancelled() {
+                c_cancelled.store(true, Ordering::Relaxed);
+                break;
+            }
+            query.reply(key_expr, i.to_string()).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+    tokio::time::sleep(SLEEP).await;
+
+    let token = CancellationToken::new();
+    let replies = ztimeout!(querier
+        .get(key_expr)
+        .consolidation(zenoh::query::ConsolidationMode::None)
+        .cancellation_token(token.clone()))
+    .unwrap();
+    for _ in 0..10 {
+        ztimeout!(replies.recv_async()).unwrap().result().unwrap();
+    }
+    token.cancel();
+    // The replies stop before the end of the query
+    while ztimeout!(replies.recv_async()).is_ok() {}
+    ztimeout!(task).unwrap();
+    assert!(cancelled.load(Ordering::Relaxed));
+
+    // An already cancelled token doesn't send the query
+    let replies = ztimeout!(querier.get(key_expr).cancellation_token(token)).unwrap();
+    assert!(ztimeout!(replies.recv_async()).is_err());
+}
+
+#[cfg(feature = "unstable")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn zenoh_session_query_cancellation() {
+    zenoh::init_log_from_env_or("error");
+
+    let (peer01, peer02) = open_session_unicast(&["tcp/127.0.0.1:17477"]).await;
+    tokio::time::sleep(SLEEP).await;
+    test_session_query_cancellation(&peer02, &peer01).await;
+    test_session_query_cancellation(&peer01, &peer01).await;
+    close_session(peer01, peer02).await;
+}
+
+#[cfg(feature = "unstable")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn zenoh_session_query_cancellation_routed() {
+    use zenoh::config::WhatAmI;
+
+    zenoh::init_log_from_env_or("error");
+
+    let endpoint = "tcp/127.0.0.1:17487";
+    let mut config = zenoh::Config::default();
+    config
+        .listen
+        .endpoints
+        .set(vec![endpoint.parse().unwrap()])
+        .unwrap();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    let router = ztimeout!(zenoh::open(config)).unwrap();
+
+    let open_client = || async {
+        let mut config = zenoh::Config::default();
+        config
+            .connect
+            .endpoints
+            .set(vec![endpoint.parse().unwrap()])
+            .unwrap();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.set_mode(Some(WhatAmI::Client)).unwrap();
+        ztimeout!(zenoh::open(config)).unwrap()
+    };
+    let client01 = open_client().await;
+    let client02 = open_client().await;
+    tokio::time::sleep(SLEEP).await;
+
+    // The cancellation is propagated by the router to the queryable
+    test_session_query_cancellation(&client02, &client01).await;
+
+    close_session(client01, client02).await;
+    ztimeout!(router.close()).unwrap();
+}